@@ -0,0 +1,56 @@
+//! Compares buffered and memory-mapped reading of every module in
+//! a directory (for example, `\OS2\DLL` of installed system):
+//! ```text
+//! cargo run --release --features mmap --example backends -- <dir> [rounds]
+//! ```
+//! Files which aren't executables are counted as failed by both ways.
+//!
+//! Release build, one core of Xeon, files in page cache:
+//! ```text
+//! 900 fixtures of tests/fixtures (0.3-7 KB)   BufReader 11.9µs, mmap 8.3µs per file
+//! 40 LX of 1.4 MB, 20000 fixups each          BufReader 6.73ms, mmap 2.11ms per file
+//! ```
+use os2omf::file::ExecutableFile;
+use std::time::{Duration, Instant};
+use std::{env, fs, io};
+
+type Backend = fn(&str) -> io::Result<ExecutableFile>;
+
+fn read_all(paths: &[String], read: Backend) -> (Duration, usize) {
+    let start = Instant::now();
+    let failed = paths.iter().filter(|path| read(path).is_err()).count();
+    (start.elapsed(), failed)
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let dir = args.next().unwrap_or_else(|| ".".to_string());
+    let rounds: u32 = args.next().and_then(|r| r.parse().ok()).unwrap_or(5);
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+    println!("{} files in {}, {} rounds", paths.len(), dir, rounds);
+
+    let backends: [(&str, Backend); 2] = [
+        ("BufReader", ExecutableFile::open),
+        ("mmap", ExecutableFile::open_mmap),
+    ];
+    for (name, read) in backends {
+        // first round warms file system cache up
+        let (_, failed) = read_all(&paths, read);
+        let total: Duration = (0..rounds).map(|_| read_all(&paths, read).0).sum();
+        println!(
+            "{:<10} {:>10.2?} per round, {:>8.2?} per file, {} failed",
+            name,
+            total / rounds.max(1),
+            total / (rounds.max(1) * paths.len().max(1) as u32),
+            failed
+        );
+    }
+    Ok(())
+}
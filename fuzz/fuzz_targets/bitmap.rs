@@ -0,0 +1,13 @@
+//! Windows and OS/2 bitmaps: sizes and palettes are counted from resource data
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use os2omf::exe286::resources::{Bitmap, Cursor, Icon, Os2Bitmap, Os2BitmapArray};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Bitmap::parse(data).map(|bitmap| bitmap.to_bmp_bytes());
+    let _ = Icon::parse(data).map(|icon| icon.to_ico_bytes());
+    let _ = Cursor::parse(data);
+    let _ = Os2Bitmap::parse(data);
+    let _ = Os2BitmapArray::parse(data);
+});
@@ -0,0 +1,22 @@
+//! LE/LX tables, object pages and everything computed from them
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use os2omf::exe386::dirtab::ModuleDirectivesTable;
+use os2omf::exe386::LinearExecutableLayout;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(layout) = LinearExecutableLayout::read_from(&mut Cursor::new(data)) else {
+        return;
+    };
+    let _ = layout.validate(data.len() as u64);
+    for object in 1..=layout.object_table.objects.len() as u32 {
+        let _ = layout.read_object_data(&mut Cursor::new(data), object);
+    }
+    for directive in &layout.module_directives_table.directives {
+        let _ = ModuleDirectivesTable::read_verify_record(directive, layout.header.byte_order());
+    }
+    let _ = layout.entry_point();
+    let _ = layout.regions();
+});
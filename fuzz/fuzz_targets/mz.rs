@@ -0,0 +1,12 @@
+//! DOS header, relocations and detection of the format
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use os2omf::exe::MzExecutableLayout;
+use os2omf::file::ExecutableFile;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = MzExecutableLayout::read_from(&mut Cursor::new(data));
+    let _ = ExecutableFile::read_from_slice(data);
+});
@@ -0,0 +1,17 @@
+//! NE tables, segment data and everything computed from them
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use os2omf::exe286::NewExecutableLayout;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut layout) = NewExecutableLayout::read_from(&mut Cursor::new(data)) else {
+        return;
+    };
+    let _ = layout.read_segments_data(&mut Cursor::new(data));
+    let _ = layout.validate(data.len() as u64);
+    let _ = layout.imports();
+    let _ = layout.entry_point();
+    let _ = layout.regions();
+});
@@ -0,0 +1,17 @@
+//! Variable-length records decoded straight from bytes: menu templates and verify records
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use os2omf::exe286::resources::Menu;
+use os2omf::exe386::dirtab::{DirectiveType, ModuleDirective, ModuleDirectivesTable};
+use os2omf::types::ByteOrder;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Menu::parse(data);
+    let directive = ModuleDirective {
+        directive_type: DirectiveType::VerifyRecord,
+        data: data.to_vec(),
+    };
+    let _ = ModuleDirectivesTable::read_verify_record(&directive, ByteOrder::Little);
+    let _ = ModuleDirectivesTable::read_verify_record(&directive, ByteOrder::Big);
+});
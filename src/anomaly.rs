@@ -0,0 +1,105 @@
+//! This module represents structural anomalies of parsed layouts.
+//! Packers, protectors and hand-patched files are leaving traces:
+//! tables out of file, tables over each other, names of missing entries.
+//! ```rust,no_run
+//! use os2omf::exe286::NewExecutableLayout;
+//!
+//! let path = "put here Windows 3.1 app/dll path";
+//! let layout = NewExecutableLayout::get(path)?;
+//! let file_len = std::fs::metadata(path)?.len();
+//!
+//! for anomaly in layout.validate(file_len) {
+//!     println!("{:?} at 0x{:X}: {}", anomaly.severity, anomaly.offset, anomaly.message);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Layouts don't keep the file, so length of file is an argument.
+use crate::regions::{Region, RegionKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Severity {
+    /// Unusual but harmless
+    Info,
+    /// Loader may still accept the file
+    Warning,
+    /// Structure is broken
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Anomaly {
+    pub severity: Severity,
+    /// Raw file pointer where anomaly is
+    pub offset: u64,
+    pub message: String,
+}
+
+impl Anomaly {
+    pub fn new(severity: Severity, offset: u64, message: String) -> Self {
+        Self {
+            severity,
+            offset,
+            message,
+        }
+    }
+}
+
+///
+/// `e_lfanew` must point after the MZ header
+///
+pub(crate) fn check_lfanew(e_lfanew: u32) -> Option<Anomaly> {
+    match e_lfanew < 0x40 {
+        true => Some(Anomaly::new(
+            Severity::Error,
+            0x3C,
+            format!("e_lfanew 0x{:X} points inside the MZ header", e_lfanew),
+        )),
+        false => None,
+    }
+}
+
+///
+/// Checks regions against file length and each other.
+/// Overlapping with DOS stub is usual (stub length is often rounded), so it's only [Severity::Info].
+///
+pub(crate) fn check_regions(regions: &[Region], file_len: u64) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let mut claimed: Vec<&Region> = regions
+        .iter()
+        .filter(|r| r.kind != RegionKind::Unclaimed)
+        .collect();
+    claimed.sort_by_key(|r| (r.start, r.len));
+
+    for region in &claimed {
+        if region.start >= file_len {
+            anomalies.push(Anomaly::new(
+                Severity::Error,
+                region.start,
+                format!("{} lies past end of file (0x{:X})", region.name, file_len),
+            ));
+        } else if region.end() > file_len {
+            anomalies.push(Anomaly::new(
+                Severity::Warning,
+                region.start,
+                format!("{} is truncated by end of file (0x{:X})", region.name, file_len),
+            ));
+        }
+    }
+
+    for (i, region) in claimed.iter().enumerate() {
+        for other in claimed[i + 1..].iter().take_while(|o| o.start < region.end()) {
+            let severity = match region.kind == RegionKind::DosStub || other.kind == RegionKind::DosStub {
+                true => Severity::Info,
+                false => Severity::Warning,
+            };
+            anomalies.push(Anomaly::new(
+                severity,
+                other.start,
+                format!("{} overlaps {}", other.name, region.name),
+            ));
+        }
+    }
+    anomalies
+}
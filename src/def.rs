@@ -0,0 +1,204 @@
+//! This module represents writers of module-definition (`.DEF`) files
+//! for parsed layouts. Module definition is a linker script what
+//! declares name of the module, description and exporting entries.
+//!
+//! Generated text is accepted by Microsoft LINK and Open Watcom wlink,
+//! so you can rebuild import library for old OS/2 or Windows DLL.
+//! ```rust,no_run
+//! use os2omf::def;
+//! use os2omf::exe286::NewExecutableLayout;
+//!
+//! let layout = NewExecutableLayout::get("put here Windows 3.1 dll path")?;
+//! let mut def_file = std::fs::File::create("KERNEL.DEF")?;
+//! def::write_ne_def(&layout, &mut def_file)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Exports are sorted by ordinals. Names from resident names table
+//! are marked `RESIDENTNAME`, entries without any name are marked `NONAME`.
+use crate::exe286::enttab::Entry;
+use crate::exe286::NewExecutableLayout;
+use crate::exe386::enttab::{BundleType, Entry as LxEntry};
+use crate::exe386::header::ModuleType;
+use crate::exe386::LinearExecutableLayout;
+use crate::types::PascalString;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// One line of `EXPORTS` section
+struct DefExport {
+    name: Option<String>,
+    resident: bool,
+    forwarder: Option<String>,
+    /// Forwarder which target is unknown, written as comment
+    unresolved: Option<String>,
+}
+
+///
+/// Writes module definition of segmented New Executable
+///
+pub fn write_ne_def<W: Write>(layout: &NewExecutableLayout, w: &mut W) -> io::Result<()> {
+    let mut exports = BTreeMap::<u16, DefExport>::new();
+
+    for (ordinal, entry) in layout.ent_tab.entries.iter() {
+        if !matches!(entry, Entry::Unused) {
+            exports.insert(*ordinal, unnamed_export());
+        }
+    }
+    let resident = layout.resn_tab.entries.iter().map(|e| (&e.name, e.ordinal, true));
+    let non_resident = layout.nres_tab.entries.iter().map(|e| (&e.name, e.ordinal, false));
+    name_exports(&mut exports, resident.chain(non_resident));
+
+    write_def(
+        w,
+        layout.new_header.module_flags().library_module,
+        layout.resn_tab.entries.first().map(|e| &e.name),
+        layout.nres_tab.entries.first().map(|e| &e.name),
+        &exports,
+    )
+}
+
+///
+/// Writes module definition of Linear Executable.
+/// Forwarders are written as `Name = MODULE.ordinal` or `Name = MODULE.Proc`.
+/// Forwarder by name gets its target from import procedure names table.
+/// Forwarder to missing module or name is left as comment: linker
+/// would make a local export of it.
+///
+pub fn write_lx_def<W: Write>(layout: &LinearExecutableLayout, w: &mut W) -> io::Result<()> {
+    let mut exports = BTreeMap::<u16, DefExport>::new();
+    let modules = layout.import_table.modules();
+
+    for bundle in &layout.entry_table.bundles {
+        if bundle.bundle_type == BundleType::Unused {
+            continue;
+        }
+        for (i, entry) in bundle.entries.iter().enumerate() {
+            let ordinal = bundle.first_ordinal.wrapping_add(i as u16);
+            let mut export = unnamed_export();
+            if let LxEntry::EntryForwarder(forwarder) = entry {
+                let module = modules.get((forwarder.module_ordinal as usize).wrapping_sub(1));
+                // bit 0 of forwarder flags: import by ordinal
+                let target = match forwarder.flags & 0x01 != 0 {
+                    true => Some(forwarder.offset_or_ordinal.to_string()),
+                    false => layout.import_table.procedure_name(forwarder.offset_or_ordinal).map(|name| lossy(&name)),
+                };
+                match (module, target) {
+                    (Some(module), Some(target)) => export.forwarder = Some(format!("{}.{}", lossy(module), target)),
+                    _ => {
+                        export.unresolved = Some(format!(
+                            "forwarder to module #{} {} 0x{:X}",
+                            forwarder.module_ordinal,
+                            if forwarder.flags & 0x01 != 0 { "ordinal" } else { "procedure name at" },
+                            forwarder.offset_or_ordinal
+                        ))
+                    }
+                }
+            }
+            exports.insert(ordinal, export);
+        }
+    }
+    let resident = layout.resident_names.entries.iter().map(|e| (&e.name, e.ordinal, true));
+    let non_resident = layout.non_resident_names.entries.iter().map(|e| (&e.name, e.ordinal, false));
+    name_exports(&mut exports, resident.chain(non_resident));
+
+    write_def(
+        w,
+        matches!(layout.header.module_type(), ModuleType::DLL),
+        layout.resident_names.entries.first().map(|e| &e.name),
+        layout.non_resident_names.entries.first().map(|e| &e.name),
+        &exports,
+    )
+}
+
+fn unnamed_export() -> DefExport {
+    DefExport {
+        name: None,
+        resident: false,
+        forwarder: None,
+        unresolved: None,
+    }
+}
+
+fn lossy(s: &PascalString) -> String {
+    String::from_utf8_lossy(s.as_bytes()).to_string()
+}
+
+///
+/// Joins names to ordinals. `@0` entries are module name
+/// and description, they aren't exports.
+///
+fn name_exports<'a, I: Iterator<Item = (&'a PascalString, u16, bool)>>(
+    exports: &mut BTreeMap<u16, DefExport>,
+    names: I,
+) {
+    for (name, ordinal, resident) in names {
+        if ordinal == 0 {
+            continue;
+        }
+        let export = exports.entry(ordinal).or_insert_with(unnamed_export);
+        if export.name.is_none() {
+            export.name = Some(lossy(name));
+            export.resident = resident;
+        }
+    }
+}
+
+///
+/// Names with characters out of linker's identifiers
+/// (e.g. dots in forwarded names) must be quoted.
+///
+fn quote(name: &str) -> String {
+    let plain = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '?' | '@' | '$'));
+    if plain && !name.is_empty() {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+fn write_def<W: Write>(
+    w: &mut W,
+    library: bool,
+    module_name: Option<&PascalString>,
+    description: Option<&PascalString>,
+    exports: &BTreeMap<u16, DefExport>,
+) -> io::Result<()> {
+    let kind = if library { "LIBRARY" } else { "NAME" };
+    match module_name {
+        Some(name) => writeln!(w, "{} {}", kind, quote(&lossy(name)))?,
+        None => writeln!(w, "{}", kind)?,
+    }
+    if let Some(description) = description.filter(|d| !d.as_bytes().is_empty()) {
+        writeln!(w, "DESCRIPTION '{}'", lossy(description).replace('\'', "''"))?;
+    }
+    if exports.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(w, "EXPORTS")?;
+    for (ordinal, export) in exports {
+        // linker needs any name for unnamed entry
+        let name = match &export.name {
+            Some(name) => quote(name),
+            None => format!("ORDINAL_{}", ordinal),
+        };
+        if let Some(forwarder) = &export.unresolved {
+            writeln!(w, "    ; {} @{}: {}", name, ordinal, forwarder)?;
+            continue;
+        }
+        let mut line = match &export.forwarder {
+            Some(target) => format!("    {} = {} @{}", name, target, ordinal),
+            None => format!("    {} @{}", name, ordinal),
+        };
+        if export.name.is_none() {
+            line.push_str(" NONAME");
+        } else if export.resident && export.forwarder.is_none() {
+            line.push_str(" RESIDENTNAME");
+        }
+        writeln!(w, "{}", line)?;
+    }
+
+    Ok(())
+}
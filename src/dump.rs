@@ -0,0 +1,533 @@
+//! This module represents writers of human-readable dumps of parsed
+//! executables in style of Microsoft EXEHDR and Borland TDUMP:
+//! ```text
+//! Segments
+//!  no. type offset   file  mem   flags
+//!    1 CODE 00000400 00120 00120 moveable, preload, relocations
+//!
+//! Exports
+//!  ord seg  offset name
+//!    1 0001 0010   WEP
+//! ```
+//! Every section can be written alone or all together by [write_summary].
+//! Offsets and addresses are hexadecimal, counts and ordinals are decimal.
+//! ```rust,no_run
+//! use os2omf::dump;
+//! use os2omf::file::ExecutableFile;
+//!
+//! let file = ExecutableFile::open("put here any exe or dll path")?;
+//! dump::write_summary(&file, &mut std::io::stdout())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Sections which make no sense for the format (e.g. exports of DOS program)
+//! are not written at all.
+use crate::exe286::NewExecutableLayout;
+use crate::exe286::enttab::{ENTRY_CONSTANT, Entry};
+use crate::exe286::header::OS;
+use crate::exe286::rsrctab::{self, ResourceId};
+use crate::exe286::segrelocs::{AddressType, RelocationType};
+use crate::exe386::LinearExecutableLayout;
+use crate::exe386::enttab::Entry as LxEntry;
+use crate::exe386::frectab::FixupTarget;
+use crate::exe386::imptab::DllImport;
+use crate::exe386::objtab::{
+    OBJ_ALIAS_REQUIRED, OBJ_BIG, OBJ_CONFORMING, OBJ_DISCARDABLE, OBJ_EXECUTABLE, OBJ_HAS_INVALID,
+    OBJ_HAS_PRELOAD, OBJ_HAS_ZERO_FILL, OBJ_IOPL, OBJ_PERM_CONTIGUOUS, OBJ_PERM_LOCKABLE, OBJ_PERM_RESIDENT,
+    OBJ_PERM_SWAPPABLE, OBJ_READABLE, OBJ_RESOURCE, OBJ_SHARABLE, OBJ_WRITEABLE,
+};
+use crate::file::ExecutableFile;
+use crate::map;
+use std::io::{self, Write};
+
+///
+/// Writes header and all sections known for the format,
+/// separated by empty lines.
+///
+pub fn write_summary<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    write_header(file, w)?;
+    if let ExecutableFile::Mz(_) = file {
+        writeln!(w)?;
+        return write_fixups(file, w);
+    }
+    let sections = [
+        write_objects,
+        write_exports,
+        write_imports,
+        write_fixups,
+        write_resources,
+    ];
+    for section in sections {
+        writeln!(w)?;
+        section(file, w)?;
+    }
+    Ok(())
+}
+
+///
+/// Writes DOS header (if it's there) and header of the extension
+///
+pub fn write_header<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    match file {
+        ExecutableFile::Mz(layout) => writeln!(w, "{}", layout.header),
+        ExecutableFile::Ne(layout) => {
+            writeln!(w, "{}", layout.dos_header)?;
+            writeln!(w)?;
+            writeln!(w, "{}", layout.new_header)
+        }
+        ExecutableFile::Lx(layout) => {
+            if let Some(dos_header) = &layout.dos_header {
+                writeln!(w, "{}", dos_header)?;
+                writeln!(w)?;
+            }
+            writeln!(w, "{}", layout.header)
+        }
+    }
+}
+
+///
+/// Writes segments of NE or objects of LE/LX module
+///
+pub fn write_objects<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    match file {
+        ExecutableFile::Mz(_) => Ok(()),
+        ExecutableFile::Ne(layout) => write_ne_segments(layout, w),
+        ExecutableFile::Lx(layout) => write_lx_objects(layout, w),
+    }
+}
+
+///
+/// Writes entries by ordinals with their names.
+/// Entries without names have an empty name column.
+///
+pub fn write_exports<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    match file {
+        ExecutableFile::Mz(_) => Ok(()),
+        ExecutableFile::Ne(layout) => write_ne_exports(layout, w),
+        ExecutableFile::Lx(layout) => write_lx_exports(layout, w),
+    }
+}
+
+///
+/// Writes every imported procedure once with count of fixups
+/// which are referencing it
+///
+pub fn write_imports<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    let mut rows = Vec::new();
+    match file {
+        ExecutableFile::Mz(_) => return Ok(()),
+        ExecutableFile::Ne(layout) => {
+            for import in layout.imports() {
+                let procedure = match import.import.name.as_bytes().is_empty() {
+                    true => format!("@{}", import.import.ordinal),
+                    false => import.import.name.to_string_lossy(),
+                };
+                rows.push((
+                    import.import.dll_name.to_string_lossy(),
+                    procedure,
+                    import.sites.len(),
+                ));
+            }
+        }
+        ExecutableFile::Lx(layout) => {
+            for usage in layout.import_table.unique_imports() {
+                let procedure = match &usage.import {
+                    DllImport::ImportName(i) => i.import_name.to_string_lossy(),
+                    DllImport::ImportOrdinal(i) => format!("@{}", i.import_ordinal),
+                };
+                rows.push((
+                    usage.import.module_name().to_string_lossy(),
+                    procedure,
+                    usage.fixup_count,
+                ));
+            }
+        }
+    }
+
+    let module_width = rows.iter().map(|(m, _, _)| m.len()).max().unwrap_or(0).max(6);
+    let procedure_width = rows.iter().map(|(_, p, _)| p.len()).max().unwrap_or(0).max(9);
+    writeln!(w, "Imports")?;
+    writeln!(
+        w,
+        " {:<module_width$} {:<procedure_width$} references",
+        "module", "procedure"
+    )?;
+    for (module, procedure, count) in rows {
+        writeln!(
+            w,
+            " {:<module_width$} {:<procedure_width$} {}",
+            module, procedure, count
+        )?;
+    }
+    Ok(())
+}
+
+///
+/// Writes relocations: DOS relocations, per-segment relocations of NE
+/// or fixup records of LE/LX module
+///
+pub fn write_fixups<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    match file {
+        ExecutableFile::Mz(layout) => {
+            writeln!(w, "Relocations")?;
+            for pointer in &layout.relocs {
+                writeln!(w, " {:04X}:{:04X}", pointer.segment, pointer.offset)?;
+            }
+            Ok(())
+        }
+        ExecutableFile::Ne(layout) => write_ne_fixups(layout, w),
+        ExecutableFile::Lx(layout) => write_lx_fixups(layout, w),
+    }
+}
+
+///
+/// Writes resources of NE module. Resources of LE/LX modules
+/// are not read by this crate yet.
+///
+pub fn write_resources<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    let layout = match file {
+        ExecutableFile::Mz(_) => return Ok(()),
+        ExecutableFile::Ne(layout) => layout,
+        ExecutableFile::Lx(_) => {
+            writeln!(w, "Resources")?;
+            return writeln!(w, " not supported for linear executables");
+        }
+    };
+
+    let os = layout.new_header.target_os();
+    let mut rows = Vec::new();
+    for resource_type in &layout.rsrc_tab.types {
+        let type_name = match &resource_type.type_id {
+            ResourceId::Integer(id) => rsrctab::type_name(*id, &os)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("#{}", id)),
+            ResourceId::Name(name) => name.to_string_lossy(),
+        };
+        for resource in &resource_type.resources {
+            let id = match &resource.id {
+                ResourceId::Integer(id) => id.to_string(),
+                ResourceId::Name(name) => name.to_string_lossy(),
+            };
+            rows.push((type_name.clone(), id, resource));
+        }
+    }
+
+    let type_width = rows.iter().map(|(t, _, _)| t.len()).max().unwrap_or(0).max(4);
+    let id_width = rows.iter().map(|(_, id, _)| id.len()).max().unwrap_or(0).max(2);
+    writeln!(w, "Resources")?;
+    writeln!(
+        w,
+        " {:<type_width$} {:<id_width$} offset   length flags",
+        "type", "id"
+    )?;
+    for (type_name, id, resource) in rows {
+        // OS/2 resources are segments: flags are segment flags
+        let flags = match os {
+            OS::Os2 => String::new(),
+            _ => resource_flags(resource.flags),
+        };
+        row(
+            w,
+            format!(
+                " {:<type_width$} {:<id_width$} {:08X} {:05X}  {}",
+                type_name, id, resource.offset, resource.length, flags
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_ne_segments<W: Write>(layout: &NewExecutableLayout, w: &mut W) -> io::Result<()> {
+    writeln!(w, "Segments")?;
+    writeln!(w, " no. type offset   file  mem   flags")?;
+    for (i, segment) in layout.seg_tab.iter().enumerate() {
+        let header = &segment.header;
+        let class = if header.is_data() { "DATA" } else { "CODE" };
+        let (offset, length) = match header.sector_base {
+            0 => (0, 0),
+            _ => (segment.file_offset(), header.physical_size()),
+        };
+        let names = [
+            (header.is_moveable(), "moveable"),
+            (header.is_preload(), "preload"),
+            (header.is_discardable(), "discardable"),
+            (header.is_data() && header.is_read_only(), "read-only"),
+            (header.has_relocations(), "relocations"),
+        ];
+        let flags: Vec<&str> = names.iter().filter(|(set, _)| *set).map(|(_, n)| *n).collect();
+        row(
+            w,
+            format!(
+                " {:>3} {} {:08X} {:05X} {:05X} {}",
+                i + 1,
+                class,
+                offset,
+                length,
+                header.min_alloc(),
+                flags.join(", ")
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_lx_objects<W: Write>(layout: &LinearExecutableLayout, w: &mut W) -> io::Result<()> {
+    let is_lx = layout.header.is_lx();
+    writeln!(w, "Objects")?;
+    writeln!(w, " no. base     size     pages flags")?;
+    for (i, object) in layout.object_table.iter().enumerate() {
+        row(
+            w,
+            format!(
+                " {:>3} {:08X} {:08X} {:<5} {}",
+                i + 1,
+                object.virtual_addr,
+                object.virtual_size,
+                object.map_size,
+                object_flags(object.flags, is_lx).join(", ")
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_ne_exports<W: Write>(layout: &NewExecutableLayout, w: &mut W) -> io::Result<()> {
+    let resident = layout.resn_tab.iter().map(|e| (&e.name, e.ordinal));
+    let non_resident = layout.nres_tab.iter().map(|e| (&e.name, e.ordinal));
+    let names = map::names(resident.chain(non_resident));
+
+    writeln!(w, "Exports")?;
+    writeln!(w, " ord seg  offset name")?;
+    for (ordinal, entry) in layout.ent_tab.iter() {
+        let (segment, offset) = match entry {
+            Entry::Unused => continue,
+            Entry::Fixed(e) => (e.segment, e.offset),
+            Entry::Moveable(e) => (e.segment, e.offset),
+        };
+        let segment = match segment {
+            ENTRY_CONSTANT => String::from("abs "),
+            n => format!("{:04X}", n),
+        };
+        let name = names.get(ordinal).map(String::as_str).unwrap_or("");
+        row(
+            w,
+            format!(" {:>3} {} {:04X}   {}", ordinal, segment, offset, name),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_lx_exports<W: Write>(layout: &LinearExecutableLayout, w: &mut W) -> io::Result<()> {
+    let resident = layout.resident_names.iter().map(|e| (&e.name, e.ordinal));
+    let non_resident = layout.non_resident_names.iter().map(|e| (&e.name, e.ordinal));
+    let names = map::names(resident.chain(non_resident));
+    let modules = layout.import_table.modules();
+
+    writeln!(w, "Exports")?;
+    writeln!(w, " ord obj  offset   name")?;
+    for bundle in &layout.entry_table {
+        for (i, entry) in bundle.entries.iter().enumerate() {
+            let ordinal = bundle.first_ordinal.wrapping_add(i as u16);
+            let address = match entry {
+                LxEntry::Unused => continue,
+                LxEntry::Entry16(e) => format!("{:04X} {:08X}", bundle.object, e.offset),
+                LxEntry::Entry32(e) => format!("{:04X} {:08X}", bundle.object, e.offset),
+                LxEntry::EntryCallGate(e) => format!("{:04X} {:08X}", bundle.object, e.offset),
+                LxEntry::EntryForwarder(e) => {
+                    let module = modules
+                        .get((e.module_ordinal as usize).wrapping_sub(1))
+                        .map(|m| m.to_string_lossy())
+                        .unwrap_or_else(|| format!("#{}", e.module_ordinal));
+                    // bit 0 of forwarder flags: import by ordinal
+                    match e.flags & 0x01 {
+                        0 => format!("fwd  {}", module),
+                        _ => format!("fwd  {}.{}", module, e.offset_or_ordinal),
+                    }
+                }
+            };
+            let name = names.get(&ordinal).map(String::as_str).unwrap_or("");
+            row(w, format!(" {:>3} {} {}", ordinal, address, name))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_ne_fixups<W: Write>(layout: &NewExecutableLayout, w: &mut W) -> io::Result<()> {
+    writeln!(w, "Fixups")?;
+    for (i, segment) in layout.seg_tab.iter().enumerate() {
+        if segment.relocs.is_empty() {
+            continue;
+        }
+        let number = (i + 1) as i32;
+        let imports = layout.imp_tab.iter().find(|t| t.seg_number == number);
+        writeln!(w, " segment {}", number)?;
+        writeln!(w, "  offset type     target")?;
+        for reloc in &segment.relocs {
+            // module names are resolved while imports are read
+            let import = imports.and_then(|t| t.imp_list.iter().find(|i| i.seg_offset == reloc.rel_seg_ptr));
+            let module = |index: u16| {
+                import
+                    .map(|i| i.dll_name.to_string_lossy())
+                    .unwrap_or_else(|| format!("#{}", index))
+            };
+            let mut target = match &reloc.rel_type {
+                RelocationType::Internal(f) if f.int_mov => format!("entry @{}", f.int_offset),
+                RelocationType::Internal(f) => format!("{:04X}:{:04X}", f.int_seg, f.int_offset),
+                RelocationType::ImportName(f) => {
+                    let name = import.map(|i| i.name.to_string_lossy()).unwrap_or_default();
+                    format!("{}.{}", module(f.imp_mod_index), name)
+                }
+                RelocationType::ImportOrdinal(f) => format!("{}.@{}", module(f.imp_mod_index), f.imp_ordinal),
+                RelocationType::OSFixup(f) => format!("FPU {:?}", f.osf_type),
+            };
+            if reloc.rel_add {
+                target.push_str(" (additive)");
+            }
+            writeln!(
+                w,
+                "  {:04X}   {:<8} {}",
+                reloc.rel_seg_ptr,
+                address_type(AddressType::from(reloc.rel_atp)),
+                target
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_lx_fixups<W: Write>(layout: &LinearExecutableLayout, w: &mut W) -> io::Result<()> {
+    let modules = layout.import_table.modules();
+    let module = |ordinal: u16| {
+        modules
+            .get((ordinal as usize).wrapping_sub(1))
+            .map(|m| m.to_string_lossy())
+            .unwrap_or_else(|| format!("#{}", ordinal))
+    };
+
+    writeln!(w, "Fixups")?;
+    writeln!(w, " page offset type     target")?;
+    for record in &layout.fixup_records_table {
+        let mut target = match &record.target_data {
+            FixupTarget::Internal(t) => match t.target_offset {
+                Some(offset) => format!("{:04X}:{:08X}", t.object_number, offset),
+                None => format!("{:04X}", t.object_number),
+            },
+            FixupTarget::ImportedOrdinal(t) => format!("{}.@{}", module(t.module_ordinal), t.import_ordinal),
+            FixupTarget::ImportedName(t) => {
+                // imports keep 0-based module index, fixups keep 1-based ordinal
+                let name = layout.import_table.imports().iter().find_map(|i| match i {
+                    DllImport::ImportName(i)
+                        if i.module_index == t.module_ordinal.wrapping_sub(1)
+                            && i.import_name_offset == t.procedure_name_offset =>
+                    {
+                        Some(i.import_name.to_string_lossy())
+                    }
+                    _ => None,
+                });
+                format!("{}.{}", module(t.module_ordinal), name.unwrap_or_default())
+            }
+            FixupTarget::FixupViaEntryTable(t) => format!("entry @{}", t.entry_number),
+        };
+        if let Some(additive) = record.additive_value {
+            target.push_str(&format!(" + 0x{:X}", additive));
+        }
+
+        let offsets = match &record.source_offset_list {
+            Some(list) => list.clone(),
+            None => vec![record.source_offset_or_count],
+        };
+        for offset in offsets {
+            writeln!(
+                w,
+                " {:>4} {:04X}   {:<8} {}",
+                record.page,
+                offset,
+                source_type(record.source),
+                target
+            )?;
+        }
+    }
+    Ok(())
+}
+
+///
+/// Writes one line of table. Empty last column leaves no trailing spaces.
+///
+fn row<W: Write>(w: &mut W, line: String) -> io::Result<()> {
+    writeln!(w, "{}", line.trim_end())
+}
+
+fn address_type(address: AddressType) -> String {
+    let name = match address {
+        AddressType::LoByte => "lobyte",
+        AddressType::Selector => "selector",
+        AddressType::Pointer32 => "far16:16",
+        AddressType::Offset16 => "offset16",
+        AddressType::Pointer48 => "far16:32",
+        AddressType::Offset32 => "offset32",
+        AddressType::Unknown(n) => return format!("0x{:02X}", n),
+    };
+    name.to_string()
+}
+
+///
+/// Source type of LX fixup is the lower nibble of the source byte
+///
+fn source_type(source: u8) -> String {
+    let name = match source & 0x0F {
+        0x00 => "byte",
+        0x02 => "selector",
+        0x03 => "far16:16",
+        0x05 => "offset16",
+        0x06 => "far16:32",
+        0x07 => "offset32",
+        0x08 => "self32",
+        n => return format!("0x{:02X}", n),
+    };
+    name.to_string()
+}
+
+fn resource_flags(flags: u16) -> String {
+    let names = [(0x0010, "moveable"), (0x0020, "pure"), (0x0040, "preload")];
+    let active: Vec<&str> = names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, n)| *n)
+        .collect();
+    active.join(", ")
+}
+
+///
+/// `OBJ_` flags by names. Bits `0x0300` mean different things
+/// in LE and LX modules.
+///
+fn object_flags(flags: u32, is_lx: bool) -> Vec<&'static str> {
+    let flags = flags as u16;
+    let mut names = Vec::new();
+    let bits = [
+        (OBJ_READABLE, "readable"),
+        (OBJ_WRITEABLE, "writeable"),
+        (OBJ_EXECUTABLE, "executable"),
+        (OBJ_RESOURCE, "resource"),
+        (OBJ_DISCARDABLE, "discardable"),
+        (OBJ_SHARABLE, "shared"),
+        (OBJ_HAS_PRELOAD, "preload"),
+        (OBJ_HAS_INVALID, "invalid pages"),
+    ];
+    names.extend(bits.iter().filter(|(bit, _)| flags & bit != 0).map(|(_, n)| *n));
+    match (flags & OBJ_PERM_CONTIGUOUS, is_lx) {
+        (OBJ_HAS_ZERO_FILL, true) => names.push("zero-filled"),
+        (OBJ_PERM_SWAPPABLE, false) => names.push("swappable"),
+        (OBJ_PERM_RESIDENT, _) => names.push("resident"),
+        (OBJ_PERM_CONTIGUOUS, _) => names.push("contiguous"),
+        _ => {}
+    }
+    let bits = [
+        (OBJ_PERM_LOCKABLE, "lockable"),
+        (OBJ_ALIAS_REQUIRED, "alias"),
+        (OBJ_BIG, "32-bit"),
+        (OBJ_CONFORMING, "conforming"),
+        (OBJ_IOPL, "IOPL"),
+    ];
+    names.extend(bits.iter().filter(|(bit, _)| flags & bit != 0).map(|(_, n)| *n));
+    names
+}
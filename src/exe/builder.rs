@@ -0,0 +1,130 @@
+//! This module builds DOS programs: MZ header, relocations and load module.
+//! Geometry of the header is computed from the contents:
+//! ```text
+//! +------------------+ <-- 0
+//! | MZ header        | 0x40 bytes
+//! +------------------+ <-- e_lfarlc = 0x40
+//! | relocations      | e_crlc far pointers
+//! | padding          | up to paragraph (e_cparhdr)
+//! +------------------+ <-- e_cparhdr * 16
+//! | load module      |
+//! +------------------+ <-- (e_cp - 1) * 512 + e_cblp
+//! ```
+//! ```rust
+//! use os2omf::exe::builder::MzBuilder;
+//! use os2omf::exe::MzHeader;
+//! use std::io::Cursor;
+//!
+//! // mov ax, 4C00h; int 21h
+//! let program = MzBuilder::new(vec![0xB8, 0x00, 0x4C, 0xCD, 0x21]).build()?;
+//! let header = MzHeader::read(&mut Cursor::new(&program))?;
+//! assert_eq!(header.load_module_size(), 5);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::exe::reltab::{FarPointer, MzRelocationTable};
+use crate::exe::{E_LFARLC, E_MAGIC, MzHeader};
+use bytemuck::Zeroable;
+use std::io::{self, Write};
+
+///
+/// DOS program to build. Fields are copied into the header as is,
+/// sizes and pointers are computed by [MzBuilder::header].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MzBuilder {
+    /// Code and data which follow the header
+    pub load_module: Vec<u8>,
+    pub relocations: Vec<FarPointer>,
+    pub min_alloc: u16,
+    pub max_alloc: u16,
+    pub ss: u16,
+    pub sp: u16,
+    pub cs: u16,
+    pub ip: u16,
+    /// Pointer to protected-mode header, `0` for plain DOS program
+    pub lfanew: u32,
+}
+
+impl MzBuilder {
+    ///
+    /// Program of `load_module` without relocations
+    /// which takes all memory DOS has (`e_maxalloc = 0xFFFF`)
+    ///
+    pub fn new(load_module: Vec<u8>) -> Self {
+        Self {
+            load_module,
+            relocations: Vec::new(),
+            min_alloc: 0,
+            max_alloc: 0xFFFF,
+            ss: 0,
+            sp: 0,
+            cs: 0,
+            ip: 0,
+            lfanew: 0,
+        }
+    }
+    ///
+    /// Header with `e_cp`, `e_cblp`, `e_cparhdr`, `e_crlc`
+    /// and `e_lfarlc` computed for the contents
+    ///
+    pub fn header(&self) -> io::Result<MzHeader> {
+        let relocations = u16::try_from(self.relocations.len()).map_err(|_| {
+            too_large(format!("{} relocations", self.relocations.len()))
+        })?;
+        let header_size = (E_LFARLC as u64 + relocations as u64 * 4).next_multiple_of(16);
+        let image_size = header_size + self.load_module.len() as u64;
+
+        let paragraphs = u16::try_from(header_size / 16)
+            .map_err(|_| too_large(format!("header of {} bytes", header_size)))?;
+        let pages = u16::try_from(image_size.div_ceil(512))
+            .map_err(|_| too_large(format!("image of {} bytes", image_size)))?;
+
+        let mut header = MzHeader::zeroed();
+        header.e_magic = E_MAGIC;
+        header.e_cblp = (image_size % 512) as u16;
+        header.e_cp = pages;
+        header.e_crlc = relocations;
+        header.e_cparhdr = paragraphs;
+        header.e_minalloc = self.min_alloc;
+        header.e_maxalloc = self.max_alloc;
+        header.e_ss = self.ss;
+        header.e_sp = self.sp;
+        header.e_ip = self.ip;
+        header.e_cs = self.cs;
+        header.e_lfarlc = E_LFARLC;
+        header.e_lfanew = self.lfanew;
+        Ok(header)
+    }
+    ///
+    /// Writes header, relocations, padding and load module
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let header = self.header()?;
+        header.write(w)?;
+
+        let relocs = MzRelocationTable {
+            relocations: self.relocations.clone(),
+        };
+        relocs.write(w)?;
+        let written = E_LFARLC as usize + self.relocations.len() * 4;
+        let padding = header.e_cparhdr as usize * 16 - written;
+        w.write_all(&vec![0_u8; padding])?;
+
+        w.write_all(&self.load_module)
+    }
+    ///
+    /// Whole program in a buffer
+    ///
+    pub fn build(&self) -> io::Result<Vec<u8>> {
+        let mut program = Vec::new();
+        self.write(&mut program)?;
+        Ok(program)
+    }
+}
+
+fn too_large(what: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("DOS program can't hold {}", what),
+    )
+}
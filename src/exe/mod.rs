@@ -8,7 +8,7 @@
 //!
 //! Extracting information from DOS executable is easy task
 //! but not all knows what follows by the DOS header.
-//! ```rust
+//! ```rust,no_run
 //! use std::fs::File;
 //! use std::io::BufReader;
 //! use os2omf::exe::MzHeader;
@@ -20,7 +20,8 @@
 //!
 //! let dos_header = MzHeader::read(&mut file_buf)?;
 //! let dos_relocations = MzRelocationTable::read(&mut file_buf, &dos_header)?;
-//! ```    
+//! # Ok::<(), std::io::Error>(())
+//! ```
 //! 
 //! If you look into filled MZ header of any Windows executable you will see
 //! once interesting thing: "`e_lfarlc` always set 0x40". This rule was since
@@ -32,23 +33,44 @@
 //! protected-mode sections of data and code.
 //! If you see anomaly long jump at `e_lfanew` it may be
 //!  - DOS Extender's runtime instead of DOS stub (e.g. DOS4GW/DOS32a/Watcom);
-//!  - Windows386 self-executable archive (W3/W4);
+//!  - Windows386 self-executable archive (W3/W4, see `exe386::w3`);
+//!  - PharLap 386|DOS-Extender program (P2/P3, see [pharlap]);
 //!  - Invalid pointer.
+//!
+//! Readers check the pointer before following it ([MzHeader::validate_lfanew]):
+//! NE and LE/LX layouts refuse zero, pointers inside the DOS header and
+//! headers which don't fit in file with [InvalidLfanew], and
+//! [crate::file::ExecutableFile] reads such files as plain DOS programs.
 //! 
 //! Use this when you are deep dive into retro software.
+pub mod builder;
+pub mod pharlap;
 pub mod reltab;
 
+use crate::anomaly::{self, Anomaly, Severity};
 use crate::exe::reltab::MzRelocationTable;
+use crate::regions::{self, Region};
+use crate::types::readable::Readable;
+use crate::types::{ByteOrder, FieldCursor};
 use bytemuck::{Pod, Zeroable};
+use std::fmt;
 use std::fs::File;
+use crate::exe286::{NE_CIGAM, NE_MAGIC};
+use crate::exe386::header::{LE_CIGAM, LE_MAGIC, LX_CIGAM, LX_MAGIC};
 use std::io;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::io::{BufReader, ErrorKind};
 
 pub const E_MAGIC: u16 = 0x5a4d;
 pub const E_CIGAM: u16 = 0x4d5a;
 pub const E_LFARLC: u16 = 0x40;
+/// Length of NE header which `e_lfanew` points to
+pub const NE_HEADER_LEN: u64 = 0x40;
+/// Length of LE/LX header fields which `e_lfanew` points to
+/// (reserved bytes up to `0xC4` may be cut)
+pub const LX_HEADER_LEN: u64 = 0xB8;
 
+#[derive(Debug, Clone)]
 pub struct MzExecutableLayout {
     pub header: MzHeader,
     pub relocs: MzRelocationTable
@@ -58,31 +80,152 @@ impl MzExecutableLayout {
     pub fn get(file_name: &str) -> Result<Self, io::Error> {
         let file = File::open(file_name)?;
         let mut reader = BufReader::new(file);
-        let result_header = MzHeader::read(&mut reader);
-
-        let header = match result_header {
-            Ok(header) => header,
-            Err(e) => return Err(e)
-        };
-
-        let result_relocs = MzRelocationTable::read(&mut reader, &header);
-
-        let relocs = match result_relocs {
-            Ok(r) => r,
-            Err(e) => return Err(e)
-        };
+        Self::read_from(&mut reader)
+    }
+    ///
+    /// Reads DOS header and relocations from the start of `reader`
+    ///
+    pub fn read_from<T: Read + Seek>(reader: &mut T) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = MzHeader::read(reader)?;
+        let relocs = MzRelocationTable::read(reader, &header)?;
 
         Ok(Self {
             header,
             relocs
         })
     }
+    ///
+    /// Byte ranges of header, relocations and program image
+    ///
+    pub fn regions(&self) -> Vec<Region> {
+        regions::finish(regions::dos_regions(&self.header))
+    }
+    ///
+    /// DOS header points to the next (protected-mode) header.
+    /// It says nothing about what is there: use [extension_type_at] for it.
+    ///
+    pub fn has_protected_mode_extension(&self) -> bool {
+        self.header.e_lfanew != 0
+    }
+    ///
+    /// Looks for `e_lfanew` which points inside the DOS header or past the
+    /// end of file. Such programs are read as plain DOS programs, but
+    /// loader of protected-mode OS would refuse them.
+    ///
+    pub fn validate(&self, file_len: u64) -> Vec<Anomaly> {
+        let mut anomalies: Vec<Anomaly> = anomaly::check_lfanew(self.header.e_lfanew)
+            .into_iter()
+            .filter(|_| self.header.e_lfanew != 0)
+            .collect();
+        if let Err(e @ InvalidLfanew::PastEnd { .. }) = self.header.validate_lfanew(file_len, 2) {
+            anomalies.push(Anomaly::new(Severity::Warning, 0x3C, e.to_string()));
+        }
+        anomalies.extend(anomaly::check_regions(&self.regions(), file_len));
+        anomalies
+    }
+}
+///
+/// `e_lfanew` which can't point to the protected-mode header.
+/// Readers return it inside of `io::Error` of `InvalidData` kind:
+/// `error.get_ref()` downcasts to it.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidLfanew {
+    /// Zero: plain DOS program without extension
+    Zero,
+    /// Points inside the DOS header (below `0x40`)
+    InsideHeader(u32),
+    /// Header of `header_len` bytes at `e_lfanew` runs past the end of file
+    PastEnd {
+        e_lfanew: u32,
+        header_len: u64,
+        file_len: u64,
+    },
+}
+impl fmt::Display for InvalidLfanew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidLfanew::Zero => write!(f, "Extended header pointer is zero: not protected mode executable"),
+            InvalidLfanew::InsideHeader(e_lfanew) => {
+                write!(f, "Extended header pointer 0x{:X} points inside the MZ header", e_lfanew)
+            }
+            InvalidLfanew::PastEnd { e_lfanew, header_len, file_len } => write!(
+                f,
+                "Extended header pointer 0x{:X} is invalid: 0x{:X} bytes of header don't fit in file of 0x{:X} bytes",
+                e_lfanew, header_len, file_len
+            ),
+        }
+    }
+}
+impl std::error::Error for InvalidLfanew {}
+impl From<InvalidLfanew> for io::Error {
+    fn from(error: InvalidLfanew) -> Self {
+        io::Error::new(ErrorKind::InvalidData, error)
+    }
+}
+///
+/// Protected-mode executable which follows the DOS program
+/// (signature at `e_lfanew`)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionType {
+    /// New Executable, segmented 16-bit module (see [crate::exe286])
+    Ne,
+    /// Linear Executable of Windows VMM or OS/2 2.0 (see [crate::exe386])
+    Le,
+    /// Linear eXecutable of OS/2 2.0+ (see [crate::exe386])
+    Lx,
+    /// Known signature of format which is not read as a layout
+    /// ([ExtensionType::EXTENDED_SIGNATURES]) at `offset` (`e_lfanew`)
+    UnknownExtended { signature: [u8; 2], offset: u64 },
+}
+impl ExtensionType {
+    ///
+    /// Signatures at `e_lfanew` which are named but not read:
+    /// PharLap `P2`/`P3` (see [pharlap]), Borland overlay manager `FB`
+    /// and Windows 386 archives `W3`/`W4` (see [crate::exe386::w3]).
+    /// Others are bytes of DOS programs which only look like a pointer.
+    ///
+    pub const EXTENDED_SIGNATURES: [[u8; 2]; 5] = [*b"P2", *b"P3", *b"FB", *b"W3", *b"W4"];
+
+    pub fn from(magic: u16) -> Option<Self> {
+        match magic {
+            NE_MAGIC | NE_CIGAM => Some(ExtensionType::Ne),
+            LE_MAGIC | LE_CIGAM => Some(ExtensionType::Le),
+            LX_MAGIC | LX_CIGAM => Some(ExtensionType::Lx),
+            _ => None,
+        }
+    }
+}
+///
+/// Reads DOS header from the start of file and signature at `e_lfanew`.
+/// Plain DOS programs (`e_lfanew` is zero, points inside the DOS header,
+/// past the end of file or to unknown signature) have no extension.
+/// Known signatures of other formats are [ExtensionType::UnknownExtended].
+/// Reader position after the call is unspecified.
+///
+pub fn extension_type_at<T: Read + Seek>(reader: &mut T) -> io::Result<Option<ExtensionType>> {
+    reader.seek(SeekFrom::Start(0))?;
+    let header = MzHeader::read(reader)?;
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let Ok(offset) = header.validate_lfanew(file_len, 2) else {
+        return Ok(None);
+    };
+
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut magic = [0; 2];
+    reader.read_exact(&mut magic)?;
+    if ExtensionType::EXTENDED_SIGNATURES.contains(&magic) {
+        return Ok(Some(ExtensionType::UnknownExtended { signature: magic, offset }));
+    }
+    Ok(ExtensionType::from(u16::from_le_bytes(magic)))
 }
 
 ///
 /// Mark Zbikowski header of DOS programs
 ///
-#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 #[repr(C)]
 pub struct MzHeader {
     /// MZ Header signature
@@ -133,7 +276,28 @@ impl MzHeader {
         let mut buf = [0; 0x40];
         r.read_exact(&mut buf)?;
 
-        let header: MzHeader = bytemuck::cast(buf);
+        let mut f = FieldCursor::new(&buf, ByteOrder::Little);
+        let header = MzHeader {
+            e_magic: f.u16(),
+            e_cblp: f.u16(),
+            e_cp: f.u16(),
+            e_crlc: f.u16(),
+            e_cparhdr: f.u16(),
+            e_minalloc: f.u16(),
+            e_maxalloc: f.u16(),
+            e_ss: f.u16(),
+            e_sp: f.u16(),
+            e_crc: f.u16(),
+            e_ip: f.u16(),
+            e_cs: f.u16(),
+            e_lfarlc: f.u16(),
+            e_ovno: f.u16(),
+            e_res: std::array::from_fn(|_| f.u16()),
+            e_oemid: f.u16(),
+            e_oeminfo: f.u16(),
+            e_res2: std::array::from_fn(|_| f.u16()),
+            e_lfanew: f.u32(),
+        };
 
         if !header.has_valid_magic() {
             return Err(io::Error::new(ErrorKind::InvalidData, "Invalid DOS header"))
@@ -142,6 +306,56 @@ impl MzHeader {
         Ok(header)
     }
     ///
+    /// Writes the header as 0x40 bytes in little-endian order,
+    /// the same layout [MzHeader::read] reads.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let words = [
+            self.e_magic,
+            self.e_cblp,
+            self.e_cp,
+            self.e_crlc,
+            self.e_cparhdr,
+            self.e_minalloc,
+            self.e_maxalloc,
+            self.e_ss,
+            self.e_sp,
+            self.e_crc,
+            self.e_ip,
+            self.e_cs,
+            self.e_lfarlc,
+            self.e_ovno,
+        ];
+        let mut buf = Vec::with_capacity(0x40);
+        words
+            .iter()
+            .chain(&self.e_res)
+            .chain(&[self.e_oemid, self.e_oeminfo])
+            .chain(&self.e_res2)
+            .for_each(|w| buf.extend_from_slice(&w.to_le_bytes()));
+        buf.extend_from_slice(&self.e_lfanew.to_le_bytes());
+        w.write_all(&buf)
+    }
+    ///
+    /// Checks that protected-mode header of `header_len` bytes at `e_lfanew`
+    /// follows the DOS header and fits in file of `file_len` bytes.
+    /// Returns `e_lfanew` as file offset.
+    ///
+    pub fn validate_lfanew(&self, file_len: u64, header_len: u64) -> Result<u64, InvalidLfanew> {
+        let offset = self.e_lfanew as u64;
+        match self.e_lfanew {
+            0 => Err(InvalidLfanew::Zero),
+            // DOS header takes 0x40 bytes
+            e_lfanew if e_lfanew < 0x40 => Err(InvalidLfanew::InsideHeader(e_lfanew)),
+            e_lfanew if offset + header_len > file_len => Err(InvalidLfanew::PastEnd {
+                e_lfanew,
+                header_len,
+                file_len,
+            }),
+            _ => Ok(offset),
+        }
+    }
+    ///
     /// Tries check out signature of PC-DOS executable
     ///
     pub fn has_valid_magic(&self) -> bool {
@@ -158,7 +372,7 @@ impl MzHeader {
         let mut pos: usize = 0;
         let mut sum: u16 = 0;
 
-        let buffer = bytemuck::bytes_of(&self.e_crc);
+        let buffer = self.e_crc.to_le_bytes();
 
         while pos < buffer.len() {
             // iterate each buffer element
@@ -183,4 +397,65 @@ impl MzHeader {
     pub fn has_default_rlcptr(&self) -> bool {
         self.e_lfarlc == E_LFARLC
     }
+    ///
+    /// Size of DOS program image in bytes (with header).
+    /// `e_cp` is a count of 512-bytes pages and `e_cblp` is
+    /// a count of bytes used at the last page (0 means whole page).
+    ///
+    pub fn image_size(&self) -> u64 {
+        if self.e_cp == 0 {
+            return 0;
+        }
+        let last_page = match self.e_cblp {
+            0 => 512,
+            n => n as u64,
+        };
+        (self.e_cp as u64 - 1) * 512 + last_page
+    }
+    ///
+    /// Size of load module: program image without the header
+    /// of `e_cparhdr` paragraphs. This is what DOS loads into memory.
+    ///
+    pub fn load_module_size(&self) -> u64 {
+        self.image_size().saturating_sub(self.e_cparhdr as u64 * 16)
+    }
+}
+impl Readable<MzHeader> for MzHeader {
+    fn read<R: Read>(r: &mut R) -> io::Result<MzHeader> {
+        Self::read(r)
+    }
+}
+///
+/// Short summary of the header for humans:
+/// ```text
+/// MZ executable
+///   Image size:   1152 bytes
+///   Header size:  64 bytes
+///   Relocations:  0
+///   Stack:        0000:00B8
+///   Entry point:  0000:0000
+///   Allocation:   0x0000..0xFFFF paragraphs
+///   CRC:          invalid
+///   Extension:    at 0x00000080
+/// ```
+///
+impl fmt::Display for MzHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magic = self.e_magic.to_le_bytes();
+        writeln!(f, "{} executable", String::from_utf8_lossy(&magic))?;
+        writeln!(f, "  Image size:   {} bytes", self.image_size())?;
+        writeln!(f, "  Header size:  {} bytes", self.e_cparhdr as u64 * 16)?;
+        writeln!(f, "  Relocations:  {}", self.e_crlc)?;
+        writeln!(f, "  Stack:        {:04X}:{:04X}", self.e_ss, self.e_sp)?;
+        writeln!(f, "  Entry point:  {:04X}:{:04X}", self.e_cs, self.e_ip)?;
+        writeln!(f, "  Allocation:   0x{:04X}..0x{:04X} paragraphs", self.e_minalloc, self.e_maxalloc)?;
+        let crc = if self.has_valid_crc() { "valid" } else { "invalid" };
+        match self.e_lfanew {
+            0 => write!(f, "  CRC:          {}", crc),
+            next => {
+                writeln!(f, "  CRC:          {}", crc)?;
+                write!(f, "  Extension:    at 0x{:08X}", next)
+            }
+        }
+    }
 }
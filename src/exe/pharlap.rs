@@ -0,0 +1,103 @@
+//! This module represents headers of PharLap 386|DOS-Extender programs.
+//! `P3` (32-bit) and `P2` (16-bit) headers have the same layout, and
+//! every pointer of them is relative to the start of the header:
+//! ```text
+//! +-------------------+ <-- header (e_lfanew when DOS stub is bound)
+//! | P3 header         | signature, level, sizes, checksum
+//! |                   | run-time parameters, relocations, segments
+//! |                   | load image offset and size
+//! |                   | ... (0x180 bytes, rest is not read here)
+//! +-------------------+
+//! | load image        | flat (level 1) or multisegmented (level 2)
+//! ```
+//! Only the part which locates the load image is read. Programs
+//! themselves are not parsed: [crate::exe::ExtensionType::UnknownExtended]
+//! names them and [PharLapHeader] tells where their image is.
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// `P2`: 286|DOS-Extender program
+pub const P2_MAGIC: u16 = 0x3250;
+/// `P3`: 386|DOS-Extender program
+pub const P3_MAGIC: u16 = 0x3350;
+/// Level of flat load image
+pub const LEVEL_FLAT: u16 = 1;
+/// Level of multisegmented load image
+pub const LEVEL_MULTISEGMENTED: u16 = 2;
+
+///
+/// Beginning of PharLap header up to load image pointers.
+/// Offsets are relative to the start of the header.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PharLapHeader {
+    pub magic: u16,
+    /// 1 is flat, 2 is multisegmented
+    pub level: u16,
+    pub header_size: u16,
+    pub file_size: u32,
+    pub checksum: u16,
+    pub rtp_offset: u32,
+    pub rtp_size: u32,
+    pub reloc_offset: u32,
+    pub reloc_size: u32,
+    pub segtab_offset: u32,
+    pub segtab_size: u32,
+    pub segtab_entry_size: u16,
+    pub load_offset: u32,
+    pub load_size: u32,
+}
+
+impl PharLapHeader {
+    /// Bytes of header which are read
+    pub const LEN: usize = 0x2E;
+    ///
+    /// Reads header at `offset`. Signatures other than `P2`/`P3`
+    /// are `InvalidData`.
+    ///
+    pub fn read<T: Read + Seek>(reader: &mut T, offset: u64) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut buf = [0_u8; Self::LEN];
+        reader.read_exact(&mut buf)?;
+        let word = |pos: usize| u16::from_le_bytes([buf[pos], buf[pos + 1]]);
+        let dword = |pos: usize| u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]]);
+
+        let magic = word(0);
+        if magic != P2_MAGIC && magic != P3_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid PharLap magic 0x{:04X} at 0x{:X}", magic, offset),
+            ));
+        }
+
+        Ok(Self {
+            magic,
+            level: word(0x02),
+            header_size: word(0x04),
+            file_size: dword(0x06),
+            checksum: word(0x0A),
+            rtp_offset: dword(0x0C),
+            rtp_size: dword(0x10),
+            reloc_offset: dword(0x14),
+            reloc_size: dword(0x18),
+            segtab_offset: dword(0x1C),
+            segtab_size: dword(0x20),
+            segtab_entry_size: word(0x24),
+            load_offset: dword(0x26),
+            load_size: dword(0x2A),
+        })
+    }
+    pub fn is_386(&self) -> bool {
+        self.magic == P3_MAGIC
+    }
+    pub fn is_flat(&self) -> bool {
+        self.level == LEVEL_FLAT
+    }
+    ///
+    /// Absolute bounds `start..end` of load image in file
+    /// which header starts at `offset`
+    ///
+    pub fn load_image(&self, offset: u64) -> (u64, u64) {
+        let start = offset + self.load_offset as u64;
+        (start, start + self.load_size as u64)
+    }
+}
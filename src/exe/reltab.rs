@@ -1,17 +1,23 @@
 use crate::exe::MzHeader;
+use crate::types::table_accessors;
 use bytemuck::{Pod, Zeroable};
 use std::io;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MzRelocationTable {
     pub relocations: Vec<FarPointer>,
 }
+table_accessors!(MzRelocationTable, relocations, FarPointer);
+///
+/// Far pointer to the word which is relocated. In file it is
+/// `offset` word first, `segment` word second.
+///
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zeroable, Pod)]
 pub struct FarPointer {
-    pub segment: u16,
     pub offset: u16,
+    pub segment: u16,
 }
 impl MzRelocationTable {
     pub fn read<T: Read + Seek>(reader: &mut T, header: &MzHeader) -> io::Result<Self> {
@@ -21,9 +27,30 @@ impl MzRelocationTable {
         for _ in 0..header.e_crlc {
             let mut far_buff = [0_u8; 4];
             reader.read_exact(&mut far_buff)?;
-            relocations.push(bytemuck::pod_read_unaligned(&far_buff))
+            relocations.push(FarPointer {
+                offset: u16::from_le_bytes([far_buff[0], far_buff[1]]),
+                segment: u16::from_le_bytes([far_buff[2], far_buff[3]]),
+            })
         }
 
         Ok(Self { relocations })
     }
+    ///
+    /// Writes pointers in the same order of words as `read` reads them
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for pointer in &self.relocations {
+            w.write_all(&pointer.offset.to_le_bytes())?;
+            w.write_all(&pointer.segment.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+impl MzRelocationTable {
+    ///
+    /// Same as `len`: count of `e_crlc` relocations read
+    ///
+    pub fn count(&self) -> usize {
+        self.relocations.len()
+    }
 }
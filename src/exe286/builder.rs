@@ -0,0 +1,542 @@
+//! This module builds segmented New Executable modules from
+//! descriptions of segments, exports and imports. All tables
+//! follow the header one-by-one, segments are aligned by sectors:
+//! ```text
+//! +------------------------+ <-- 0
+//! | DOS stub               | see [crate::exe::builder::MzBuilder]
+//! +------------------------+ <-- e_lfanew = 0x40
+//! | NE header              |
+//! | segment table          |
+//! | resident names         | e_rsrc_tab = e_resn_tab: no resources
+//! | module references      |
+//! | imported names         |
+//! | entry table            |
+//! | non-resident names     |
+//! +------------------------+ <-- sector_base << e_align
+//! | segment data           |
+//! | relocations            | if segment has imports or relocations
+//! +------------------------+
+//! ```
+//! ```rust
+//! use os2omf::exe286::builder::{ExportSpec, ImportSpec, NeBuilder, SegmentSpec};
+//! use os2omf::exe286::NewExecutableLayout;
+//! use os2omf::omf::coment::ImportEntry;
+//! use std::io::Cursor;
+//!
+//! let mut builder = NeBuilder::new("HELLO");
+//! // call far 0000:FFFF; retf
+//! builder.segments.push(SegmentSpec::code(vec![0x9A, 0xFF, 0xFF, 0, 0, 0xCB]));
+//! builder.exports.push(ExportSpec::new("HELLO", 1, 1, 0));
+//! builder.imports.push(ImportSpec::new("KERNEL", ImportEntry::Ordinal(70), 1, 1));
+//!
+//! let module = builder.build()?;
+//! let layout = NewExecutableLayout::read_from(&mut Cursor::new(&module))?;
+//! assert_eq!(layout.imp_tab[0].imp_list[0].display_symbol(), "#70");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::exe::builder::MzBuilder;
+use crate::exe286::enttab::{Entry, EntryTable, FixedEntry, MoveableEntry, MOVEABLE_ENTRY_MAGIC};
+use crate::exe286::header::NewExecutableHeader;
+use crate::exe286::modtab::ModuleReferencesTable;
+use crate::exe286::nrestab::{NonResidentNameEntry, NonResidentNameTable};
+use crate::exe286::resntab::{ResidentNameEntry, ResidentNameTable};
+use crate::exe286::segrelocs::{
+    ImportName, ImportOrdinal, RelocationEntry, RelocationTable, RelocationType,
+};
+use crate::exe286::segtab::{SEG_DATA, SEG_RELOCS, SegmentHeader};
+use crate::exe286::NE_MAGIC;
+use crate::omf::coment::ImportEntry;
+use crate::types::PascalString;
+use std::io::{self, Write};
+
+/// Offset of NE header after the DOS stub
+const NE_OFFSET: u32 = 0x40;
+/// Relocation record patches 32-bit far pointer `segment:offset`
+pub const ADDRESS_POINTER32: u8 = 0x03;
+
+///
+/// Segment of module. Empty `data` makes segment without data
+/// in file which loader fills with `min_alloc` zeros.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentSpec {
+    pub data: Vec<u8>,
+    /// Segment flags. [SEG_RELOCS] is set by builder when it's needed
+    pub flags: u16,
+    pub min_alloc: u16,
+    /// Internal and OS fixups. Relocations of imports are made from [ImportSpec]
+    pub relocations: Vec<RelocationEntry>,
+}
+
+impl SegmentSpec {
+    pub fn code(data: Vec<u8>) -> Self {
+        let min_alloc = data.len() as u16;
+        Self {
+            data,
+            flags: 0,
+            min_alloc,
+            relocations: Vec::new(),
+        }
+    }
+    pub fn data(data: Vec<u8>) -> Self {
+        Self {
+            flags: SEG_DATA,
+            ..Self::code(data)
+        }
+    }
+}
+
+///
+/// Exported entry `segment:offset` with its ordinal and name
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSpec {
+    pub name: String,
+    pub ordinal: u16,
+    /// 1-based segment number
+    pub segment: u8,
+    pub offset: u16,
+    /// Entry flags: `0x01` exported, `0x02` uses shared data segment
+    pub flags: u8,
+    /// Entry of moveable segment goes through `INT 3Fh` thunk
+    pub moveable: bool,
+    /// Name is kept in resident names table, else in non-resident one
+    pub resident: bool,
+}
+
+impl ExportSpec {
+    ///
+    /// Resident exported entry of fixed segment
+    ///
+    pub fn new(name: &str, ordinal: u16, segment: u8, offset: u16) -> Self {
+        Self {
+            name: name.to_string(),
+            ordinal,
+            segment,
+            offset,
+            flags: 0x01,
+            moveable: false,
+            resident: true,
+        }
+    }
+}
+
+///
+/// Dynamic link which patches far pointer at `offset` of `segment`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSpec {
+    /// Name of DLL
+    pub module: String,
+    pub entry: ImportEntry,
+    /// 1-based segment number of the site
+    pub segment: u16,
+    pub offset: u16,
+    /// Address type of relocation, see [crate::exe286::segrelocs::AddressType]
+    pub address_type: u8,
+}
+
+impl ImportSpec {
+    pub fn new(module: &str, entry: ImportEntry, segment: u16, offset: u16) -> Self {
+        Self {
+            module: module.to_string(),
+            entry,
+            segment,
+            offset,
+            address_type: ADDRESS_POINTER32,
+        }
+    }
+}
+
+///
+/// Segmented module to build. Fields are copied into the header as is,
+/// tables, pointers and sizes are computed by [NeBuilder::write].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeBuilder {
+    pub module_name: String,
+    /// First name of non-resident names table
+    pub description: String,
+    /// Raw `e_flags`, see [crate::exe286::header::ModuleFlags]
+    pub flags: u16,
+    /// Raw `e_os`, see [crate::exe286::header::OS]
+    pub os: u8,
+    /// Segments are aligned by `1 << alignment` bytes
+    pub alignment: u16,
+    pub autodata: u16,
+    pub heap: u16,
+    pub stack: u16,
+    /// Entry point as 1-based segment number and offset
+    pub cs: u16,
+    pub ip: u16,
+    pub ss: u16,
+    pub sp: u16,
+    pub segments: Vec<SegmentSpec>,
+    pub exports: Vec<ExportSpec>,
+    pub imports: Vec<ImportSpec>,
+}
+
+///
+/// Tables of module in order of their placement
+///
+struct Tables {
+    segments: Vec<SegmentHeader>,
+    relocations: Vec<RelocationTable>,
+    data: Vec<Vec<u8>>,
+    resn_tab: ResidentNameTable,
+    mod_tab: ModuleReferencesTable,
+    imp_tab: Vec<u8>,
+    ent_tab: EntryTable,
+    nres_tab: NonResidentNameTable,
+}
+
+impl NeBuilder {
+    ///
+    /// Windows module without segments, exports and imports,
+    /// which segments are aligned by 16 bytes
+    ///
+    pub fn new(module_name: &str) -> Self {
+        Self {
+            module_name: module_name.to_string(),
+            description: module_name.to_string(),
+            flags: 0,
+            os: 2,
+            alignment: 4,
+            autodata: 0,
+            heap: 0,
+            stack: 0,
+            cs: 0,
+            ip: 0,
+            ss: 0,
+            sp: 0,
+            segments: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+    ///
+    /// Writes DOS stub, header, tables and segments
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let tables = self.tables()?;
+
+        let mut stub = MzBuilder::new(Vec::new());
+        stub.lfanew = NE_OFFSET;
+        let stub = stub.build()?;
+
+        let mut header = self.header(&tables)?;
+        let mut resident = Vec::new();
+        tables.resn_tab.write(&mut resident)?;
+        let mut modules = Vec::new();
+        tables.mod_tab.write(&mut modules)?;
+        let mut entries = Vec::new();
+        tables.ent_tab.write(&mut entries)?;
+        let mut non_resident = Vec::new();
+        tables.nres_tab.write(&mut non_resident)?;
+
+        // pointers of tables are relative to NE header
+        let mut position = 0x40 + tables.segments.len() * 8;
+        header.e_seg_tab = 0x40;
+        header.e_rsrc_tab = relative(position)?;
+        header.e_resn_tab = relative(position)?;
+        position += resident.len();
+        header.e_mod_tab = relative(position)?;
+        position += modules.len();
+        header.e_imp_tab = relative(position)?;
+        position += tables.imp_tab.len();
+        header.e_ent_tab = relative(position)?;
+        // zero byte which ends entry table isn't counted
+        header.e_cb_ent = relative(entries.len() - 1)?;
+        position += entries.len();
+        header.e_nres_tab = NE_OFFSET + position as u32;
+        header.e_cbnres = relative(non_resident.len())?;
+        position += non_resident.len();
+
+        // segments follow the tables from the next sector
+        let mut file = stub;
+        file.resize(NE_OFFSET as usize, 0);
+        let mut image = Vec::new();
+        let mut segments = tables.segments;
+        let sector = 1_usize << self.alignment;
+        let mut offset = (NE_OFFSET as usize + position).next_multiple_of(sector);
+        for (i, segment) in segments.iter_mut().enumerate() {
+            let data = &tables.data[i];
+            if data.is_empty() {
+                continue;
+            }
+            segment.sector_base = u16::try_from(offset >> self.alignment).map_err(|_| {
+                invalid_input(format!("Segment #{} is out of sectors", i + 1))
+            })?;
+            image.resize(offset - NE_OFFSET as usize - position, 0);
+            image.extend_from_slice(data);
+            if segment.has_relocations() {
+                tables.relocations[i].write(&mut image)?;
+            }
+            offset = (NE_OFFSET as usize + position + image.len()).next_multiple_of(sector);
+        }
+
+        header.write(&mut file)?;
+        for segment in &segments {
+            segment.write(&mut file)?;
+        }
+        file.extend_from_slice(&resident);
+        file.extend_from_slice(&modules);
+        file.extend_from_slice(&tables.imp_tab);
+        file.extend_from_slice(&entries);
+        file.extend_from_slice(&non_resident);
+        file.extend_from_slice(&image);
+        w.write_all(&file)
+    }
+    ///
+    /// Whole module in a buffer
+    ///
+    pub fn build(&self) -> io::Result<Vec<u8>> {
+        let mut module = Vec::new();
+        self.write(&mut module)?;
+        Ok(module)
+    }
+
+    fn header(&self, tables: &Tables) -> io::Result<NewExecutableHeader> {
+        let moveable = tables
+            .ent_tab
+            .entries
+            .iter()
+            .filter(|(_, e)| matches!(e, Entry::Moveable(_)))
+            .count();
+        Ok(NewExecutableHeader {
+            e_magic: NE_MAGIC.to_le_bytes(),
+            e_link_maj: NewExecutableHeader::LINK_VERSION_510.0,
+            e_link_min: NewExecutableHeader::LINK_VERSION_510.1,
+            e_ent_tab: 0,
+            e_cb_ent: 0,
+            e_load_crc: 0,
+            e_flags: self.flags,
+            e_autodata: self.autodata,
+            e_heap: self.heap,
+            e_stack: self.stack,
+            e_csip: ((self.cs as u32) << 16) | self.ip as u32,
+            e_sssp: ((self.ss as u32) << 16) | self.sp as u32,
+            e_cseg: relative(tables.segments.len())?,
+            e_cmod: relative(tables.mod_tab.len())?,
+            e_cbnres: 0,
+            e_seg_tab: 0,
+            e_rsrc_tab: 0,
+            e_resn_tab: 0,
+            e_mod_tab: 0,
+            e_imp_tab: 0,
+            e_nres_tab: 0,
+            e_cmov_ent: moveable as u16,
+            e_align: self.alignment,
+            e_crsrc: 0,
+            e_os: self.os,
+            e_flag_others: 0,
+            e_ret_thunk: 0,
+            e_segref_thunk: 0,
+            min_code_swap: 0,
+            expected_win_ver: [0, 0],
+        })
+    }
+
+    fn tables(&self) -> io::Result<Tables> {
+        if self.alignment == 0 || self.alignment > 15 {
+            return Err(invalid_input(format!(
+                "Alignment shift {} is out of range",
+                self.alignment
+            )));
+        }
+        // segment byte of bundle: 0 marks unused entries, 0xFF moveable ones
+        if let Some(export) = self.exports.iter().find(|e| e.segment == 0 || e.segment == 0xFF) {
+            return Err(invalid_input(format!(
+                "Export {} has segment {} which is reserved for bundles",
+                export.name, export.segment
+            )));
+        }
+        let mut data: Vec<Vec<u8>> = self.segments.iter().map(|s| s.data.clone()).collect();
+        let mut relocations: Vec<RelocationTable> = self
+            .segments
+            .iter()
+            .map(|s| RelocationTable {
+                rel_entries: s.relocations.clone(),
+            })
+            .collect();
+
+        // imported names table starts with zero byte:
+        // offset 0 is never a name
+        let mut imp_tab = vec![0_u8];
+        let mut modules: Vec<(String, u16)> = Vec::new();
+        let mut procedures: Vec<(String, u16)> = Vec::new();
+        let name_offset = |names: &mut Vec<(String, u16)>, imp_tab: &mut Vec<u8>, name: &str| {
+            if let Some((_, offset)) = names.iter().find(|(n, _)| n == name) {
+                return Ok(*offset);
+            }
+            let offset = relative(imp_tab.len())?;
+            imp_tab.push(pascal(name)?.pascal_len());
+            imp_tab.extend_from_slice(name.as_bytes());
+            names.push((name.to_string(), offset));
+            Ok::<u16, io::Error>(offset)
+        };
+        for import in &self.imports {
+            let index = (import.segment as usize).wrapping_sub(1);
+            let Some(segment) = data.get_mut(index) else {
+                return Err(invalid_input(format!(
+                    "Import of {} refers to missing segment #{}",
+                    import.module, import.segment
+                )));
+            };
+            // the site is the only place of relocation chain
+            let site = import.offset as usize;
+            let Some(word) = segment.get_mut(site..site + 2) else {
+                return Err(invalid_input(format!(
+                    "Import site 0x{:04X} is out of segment #{}",
+                    import.offset, import.segment
+                )));
+            };
+            word.copy_from_slice(&0xFFFF_u16.to_le_bytes());
+
+            name_offset(&mut modules, &mut imp_tab, &import.module)?;
+            let module = modules.iter().position(|(n, _)| *n == import.module).unwrap_or(0);
+            let imp_mod_index = module as u16 + 1;
+            let (rel_rtp, rel_type) = match &import.entry {
+                ImportEntry::Ordinal(imp_ordinal) => (
+                    0x01,
+                    RelocationType::ImportOrdinal(ImportOrdinal {
+                        imp_mod_index,
+                        imp_ordinal: *imp_ordinal,
+                    }),
+                ),
+                ImportEntry::Name(name) => (
+                    0x02,
+                    RelocationType::ImportName(ImportName {
+                        imp_mod_index,
+                        imp_offset: name_offset(&mut procedures, &mut imp_tab, name)?,
+                    }),
+                ),
+            };
+            relocations[index].rel_entries.push(RelocationEntry {
+                rel_rtp,
+                rel_atp: import.address_type,
+                rel_add: false,
+                rel_seg_ptr: import.offset,
+                rel_type,
+            });
+        }
+
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for (i, spec) in self.segments.iter().enumerate() {
+            let length = spec.data.len();
+            if length > 0x10000 {
+                return Err(invalid_input(format!(
+                    "Segment #{} of {} bytes is larger than 64K",
+                    i + 1,
+                    length
+                )));
+            }
+            let mut flags = spec.flags & !SEG_RELOCS;
+            if !relocations[i].rel_entries.is_empty() {
+                flags |= SEG_RELOCS;
+            }
+            segments.push(SegmentHeader {
+                sector_base: 0,
+                // zero length means 64K
+                sector_length: length as u16,
+                flags,
+                min_alloc: spec.min_alloc,
+            });
+        }
+
+        Ok(Tables {
+            segments,
+            relocations,
+            data,
+            resn_tab: self.resident_names()?,
+            mod_tab: ModuleReferencesTable {
+                m_offsets: modules.iter().map(|(_, offset)| *offset).collect(),
+            },
+            imp_tab,
+            ent_tab: self.entries()?,
+            nres_tab: self.non_resident_names()?,
+        })
+    }
+    ///
+    /// Ordinals follow one-by-one from `@1`, gaps are unused entries
+    ///
+    fn entries(&self) -> io::Result<EntryTable> {
+        let last = self.exports.iter().map(|e| e.ordinal).max().unwrap_or(0);
+        let mut entries: Vec<(u16, Entry)> = (1..=last).map(|o| (o, Entry::Unused)).collect();
+
+        for export in &self.exports {
+            let slot = match (export.ordinal as usize).checked_sub(1) {
+                Some(index) => &mut entries[index].1,
+                None => {
+                    return Err(invalid_input(format!(
+                        "Export {} has ordinal @0",
+                        export.name
+                    )));
+                }
+            };
+            if *slot != Entry::Unused {
+                return Err(invalid_input(format!(
+                    "Export {} has ordinal @{} of another export",
+                    export.name, export.ordinal
+                )));
+            }
+            *slot = match export.moveable {
+                true => Entry::Moveable(MoveableEntry {
+                    flags: export.flags,
+                    magic: MOVEABLE_ENTRY_MAGIC,
+                    segment: export.segment,
+                    offset: export.offset,
+                }),
+                false => Entry::Fixed(FixedEntry {
+                    segment: export.segment,
+                    flags: export.flags,
+                    offset: export.offset,
+                }),
+            };
+        }
+        Ok(EntryTable { entries })
+    }
+
+    fn resident_names(&self) -> io::Result<ResidentNameTable> {
+        let mut entries = vec![ResidentNameEntry {
+            name: pascal(&self.module_name)?,
+            ordinal: 0,
+        }];
+        for export in self.exports.iter().filter(|e| e.resident) {
+            entries.push(ResidentNameEntry {
+                name: pascal(&export.name)?,
+                ordinal: export.ordinal,
+            });
+        }
+        Ok(ResidentNameTable { entries })
+    }
+
+    fn non_resident_names(&self) -> io::Result<NonResidentNameTable> {
+        let mut entries = vec![NonResidentNameEntry {
+            name: pascal(&self.description)?,
+            ordinal: 0,
+        }];
+        for export in self.exports.iter().filter(|e| !e.resident) {
+            entries.push(NonResidentNameEntry {
+                name: pascal(&export.name)?,
+                ordinal: export.ordinal,
+            });
+        }
+        Ok(NonResidentNameTable { entries })
+    }
+}
+
+fn pascal(name: &str) -> io::Result<PascalString> {
+    let length = u8::try_from(name.len())
+        .map_err(|_| invalid_input(format!("Name {} is longer than 255 bytes", name)))?;
+    Ok(PascalString::new(length, name.as_bytes().to_vec()))
+}
+
+fn relative(value: usize) -> io::Result<u16> {
+    u16::try_from(value)
+        .map_err(|_| invalid_input(format!("Tables of module are larger than 64K: 0x{:X}", value)))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
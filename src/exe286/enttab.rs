@@ -21,7 +21,8 @@
 //! That's why procedure ordinals not always follows one-by-one. 
 //! Unused entries enumerates too. And it helps us to make @1 export procedure
 //! and @680 exporting procedure. Space between will be big bundle of unused entries.
-use std::io::{self, Read, Seek, SeekFrom};
+use crate::types::table_accessors;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 ///
 /// This table contains one member for every entry point in the program (EXE/DRV/SYS) or
@@ -40,30 +41,73 @@ use std::io::{self, Read, Seek, SeekFrom};
 ///
 /// Open Watcom 1.8 links NE segmented programs correctly (bases on Microsoft link 5.10)
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EntryTable {
-    pub entries: Vec<Entry>,
+    /// Entries with their ordinals. Unused entries are kept too,
+    /// so ordinals follow one-by-one from `@1`.
+    pub entries: Vec<(u16, Entry)>,
 }
+table_accessors!(EntryTable, entries, (u16, Entry));
 
 impl EntryTable {
+    ///
+    /// Reads exactly `cb_ent_tab` bytes of entry table.
+    /// Bundles which are not fitting into declared size are errors.
+    /// Moveable entries are read whatever thunk they hold: entry without
+    /// `INT 3Fh` (see [MoveableEntry::validate_magic]) is a warning of
+    /// [crate::exe286::NewExecutableLayout::validate].
+    ///
     pub fn read<R: Read + Seek>(
         reader: &mut R,
         e_enttab: u64,
         cb_ent_tab: u16,
     ) -> io::Result<Self> {
-        let mut entries: Vec<Entry> = Vec::new();
+        Self::read_bundles(reader, e_enttab, cb_ent_tab, true)
+    }
+    ///
+    /// Like [EntryTable::read] but stops at the last bundle
+    /// which fits into `cb_ent_tab` bytes instead of error.
+    ///
+    pub fn read_lenient<R: Read + Seek>(
+        reader: &mut R,
+        e_enttab: u64,
+        cb_ent_tab: u16,
+    ) -> io::Result<Self> {
+        Self::read_bundles(reader, e_enttab, cb_ent_tab, false)
+    }
+
+    fn read_bundles<R: Read + Seek>(
+        reader: &mut R,
+        e_enttab: u64,
+        cb_ent_tab: u16,
+        strict: bool,
+    ) -> io::Result<Self> {
+        let mut entries: Vec<(u16, Entry)> = Vec::new();
         // In practice: pointer checking optional operation too
         // If file really linked as New Executable (by Microsoft LINK.EXE)
         // Independent on format version -- wrong pointer *always* return empty entry table
         reader.seek(SeekFrom::Start(e_enttab))?;
         let mut bytes_remaining = cb_ent_tab;
-        let mut _ordinal: u16 = 1; // entry index means ordinal in non/resident names tables
+        let mut ordinal: u16 = 1; // entry index means ordinal in non/resident names tables
+        let inconsistent = |message: String| match strict {
+            true => Err(io::Error::new(io::ErrorKind::InvalidData, message)),
+            false => Ok(()),
+        };
 
         while bytes_remaining > 0 {
             // Read bundle header
+            bytes_remaining = match bytes_remaining.checked_sub(2) {
+                Some(remaining) => remaining,
+                None => {
+                    inconsistent(format!(
+                        "Bundle header exceeds remaining bytes: remaining={}",
+                        bytes_remaining
+                    ))?;
+                    break;
+                }
+            };
             let mut buffer = [0; 2];
             reader.read_exact(&mut buffer)?;
-            bytes_remaining -= 2;
 
             let entries_count = buffer[0];
             let seg_id = buffer[1];
@@ -75,9 +119,10 @@ impl EntryTable {
 
             if seg_id == 0 {
                 // Unused entries (padding between actual entries)
+                // take only the bundle header bytes
                 for _ in 0..entries_count {
-                    entries.push(Entry::Unused);
-                    _ordinal += 1;
+                    entries.push((ordinal, Entry::Unused));
+                    ordinal = ordinal.wrapping_add(1);
                 }
                 continue;
             }
@@ -89,16 +134,16 @@ impl EntryTable {
             };
             let bundle_size = (entries_count as u16) * entry_size;
 
-            if bundle_size > bytes_remaining {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!(
+            bytes_remaining = match bytes_remaining.checked_sub(bundle_size) {
+                Some(remaining) => remaining,
+                None => {
+                    inconsistent(format!(
                         "Bundle size exceeds remaining bytes: bundle_size={}, remaining={}",
                         bundle_size, bytes_remaining
-                    ),
-                ));
-            }
-            bytes_remaining -= bundle_size;
+                    ))?;
+                    break;
+                }
+            };
 
             for _ in 0..entries_count {
                 let entry = if seg_id == 0xFF {
@@ -106,23 +151,116 @@ impl EntryTable {
                 } else {
                     Entry::Fixed(FixedEntry::read(reader, seg_id)?)
                 };
-                entries.push(entry);
-                _ordinal += 1;
+                entries.push((ordinal, entry));
+                ordinal = ordinal.wrapping_add(1);
             }
         }
 
         Ok(Self { entries })
     }
+    ///
+    /// Writes entries bundled the way LINK does: runs of unused entries,
+    /// fixed entries of the same segment and moveable entries
+    /// become bundles up to 255 entries, then zero byte ends the table.
+    /// `e_cb_ent` of the header is the written size without this byte.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut rest = self.entries.as_slice();
+        while let Some((_, first)) = rest.first() {
+            let kind = Self::bundle_type(first);
+            let count = rest
+                .iter()
+                .take(u8::MAX as usize)
+                .take_while(|(_, entry)| Self::bundle_type(entry) == kind)
+                .count();
+            let (bundle, next) = rest.split_at(count);
+
+            w.write_all(&[count as u8, kind])?;
+            for (_, entry) in bundle {
+                match entry {
+                    Entry::Unused => {}
+                    Entry::Fixed(f) => f.write(w)?,
+                    Entry::Moveable(m) => m.write(w)?,
+                }
+            }
+            rest = next;
+        }
+        w.write_all(&[0])
+    }
+    ///
+    /// Segment byte of bundle header: `0` for unused entries,
+    /// `0xFF` for moveable ones and segment number for fixed ones
+    ///
+    fn bundle_type(entry: &Entry) -> u8 {
+        match entry {
+            Entry::Unused => 0,
+            Entry::Fixed(f) => f.segment,
+            Entry::Moveable(_) => 0xFF,
+        }
+    }
+    ///
+    /// Returns entry by its ordinal (`@1` is the first entry)
+    ///
+    pub fn by_ordinal(&self, ordinal: u16) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|(o, _)| *o == ordinal)
+            .map(|(_, entry)| entry)
+    }
+    ///
+    /// Count of ordinal slots, unused entries are counted too
+    ///
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+    ///
+    /// Count of entries which are really exported (not `Unused`)
+    ///
+    pub fn export_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| !matches!(entry, Entry::Unused))
+            .count()
+    }
+    ///
+    /// Like [EntryTable::by_ordinal] but takes the entry by index
+    /// `ordinal - 1`: ordinals follow one-by-one from `@1`.
+    ///
+    pub fn get_entry(&self, ordinal: u16) -> Option<&Entry> {
+        let index = (ordinal as usize).checked_sub(1)?;
+        match self.entries.get(index) {
+            Some((o, entry)) if *o == ordinal => Some(entry),
+            _ => self.by_ordinal(ordinal),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Entry {
     Unused,
     Fixed(FixedEntry),
     Moveable(MoveableEntry),
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Segment number of fixed bundles which entries are constants, not addresses
+pub const ENTRY_CONSTANT: u8 = 0xFE;
+
+impl Entry {
+    ///
+    /// 1-based number of segment where entry points to.
+    /// Unused entries and constants (see [ENTRY_CONSTANT]) have no segment.
+    ///
+    pub fn segment(&self) -> Option<u8> {
+        match self {
+            Entry::Unused => None,
+            Entry::Fixed(f) if f.segment == ENTRY_CONSTANT => None,
+            Entry::Fixed(f) => Some(f.segment),
+            Entry::Moveable(m) => Some(m.segment),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FixedEntry {
     pub segment: u8,
     pub flags: u8,
@@ -136,14 +274,23 @@ impl FixedEntry {
         Ok(Self {
             segment,
             flags: buf[0],
-            offset: u16::from_le_bytes(buf[1..3].try_into().unwrap()),
+            offset: u16::from_le_bytes([buf[1], buf[2]]),
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let [lo, hi] = self.offset.to_le_bytes();
+        w.write_all(&[self.flags, lo, hi])
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// `INT 3Fh` instruction which every moveable entry holds
+pub const MOVEABLE_ENTRY_MAGIC: [u8; 2] = [0xCD, 0x3F];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MoveableEntry {
     pub flags: u8,
+    /// `INT 3Fh` ([MOVEABLE_ENTRY_MAGIC]): loader calls it
+    /// to load the segment when entry is used first time
     pub magic: [u8; 2],
     pub segment: u8,
     pub offset: u16,
@@ -157,7 +304,23 @@ impl MoveableEntry {
             flags: buf[0],
             magic: [buf[1], buf[2]],
             segment: buf[3],
-            offset: u16::from_le_bytes(buf[4..6].try_into().unwrap()),
+            offset: u16::from_le_bytes([buf[4], buf[5]]),
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let [lo, hi] = self.offset.to_le_bytes();
+        w.write_all(&[self.flags, self.magic[0], self.magic[1], self.segment, lo, hi])
+    }
+    ///
+    /// Entry holds `INT 3Fh` thunk ([MOVEABLE_ENTRY_MAGIC])
+    ///
+    pub fn validate_magic(&self) -> bool {
+        self.magic == MOVEABLE_ENTRY_MAGIC
+    }
+    ///
+    /// Same as [MoveableEntry::validate_magic]
+    ///
+    pub fn is_valid(&self) -> bool {
+        self.validate_magic()
+    }
 }
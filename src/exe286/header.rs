@@ -1,12 +1,22 @@
+//! This module represents the New Executable header and flags
+//! decoded from it.
+//!
+//! `NewExecutableHeader` implements `Display` which prints short
+//! summary of the module. It's made for humans (e.g. command line tools).
+//! For machine processing use the header fields and flags structures.
 use bytemuck::{Pod, Zeroable};
-use std::io::{self, Read, Seek, SeekFrom};
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 use crate::exe286;
+use crate::exe286::segtab::Segment;
+use crate::types::readable::Readable;
+use crate::types::{ByteOrder, FieldCursor};
 
 ///
 /// OS/2 & Windows file header definitions
 ///
-#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 #[repr(C)]
 pub struct NewExecutableHeader {
     pub e_magic: [u8; 2],
@@ -35,12 +45,25 @@ pub struct NewExecutableHeader {
     pub e_crsrc: u16,
     pub e_os: u8,
     pub e_flag_others: u8,
-    pub e_ret_thunk: u16,    // <-- offset
-    pub e_segref_thunk: u16, // <-- segment reference thunk offset
+    /// Offset of return thunks (OS/2). Windows modules with fast-load
+    /// flag keep here the start of fast-load area, see [Self::fastload_area]
+    pub e_ret_thunk: u16,
+    /// Offset of segment reference thunks (OS/2). Windows modules with fast-load
+    /// flag keep here the length of fast-load area
+    pub e_segref_thunk: u16,
     pub min_code_swap: u16,
     pub expected_win_ver: [u8; 2],
 }
+///
+/// Processor which module requires. Values are numbers of processor
+/// bits of `e_flags`: bit 4 (`0x0010`) is 8086, bit 7 (`0x0080`) is 8087.
+///
+/// It's not the same enum as [crate::exe386::header::CPU]: `e32_cpu`
+/// of LE/LX header is a plain number (`1` is 286, `2` is 386, `3` is 486),
+/// so the same values mean different processors here and there.
+///
 #[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CPU {
     Undefined = 0,
     I8086 = 0x0004,
@@ -49,6 +72,10 @@ pub enum CPU {
     I8087 = 0x0007,
 }
 impl CPU {
+    ///
+    /// Lossy conversion: unknown values become [CPU::Undefined].
+    /// Use `CPU::try_from` to keep them.
+    ///
     pub fn from(flags: u16) -> CPU {
         match flags {
             0x0004 => CPU::I8086,
@@ -59,31 +86,175 @@ impl CPU {
         }
     }
 }
+impl TryFrom<u16> for CPU {
+    type Error = u16;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match CPU::from(value) {
+            CPU::Undefined => Err(value),
+            cpu => Ok(cpu),
+        }
+    }
+}
+impl fmt::Display for CPU {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CPU::Undefined => "undefined",
+            CPU::I8086 => "i8086",
+            CPU::I286 => "i286",
+            CPU::I386 => "i386",
+            CPU::I8087 => "i8087",
+        };
+        f.write_str(name)
+    }
+}
+///
+/// Target operating system (`e_os`).
+/// Values above 4 are written by third-party linkers and DOS extenders.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OS {
-    /// None or Any
-    Unknown = 0,
     /// OS/2 1x versions and usage of I286 instructions
-    Os2 = 1,
+    Os2,
     /// Windows 1.x-3x and usage of I286 instructions
-    Windows286 = 2,
+    Windows286,
     /// European MS-DOS 4.0
     /// (in different words: Multitasking MS-DOS)
-    Dos4 = 3,
+    Dos4,
     /// Windows 1.x-3x and usage of I386 instructions
-    Windows386 = 4
+    Windows386,
+    /// Borland Operating System Services (Borland DOS extender)
+    Boss,
+    /// PharLap 286|DOS-Extender, OS/2 flavored module
+    PharLapOs2,
+    /// PharLap 286|DOS-Extender, Windows flavored module
+    PharLapWindows,
+    /// None, any or not known by this crate
+    Unknown(u8),
+}
+impl OS {
+    pub fn from(os: u8) -> Self {
+        match os {
+            1 => OS::Os2,
+            2 => OS::Windows286,
+            3 => OS::Dos4,
+            4 => OS::Windows386,
+            5 => OS::Boss,
+            0x81 => OS::PharLapOs2,
+            0x82 => OS::PharLapWindows,
+            n => OS::Unknown(n),
+        }
+    }
+    pub fn is_windows(&self) -> bool {
+        matches!(self, OS::Windows286 | OS::Windows386)
+    }
+}
+impl fmt::Display for OS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OS::Unknown(0) => "unknown",
+            OS::Unknown(n) => return write!(f, "unknown (0x{:02X})", n),
+            OS::Os2 => "OS/2",
+            OS::Windows286 => "Windows",
+            OS::Dos4 => "DOS 4.x",
+            OS::Windows386 => "Windows (386)",
+            OS::Boss => "Borland OS Services",
+            OS::PharLapOs2 => "PharLap 286 (OS/2)",
+            OS::PharLapWindows => "PharLap 286 (Windows)",
+        };
+        f.write_str(name)
+    }
 }
 
 ///
 /// Interface of New Executable header
 ///
 impl NewExecutableHeader {
+    /// LINK 4.0: entry table is a list of bundles.
+    /// Earlier linkers wrote entries without bundle headers.
+    pub const LINK_VERSION_40: (u8, u8) = (4, 0);
+    /// LINK 5.10 (Windows 3.x SDK) and OpenWatcom wlink baseline
+    pub const LINK_VERSION_510: (u8, u8) = (5, 10);
+
+    ///
+    /// Reads the header at `e_lfanew`. [Readable::read] reads it
+    /// from the current position.
+    ///
     pub fn read<TRead: Read + Seek>(r: &mut TRead, e_lfanew: u32) -> io::Result<Self> {
         r.seek(SeekFrom::Start(e_lfanew as u64))?;
-
-        let mut buf = [0; 0x40];
-        r.read_exact(&mut buf)?;
-
-        Ok(bytemuck::cast(buf))
+        <Self as Readable<Self>>::read(r)
+    }
+    ///
+    /// Writes the header as 0x40 bytes in little-endian order,
+    /// the same layout [NewExecutableHeader::read] reads.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(0x40);
+        buf.extend_from_slice(&self.e_magic);
+        buf.extend_from_slice(&[self.e_link_maj, self.e_link_min]);
+        buf.extend_from_slice(&self.e_ent_tab.to_le_bytes());
+        buf.extend_from_slice(&self.e_cb_ent.to_le_bytes());
+        buf.extend_from_slice(&self.e_load_crc.to_le_bytes());
+        for word in [self.e_flags, self.e_autodata, self.e_heap, self.e_stack] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf.extend_from_slice(&self.e_csip.to_le_bytes());
+        buf.extend_from_slice(&self.e_sssp.to_le_bytes());
+        for word in [
+            self.e_cseg,
+            self.e_cmod,
+            self.e_cbnres,
+            self.e_seg_tab,
+            self.e_rsrc_tab,
+            self.e_resn_tab,
+            self.e_mod_tab,
+            self.e_imp_tab,
+        ] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf.extend_from_slice(&self.e_nres_tab.to_le_bytes());
+        for word in [self.e_cmov_ent, self.e_align, self.e_crsrc] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf.extend_from_slice(&[self.e_os, self.e_flag_others]);
+        for word in [self.e_ret_thunk, self.e_segref_thunk, self.min_code_swap] {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf.extend_from_slice(&self.expected_win_ver);
+        w.write_all(&buf)
+    }
+    ///
+    /// Version of linker as `(major, minor)`.
+    ///
+    /// Linker version determines entry table format: tables
+    /// written by linkers older than [Self::LINK_VERSION_40] aren't bundled
+    /// and [crate::exe286::enttab::EntryTable] can't read them.
+    ///
+    pub fn linker_version(&self) -> (u8, u8) {
+        (self.e_link_maj, self.e_link_min)
+    }
+    ///
+    /// Module is linked by linker of `major.minor` version or newer
+    ///
+    pub fn is_at_least_version(&self, major: u8, minor: u8) -> bool {
+        self.linker_version() >= (major, minor)
+    }
+    ///
+    /// Target operating system decoded from `e_os`
+    ///
+    pub fn target_os(&self) -> OS {
+        OS::from(self.e_os)
+    }
+    ///
+    /// Windows version expected by the module as `(major, minor)`.
+    /// Only Windows modules have it, for others `expected_win_ver` is garbage.
+    ///
+    pub fn expected_windows_version(&self) -> Option<(u8, u8)> {
+        match self.target_os().is_windows() {
+            // e_expver is WORD: major version in high byte
+            true => Some((self.expected_win_ver[1], self.expected_win_ver[0])),
+            false => None,
+        }
     }
     pub fn is_valid_magic(&self) -> bool {
         match u16::from_le_bytes(self.e_magic) {
@@ -119,6 +290,127 @@ impl NewExecutableHeader {
             fastload_area: self.e_flag_others & 0x0008 != 0,
         }
     }
+    ///
+    /// Fast-load (gang-load) area of Windows module as absolute file offset
+    /// and length in bytes. Loader reads it at once and takes segments from it.
+    ///
+    /// Windows reuses `e_ret_thunk` and `e_segref_thunk` for the area,
+    /// counted in `e_align` sectors. Other modules or modules without
+    /// fast-load flag have `None`: fields keep thunk offsets there.
+    ///
+    pub fn fastload_area(&self) -> Option<(u64, u64)> {
+        let flags = match self.extended_flags() {
+            ExtendedFlags::Windows(flags) => flags,
+            _ => return None,
+        };
+        if !flags.fastload_area || self.e_segref_thunk == 0 {
+            return None;
+        }
+        Some((
+            Segment::sector_to_file_offset(self.e_ret_thunk, self.e_align),
+            Segment::sector_to_file_offset(self.e_segref_thunk, self.e_align),
+        ))
+    }
+    ///
+    /// Decodes `e_flag_others` by flags of target OS.
+    /// PharLap modules follow the OS they are flavored by.
+    ///
+    pub fn extended_flags(&self) -> ExtendedFlags {
+        match self.target_os() {
+            OS::Os2 | OS::PharLapOs2 => ExtendedFlags::Os2(self.other_os2_flags()),
+            OS::Windows286 | OS::Windows386 | OS::PharLapWindows => {
+                ExtendedFlags::Windows(self.other_windows_flags())
+            }
+            _ => ExtendedFlags::Unknown(self.e_flag_others),
+        }
+    }
+}
+impl Readable<NewExecutableHeader> for NewExecutableHeader {
+    fn read<R: Read>(r: &mut R) -> io::Result<NewExecutableHeader> {
+        let mut buf = [0; 0x40];
+        r.read_exact(&mut buf)?;
+
+        let mut f = FieldCursor::new(&buf, ByteOrder::Little);
+        Ok(Self {
+            e_magic: f.array(),
+            e_link_maj: f.u8(),
+            e_link_min: f.u8(),
+            e_ent_tab: f.u16(),
+            e_cb_ent: f.u16(),
+            e_load_crc: f.u32(),
+            e_flags: f.u16(),
+            e_autodata: f.u16(),
+            e_heap: f.u16(),
+            e_stack: f.u16(),
+            e_csip: f.u32(),
+            e_sssp: f.u32(),
+            e_cseg: f.u16(),
+            e_cmod: f.u16(),
+            e_cbnres: f.u16(),
+            e_seg_tab: f.u16(),
+            e_rsrc_tab: f.u16(),
+            e_resn_tab: f.u16(),
+            e_mod_tab: f.u16(),
+            e_imp_tab: f.u16(),
+            e_nres_tab: f.u32(),
+            e_cmov_ent: f.u16(),
+            e_align: f.u16(),
+            e_crsrc: f.u16(),
+            e_os: f.u8(),
+            e_flag_others: f.u8(),
+            e_ret_thunk: f.u16(),
+            e_segref_thunk: f.u16(),
+            min_code_swap: f.u16(),
+            expected_win_ver: f.array(),
+        })
+    }
+}
+
+///
+/// Short summary of the header.
+/// Expected Windows version is printed for Windows modules only:
+/// ```text
+/// NE executable module
+///   Linker:       5.10
+///   Segments:     2
+///   Modules:      1
+///   OS:           Windows
+///   Windows:      3.10
+///   Entry point:  0001:0010
+///   Stack:        0002:0400
+///   Heap:         1024
+///   Flags:        SINGLEDATA, protected mode only
+/// ```
+///
+impl fmt::Display for NewExecutableHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = self.module_flags();
+        let mut active = Vec::new();
+        if flags.library_module {
+            active.push(String::from("library"));
+        }
+        active.push(flags.data_segment.to_string());
+        let names = [
+            (flags.protected_mode_only, "protected mode only"),
+            (flags.non_conforming, "non-conforming"),
+            (flags.image_error, "image error"),
+            (flags.linkage_errors, "linkage errors"),
+        ];
+        active.extend(names.iter().filter(|(set, _)| *set).map(|(_, n)| n.to_string()));
+
+        writeln!(f, "NE executable module")?;
+        writeln!(f, "  Linker:       {}.{:02}", self.e_link_maj, self.e_link_min)?;
+        writeln!(f, "  Segments:     {}", self.e_cseg)?;
+        writeln!(f, "  Modules:      {}", self.e_cmod)?;
+        writeln!(f, "  OS:           {}", self.target_os())?;
+        if let Some((major, minor)) = self.expected_windows_version() {
+            writeln!(f, "  Windows:      {}.{:02}", major, minor)?;
+        }
+        writeln!(f, "  Entry point:  {:04X}:{:04X}", self.e_csip >> 16, self.e_csip & 0xFFFF)?;
+        writeln!(f, "  Stack:        {:04X}:{:04X}", self.e_sssp >> 16, self.e_sssp & 0xFFFF)?;
+        writeln!(f, "  Heap:         {}", self.e_heap)?;
+        write!(f, "  Flags:        {}", active.join(", "))
+    }
 }
 
 /// One `WORD` field `e_flags` contains 2 categories
@@ -142,7 +434,7 @@ impl NewExecutableHeader {
 /// //    are application flags byte-mask
 /// ```
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleFlags {
     /// Library module.
     ///  - The `SS:SP` information is invalid,
@@ -171,7 +463,7 @@ pub struct ModuleFlags {
     /// (this flag not belongs to OS/2)
     pub protected_mode_only: bool,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DataSegment {
     /// Data segment is missing
     No = 0x0000,
@@ -180,16 +472,33 @@ pub enum DataSegment {
     /// For each application instances will be made
     /// new data segment.
     Multiple = 0x0002,
+    /// Both bits are set. Loader refuses this combination
+    Invalid = 0x0003,
 }
 impl DataSegment {
+    ///
+    /// Data segment kind holds in the lowest two bits of `e_flags`
+    ///
     pub fn from(f: u16) -> Self {
-        match f & 0x0000 {
+        match f & 0x0003 {
+            0 => DataSegment::No,
             1 => DataSegment::Single,
             2 => DataSegment::Multiple,
-            _ => DataSegment::No,
+            _ => DataSegment::Invalid,
         }
     }
 }
+impl fmt::Display for DataSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataSegment::No => "NOAUTODATA",
+            DataSegment::Single => "SINGLEDATA",
+            DataSegment::Multiple => "MULTIPLEDATA",
+            DataSegment::Invalid => "invalid DATA",
+        };
+        f.write_str(name)
+    }
+}
 ///
 /// If application runs under OS/2 1.x versions
 /// The field `e_flagothers` defines like this byte-mask
@@ -200,6 +509,7 @@ impl DataSegment {
 /// It would be better if `e_flagothers` byte-mask reinterprets like this structure
 /// in the [OS::Os2] case.
 ///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleOs2Flags {
     pub os2_protected_mode: bool,
     pub proportional_fonts: bool,
@@ -211,8 +521,19 @@ pub struct ModuleOs2Flags {
 /// We can reinterpret `e_flagothers` byte-mask like this.
 ///
 /// This list of flags came with Windows 3.10 SDK.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleWindowsFlags {
     pub win3x_protected_mode: bool,
     pub proportional_fonts: bool,
     pub fastload_area: bool,
-}
\ No newline at end of file
+}
+///
+/// `e_flag_others` decoded by target OS (see [NewExecutableHeader::extended_flags])
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtendedFlags {
+    Os2(ModuleOs2Flags),
+    Windows(ModuleWindowsFlags),
+    /// Raw byte of other targets
+    Unknown(u8),
+}
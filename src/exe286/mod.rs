@@ -10,37 +10,68 @@
 //! Files linked as NE executables are appearing in Windows 1.x till 3.x,
 //! IBM OS/2 1.x, multitasking MS-DOS 4.x, and other DOS editions.
 //! Let's extract all data and symbols from those files:
-//! ```rust
+//! ```rust,no_run
 //! use os2omf::exe286::NewExecutableLayout;
 //!
 //! let file_str = "put here Windows 3.1 app/dll path";
 //! let layout = NewExecutableLayout::get(file_str)?;
-//!
+//! # Ok::<(), std::io::Error>(())
 //! ```
 //! That's all. `layout` contains all extracted and processed data
 //! of nested structures what follows by the header.
-use crate::exe::MzHeader;
+//!
+//! ### Table pointers
+//! Header pointers are near: they're relative to `e_lfanew`. The only
+//! exception is the non-resident names table, which is not loaded
+//! with the module and is addressed from the start of file:
+//! ```text
+//! e_seg_tab, e_rsrc_tab, e_resn_tab,
+//! e_mod_tab, e_imp_tab, e_ent_tab     e_lfanew + pointer
+//! e_nres_tab                          pointer
+//! segment data                        sector_base << e_align
+//! ```
+//! Every table reader takes the absolute `u64` file offset computed
+//! like that and seeks to it by itself.
+use crate::exe::{MzHeader, NE_HEADER_LEN};
 use crate::exe286::enttab::EntryTable;
-use crate::exe286::header::NewExecutableHeader;
+use crate::exe286::header::{NewExecutableHeader, OS};
 use crate::exe286::modtab::ModuleReferencesTable;
 use crate::exe286::nrestab::NonResidentNameTable;
 use crate::exe286::resntab::ResidentNameTable;
-use crate::exe286::segtab::{ImportsTable, Segment};
+use crate::exe286::resources::{Font, MessageTable, VersionInfo};
+use crate::exe286::rsrctab::{ResourceTable, OS2_RT_MESSAGE, RT_FONT, RT_VERSION};
+use crate::exe286::segtab::{ImportReferences, ImportsTable, Segment};
+use crate::exe286::segrelocs::InternalFixup;
+use crate::anomaly::{self, Anomaly, Severity};
+use crate::exe286::enttab::Entry;
+use crate::regions::{self, Region, RegionKind};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::BufReader;
+use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
 
 pub const NE_MAGIC: u16 = 0x454e;
 pub const NE_CIGAM: u16 = 0x4e45;
+/// Minimal length of DOS program in bound (Family API) module.
+/// API emulation library alone is some kilobytes.
+pub const FAMILY_API_MIN_STUB: u64 = 0x400;
+/// Module name which loader of bound module keeps in its table
+/// of emulated modules. Plain stubs have no reason to hold it.
+pub const FAMILY_API_SIGNATURE: &[u8] = b"DOSCALLS";
 
 // connect modules "files" here
+pub mod builder;
 pub mod enttab;
 pub mod header;
 pub mod modtab;
 pub mod nrestab;
 pub mod resntab;
+pub mod resources;
+pub mod rsrctab;
 pub mod segrelocs;
 pub mod segtab;
 /// ### Segmented New Executable Layout
@@ -48,7 +79,7 @@ pub mod segtab;
 /// This book traditionally has table of content
 /// Main regions of this book is a segments like sections in PE32/+ or ELF32/64 files
 ///
-/// ```
+/// ```text
 /// +----+---+--------+---------+
 /// | MZ |   |e_lfarlc|e_lfanew ------+
 /// +----+---+----|---+---------+     |
@@ -95,23 +126,53 @@ pub mod segtab;
 ///
 /// ```
 
+#[derive(Debug, Clone)]
 pub struct NewExecutableLayout {
     pub dos_header: MzHeader,
     pub new_header: NewExecutableHeader,
     pub ent_tab: EntryTable,
+    /// Segment table records with data and relocations
+    /// ([segtab::Segment]), in order of segment numbers
     pub seg_tab: Vec<Segment>,
     pub nres_tab: NonResidentNameTable,
     pub resn_tab: ResidentNameTable,
     pub mod_tab: ModuleReferencesTable,
+    /// Imports collected from relocations of every segment
+    /// ([segtab::ImportsTable]), one table per segment
     pub imp_tab: Vec<ImportsTable>,
+    pub rsrc_tab: ResourceTable,
+    /// Why resource table couldn't be read. `rsrc_tab` is empty then.
+    pub rsrc_error: Option<String>,
+}
+///
+/// `segment:offset` address of module resolved through segment table
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentedAddress {
+    /// 1-based segment number
+    pub segment: u16,
+    pub offset: u16,
+    /// Raw file pointer to the byte. `None` when the byte isn't in file:
+    /// segment has no data (`.bss`) or offset is past its data
+    pub file_offset: Option<u64>,
+    /// Offset in flat load image where segments follow one-by-one
+    /// (see [NewExecutableLayout::image_offset])
+    pub image_offset: u64,
 }
 
 impl NewExecutableLayout {
     pub fn get(path: &str) -> io::Result<Self> {
         let file = File::open(path)?;
         let mut reader = BufReader::new(file);
-
-        let dos_header = MzHeader::read(&mut reader)?;
+        Self::read_from(&mut reader)
+    }
+    ///
+    /// Reads DOS header, NE header and all tables from `reader`.
+    /// Pointers are file offsets: DOS header is expected at the start.
+    ///
+    pub fn read_from<T: Read + Seek>(reader: &mut T) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let dos_header = MzHeader::read(reader)?;
         if !dos_header.has_valid_magic() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -119,18 +180,13 @@ impl NewExecutableLayout {
             ));
         }
 
-        reader.seek(SeekFrom::Start(dos_header.e_lfanew as u64))?;
-
-        if dos_header.e_lfanew == 0_u32 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Not protected mode executable",
-            ));
-        }
+        let file_len = reader.seek(SeekFrom::End(0))?;
+        dos_header.validate_lfanew(file_len, NE_HEADER_LEN)?;
 
         let offset = |ptr: u16| ptr as u64 + dos_header.e_lfanew as u64;
 
-        let new_header = NewExecutableHeader::read(&mut reader, dos_header.e_lfanew)?;
+        // every table reader seeks to its table itself
+        let new_header = NewExecutableHeader::read(reader, dos_header.e_lfanew)?;
         if !new_header.is_valid_magic() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -140,15 +196,15 @@ impl NewExecutableLayout {
         // Now we are extremely needed the e_lfanew just because
         // all pointers in Windows-OS/2 header are relative.
         // This is a chance to little compress data to NEAR pointers
-        let nres_tab = NonResidentNameTable::read(&mut reader, new_header.e_nres_tab)?;
-        let resn_tab = ResidentNameTable::read(&mut reader, offset(new_header.e_resn_tab))?;
+        let nres_tab = NonResidentNameTable::read(reader, new_header.e_nres_tab as u64)?;
+        let resn_tab = ResidentNameTable::read(reader, offset(new_header.e_resn_tab))?;
         let ent_table = EntryTable::read(
-            &mut reader,
+            reader,
             offset(new_header.e_ent_tab),
             new_header.e_cb_ent,
         )?;
         let mod_tab = ModuleReferencesTable::read(
-            &mut reader,
+            reader,
             offset(new_header.e_mod_tab),
             new_header.e_cmod,
         )?;
@@ -158,17 +214,44 @@ impl NewExecutableLayout {
         reader.seek(SeekFrom::Start(offset(new_header.e_seg_tab)))?;
 
         for _ in 0..new_header.e_cseg {
-            let seg = Segment::read(&mut reader, new_header.e_align)?;
+            let seg = Segment::read(reader, new_header.e_align)?;
             segments.push(seg);
         }
 
+        // Resource table is missing if it ends where it starts.
+        // OS/2 modules keep resources in segments instead.
+        let rsrc_tab = if new_header.e_rsrc_tab == new_header.e_resn_tab {
+            Ok(ResourceTable::empty())
+        } else if new_header.target_os() == OS::Os2 {
+            ResourceTable::read_os2(
+                reader,
+                offset(new_header.e_rsrc_tab),
+                new_header.e_crsrc,
+                &segments,
+            )
+        } else {
+            ResourceTable::read(reader, offset(new_header.e_rsrc_tab))
+        };
+        // Broken resources don't make code and tables unreadable:
+        // the table stays empty and validate() tells why
+        let (rsrc_tab, rsrc_error) = match rsrc_tab {
+            Ok(table) => (table, None),
+            Err(e) if matches!(e.kind(), io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof) => {
+                (ResourceTable::empty(), Some(e.to_string()))
+            }
+            Err(e) => return Err(e),
+        };
+
+        // one copy of each module name for imports of all segments
+        let mut modules = HashMap::new();
         for (i, s) in segments.as_slice().iter().enumerate() {
-            imp_list.push(ImportsTable::read(
-                &mut reader,
+            imp_list.push(ImportsTable::read_with_modules(
+                reader,
                 &s.relocs,
-                offset(new_header.e_imp_tab) as u32,
-                offset(new_header.e_mod_tab) as u32,
+                offset(new_header.e_imp_tab),
+                offset(new_header.e_mod_tab),
                 (i + 1) as i32,
+                &mut modules,
             )?);
         }
 
@@ -181,8 +264,451 @@ impl NewExecutableLayout {
             seg_tab: segments,
             mod_tab,
             imp_tab: imp_list,
+            rsrc_tab,
+            rsrc_error,
         };
 
         Ok(layout)
     }
+    ///
+    /// Reads data of every segment (see [Segment::read_data]) from
+    /// the same `reader` layout was read from. After that the layout
+    /// needs no reader for segments contents.
+    ///
+    pub fn read_segments_data<T: Read + Seek>(&mut self, reader: &mut T) -> io::Result<()> {
+        for segment in &mut self.seg_tab {
+            segment.read_data(reader)?;
+        }
+        Ok(())
+    }
+    ///
+    /// Byte ranges of DOS part, NE header, all tables
+    /// and segments data with their relocations.
+    /// See [crate::regions] for details.
+    ///
+    pub fn regions(&self) -> Vec<Region> {
+        let mut list = regions::dos_regions(&self.dos_header);
+        let header = &self.new_header;
+        let base = self.dos_header.e_lfanew as u64;
+        let table = |ptr: u16, len: u64, name: &str| {
+            Region::new(base + ptr as u64, len, RegionKind::Table, name)
+        };
+
+        list.push(Region::new(base, 0x40, RegionKind::Header, "NE header"));
+        list.push(table(header.e_seg_tab, header.e_cseg as u64 * 8, "Segment table"));
+        // Resource table has no size in header. It ends where resident names begin.
+        list.push(table(
+            header.e_rsrc_tab,
+            header.e_resn_tab.saturating_sub(header.e_rsrc_tab) as u64,
+            "Resource table",
+        ));
+        let resident_len = self
+            .resn_tab
+            .entries
+            .iter()
+            .map(|e| 3 + e.name.len() as u64)
+            .sum::<u64>()
+            + 1;
+        list.push(table(header.e_resn_tab, resident_len, "Resident names table"));
+        list.push(table(header.e_mod_tab, header.e_cmod as u64 * 2, "Module references table"));
+        list.push(table(
+            header.e_imp_tab,
+            header.e_ent_tab.saturating_sub(header.e_imp_tab) as u64,
+            "Imported names table",
+        ));
+        list.push(table(header.e_ent_tab, header.e_cb_ent as u64, "Entry table"));
+        if header.e_nres_tab != 0 {
+            list.push(Region::new(
+                header.e_nres_tab as u64,
+                header.e_cbnres as u64,
+                RegionKind::Table,
+                "Non-resident names table",
+            ));
+        }
+
+        if let Some((start, len)) = header.fastload_area() {
+            list.push(Region::new(start, len, RegionKind::Data, "Fast-load area"));
+        }
+
+        for (i, segment) in self.seg_tab.iter().enumerate() {
+            if segment.header.sector_base == 0 {
+                continue;
+            }
+            let start = segment.file_offset();
+            let len = segment.header.physical_size();
+            let Some(end) = start.checked_add(len) else {
+                continue;
+            };
+            list.push(Region::new(start, len, RegionKind::Data, &format!("Segment #{}", i + 1)));
+            if segment.header.has_relocations() {
+                list.push(Region::new(
+                    end,
+                    2 + segment.relocs.rel_entries.len() as u64 * 8,
+                    RegionKind::Relocations,
+                    &format!("Segment #{} relocations", i + 1),
+                ));
+            }
+        }
+
+        regions::finish(list)
+    }
+    ///
+    /// Looks for structural anomalies: tables out of file or over each other,
+    /// names of missing entries, segments data over non-resident names.
+    /// `file_len` is the length of the file layout was read from.
+    ///
+    pub fn validate(&self, file_len: u64) -> Vec<Anomaly> {
+        let mut anomalies: Vec<Anomaly> = anomaly::check_lfanew(self.dos_header.e_lfanew)
+            .into_iter()
+            .collect();
+        anomalies.extend(anomaly::check_regions(&self.regions(), file_len));
+
+        let resident = self.resn_tab.entries.iter().map(|e| (&e.name, e.ordinal));
+        let non_resident = self.nres_tab.entries.iter().map(|e| (&e.name, e.ordinal));
+        for (name, ordinal) in resident.chain(non_resident) {
+            // @0 is module name or description
+            if ordinal == 0 || matches!(self.ent_tab.by_ordinal(ordinal), Some(Entry::Fixed(_) | Entry::Moveable(_))) {
+                continue;
+            }
+            anomalies.push(Anomaly::new(
+                Severity::Warning,
+                self.dos_header.e_lfanew as u64 + self.new_header.e_ent_tab as u64,
+                format!(
+                    "Name {} refers to missing entry @{}",
+                    String::from_utf8_lossy(name.as_bytes()),
+                    ordinal
+                ),
+            ));
+        }
+
+        for (ordinal, entry) in &self.ent_tab.entries {
+            let Entry::Moveable(entry) = entry else {
+                continue;
+            };
+            if entry.validate_magic() {
+                continue;
+            }
+            anomalies.push(Anomaly::new(
+                Severity::Warning,
+                self.dos_header.e_lfanew as u64 + self.new_header.e_ent_tab as u64,
+                format!(
+                    "Moveable entry @{} has no INT 3Fh thunk: {:02X} {:02X}",
+                    ordinal, entry.magic[0], entry.magic[1]
+                ),
+            ));
+        }
+
+        if let Some(error) = &self.rsrc_error {
+            anomalies.push(Anomaly::new(
+                Severity::Error,
+                self.dos_header.e_lfanew as u64 + self.new_header.e_rsrc_tab as u64,
+                format!("Resource table is unreadable: {}", error),
+            ));
+        }
+
+        let nres_tab = self.new_header.e_nres_tab as u64;
+        for (i, segment) in self.seg_tab.iter().enumerate() {
+            let start = segment.file_offset();
+            let Some(end) = start.checked_add(segment.header.physical_size()) else {
+                continue;
+            };
+            if segment.header.sector_base != 0 && nres_tab != 0 && start < nres_tab && end > nres_tab {
+                anomalies.push(Anomaly::new(
+                    Severity::Warning,
+                    start,
+                    format!("Segment #{} data extends past non-resident names table", i + 1),
+                ));
+            }
+        }
+
+        anomalies
+    }
+    ///
+    /// Parses first `RT_VERSION` resource if module has it.
+    /// OS/2 modules have no version resources.
+    ///
+    pub fn version_info(&self) -> io::Result<Option<VersionInfo>> {
+        if self.is_os2() {
+            return Ok(None);
+        }
+        match self.rsrc_tab.resources_of(RT_VERSION).next() {
+            Some(resource) => VersionInfo::parse(&resource.data).map(Some),
+            None => Ok(None),
+        }
+    }
+    ///
+    /// Parses all `RT_FONT` resources (fonts of `.FON` module).
+    /// Face name of every font is in [Font::face].
+    /// Fonts of OS/2 modules have another format and aren't decoded.
+    ///
+    pub fn fonts(&self) -> io::Result<Vec<Font>> {
+        if self.is_os2() {
+            return Ok(Vec::new());
+        }
+        self.rsrc_tab
+            .resources_of(RT_FONT)
+            .map(|resource| Font::parse(&resource.data))
+            .collect()
+    }
+    ///
+    /// Parses all OS/2 `RT_MESSAGE` resources. Windows modules have none.
+    ///
+    pub fn messages(&self) -> io::Result<Vec<MessageTable>> {
+        if !self.is_os2() {
+            return Ok(Vec::new());
+        }
+        self.rsrc_tab
+            .resources_of(OS2_RT_MESSAGE)
+            .map(|resource| MessageTable::parse(&resource.data))
+            .collect()
+    }
+    fn is_os2(&self) -> bool {
+        self.new_header.target_os() == OS::Os2
+    }
+    ///
+    /// Segment where entry number `entry_idx` of `ent_tab.entries` points to.
+    /// Unused entries, constants and wrong segment numbers give `None`.
+    ///
+    pub fn segment_for_entry(&self, entry_idx: usize) -> Option<&Segment> {
+        let (_, entry) = self.ent_tab.entries.get(entry_idx)?;
+        self.segment_of(entry)
+    }
+    ///
+    /// Segment of exported entry `@ordinal`. Use it with ordinals
+    /// of resident or non-resident names.
+    ///
+    pub fn segment_for_ordinal(&self, ordinal: u16) -> Option<&Segment> {
+        self.segment_of(self.ent_tab.by_ordinal(ordinal)?)
+    }
+    ///
+    /// Entry point `CS:IP` from `e_csip`. Libraries without
+    /// initialization routine (`CS` is zero) and wrong segment
+    /// numbers give `None`.
+    ///
+    pub fn entry_point(&self) -> Option<SegmentedAddress> {
+        let csip = self.new_header.e_csip;
+        self.address((csip >> 16) as u16, csip as u16)
+    }
+    ///
+    /// Initial stack `SS:SP` from `e_sssp`. Zero `SS` means
+    /// the automatic data segment (`e_autodata`): stack is in DGROUP.
+    /// Modules without both of them (libraries) give `None`.
+    ///
+    pub fn initial_stack(&self) -> Option<SegmentedAddress> {
+        let sssp = self.new_header.e_sssp;
+        let ss = match (sssp >> 16) as u16 {
+            0 => self.new_header.e_autodata,
+            ss => ss,
+        };
+        self.address(ss, sssp as u16)
+    }
+    ///
+    /// Resolves `segment:offset` (1-based `segment`) to file
+    /// and load image offsets. Wrong segment numbers give `None`.
+    ///
+    pub fn address(&self, segment: u16, offset: u16) -> Option<SegmentedAddress> {
+        let index = (segment as usize).checked_sub(1)?;
+        self.seg_tab.get(index)?;
+
+        let file_offset = self
+            .segment_file_range(index)
+            .filter(|&(_, len)| (offset as u64) < len)
+            .map(|(start, _)| start + offset as u64);
+        Some(SegmentedAddress {
+            segment,
+            offset,
+            file_offset,
+            image_offset: self.image_offset(index)? + offset as u64,
+        })
+    }
+    ///
+    /// Data of segment `index` (0-based, index of `seg_tab`) in file
+    /// as `(file offset, length)`. Segments without data give `None`.
+    ///
+    pub fn segment_file_range(&self, index: usize) -> Option<(u64, u64)> {
+        let segment = self.seg_tab.get(index)?;
+        if segment.header.sector_base == 0 {
+            return None;
+        }
+        Some((segment.file_offset(), segment.header.physical_size()))
+    }
+    ///
+    /// Offset of segment `index` (0-based) in flat load image.
+    /// Every segment before it takes its allocation size
+    /// (`min_alloc`, but not less than data in file) rounded up
+    /// to paragraph, like the loader puts them in memory one-by-one.
+    ///
+    pub fn image_offset(&self, index: usize) -> Option<u64> {
+        let preceding = self.seg_tab.get(..index)?;
+        Some(
+            preceding
+                .iter()
+                .map(|s| {
+                    let data = if s.header.sector_base == 0 { 0 } else { s.header.physical_size() };
+                    s.header.min_alloc().max(data).next_multiple_of(16)
+                })
+                .sum(),
+        )
+    }
+    fn segment_of(&self, entry: &Entry) -> Option<&Segment> {
+        let number = entry.segment()? as usize;
+        self.seg_tab.get(number.checked_sub(1)?)
+    }
+    ///
+    /// Target `(segment, offset)` of internal relocation. Fixed targets
+    /// are passed through. Moveable targets (segment `0xFF`) keep
+    /// an entry ordinal instead of offset, so the target is where this
+    /// entry points to. Unused entries and constants give `None`.
+    ///
+    pub fn resolve_internal_fixup(&self, fixup: &InternalFixup) -> Option<(u8, u16)> {
+        let Some(ordinal) = fixup.entry_ordinal() else {
+            return Some((fixup.int_seg, fixup.int_offset));
+        };
+        let entry = self.ent_tab.get_entry(ordinal)?;
+        match entry {
+            Entry::Fixed(f) => Some((entry.segment()?, f.offset)),
+            Entry::Moveable(m) => Some((m.segment, m.offset)),
+            Entry::Unused => None,
+        }
+    }
+    ///
+    /// Operating system module is linked for (`e_os`)
+    ///
+    pub fn expected_os(&self) -> OS {
+        self.new_header.target_os()
+    }
+    ///
+    /// Real-mode program of bound (Family API) module as file offset and
+    /// length. It is the DOS program with API emulation and the loader
+    /// which `BIND.EXE` puts instead of plain stub, so the protected-mode
+    /// program follows it at `e_lfanew`. `None` if module is not bound.
+    /// `reader` is the same module, see [NewExecutableLayout::is_family_api_bound].
+    ///
+    pub fn family_api_loader<T: Read + Seek>(&self, reader: &mut T) -> io::Result<Option<(u64, u64)>> {
+        if !self.is_family_api_bound(reader)? {
+            return Ok(None);
+        }
+        let start = self.dos_header.e_cparhdr as u64 * 16;
+        Ok(Some((start, self.dos_header.image_size() - start)))
+    }
+    ///
+    /// OS/2 1.x application bound by `BIND.EXE` to run under DOS too.
+    ///
+    /// Bound module is an OS/2 application (libraries can't be bound)
+    /// which imports `DOSCALLS` and has a real DOS program instead of stub:
+    /// at least [FAMILY_API_MIN_STUB] bytes long and with DOS relocations.
+    /// Plain stubs which only print "This program cannot be run
+    /// in DOS mode" are some dozens of bytes without relocations.
+    /// Loader code differs between `BIND.EXE` versions, so only the
+    /// [FAMILY_API_SIGNATURE] is looked for in DOS program read
+    /// from `reader` (the same module layout was read from).
+    ///
+    pub fn is_family_api_bound<T: Read + Seek>(&self, reader: &mut T) -> io::Result<bool> {
+        let header = &self.dos_header;
+        let stub_start = header.e_cparhdr as u64 * 16;
+        let stub_len = header.image_size().saturating_sub(stub_start);
+
+        let bound = self.new_header.target_os() == OS::Os2
+            && !self.new_header.module_flags().library_module
+            && header.e_crlc != 0
+            && stub_len >= FAMILY_API_MIN_STUB
+            && self
+                .imp_tab
+                .iter()
+                .flat_map(|table| table.imp_list.iter())
+                .any(|import| import.dll_name.compare_str_ignore_case("DOSCALLS"));
+        if !bound {
+            return Ok(false);
+        }
+
+        // DOS program may be cut by the end of file
+        reader.seek(SeekFrom::Start(stub_start))?;
+        let mut program = Vec::new();
+        reader.take(stub_len).read_to_end(&mut program)?;
+        Ok(program
+            .windows(FAMILY_API_SIGNATURE.len())
+            .any(|window| window == FAMILY_API_SIGNATURE))
+    }
+    ///
+    /// Merges per-segment imports into one list of unique procedures.
+    /// Every procedure imported many times keeps all its call sites.
+    ///
+    /// Use `imp_tab` instead if you are working with relocations
+    /// of the exact segment.
+    ///
+    pub fn imports(&self) -> Vec<ImportReferences> {
+        let mut unique: Vec<ImportReferences> = Vec::new();
+        let mut positions: HashMap<&segtab::DllImport, usize> = HashMap::new();
+
+        for table in &self.imp_tab {
+            for import in &table.imp_list {
+                let site = (table.seg_number, import.seg_offset);
+                match positions.get(import) {
+                    Some(&i) => unique[i].sites.push(site),
+                    None => {
+                        positions.insert(import, unique.len());
+                        unique.push(ImportReferences {
+                            import: import.clone(),
+                            sites: vec![site],
+                        });
+                    }
+                }
+            }
+        }
+
+        unique
+    }
+    ///
+    /// Flattens imports of all segments into one list where
+    /// every procedure appears once. Unlike `imports` call sites
+    /// are dropped: the `seg_offset` of each record is the first one found.
+    ///
+    pub fn all_imports_deduplicated(&self) -> Vec<segtab::DllImport> {
+        let mut seen = HashSet::new();
+        self.imp_tab
+            .iter()
+            .flat_map(|table| table.imp_list.iter())
+            .filter(|import| seen.insert(*import))
+            .cloned()
+            .collect()
+    }
+}
+///
+/// Short summary of the module: name, target OS and counts
+/// of parsed records. Use [NewExecutableHeader] `Display` for header details.
+/// ```text
+/// NE module KERNEL
+///   OS:           Windows
+///   Segments:     3
+///   Entries:      12
+///   Names:        4 resident, 9 non-resident
+///   Modules:      1
+///   Imports:      7
+///   Resources:    2
+/// ```
+///
+impl fmt::Display for NewExecutableLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .resn_tab
+            .iter()
+            .find(|e| e.ordinal == 0)
+            .map(|e| e.name.to_string_lossy())
+            .unwrap_or_default();
+        let resources: usize = self.rsrc_tab.types.iter().map(|t| t.resources.len()).sum();
+
+        writeln!(f, "NE module {}", name)?;
+        writeln!(f, "  OS:           {}", self.new_header.target_os())?;
+        writeln!(f, "  Segments:     {}", self.seg_tab.len())?;
+        writeln!(f, "  Entries:      {}", self.ent_tab.len())?;
+        writeln!(
+            f,
+            "  Names:        {} resident, {} non-resident",
+            self.resn_tab.len(),
+            self.nres_tab.len()
+        )?;
+        writeln!(f, "  Modules:      {}", self.mod_tab.len())?;
+        writeln!(f, "  Imports:      {}", self.imports().len())?;
+        write!(f, "  Resources:    {}", resources)
+    }
 }
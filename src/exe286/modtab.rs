@@ -1,7 +1,7 @@
 //! This module represents structure and methods of module-references 
 //! Module references table stores relative pointers to .EXE/.DLL module
 //! names. Imports table contains not only importing modules.
-use std::io::{self, Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 ///
 /// Module References Table
 /// Represents WORD records array where count of records defines
@@ -21,11 +21,19 @@ use std::io::{self, Read, Seek, SeekFrom};
 /// 1) Select a 2nd offset (modtab[[1]] = 0x0004)
 /// 2) e_lfanew + e_imptab + modtab[[1]]
 /// 3) Read the Pascal-String
+///
+/// Offsets are relative to the imported names table start,
+/// they aren't raw file pointers.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleReferencesTable {
     pub m_offsets: Vec<u16>,
 }
 
 impl ModuleReferencesTable {
+    ///
+    /// Reads `cmod` offsets by absolute `e_modtab` file pointer.
+    /// Reader position before the call doesn't matter.
+    ///
     pub fn read<TRead: Read + Seek>(reader: &mut TRead, e_modtab: u64, cmod: u16) -> io::Result<Self> {
         reader.seek(SeekFrom::Start(e_modtab))?;
         
@@ -34,11 +42,55 @@ impl ModuleReferencesTable {
 
         for _ in 0..cmod {
             reader.read_exact(&mut buf)?;
-            references.push(bytemuck::cast(buf));
+            references.push(u16::from_le_bytes(buf));
         }
 
         Ok(ModuleReferencesTable {
             m_offsets: references,
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for offset in &self.m_offsets {
+            w.write_all(&offset.to_le_bytes())?;
+        }
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.m_offsets.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.m_offsets.is_empty()
+    }
+    ///
+    /// Offsets of module names from the imported names table start
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        self.m_offsets.iter().copied()
+    }
+    pub fn get(&self, index: usize) -> Option<u16> {
+        self.m_offsets.get(index).copied()
+    }
+}
+impl std::ops::Index<usize> for ModuleReferencesTable {
+    type Output = u16;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.m_offsets[index]
+    }
+}
+impl IntoIterator for ModuleReferencesTable {
+    type Item = u16;
+    type IntoIter = std::vec::IntoIter<u16>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.m_offsets.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a ModuleReferencesTable {
+    type Item = u16;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, u16>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.m_offsets.iter().copied()
+    }
 }
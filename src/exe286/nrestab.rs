@@ -1,6 +1,6 @@
 //! This module represents methods for extract non-resident names from file
-use crate::types::PascalString;
-use std::io::{self, Read, Seek, SeekFrom};
+use crate::types::{table_accessors, ByteOrder, NameTable, PascalString};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 ///
 /// This table contains a list of ASCII strings.
@@ -17,15 +17,24 @@ use std::io::{self, Read, Seek, SeekFrom};
 /// follows the module description string is 0. (Offsets are from the
 /// beginning of the table.)
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NonResidentNameTable {
     pub entries: Vec<NonResidentNameEntry>,
 }
+table_accessors!(NonResidentNameTable, entries, NonResidentNameEntry);
 
 impl NonResidentNameTable {
     /// Reads all known non-resident names and returns vector
-    /// of symbols by known address
-    pub fn read<R: Read + Seek>(reader: &mut R, e_nres_tab: u32) -> io::Result<Self> {
+    /// of symbols by known address. `e_nres_tab` is absolute:
+    /// unlike other NE pointers it's not relative to `e_lfanew`.
+    pub fn read<R: Read + Seek>(reader: &mut R, e_nres_tab: u64) -> io::Result<Self> {
+        Self::read_with_order(reader, e_nres_tab, ByteOrder::Little)
+    }
+    ///
+    /// Reads table of LX module where ordinals are in module byte order.
+    /// NE modules are always little-endian.
+    ///
+    pub fn read_with_order<R: Read + Seek>(reader: &mut R, e_nres_tab: u64, order: ByteOrder) -> io::Result<Self> {
         let mut entries = Vec::new();
         // In practice, we don't need actually `e_cbnres` field from NE header.
         // If non-resident table is empty - it defines in moment without this helping hand
@@ -33,16 +42,42 @@ impl NonResidentNameTable {
             return Ok(Self { entries });
         }
 
-        reader.seek(SeekFrom::Start(e_nres_tab as u64))?;
+        reader.seek(SeekFrom::Start(e_nres_tab))?;
 
-        while let Some(entry) = NonResidentNameEntry::read(reader)? {
+        while let Some(entry) = NonResidentNameEntry::read_with_order(reader, order)? {
             entries.push(entry);
         }
         Ok(Self { entries })
     }
 }
 
-#[derive(Debug, Clone)]
+impl NonResidentNameTable {
+    ///
+    /// Writes names with ordinals and the terminating zero byte
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_with_order(w, ByteOrder::Little)
+    }
+    ///
+    /// Writes table of LX module with ordinals in module byte order
+    ///
+    pub fn write_with_order<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        for entry in &self.entries {
+            w.write_all(&[entry.name.pascal_len()])?;
+            w.write_all(entry.name.as_bytes())?;
+            order.write_u16(w, entry.ordinal)?;
+        }
+        w.write_all(&[0])
+    }
+}
+
+impl NameTable for NonResidentNameTable {
+    fn name_entries(&self) -> impl Iterator<Item = (u16, &PascalString)> {
+        self.entries.iter().map(|e| (e.ordinal, &e.name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NonResidentNameEntry {
     pub name: PascalString,
     pub ordinal: u16,
@@ -51,6 +86,9 @@ pub struct NonResidentNameEntry {
 #[warn(duplicate_macro_attributes)]
 impl NonResidentNameEntry {
     pub fn read<TRead: Read>(r: &mut TRead) -> io::Result<Option<Self>> {
+        Self::read_with_order(r, ByteOrder::Little)
+    }
+    pub fn read_with_order<TRead: Read>(r: &mut TRead, order: ByteOrder) -> io::Result<Option<Self>> {
         let len = {
             let mut len = 0;
             r.read_exact(std::slice::from_mut(&mut len))?;
@@ -64,11 +102,7 @@ impl NonResidentNameEntry {
             r.read_exact(name.as_mut_slice())?;
             name
         };
-        let index = {
-            let mut buf = [0; 2];
-            r.read_exact(&mut buf)?;
-            u16::from_le_bytes(buf)
-        };
+        let index = order.read_u16(r)?;
         Ok(Some(Self {
             name: PascalString::new(len, name),
             ordinal: index,
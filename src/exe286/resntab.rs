@@ -1,6 +1,6 @@
 //! This module represents methods for extract resident names from file
-use crate::types::PascalString;
-use std::io::{self, Read, Seek, SeekFrom};
+use crate::types::{table_accessors, ByteOrder, NameTable, PascalString};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 ///
 /// This table contains a list of ASCII strings.
@@ -19,24 +19,58 @@ use std::io::{self, Read, Seek, SeekFrom};
 /// that follows the module name is 0. (Offsets are from the beginning of
 /// the record.)
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResidentNameTable {
     pub entries: Vec<ResidentNameEntry>,
 }
+table_accessors!(ResidentNameTable, entries, ResidentNameEntry);
 
 impl ResidentNameTable {
     pub fn read<R: Read + Seek>(reader: &mut R, e_resntab: u64) -> io::Result<Self> {
+        Self::read_with_order(reader, e_resntab, ByteOrder::Little)
+    }
+    ///
+    /// Reads table of LX module where ordinals are in module byte order.
+    /// NE modules are always little-endian.
+    ///
+    pub fn read_with_order<R: Read + Seek>(reader: &mut R, e_resntab: u64, order: ByteOrder) -> io::Result<Self> {
         let mut entries = Vec::new();
         reader.seek(SeekFrom::Start(e_resntab))?;
 
-        while let Some(entry) = ResidentNameEntry::read(reader)? {
+        while let Some(entry) = ResidentNameEntry::read_with_order(reader, order)? {
             entries.push(entry);
         }
         Ok(Self { entries })
     }
 }
 
-#[derive(Debug, Clone)]
+impl ResidentNameTable {
+    ///
+    /// Writes names with ordinals and the terminating zero byte
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_with_order(w, ByteOrder::Little)
+    }
+    ///
+    /// Writes table of LX module with ordinals in module byte order
+    ///
+    pub fn write_with_order<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        for entry in &self.entries {
+            w.write_all(&[entry.name.pascal_len()])?;
+            w.write_all(entry.name.as_bytes())?;
+            order.write_u16(w, entry.ordinal)?;
+        }
+        w.write_all(&[0])
+    }
+}
+
+impl NameTable for ResidentNameTable {
+    fn name_entries(&self) -> impl Iterator<Item = (u16, &PascalString)> {
+        self.entries.iter().map(|e| (e.ordinal, &e.name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResidentNameEntry {
     pub name: PascalString,
     pub ordinal: u16,
@@ -44,6 +78,9 @@ pub struct ResidentNameEntry {
 
 impl ResidentNameEntry {
     pub fn read<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        Self::read_with_order(r, ByteOrder::Little)
+    }
+    pub fn read_with_order<R: Read>(r: &mut R, order: ByteOrder) -> io::Result<Option<Self>> {
         // TODO: make it general. "resident names" and "not resident names" are the same structures but have different locations.
         let len = {
             let mut len = 0;
@@ -58,11 +95,7 @@ impl ResidentNameEntry {
             r.read_exact(name.as_mut_slice())?;
             name
         };
-        let index = {
-            let mut buf = [0; 2];
-            r.read_exact(&mut buf)?;
-            u16::from_le_bytes(buf)
-        };
+        let index = order.read_u16(r)?;
         Ok(Some(Self {
             name: PascalString::new(len, name),
             ordinal: index,
@@ -0,0 +1,1302 @@
+//! This module represents decoders of typed resources of Windows modules.
+//! Resource data is taken from the resource table (see `rsrctab`).
+//! OS/2 bitmaps, pointers and messages have their own decoders
+//! ([Os2BitmapArray], [MessageTable]).
+//!
+//! Graphic resources are device independent bitmaps (DIB) without
+//! file headers. To get a viewable file the header must be rebuilt:
+//! ```rust,no_run
+//! use os2omf::exe286::NewExecutableLayout;
+//! use os2omf::exe286::resources::GroupIcon;
+//! use os2omf::exe286::rsrctab::RT_GROUP_ICON;
+//!
+//! let layout = NewExecutableLayout::get("put here Windows 3.1 app path")?;
+//! for group in layout.rsrc_tab.resources_of(RT_GROUP_ICON) {
+//!     let icon = GroupIcon::parse(&group.data)?;
+//!     std::fs::write("icon.ico", icon.to_ico_bytes(&layout.rsrc_tab)?)?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Bitmap header may be `BITMAPCOREHEADER` (12 bytes, RGB triples palette)
+//! or `BITMAPINFOHEADER` (40 bytes, RGB quads palette).
+//!
+//! Version information of 16-bit modules is a tree of blocks
+//! with ASCII keys. Every block is aligned by `DWORD`:
+//! ```
+//! // +---------+---------+-----------------+-----+--------+-----+----------+
+//! // | cbBlock | cbValue | szKey (ASCIIZ)  | pad | value  | pad | children |
+//! // | WORD    | WORD    |                 |     |        |     |          |
+//! // +---------+---------+-----------------+-----+--------+-----+----------+
+//! ```
+use crate::exe286::rsrctab::{ResourceTable, RT_ICON};
+use std::collections::BTreeMap;
+use std::io;
+
+const BITMAPCOREHEADER: u32 = 12;
+const BITMAPINFOHEADER: u32 = 40;
+/// Compression with 3 color masks after header
+const BI_BITFIELDS: u32 = 3;
+
+///
+/// Device independent bitmap (`RT_BITMAP` resource).
+/// `data` holds all resource bytes: header, palette and pixels.
+///
+#[derive(Debug, Clone)]
+pub struct Bitmap {
+    pub header_size: u32,
+    pub width: i32,
+    pub height: i32,
+    pub planes: u16,
+    pub bit_count: u16,
+    /// Size of color table in bytes (with color masks)
+    pub palette_size: u32,
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let header_size = u32_at(bytes, 0)?;
+        let (width, height, planes, bit_count) = match header_size {
+            BITMAPCOREHEADER => (
+                u16_at(bytes, 4)? as i32,
+                u16_at(bytes, 6)? as i32,
+                u16_at(bytes, 8)?,
+                u16_at(bytes, 10)?,
+            ),
+            BITMAPINFOHEADER => (
+                u32_at(bytes, 4)? as i32,
+                u32_at(bytes, 8)? as i32,
+                u16_at(bytes, 12)?,
+                u16_at(bytes, 14)?,
+            ),
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown bitmap header size {}", n),
+                ));
+            }
+        };
+
+        // colors count is from file: sizes are counted in u64
+        let palette_size: u64 = match header_size {
+            // RGBTRIPLE for every color, no colors count in header
+            BITMAPCOREHEADER => match bit_count {
+                1..=8 => 3 << bit_count,
+                _ => 0,
+            },
+            _ => {
+                let colors_used = u32_at(bytes, 32)? as u64;
+                let colors = match (colors_used, bit_count) {
+                    (0, 1..=8) => 1 << bit_count,
+                    (n, _) => n,
+                };
+                let masks = match u32_at(bytes, 16)? {
+                    BI_BITFIELDS => 12,
+                    _ => 0,
+                };
+                colors * 4 + masks
+            }
+        };
+        if header_size as u64 + palette_size > bytes.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Bitmap palette of {} bytes is out of {} bytes of resource", palette_size, bytes.len()),
+            ));
+        }
+
+        Ok(Self {
+            header_size,
+            width,
+            height,
+            planes,
+            bit_count,
+            palette_size: palette_size as u32,
+            data: bytes.to_vec(),
+        })
+    }
+    ///
+    /// Offset of pixels from the start of resource data
+    ///
+    pub fn bits_offset(&self) -> u32 {
+        self.header_size + self.palette_size
+    }
+    ///
+    /// Prepends `BITMAPFILEHEADER` so result can be written as `.BMP` file
+    ///
+    pub fn to_bmp_bytes(&self) -> Vec<u8> {
+        let mut bmp = Vec::with_capacity(14 + self.data.len());
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(14 + self.data.len() as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]); // reserved
+        bmp.extend_from_slice(&(14 + self.bits_offset()).to_le_bytes());
+        bmp.extend_from_slice(&self.data);
+        bmp
+    }
+}
+
+///
+/// Image of `RT_ICON` resource. It's a DIB which height is doubled:
+/// XOR (color) image is followed by monochrome AND mask.
+///
+#[derive(Debug, Clone)]
+pub struct Icon {
+    pub bitmap: Bitmap,
+}
+
+impl Icon {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            bitmap: Bitmap::parse(bytes)?,
+        })
+    }
+    pub fn width(&self) -> u32 {
+        self.bitmap.width.unsigned_abs()
+    }
+    /// Height of image without AND mask
+    pub fn height(&self) -> u32 {
+        self.bitmap.height.unsigned_abs() / 2
+    }
+    ///
+    /// Writes single-image `.ICO` file
+    ///
+    pub fn to_ico_bytes(&self) -> Vec<u8> {
+        let entry = DirectoryEntry {
+            width: self.width(),
+            height: self.height(),
+            bit_count: self.bitmap.bit_count,
+            x: self.bitmap.planes,
+            y: self.bitmap.bit_count,
+            data: &self.bitmap.data,
+        };
+        write_directory(1, &[entry])
+    }
+}
+
+///
+/// Image of `RT_CURSOR` resource: hotspot and icon-like DIB
+///
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    pub hotspot_x: u16,
+    pub hotspot_y: u16,
+    pub image: Icon,
+}
+
+impl Cursor {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            hotspot_x: u16_at(bytes, 0)?,
+            hotspot_y: u16_at(bytes, 2)?,
+            image: Icon::parse(&bytes[4..])?,
+        })
+    }
+    ///
+    /// Writes single-image `.CUR` file. Hotspot takes
+    /// places of planes and bit count in directory entry.
+    ///
+    pub fn to_cur_bytes(&self) -> Vec<u8> {
+        let entry = DirectoryEntry {
+            width: self.image.width(),
+            height: self.image.height(),
+            bit_count: self.image.bitmap.bit_count,
+            x: self.hotspot_x,
+            y: self.hotspot_y,
+            data: &self.image.bitmap.data,
+        };
+        write_directory(2, &[entry])
+    }
+}
+
+///
+/// One image of `RT_GROUP_ICON` directory.
+/// `id` is an identifier of `RT_ICON` resource.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct GroupIconEntry {
+    pub width: u8,
+    pub height: u8,
+    pub color_count: u8,
+    pub planes: u16,
+    pub bit_count: u16,
+    pub bytes_in_res: u32,
+    pub id: u16,
+}
+
+///
+/// Directory of icon images (`RT_GROUP_ICON` resource)
+///
+#[derive(Debug, Clone)]
+pub struct GroupIcon {
+    pub entries: Vec<GroupIconEntry>,
+}
+
+impl GroupIcon {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let count = u16_at(bytes, 4)?;
+        let mut entries = Vec::with_capacity(count as usize);
+        for i in 0..count as usize {
+            let entry = 6 + i * 14;
+            entries.push(GroupIconEntry {
+                width: u8_at(bytes, entry)?,
+                height: u8_at(bytes, entry + 1)?,
+                color_count: u8_at(bytes, entry + 2)?,
+                planes: u16_at(bytes, entry + 4)?,
+                bit_count: u16_at(bytes, entry + 6)?,
+                bytes_in_res: u32_at(bytes, entry + 8)?,
+                id: u16_at(bytes, entry + 12)?,
+            });
+        }
+        Ok(Self { entries })
+    }
+    ///
+    /// Collects referenced `RT_ICON` images into `.ICO` file.
+    /// Missing images are errors.
+    ///
+    pub fn to_ico_bytes(&self, rsrc_tab: &ResourceTable) -> io::Result<Vec<u8>> {
+        let mut images = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let icon = rsrc_tab.find(RT_ICON, entry.id).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Icon #{} of group is missing", entry.id),
+                )
+            })?;
+            images.push(DirectoryEntry {
+                width: entry.width as u32,
+                height: entry.height as u32,
+                bit_count: entry.bit_count,
+                x: entry.planes,
+                y: entry.bit_count,
+                data: &icon.data,
+            });
+        }
+        Ok(write_directory(1, &images))
+    }
+}
+
+const VS_FFI_SIGNATURE: u32 = 0xFEEF04BD;
+
+///
+/// `VS_FIXEDFILEINFO` structure. Versions are split to
+/// `(major, minor, build, revision)` words.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedFileInfo {
+    pub struct_version: u32,
+    pub file_version: (u16, u16, u16, u16),
+    pub product_version: (u16, u16, u16, u16),
+    pub file_flags_mask: u32,
+    pub file_flags: u32,
+    pub file_os: u32,
+    pub file_type: u32,
+    pub file_subtype: u32,
+    pub file_date: u64,
+}
+
+impl FixedFileInfo {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let signature = u32_at(bytes, 0)?;
+        if signature != VS_FFI_SIGNATURE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid VS_FIXEDFILEINFO signature 0x{:08X}", signature),
+            ));
+        }
+        let version = |offset: usize| -> io::Result<(u16, u16, u16, u16)> {
+            let ms = u32_at(bytes, offset)?;
+            let ls = u32_at(bytes, offset + 4)?;
+            Ok(((ms >> 16) as u16, ms as u16, (ls >> 16) as u16, ls as u16))
+        };
+        Ok(Self {
+            struct_version: u32_at(bytes, 4)?,
+            file_version: version(8)?,
+            product_version: version(16)?,
+            file_flags_mask: u32_at(bytes, 24)?,
+            file_flags: u32_at(bytes, 28)?,
+            file_os: u32_at(bytes, 32)?,
+            file_type: u32_at(bytes, 36)?,
+            file_subtype: u32_at(bytes, 40)?,
+            file_date: ((u32_at(bytes, 44)? as u64) << 32) | u32_at(bytes, 48)? as u64,
+        })
+    }
+}
+
+///
+/// Decoded `RT_VERSION` resource
+///
+#[derive(Debug, Clone, Default)]
+pub struct VersionInfo {
+    pub fixed: Option<FixedFileInfo>,
+    /// `StringFileInfo` tables: language-codepage key (e.g. `040904E4`)
+    /// to string values (e.g. `FileVersion` to `3.10`)
+    pub strings: BTreeMap<String, BTreeMap<String, String>>,
+    /// `VarFileInfo\Translation` pairs of language and codepage
+    pub translations: Vec<(u16, u16)>,
+}
+
+///
+/// One block of version information tree
+///
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    /// Offsets of children (from resource start)
+    children: (usize, usize),
+}
+
+impl VersionInfo {
+    ///
+    /// Parses `VS_VERSION_INFO` tree. Truncated children are skipped,
+    /// only broken root block is an error.
+    ///
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let root = read_version_block(bytes, 0, bytes.len()).ok_or_else(|| truncated(0))?;
+        if root.key != "VS_VERSION_INFO" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected root key of version info: {}", root.key),
+            ));
+        }
+
+        let mut info = VersionInfo::default();
+        if !root.value.is_empty() {
+            info.fixed = Some(FixedFileInfo::parse(root.value)?);
+        }
+        for child in version_children(bytes, root.children) {
+            match child.key.as_str() {
+                "StringFileInfo" => {
+                    for table in version_children(bytes, child.children) {
+                        let strings = version_children(bytes, table.children)
+                            .map(|s| (s.key, ascii_z(s.value)))
+                            .collect();
+                        info.strings.insert(table.key, strings);
+                    }
+                }
+                "VarFileInfo" => {
+                    for var in version_children(bytes, child.children).filter(|v| v.key == "Translation") {
+                        info.translations.extend(
+                            var.value
+                                .chunks_exact(4)
+                                .map(|p| (u16::from_le_bytes([p[0], p[1]]), u16::from_le_bytes([p[2], p[3]]))),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+///
+/// Reads block at `offset` which must end before `limit`
+///
+fn read_version_block(bytes: &[u8], offset: usize, limit: usize) -> Option<VersionBlock<'_>> {
+    let length = u16_at(bytes, offset).ok()? as usize;
+    let value_length = u16_at(bytes, offset + 2).ok()? as usize;
+    let end = offset + length;
+    if length < 4 || end > limit.min(bytes.len()) {
+        return None;
+    }
+
+    let key_start = offset + 4;
+    let key_end = key_start + bytes[key_start..end].iter().position(|&b| b == 0)?;
+    let key = String::from_utf8_lossy(&bytes[key_start..key_end]).to_string();
+
+    let value_start = align4(key_end + 1).min(end);
+    let value_end = (value_start + value_length).min(end);
+    Some(VersionBlock {
+        key,
+        value: &bytes[value_start..value_end],
+        children: (align4(value_end).min(end), end),
+    })
+}
+
+fn version_children(bytes: &[u8], (start, end): (usize, usize)) -> impl Iterator<Item = VersionBlock<'_>> {
+    let mut offset = start;
+    std::iter::from_fn(move || {
+        let block = read_version_block(bytes, offset, end)?;
+        offset = align4(offset + u16_at(bytes, offset).ok()? as usize);
+        Some(block)
+    })
+}
+
+fn ascii_z(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+///
+/// Menu, class or text of dialog: missing, ordinal
+/// (`0xFF` escape and `WORD`) or ASCII string
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameOrOrdinal {
+    None,
+    Ordinal(u16),
+    Name(String),
+}
+
+/// Dialog style flag: point size and face name follow the caption
+pub const DS_SETFONT: u32 = 0x0040;
+
+///
+/// `RT_DIALOG` resource in 16-bit format
+///
+#[derive(Debug, Clone)]
+pub struct DialogTemplate {
+    pub style: u32,
+    pub x: i16,
+    pub y: i16,
+    pub cx: i16,
+    pub cy: i16,
+    pub menu: NameOrOrdinal,
+    pub class: NameOrOrdinal,
+    pub caption: String,
+    /// Point size and face name if [DS_SETFONT] is set
+    pub font: Option<(u16, String)>,
+    pub items: Vec<DialogItem>,
+}
+
+///
+/// Class of dialog control. Predefined classes are one byte codes `0x80..0x85`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogClass {
+    Button,
+    Edit,
+    Static,
+    ListBox,
+    ScrollBar,
+    ComboBox,
+    /// Other byte with high bit set
+    Predefined(u8),
+    Name(String),
+}
+
+impl DialogClass {
+    pub fn from(code: u8) -> Self {
+        match code {
+            0x80 => DialogClass::Button,
+            0x81 => DialogClass::Edit,
+            0x82 => DialogClass::Static,
+            0x83 => DialogClass::ListBox,
+            0x84 => DialogClass::ScrollBar,
+            0x85 => DialogClass::ComboBox,
+            n => DialogClass::Predefined(n),
+        }
+    }
+}
+
+///
+/// Control of dialog (`DLGITEMTEMPLATE`). Items aren't aligned in 16-bit format.
+///
+#[derive(Debug, Clone)]
+pub struct DialogItem {
+    pub x: i16,
+    pub y: i16,
+    pub cx: i16,
+    pub cy: i16,
+    pub id: u16,
+    pub style: u32,
+    pub class: DialogClass,
+    pub text: NameOrOrdinal,
+    /// Creation data passed to control
+    pub extra: Vec<u8>,
+}
+
+impl DialogTemplate {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ResourceReader { bytes, pos: 0 };
+        let style = r.u32()?;
+        let count = r.u8()?;
+        let (x, y, cx, cy) = (r.i16()?, r.i16()?, r.i16()?, r.i16()?);
+        let menu = r.name_or_ordinal()?;
+        let class = r.name_or_ordinal()?;
+        let caption = r.asciiz()?;
+        let font = match style & DS_SETFONT != 0 {
+            true => Some((r.u16()?, r.asciiz()?)),
+            false => None,
+        };
+
+        let mut items = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (x, y, cx, cy) = (r.i16()?, r.i16()?, r.i16()?, r.i16()?);
+            let id = r.u16()?;
+            let style = r.u32()?;
+            let class = match r.u8()? {
+                code if code & 0x80 != 0 => DialogClass::from(code),
+                // string class: first byte is already read
+                _ => {
+                    r.pos -= 1;
+                    DialogClass::Name(r.asciiz()?)
+                }
+            };
+            let text = r.name_or_ordinal()?;
+            let extra_length = r.u8()? as usize;
+            let extra = r.take(extra_length)?.to_vec();
+
+            items.push(DialogItem {
+                x,
+                y,
+                cx,
+                cy,
+                id,
+                style,
+                class,
+                text,
+                extra,
+            });
+        }
+
+        Ok(Self {
+            style,
+            x,
+            y,
+            cx,
+            cy,
+            menu,
+            class,
+            caption,
+            font,
+            items,
+        })
+    }
+}
+
+/// Menu item opens submenu, it has no identifier
+pub const MF_POPUP: u16 = 0x0010;
+/// Last item of current menu level
+pub const MF_END: u16 = 0x0080;
+/// Submenus deeper than this are `InvalidData`: nesting of hostile
+/// resource would exhaust the stack of recursive reader
+pub const MAX_MENU_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuItem {
+    Item { flags: u16, id: u16, text: String },
+    Popup { flags: u16, text: String, children: Vec<MenuItem> },
+}
+
+///
+/// `RT_MENU` resource in 16-bit format: header and items stream.
+/// Popup items are followed by their children, [MF_END] closes a level.
+///
+#[derive(Debug, Clone)]
+pub struct Menu {
+    pub version: u16,
+    pub items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ResourceReader { bytes, pos: 0 };
+        let version = r.u16()?;
+        let header_size = r.u16()?;
+        r.take(header_size as usize)?;
+
+        Ok(Self {
+            version,
+            items: Self::read_level(&mut r, 1)?,
+        })
+    }
+
+    fn read_level(r: &mut ResourceReader, depth: usize) -> io::Result<Vec<MenuItem>> {
+        if depth > MAX_MENU_DEPTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Menu is nested deeper than {} levels at 0x{:X}", MAX_MENU_DEPTH, r.pos),
+            ));
+        }
+        let mut items = Vec::new();
+        loop {
+            let flags = r.u16()?;
+            let item = match flags & MF_POPUP != 0 {
+                true => {
+                    let text = r.asciiz()?;
+                    MenuItem::Popup {
+                        flags,
+                        text,
+                        children: Self::read_level(r, depth + 1)?,
+                    }
+                }
+                false => MenuItem::Item {
+                    flags,
+                    id: r.u16()?,
+                    text: r.asciiz()?,
+                },
+            };
+            items.push(item);
+            if flags & MF_END != 0 {
+                return Ok(items);
+            }
+        }
+    }
+}
+
+/// `dfType` bit of vector fonts
+pub const FNT_VECTOR: u16 = 0x0001;
+
+///
+/// Raster font (`RT_FONT` resource or `.FNT` file) of version 2.0 or 3.0.
+/// Version 3.0 has longer header and 32-bit glyph offsets.
+///
+#[derive(Debug, Clone)]
+pub struct Font {
+    pub version: u16,
+    pub size: u32,
+    pub copyright: String,
+    pub font_type: u16,
+    pub points: u16,
+    pub vert_res: u16,
+    pub horiz_res: u16,
+    pub ascent: u16,
+    pub italic: bool,
+    pub underline: bool,
+    pub strike_out: bool,
+    pub weight: u16,
+    pub charset: u8,
+    /// Zero for proportional fonts
+    pub pix_width: u16,
+    pub pix_height: u16,
+    pub first_char: u8,
+    pub last_char: u8,
+    pub default_char: u8,
+    pub break_char: u8,
+    pub bits_offset: u32,
+    pub face: String,
+    /// Width and data offset of every glyph from `first_char` till `last_char`
+    pub chars: Vec<(u16, u32)>,
+    pub data: Vec<u8>,
+}
+
+///
+/// Bitmap of one character. Font bitmaps are stored by columns:
+/// every 8 pixels wide column takes `height` bytes.
+///
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>,
+}
+
+impl Glyph {
+    pub fn pixel(&self, x: u16, y: u16) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let byte = (x / 8) as usize * self.height as usize + y as usize;
+        self.data.get(byte).is_some_and(|b| b & (0x80 >> (x % 8)) != 0)
+    }
+}
+
+impl Font {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let version = u16_at(bytes, 0)?;
+        let (char_table, offset_size) = match version {
+            0x0200 => (118, 2),
+            0x0300 => (148, 4),
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported font version 0x{:04X}", n),
+                ));
+            }
+        };
+        let font_type = u16_at(bytes, 66)?;
+        if font_type & FNT_VECTOR != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Vector fonts are not supported",
+            ));
+        }
+
+        let first_char = u8_at(bytes, 95)?;
+        let last_char = u8_at(bytes, 96)?;
+        // one more entry after last char is a sentinel
+        let count = (last_char as usize).saturating_sub(first_char as usize) + 2;
+        let mut chars = Vec::with_capacity(count);
+        for i in 0..count {
+            let entry = char_table + i * (2 + offset_size);
+            let offset = match offset_size {
+                2 => u16_at(bytes, entry + 2)? as u32,
+                _ => u32_at(bytes, entry + 2)?,
+            };
+            chars.push((u16_at(bytes, entry)?, offset));
+        }
+
+        let face_offset = u32_at(bytes, 105)? as usize;
+        let face = match bytes.get(face_offset..) {
+            Some(face) if face_offset != 0 => ascii_z(face),
+            _ => String::new(),
+        };
+
+        Ok(Self {
+            version,
+            size: u32_at(bytes, 2)?,
+            copyright: ascii_z(bytes.get(6..66).unwrap_or_default()),
+            font_type,
+            points: u16_at(bytes, 68)?,
+            vert_res: u16_at(bytes, 70)?,
+            horiz_res: u16_at(bytes, 72)?,
+            ascent: u16_at(bytes, 74)?,
+            italic: u8_at(bytes, 80)? != 0,
+            underline: u8_at(bytes, 81)? != 0,
+            strike_out: u8_at(bytes, 82)? != 0,
+            weight: u16_at(bytes, 83)?,
+            charset: u8_at(bytes, 85)?,
+            pix_width: u16_at(bytes, 86)?,
+            pix_height: u16_at(bytes, 88)?,
+            first_char,
+            last_char,
+            default_char: u8_at(bytes, 97)?,
+            break_char: u8_at(bytes, 98)?,
+            bits_offset: u32_at(bytes, 113)?,
+            face,
+            chars,
+            data: bytes.to_vec(),
+        })
+    }
+    ///
+    /// Bitmap of character `ch` or `None` if it's out of font
+    /// or its data is out of resource.
+    ///
+    pub fn glyph(&self, ch: u8) -> Option<Glyph> {
+        let index = ch.checked_sub(self.first_char)? as usize;
+        if ch > self.last_char {
+            return None;
+        }
+        let (width, offset) = *self.chars.get(index)?;
+        let length = width.div_ceil(8) as usize * self.pix_height as usize;
+        let data = self.data.get(offset as usize..offset as usize + length)?;
+
+        Some(Glyph {
+            width,
+            height: self.pix_height,
+            data: data.to_vec(),
+        })
+    }
+}
+
+///
+/// One font of `RT_FONTDIR` resource. `ordinal` is an identifier of `RT_FONT` resource.
+///
+#[derive(Debug, Clone)]
+pub struct FontDirEntry {
+    pub ordinal: u16,
+    pub version: u16,
+    pub font_type: u16,
+    pub points: u16,
+    pub weight: u16,
+    pub italic: bool,
+    pub charset: u8,
+    pub pix_width: u16,
+    pub pix_height: u16,
+    pub device: String,
+    pub face: String,
+}
+
+///
+/// Directory of fonts of `.FON` module (`RT_FONTDIR` resource).
+/// Every entry is a copy of font header (113 bytes) with device and face names.
+///
+#[derive(Debug, Clone)]
+pub struct FontDir {
+    pub entries: Vec<FontDirEntry>,
+}
+
+impl FontDir {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ResourceReader { bytes, pos: 0 };
+        let count = r.u16()?;
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let ordinal = r.u16()?;
+            let header = r.take(113)?;
+            entries.push(FontDirEntry {
+                ordinal,
+                version: u16_at(header, 0)?,
+                font_type: u16_at(header, 66)?,
+                points: u16_at(header, 68)?,
+                weight: u16_at(header, 83)?,
+                italic: u8_at(header, 80)? != 0,
+                charset: u8_at(header, 85)?,
+                pix_width: u16_at(header, 86)?,
+                pix_height: u16_at(header, 88)?,
+                device: r.asciiz()?,
+                face: r.asciiz()?,
+            });
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// `usType` of OS/2 bitmap array header ("BA")
+pub const BFT_BITMAPARRAY: u16 = 0x4142;
+/// `usType` of OS/2 bitmap ("BM")
+pub const BFT_BMAP: u16 = 0x4D42;
+/// `usType` of OS/2 monochrome icon ("IC")
+pub const BFT_ICON: u16 = 0x4349;
+/// `usType` of OS/2 monochrome pointer ("PT")
+pub const BFT_POINTER: u16 = 0x5450;
+/// `usType` of OS/2 color icon ("CI")
+pub const BFT_COLORICON: u16 = 0x4943;
+/// `usType` of OS/2 color pointer ("CP")
+pub const BFT_COLORPOINTER: u16 = 0x5043;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Os2BitmapKind {
+    Bitmap,
+    Icon,
+    Pointer,
+    ColorIcon,
+    ColorPointer,
+}
+
+impl Os2BitmapKind {
+    pub fn from(us_type: u16) -> Option<Self> {
+        match us_type {
+            BFT_BMAP => Some(Os2BitmapKind::Bitmap),
+            BFT_ICON => Some(Os2BitmapKind::Icon),
+            BFT_POINTER => Some(Os2BitmapKind::Pointer),
+            BFT_COLORICON => Some(Os2BitmapKind::ColorIcon),
+            BFT_COLORPOINTER => Some(Os2BitmapKind::ColorPointer),
+            _ => None,
+        }
+    }
+}
+
+///
+/// OS/2 `BITMAPFILEHEADER` with bitmap header following by it.
+/// Unlike Windows, OS/2 keeps file headers inside of resources.
+/// ```
+/// // +--------+--------+----------+----------+---------+----------------+---------+
+/// // | usType | cbSize | xHotspot | yHotspot | offBits | BITMAPINFO     | palette |
+/// // | WORD   | DWORD  | SHORT    | SHORT    | DWORD   | (12 bytes 1.x) | RGB2    |
+/// // +--------+--------+----------+----------+---------+----------------+---------+
+/// ```
+/// `offBits` is counted from the start of resource (or of `.BMP` file).
+///
+#[derive(Debug, Clone)]
+pub struct Os2BitmapHeader {
+    pub kind: Os2BitmapKind,
+    pub size: u32,
+    /// Hotspot of pointers (zero for bitmaps)
+    pub hotspot_x: i16,
+    pub hotspot_y: i16,
+    pub bits_offset: u32,
+    /// `cbFix`: 12 for OS/2 1.x headers
+    pub header_size: u32,
+    pub width: u32,
+    pub height: u32,
+    pub planes: u16,
+    pub bit_count: u16,
+    /// Colors as `[blue, green, red]`
+    pub palette: Vec<[u8; 3]>,
+}
+
+impl Os2BitmapHeader {
+    fn read(r: &mut ResourceReader) -> io::Result<Self> {
+        let start = r.pos;
+        let us_type = r.u16()?;
+        let kind = Os2BitmapKind::from(us_type).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown OS/2 bitmap type 0x{:04X} at 0x{:X}", us_type, start),
+            )
+        })?;
+        let size = r.u32()?;
+        let hotspot_x = r.i16()?;
+        let hotspot_y = r.i16()?;
+        let bits_offset = r.u32()?;
+
+        let info = r.pos;
+        let header_size = r.u32()?;
+        // 1.x header has 16-bit dimensions and RGB triples palette,
+        // 2.x header has 32-bit dimensions and RGB quads palette
+        let (width, height, planes, bit_count) = match header_size {
+            BITMAPCOREHEADER => (r.u16()? as u32, r.u16()? as u32, r.u16()?, r.u16()?),
+            16.. => (r.u32()?, r.u32()?, r.u16()?, r.u16()?),
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown OS/2 bitmap header size {}", n),
+                ));
+            }
+        };
+        let colors_used = match header_size {
+            36.. => u32_at(r.bytes, info + 32)?,
+            _ => 0,
+        };
+        r.pos = info + header_size as usize;
+
+        let colors = match (colors_used, bit_count) {
+            (0, 1..=8) => 1 << bit_count,
+            (n, _) => n,
+        };
+        let entry_size = match header_size {
+            BITMAPCOREHEADER => 3,
+            _ => 4,
+        };
+        let present = r.bytes.len().saturating_sub(r.pos) / entry_size;
+        if colors as u64 > present as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("OS/2 bitmap palette of {} colors at 0x{:X} is out of resource", colors, r.pos),
+            ));
+        }
+        let mut palette = Vec::with_capacity(colors as usize);
+        for _ in 0..colors {
+            let rgb = r.take(entry_size)?;
+            palette.push([rgb[0], rgb[1], rgb[2]]);
+        }
+
+        Ok(Self {
+            kind,
+            size,
+            hotspot_x,
+            hotspot_y,
+            bits_offset,
+            header_size,
+            width,
+            height,
+            planes,
+            bit_count,
+            palette,
+        })
+    }
+}
+
+///
+/// Image of OS/2 `RT_BITMAP` or `RT_POINTER` resource.
+/// Icons and pointers are holding monochrome AND/XOR image (doubled height) first.
+/// Color icons and pointers have the second header with color image.
+///
+#[derive(Debug, Clone)]
+pub struct Os2Bitmap {
+    pub headers: Vec<Os2BitmapHeader>,
+}
+
+impl Os2Bitmap {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        Self::read(&mut ResourceReader { bytes, pos: 0 })
+    }
+    fn read(r: &mut ResourceReader) -> io::Result<Self> {
+        let first = Os2BitmapHeader::read(r)?;
+        let headers = match first.kind {
+            Os2BitmapKind::ColorIcon | Os2BitmapKind::ColorPointer => vec![first, Os2BitmapHeader::read(r)?],
+            _ => vec![first],
+        };
+        Ok(Self { headers })
+    }
+    pub fn kind(&self) -> Os2BitmapKind {
+        self.headers[0].kind
+    }
+}
+
+///
+/// One bitmap of array. `display_width` and `display_height` tell
+/// what display resolution this bitmap is for (zero means any).
+///
+#[derive(Debug, Clone)]
+pub struct Os2BitmapArrayEntry {
+    /// Offset of `BITMAPARRAYFILEHEADER` from the start of resource
+    pub offset: u32,
+    pub display_width: u16,
+    pub display_height: u16,
+    pub bitmap: Os2Bitmap,
+}
+
+///
+/// OS/2 bitmap array: chain of `BITMAPARRAYFILEHEADER`s ("BA")
+/// linked by `offNext`. Every header keeps one bitmap for different displays.
+/// ```
+/// // +--------+--------+---------+-----------+-----------+-------------------+
+/// // | usType | cbSize | offNext | cxDisplay | cyDisplay | BITMAPFILEHEADER  |
+/// // | WORD   | DWORD  | DWORD   | WORD      | WORD      | ...               |
+/// // +--------+--------+---------+-----------+-----------+-------------------+
+/// ```
+/// Resource without array header is read as array of one bitmap.
+///
+#[derive(Debug, Clone)]
+pub struct Os2BitmapArray {
+    pub entries: Vec<Os2BitmapArrayEntry>,
+}
+
+impl Os2BitmapArray {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ResourceReader { bytes, pos: 0 };
+        if u16_at(bytes, 0)? != BFT_BITMAPARRAY {
+            return Ok(Self {
+                entries: vec![Os2BitmapArrayEntry {
+                    offset: 0,
+                    display_width: 0,
+                    display_height: 0,
+                    bitmap: Os2Bitmap::read(&mut r)?,
+                }],
+            });
+        }
+
+        let mut entries = Vec::new();
+        let mut offset = 0_u32;
+        loop {
+            r.pos = offset as usize;
+            if r.u16()? != BFT_BITMAPARRAY {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Bitmap array header expected at 0x{:X}", offset),
+                ));
+            }
+            r.u32()?; // cbSize
+            let next = r.u32()?;
+            let display_width = r.u16()?;
+            let display_height = r.u16()?;
+            entries.push(Os2BitmapArrayEntry {
+                offset,
+                display_width,
+                display_height,
+                bitmap: Os2Bitmap::read(&mut r)?,
+            });
+
+            if next == 0 {
+                return Ok(Self { entries });
+            }
+            // chain going back would never end
+            if next <= offset {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Bitmap array header at 0x{:X} points back to 0x{:X}", offset, next),
+                ));
+            }
+            offset = next;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Error,
+    Warning,
+    Info,
+    Help,
+    Prompt,
+    Other(u8),
+}
+
+impl MessageKind {
+    pub fn from(code: u8) -> Self {
+        match code {
+            b'E' => MessageKind::Error,
+            b'W' => MessageKind::Warning,
+            b'I' => MessageKind::Info,
+            b'H' => MessageKind::Help,
+            b'P' => MessageKind::Prompt,
+            n => MessageKind::Other(n),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub number: u16,
+    pub kind: MessageKind,
+    /// Text with `%1`..`%9` placeholders for `DosGetMessage`
+    pub text: String,
+}
+
+/// Magic of message files made by `MKMSGF`
+pub const MKMSGF_MAGIC: &[u8; 8] = b"\xFFMKMSGF\0";
+
+///
+/// Messages of OS/2 `RT_MESSAGE` resource or of bound message segment
+/// (what `DosGetMessage` reads). Also reads `.MSG` files.
+/// ```
+/// // +-----------+-------+-------+-----------+---------------------+
+/// // | component | count | first | offset16  | index (offsets)     |
+/// // | 3 chars   | WORD  | WORD  | BYTE      | WORD or DWORD each  |
+/// // +-----------+-------+-------+-----------+---------------------+
+/// ```
+/// `.MSG` file starts with `MKMSGF` magic and keeps pointer to the index.
+/// Every message starts with a kind letter (`E`, `W`, `I`, `H`, `P`).
+///
+#[derive(Debug, Clone)]
+pub struct MessageTable {
+    /// Component identifier (e.g. `SYS`)
+    pub component: String,
+    pub messages: Vec<Message>,
+}
+
+impl MessageTable {
+    pub fn parse(bytes: &[u8]) -> io::Result<Self> {
+        // Header fields are the same, .MSG file only has magic
+        // before them and keeps index elsewhere
+        let (header, index) = match bytes.starts_with(MKMSGF_MAGIC) {
+            true => (8, u16_at(bytes, 18)? as usize),
+            false => (0, 8),
+        };
+        let component = String::from_utf8_lossy(bytes.get(header..header + 3).ok_or_else(|| truncated(header))?).to_string();
+        let count = u16_at(bytes, header + 3)?;
+        let first = u16_at(bytes, header + 5)?;
+        let offset_size = match u8_at(bytes, header + 7)? {
+            0 => 4,
+            _ => 2,
+        };
+
+        let mut offsets = Vec::with_capacity(count as usize);
+        for i in 0..count as usize {
+            let entry = index + i * offset_size;
+            offsets.push(match offset_size {
+                2 => u16_at(bytes, entry)? as usize,
+                _ => u32_at(bytes, entry)? as usize,
+            });
+        }
+
+        let mut messages = Vec::with_capacity(offsets.len());
+        for (i, &start) in offsets.iter().enumerate() {
+            let end = offsets.get(i + 1).copied().unwrap_or(bytes.len());
+            let text = bytes.get(start..end).filter(|t| !t.is_empty()).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Message {} is out of resource (0x{:X}..0x{:X})", first as usize + i, start, end),
+                )
+            })?;
+            messages.push(Message {
+                number: first.wrapping_add(i as u16),
+                kind: MessageKind::from(text[0]),
+                text: String::from_utf8_lossy(&text[1..]).to_string(),
+            });
+        }
+
+        Ok(Self {
+            component,
+            messages,
+        })
+    }
+    pub fn get(&self, number: u16) -> Option<&Message> {
+        self.messages.iter().find(|m| m.number == number)
+    }
+}
+
+///
+/// Sequential reader of resource bytes
+///
+struct ResourceReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ResourceReader<'a> {
+    fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + length)
+            .ok_or_else(|| truncated(self.pos))?;
+        self.pos += length;
+        Ok(bytes)
+    }
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> io::Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    fn i16(&mut self) -> io::Result<i16> {
+        Ok(self.u16()? as i16)
+    }
+    fn u32(&mut self) -> io::Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn asciiz(&mut self) -> io::Result<String> {
+        let rest = self.bytes.get(self.pos..).unwrap_or_default();
+        let length = rest.iter().position(|&b| b == 0).ok_or_else(|| truncated(self.bytes.len()))?;
+        let text = String::from_utf8_lossy(&rest[..length]).to_string();
+        self.pos += length + 1;
+        Ok(text)
+    }
+    fn name_or_ordinal(&mut self) -> io::Result<NameOrOrdinal> {
+        match self.u8()? {
+            0 => Ok(NameOrOrdinal::None),
+            0xFF => Ok(NameOrOrdinal::Ordinal(self.u16()?)),
+            _ => {
+                self.pos -= 1;
+                Ok(NameOrOrdinal::Name(self.asciiz()?))
+            }
+        }
+    }
+}
+
+struct DirectoryEntry<'a> {
+    width: u32,
+    height: u32,
+    bit_count: u16,
+    /// Planes of icon or hotspot X of cursor
+    x: u16,
+    /// Bit count of icon or hotspot Y of cursor
+    y: u16,
+    data: &'a [u8],
+}
+
+///
+/// Writes `ICONDIR` with entries and images after it.
+/// `kind` is 1 for icons and 2 for cursors.
+///
+fn write_directory(kind: u16, entries: &[DirectoryEntry]) -> Vec<u8> {
+    let mut file = Vec::new();
+    file.extend_from_slice(&0_u16.to_le_bytes());
+    file.extend_from_slice(&kind.to_le_bytes());
+    file.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+    let mut image_offset = 6 + entries.len() as u32 * 16;
+    for entry in entries {
+        // 256 pixels and 256 colors are written as 0
+        let colors = match entry.bit_count {
+            1..=7 => 1_u8 << entry.bit_count,
+            _ => 0,
+        };
+        file.push(entry.width as u8);
+        file.push(entry.height as u8);
+        file.push(colors);
+        file.push(0);
+        file.extend_from_slice(&entry.x.to_le_bytes());
+        file.extend_from_slice(&entry.y.to_le_bytes());
+        file.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+        file.extend_from_slice(&image_offset.to_le_bytes());
+        image_offset += entry.data.len() as u32;
+    }
+    for entry in entries {
+        file.extend_from_slice(entry.data);
+    }
+    file
+}
+
+fn truncated(offset: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("Resource is truncated at 0x{:X}", offset),
+    )
+}
+
+fn u8_at(bytes: &[u8], offset: usize) -> io::Result<u8> {
+    bytes.get(offset).copied().ok_or_else(|| truncated(offset))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    match bytes.get(offset..offset + 2) {
+        Some(b) => Ok(u16::from_le_bytes([b[0], b[1]])),
+        None => Err(truncated(offset)),
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    match bytes.get(offset..offset + 4) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(truncated(offset)),
+    }
+}
@@ -0,0 +1,323 @@
+//! This module represents structure and method-reader of resource table.
+//! Resource table of Windows modules is a list of resource types
+//! and every type holds a list of resources of that type.
+//! ```
+//! // +----------------------+
+//! // | rscAlignShift        | <-- resource data offsets are shifted by it
+//! // +----------------------+
+//! // | TYPEINFO             | <-- [TYPE_ID; COUNT; RESERVED]
+//! // |+--------------------+|
+//! // || NAMEINFO           || <-- [OFFSET; LENGTH; FLAGS; ID; RESERVED]
+//! // || ...                ||
+//! // |+--------------------+|
+//! // | TYPEINFO             |
+//! // | ...                  |
+//! // +----------------------+
+//! // | 0x0000               | <-- end of types
+//! // +----------------------+
+//! // | Resource names       | <-- Pascal strings, ends with zero byte
+//! // +----------------------+
+//! ```
+//! Type and resource identifiers with the high bit set are integers.
+//! Otherwise they are offsets of Pascal strings from the table start.
+//!
+//! OS/2 1.x modules keep resources in segments. Their resource table is
+//! `e_crsrc` pairs of type and name words, and resources are
+//! the last `e_crsrc` segments of the segment table
+//! (see [ResourceTable::read_os2]). Types of OS/2 resources have
+//! their own numbering (see [type_name]).
+use crate::exe286::header::OS;
+use crate::exe286::segtab::Segment;
+use crate::types::PascalString;
+use std::io::{self, Read, Seek, SeekFrom};
+
+pub const RT_CURSOR: u16 = 1;
+pub const RT_BITMAP: u16 = 2;
+pub const RT_ICON: u16 = 3;
+pub const RT_MENU: u16 = 4;
+pub const RT_DIALOG: u16 = 5;
+pub const RT_STRING: u16 = 6;
+pub const RT_FONTDIR: u16 = 7;
+pub const RT_FONT: u16 = 8;
+pub const RT_ACCELERATOR: u16 = 9;
+pub const RT_RCDATA: u16 = 10;
+pub const RT_GROUP_CURSOR: u16 = 12;
+pub const RT_GROUP_ICON: u16 = 14;
+pub const RT_VERSION: u16 = 16;
+
+// OS/2 resource types (PM toolkit numbering)
+pub const OS2_RT_POINTER: u16 = 1;
+pub const OS2_RT_BITMAP: u16 = 2;
+pub const OS2_RT_MENU: u16 = 3;
+pub const OS2_RT_DIALOG: u16 = 4;
+pub const OS2_RT_STRING: u16 = 5;
+pub const OS2_RT_FONTDIR: u16 = 6;
+pub const OS2_RT_FONT: u16 = 7;
+pub const OS2_RT_ACCELTABLE: u16 = 8;
+pub const OS2_RT_RCDATA: u16 = 9;
+pub const OS2_RT_MESSAGE: u16 = 10;
+pub const OS2_RT_DLGINCLUDE: u16 = 11;
+pub const OS2_RT_VKEYTBL: u16 = 12;
+pub const OS2_RT_KEYTBL: u16 = 13;
+pub const OS2_RT_CHARTBL: u16 = 14;
+pub const OS2_RT_DISPLAYINFO: u16 = 15;
+pub const OS2_RT_FKASHORT: u16 = 16;
+pub const OS2_RT_FKALONG: u16 = 17;
+pub const OS2_RT_HELPTABLE: u16 = 18;
+pub const OS2_RT_HELPSUBTABLE: u16 = 19;
+pub const OS2_RT_FDDIR: u16 = 20;
+pub const OS2_RT_FD: u16 = 21;
+
+///
+/// Name of integer resource type. Numbering depends on target OS:
+/// the same `1` is a cursor of Windows and a pointer of OS/2.
+///
+pub fn type_name(type_id: u16, os: &OS) -> Option<&'static str> {
+    let name = match os {
+        OS::Os2 => match type_id {
+            OS2_RT_POINTER => "POINTER",
+            OS2_RT_BITMAP => "BITMAP",
+            OS2_RT_MENU => "MENU",
+            OS2_RT_DIALOG => "DIALOG",
+            OS2_RT_STRING => "STRINGTABLE",
+            OS2_RT_FONTDIR => "FONTDIR",
+            OS2_RT_FONT => "FONT",
+            OS2_RT_ACCELTABLE => "ACCELTABLE",
+            OS2_RT_RCDATA => "RCDATA",
+            OS2_RT_MESSAGE => "MESSAGETABLE",
+            OS2_RT_DLGINCLUDE => "DLGINCLUDE",
+            OS2_RT_VKEYTBL => "VKEYTBL",
+            OS2_RT_KEYTBL => "KEYTBL",
+            OS2_RT_CHARTBL => "CHARTBL",
+            OS2_RT_DISPLAYINFO => "DISPLAYINFO",
+            OS2_RT_FKASHORT => "FKASHORT",
+            OS2_RT_FKALONG => "FKALONG",
+            OS2_RT_HELPTABLE => "HELPTABLE",
+            OS2_RT_HELPSUBTABLE => "HELPSUBTABLE",
+            OS2_RT_FDDIR => "FDDIR",
+            OS2_RT_FD => "FD",
+            _ => return None,
+        },
+        _ => match type_id {
+            RT_CURSOR => "CURSOR",
+            RT_BITMAP => "BITMAP",
+            RT_ICON => "ICON",
+            RT_MENU => "MENU",
+            RT_DIALOG => "DIALOG",
+            RT_STRING => "STRINGTABLE",
+            RT_FONTDIR => "FONTDIR",
+            RT_FONT => "FONT",
+            RT_ACCELERATOR => "ACCELERATORS",
+            RT_RCDATA => "RCDATA",
+            RT_GROUP_CURSOR => "GROUP_CURSOR",
+            RT_GROUP_ICON => "GROUP_ICON",
+            RT_VERSION => "VERSIONINFO",
+            _ => return None,
+        },
+    };
+    Some(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceId {
+    /// Integer identifier (high bit is cleared)
+    Integer(u16),
+    /// Identifier string
+    Name(PascalString),
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceTable {
+    pub align_shift: u16,
+    pub types: Vec<ResourceType>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceType {
+    pub type_id: ResourceId,
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Resource {
+    pub id: ResourceId,
+    /// Raw file pointer to resource data
+    pub offset: u64,
+    /// Length of data in file (aligned, may be larger than actual resource)
+    pub length: u64,
+    pub flags: u16,
+    pub data: Vec<u8>,
+}
+
+impl ResourceTable {
+    pub fn empty() -> Self {
+        Self {
+            align_shift: 0,
+            types: Vec::new(),
+        }
+    }
+    ///
+    /// Reads resource table by absolute `e_rsrc_tab` offset
+    /// with data of every resource.
+    ///
+    pub fn read<R: Read + Seek>(reader: &mut R, e_rsrc_tab: u64) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(e_rsrc_tab))?;
+        let align_shift = read_u16(reader)?;
+        if align_shift >= 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid resource alignment shift {}", align_shift),
+            ));
+        }
+
+        // Identifiers are resolved later: names follow the types list
+        let mut raw_types: Vec<(u16, Vec<[u16; 4]>)> = Vec::new();
+        loop {
+            let type_id = read_u16(reader)?;
+            if type_id == 0 {
+                break;
+            }
+            let count = read_u16(reader)?;
+            read_u32(reader)?; // reserved
+
+            let mut names = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let offset = read_u16(reader)?;
+                let length = read_u16(reader)?;
+                let flags = read_u16(reader)?;
+                let id = read_u16(reader)?;
+                read_u32(reader)?; // reserved
+                names.push([offset, length, flags, id]);
+            }
+            raw_types.push((type_id, names));
+        }
+
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let mut types = Vec::with_capacity(raw_types.len());
+        for (type_id, names) in raw_types {
+            let type_id = Self::read_id(reader, e_rsrc_tab, type_id)?;
+            let mut resources = Vec::with_capacity(names.len());
+
+            for [offset, length, flags, id] in names {
+                let offset = (offset as u64) << align_shift;
+                let length = (length as u64) << align_shift;
+                let mut data = Vec::new();
+                if offset < file_length {
+                    reader.seek(SeekFrom::Start(offset))?;
+                    reader.by_ref().take(length).read_to_end(&mut data)?;
+                }
+                resources.push(Resource {
+                    id: Self::read_id(reader, e_rsrc_tab, id)?,
+                    offset,
+                    length,
+                    flags,
+                    data,
+                });
+            }
+            types.push(ResourceType {
+                type_id,
+                resources,
+            });
+        }
+
+        Ok(Self {
+            align_shift,
+            types,
+        })
+    }
+
+    ///
+    /// Reads OS/2 resource table: `e_crsrc` pairs of type and name
+    /// by absolute `e_rsrc_tab` offset. Resource number `i` is the data
+    /// of segment `e_cseg - e_crsrc + i` (0-based) of `segments`.
+    ///
+    pub fn read_os2<R: Read + Seek>(
+        reader: &mut R,
+        e_rsrc_tab: u64,
+        e_crsrc: u16,
+        segments: &[Segment],
+    ) -> io::Result<Self> {
+        let first_segment = segments.len().checked_sub(e_crsrc as usize).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} resources but {} segments", e_crsrc, segments.len()),
+            )
+        })?;
+        reader.seek(SeekFrom::Start(e_rsrc_tab))?;
+        let mut pairs = Vec::with_capacity(e_crsrc as usize);
+        for _ in 0..e_crsrc {
+            pairs.push((read_u16(reader)?, read_u16(reader)?));
+        }
+
+        let mut types: Vec<ResourceType> = Vec::new();
+        for (i, (type_id, name)) in pairs.into_iter().enumerate() {
+            let segment = &segments[first_segment + i];
+            let mut data = Vec::new();
+            if segment.header.sector_base != 0 {
+                reader.seek(SeekFrom::Start(segment.file_offset()))?;
+                reader.by_ref().take(segment.header.physical_size()).read_to_end(&mut data)?;
+            }
+            let resource = Resource {
+                id: ResourceId::Integer(name),
+                offset: segment.file_offset(),
+                length: segment.header.physical_size(),
+                flags: segment.header.flags,
+                data,
+            };
+            let type_id = ResourceId::Integer(type_id);
+            match types.iter_mut().find(|t| t.type_id == type_id) {
+                Some(t) => t.resources.push(resource),
+                None => types.push(ResourceType {
+                    type_id,
+                    resources: vec![resource],
+                }),
+            }
+        }
+
+        Ok(Self {
+            align_shift: segments.first().map(|s| s.shift_count).unwrap_or(0),
+            types,
+        })
+    }
+
+    fn read_id<R: Read + Seek>(reader: &mut R, e_rsrc_tab: u64, id: u16) -> io::Result<ResourceId> {
+        if id & 0x8000 != 0 {
+            return Ok(ResourceId::Integer(id & 0x7FFF));
+        }
+        reader.seek(SeekFrom::Start(e_rsrc_tab + id as u64))?;
+        let mut len = 0_u8;
+        reader.read_exact(std::slice::from_mut(&mut len))?;
+        let mut name = vec![0; len as usize];
+        reader.read_exact(&mut name)?;
+
+        Ok(ResourceId::Name(PascalString::new(len, name)))
+    }
+    ///
+    /// All resources of integer type `type_id` (e.g. [RT_ICON])
+    ///
+    pub fn resources_of(&self, type_id: u16) -> impl Iterator<Item = &Resource> {
+        self.types
+            .iter()
+            .filter(move |t| t.type_id == ResourceId::Integer(type_id))
+            .flat_map(|t| t.resources.iter())
+    }
+    ///
+    /// Resource of integer type and integer identifier
+    ///
+    pub fn find(&self, type_id: u16, id: u16) -> Option<&Resource> {
+        self.resources_of(type_id)
+            .find(|r| r.id == ResourceId::Integer(id))
+    }
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
@@ -13,27 +13,75 @@
 //! 
 //! FPU fixups are instructions what Windows
 //! wants to "fix-up" while application runs
+use crate::exe286::segtab::Segment;
+use crate::types::table_accessors;
+use std::collections::HashSet;
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
 
-#[derive(Debug, Clone)]
+///
+/// Target of internal relocation. Segment `0xFF` is not a segment number:
+/// it marks moveable target, and `int_offset` holds the entry table ordinal
+/// of the target instead of offset.
+/// ```
+/// use os2omf::exe286::segrelocs::InternalFixup;
+///
+/// // 0x0002:0x0010
+/// let fixed = InternalFixup { int_seg: 2, int_mov: false, int_offset: 0x10 };
+/// assert_eq!(fixed.target_segment(), Some(2));
+/// assert_eq!(fixed.entry_ordinal(), None);
+///
+/// // entry @5 of moveable segment
+/// let moveable = InternalFixup { int_seg: 0xFF, int_mov: true, int_offset: 5 };
+/// assert_eq!(moveable.target_segment(), None);
+/// assert_eq!(moveable.entry_ordinal(), Some(5));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InternalFixup {
+    /// 1-based segment number or `0xFF` for moveable target
     pub int_seg: u8,
     /// Is moveable?
     pub int_mov: bool,
+    /// Offset in segment or entry ordinal for moveable target
     pub int_offset: u16,
 }
-#[derive(Clone, Debug)]
+
+impl InternalFixup {
+    pub fn is_moveable(&self) -> bool {
+        self.int_mov
+    }
+    ///
+    /// Segment number of fixed target. Moveable targets
+    /// have no segment here, see [InternalFixup::entry_ordinal].
+    ///
+    pub fn target_segment(&self) -> Option<u8> {
+        match self.int_mov {
+            true => None,
+            false => Some(self.int_seg),
+        }
+    }
+    ///
+    /// Entry table ordinal of moveable target
+    ///
+    pub fn entry_ordinal(&self) -> Option<u16> {
+        match self.int_mov {
+            true => Some(self.int_offset),
+            false => None,
+        }
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ImportOrdinal {
     pub imp_mod_index: u16,
     pub imp_ordinal: u16,
 }
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ImportName {
     pub imp_mod_index: u16,
     pub imp_offset: u16,
 }
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FPUFixup {
     /// See FPUFixupType
     pub osf_type: FPUFixupType,
@@ -47,7 +95,7 @@ pub struct FPUFixup {
 /// Type what marked as "j" will be second in
 /// command/opcode sequence
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(u16)]
 pub enum FPUFixupType {
     FiArqqFjArqq = 0x0001,
@@ -69,7 +117,40 @@ impl FPUFixupType {
         }
     }
 }
-#[derive(Debug, Clone)]
+///
+/// Type of the address what relocation patches
+/// in the segment data. Stored in the first byte of record.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    /// Low byte of the offset
+    LoByte,
+    /// 16-bit selector (segment)
+    Selector,
+    /// 32-bit far pointer `segment:offset`
+    Pointer32,
+    /// 16-bit offset
+    Offset16,
+    /// 48-bit far pointer `selector:offset32`
+    Pointer48,
+    /// 32-bit offset
+    Offset32,
+    Unknown(u8),
+}
+impl AddressType {
+    pub fn from(value: u8) -> Self {
+        match value {
+            0x00 => AddressType::LoByte,
+            0x02 => AddressType::Selector,
+            0x03 => AddressType::Pointer32,
+            0x05 => AddressType::Offset16,
+            0x0B => AddressType::Pointer48,
+            0x0D => AddressType::Offset32,
+            n => AddressType::Unknown(n),
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelocationType {
     Internal(InternalFixup),
     ImportName(ImportName),
@@ -83,7 +164,7 @@ pub enum RelocationType {
 /// Derivatives of RelocationType are having 32-bit size
 /// And it helps to define size of all table avoiding shit.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RelocationEntry {
     pub rel_rtp: u8,   // Address Type
     pub rel_atp: u8,   // Relocation type
@@ -91,20 +172,133 @@ pub struct RelocationEntry {
     pub rel_seg_ptr: u16,
     pub rel_type: RelocationType,
 }
+impl RelocationEntry {
+    ///
+    /// Writes 8-byte relocation record
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let (kind, target) = match &self.rel_type {
+            RelocationType::Internal(f) => (0x00, [f.int_seg, 0, 0, 0]),
+            RelocationType::ImportOrdinal(i) => (0x01, words(i.imp_mod_index, i.imp_ordinal)),
+            RelocationType::ImportName(i) => (0x02, words(i.imp_mod_index, i.imp_offset)),
+            RelocationType::OSFixup(f) => (0x03, words(f.osf_type.clone() as u16, f.osf_padd)),
+        };
+        let mut buf = [0_u8; 8];
+        buf[0] = self.rel_atp;
+        buf[1] = kind | if self.rel_add { 0x04 } else { 0 };
+        buf[2..4].copy_from_slice(&self.rel_seg_ptr.to_le_bytes());
+        buf[4..8].copy_from_slice(&target);
+        if let RelocationType::Internal(f) = &self.rel_type {
+            buf[6..8].copy_from_slice(&f.int_offset.to_le_bytes());
+        }
+        w.write_all(&buf)
+    }
+    ///
+    /// Additive relocation adds the target to the value already
+    /// stored at `rel_seg_ptr` and patches only this place.
+    ///
+    pub fn is_additive(&self) -> bool {
+        self.rel_add
+    }
+    ///
+    /// Offsets in segment `data` which this record patches.
+    /// Additive record patches only `rel_seg_ptr`. Other records are chains:
+    /// the word at the offset holds the offset of next place to patch,
+    /// and `0xFFFF` ends the chain.
+    ///
+    /// Broken chains are errors here, use [RelocationEntry::chain]
+    /// to get the sites found before the break.
+    ///
+    pub fn sites(&self, data: &[u8]) -> io::Result<Vec<u16>> {
+        let chain = self.chain(data);
+        match chain.end {
+            ChainEnd::Terminated => Ok(chain.sites),
+            ChainEnd::OutOfBounds(site) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Relocation at 0x{:04X} is out of segment bounds", site),
+            )),
+            ChainEnd::Cycle(site) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Relocation chain loops back to 0x{:04X}", site),
+            )),
+        }
+    }
+    ///
+    /// Walks the chain of record through segment `data`.
+    /// Walk stops at `0xFFFF`, at the offset outside of segment
+    /// or at the site which is already visited (packed files have such chains).
+    ///
+    pub fn chain(&self, data: &[u8]) -> RelocationChain {
+        if self.rel_add {
+            return RelocationChain {
+                sites: vec![self.rel_seg_ptr],
+                end: ChainEnd::Terminated,
+            };
+        }
+        let mut sites = Vec::new();
+        let mut visited = HashSet::new();
+        let mut site = self.rel_seg_ptr;
+        // every word can be visited once, so the walk is capped by segment size
+        let end = loop {
+            if !visited.insert(site) {
+                break ChainEnd::Cycle(site);
+            }
+            let next = match RelocationTable::read_word(data, site) {
+                Ok(next) => next,
+                Err(_) => break ChainEnd::OutOfBounds(site),
+            };
+            sites.push(site);
+            if next == 0xFFFF {
+                break ChainEnd::Terminated;
+            }
+            site = next;
+        };
+        RelocationChain { sites, end }
+    }
+}
+///
+/// Reason of the relocation chain end
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainEnd {
+    /// `0xFFFF` terminator or additive relocation
+    Terminated,
+    /// Chain points outside of segment data
+    OutOfBounds(u16),
+    /// Chain points to the site which is already in chain
+    Cycle(u16),
+}
+///
+/// Sites of one relocation record in segment data
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelocationChain {
+    pub sites: Vec<u16>,
+    pub end: ChainEnd,
+}
+impl RelocationChain {
+    ///
+    /// Chain is broken and some of real sites may be lost
+    ///
+    pub fn is_abnormal(&self) -> bool {
+        self.end != ChainEnd::Terminated
+    }
+}
 ///
 /// Relocation table is a sequence of defined
 /// relocation records.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RelocationTable {
     pub rel_entries: Vec<RelocationEntry>,
 }
+table_accessors!(RelocationTable, rel_entries, RelocationEntry);
 
 impl RelocationTable {
     pub fn read<TRead: Read>(r: &mut TRead) -> io::Result<Self> {
         let mut count_buf = [0; 2];
         r.read_exact(&mut count_buf)?;
-        let count: u16 = bytemuck::cast(count_buf);
+        let count = u16::from_le_bytes(count_buf);
 
         let mut entries = Vec::with_capacity(count as usize);
 
@@ -191,3 +385,153 @@ impl RelocationTable {
         })
     }
 }
+
+impl RelocationTable {
+    ///
+    /// Writes count of records and the records, the same layout `read` reads
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let count = u16::try_from(self.rel_entries.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} relocations don't fit into segment", self.rel_entries.len()),
+            )
+        })?;
+        w.write_all(&count.to_le_bytes())?;
+        for entry in &self.rel_entries {
+            entry.write(w)?;
+        }
+        Ok(())
+    }
+    ///
+    /// Every site of every record in loaded segment `data`,
+    /// in order of records. Additive records have a single site.
+    /// Broken chains keep the sites found before the break,
+    /// use [RelocationTable::chains] to know which were broken.
+    ///
+    pub fn chain_sites(&self, segment_data: &[u8]) -> Vec<Vec<u16>> {
+        self.chains(segment_data)
+            .into_iter()
+            .map(|chain| chain.sites)
+            .collect()
+    }
+    ///
+    /// Chains of every record in loaded segment `data`
+    ///
+    pub fn chains(&self, segment_data: &[u8]) -> Vec<RelocationChain> {
+        self.rel_entries
+            .iter()
+            .map(|entry| entry.chain(segment_data))
+            .collect()
+    }
+    ///
+    /// Patches segment `data` by internal relocations like a real-mode loader does.
+    /// Module image is expected to be loaded at `load_segment` paragraph,
+    /// so target segment value is a paragraph of [Segment::linear_address].
+    ///
+    /// Places to patch are found by [RelocationEntry::sites].
+    ///
+    /// Moveable targets (segment `0xFF`) are entry table ordinals and
+    /// can't be resolved by segments only. They are skipped, see
+    /// `NewExecutableLayout::resolve_internal_fixup`.
+    ///
+    pub fn apply_internal_fixups(
+        &self,
+        data: &mut [u8],
+        segments: &[Segment],
+        load_segment: u16,
+    ) -> io::Result<()> {
+        for entry in &self.rel_entries {
+            let fixup = match &entry.rel_type {
+                RelocationType::Internal(fixup) if !fixup.int_mov => fixup,
+                _ => continue,
+            };
+            let segment = segments
+                .get((fixup.int_seg as usize).wrapping_sub(1))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Relocation targets unknown segment #{}", fixup.int_seg),
+                    )
+                })?;
+            let paragraph = (segment.linear_address(load_segment) >> 4) as u16;
+            let address_type = AddressType::from(entry.rel_atp);
+
+            for site in entry.sites(data)? {
+                Self::patch(data, site, address_type, paragraph, fixup.int_offset, entry.rel_add)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_word(data: &[u8], site: u16) -> io::Result<u16> {
+        let site = site as usize;
+        match data.get(site..site + 2) {
+            Some(word) => Ok(u16::from_le_bytes([word[0], word[1]])),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Relocation at 0x{:04X} is out of segment bounds", site),
+            )),
+        }
+    }
+
+    fn patch(
+        data: &mut [u8],
+        site: u16,
+        address_type: AddressType,
+        segment: u16,
+        offset: u16,
+        additive: bool,
+    ) -> io::Result<()> {
+        let (width, value): (usize, u64) = match address_type {
+            AddressType::LoByte => (1, offset as u64 & 0xFF),
+            AddressType::Selector => (2, segment as u64),
+            AddressType::Offset16 => (2, offset as u64),
+            AddressType::Pointer32 => (4, ((segment as u64) << 16) | offset as u64),
+            AddressType::Offset32 => (4, offset as u64),
+            AddressType::Pointer48 => (6, ((segment as u64) << 32) | offset as u64),
+            AddressType::Unknown(n) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown relocation address type 0x{:02X}", n),
+                ));
+            }
+        };
+        let site = site as usize;
+        let bytes = data.get_mut(site..site + width).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Relocation at 0x{:04X} is out of segment bounds", site),
+            )
+        })?;
+
+        let mut current = [0_u8; 8];
+        current[..width].copy_from_slice(bytes);
+        let current = u64::from_le_bytes(current);
+        // additive far pointers are adding segment and offset parts separately
+        let patched = match (additive, address_type) {
+            (false, _) => value,
+            (true, AddressType::Pointer32) => {
+                let off = (current as u16).wrapping_add(offset) as u64;
+                let seg = ((current >> 16) as u16).wrapping_add(segment) as u64;
+                (seg << 16) | off
+            }
+            (true, AddressType::Pointer48) => {
+                let off = (current as u32).wrapping_add(offset as u32) as u64;
+                let seg = ((current >> 32) as u16).wrapping_add(segment) as u64;
+                (seg << 32) | off
+            }
+            (true, _) => current.wrapping_add(value),
+        };
+        bytes.copy_from_slice(&patched.to_le_bytes()[..width]);
+
+        Ok(())
+    }
+}
+
+fn words(first: u16, second: u16) -> [u8; 4] {
+    let [a, b] = first.to_le_bytes();
+    let [c, d] = second.to_le_bytes();
+    [a, b, c, d]
+}
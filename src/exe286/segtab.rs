@@ -1,8 +1,18 @@
 //! This module represents details of `SegmentsTable` and implements
 //! methods for extracting raw bytes into big table of segments.
+//!
+//! Segment data is addressed by sectors, not bytes. File offset of
+//! the data is `sector_base << e_align`, where zero `e_align` means
+//! the default 512-byte sector ([Segment::DEFAULT_SECTOR_SHIFT]).
+//! Sector 0 means the segment has no data in file: it's a `.bss`
+//! prototype filled with zeros by the loader.
+use crate::exe286::header::OS;
 use crate::exe286::segrelocs::{RelocationTable, RelocationType};
+use crate::types::readable::Readable;
 use crate::types::PascalString;
-use std::io::{self, Read, Seek, SeekFrom};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 ///
 /// This table contains one 8-byte record for every code and data segment
 /// in the program or library module.
@@ -23,12 +33,32 @@ pub struct Segment {
 }
 
 impl Segment {
+    /// Shift used by the loader when `e_align` is zero (512-byte sectors)
+    pub const DEFAULT_SECTOR_SHIFT: u16 = 9;
+
+    ///
+    /// File offset of the `sector` with `shift` alignment of module.
+    /// Zero `shift` is replaced by [Self::DEFAULT_SECTOR_SHIFT].
+    /// Shift out of `u64` range gives `u64::MAX`, which is past any file.
+    ///
+    pub fn sector_to_file_offset(sector: u16, shift: u16) -> u64 {
+        let shift = if shift == 0 { Self::DEFAULT_SECTOR_SHIFT } else { shift };
+        (sector as u64).checked_shl(shift as u32).unwrap_or(u64::MAX)
+    }
+
     pub fn read<T: Read + Seek>(reader: &mut T, alignment: u16) -> io::Result<Self> {
-        let alignment = if alignment == 0 { 9 } else { alignment };
+        let alignment = if alignment == 0 { Self::DEFAULT_SECTOR_SHIFT } else { alignment };
         let header = SegmentHeader::read(reader)?;
 
-        let relocs = if !header.relocations_stripped() {
-            Self::read_relocs(reader, alignment as u64, &header)?
+        // .BSS segments have no data in file, so there's nothing
+        // for relocations to follow even if the flag is set.
+        let relocs = if header.has_relocations() && header.sector_base != 0 {
+            // Segment records are read one-by-one from the segments table.
+            // Come back after relocations, or the next record will be garbage.
+            let next_record = reader.stream_position()?;
+            let relocs = Self::read_relocs(reader, alignment, &header)?;
+            reader.seek(SeekFrom::Start(next_record))?;
+            relocs
         } else {
             RelocationTable {
                 rel_entries: vec![],
@@ -42,47 +72,105 @@ impl Segment {
             relocs,
         })
     }
-
+    ///
+    /// Per-segment relocations are following right after segment data.
+    /// Length of data is not a `sector_length` literally: zero length
+    /// means 64K segment (see [SegmentHeader::data_length]).
+    ///
     fn read_relocs<T: Read + Seek>(
         reader: &mut T,
-        alignment: u64,
+        alignment: u16,
         header: &SegmentHeader,
     ) -> io::Result<RelocationTable> {
-        let position = match (header.sector_base as u64).checked_mul(1 << alignment) {
-            Some(base_shifted) => base_shifted.checked_add(header.sector_length as u64),
-            None => None,
-        };
+        let position = Self::sector_to_file_offset(header.sector_base, alignment)
+            .checked_add(header.physical_size());
+
+        let file_length = reader.seek(SeekFrom::End(0))?;
 
         let position = match position {
-            Some(pos) => pos,
-            None => {
-                return Ok(RelocationTable {
-                    rel_entries: vec![],
-                });
+            Some(pos) if pos + 2 <= file_length => pos,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Relocations of segment at sector 0x{:X} are out of file bounds",
+                        header.sector_base
+                    ),
+                ));
             }
         };
 
-        let current_pos = reader.stream_position()?;
-        let file_length = reader.seek(SeekFrom::End(0))?;
-        reader.seek(SeekFrom::Start(current_pos))?;
-
-        if position + 2 > file_length {
-            return Ok(RelocationTable {
-                rel_entries: vec![],
-            });
-        }
-
         reader.seek(SeekFrom::Start(position))?;
         RelocationTable::read(reader)
     }
 
+    ///
+    /// Raw file pointer to the segment data. Use it
+    /// for reading segment contents from the file.
+    ///
+    pub fn file_offset(&self) -> u64 {
+        Self::sector_to_file_offset(self.header.sector_base, self.shift_count)
+    }
+    ///
+    /// Real-mode linear address of the segment when module image
+    /// is loaded at `load_segment` paragraph. Unlike [Segment::file_offset]
+    /// this is a run-time address used for relocations computing.
+    /// ```
+    /// // linear = (load_segment << 4) + (sector_base << shift_count)
+    /// ```
+    ///
+    pub fn linear_address(&self, load_segment: u16) -> u32 {
+        ((load_segment as u32) << 4).wrapping_add(self.file_offset() as u32)
+    }
+
+    ///
+    /// Count of relocation records of the segment
+    ///
+    pub fn relocation_count(&self) -> usize {
+        self.relocs.rel_entries.len()
+    }
+    pub fn has_relocations(&self) -> bool {
+        !self.relocs.rel_entries.is_empty()
+    }
+    ///
+    /// Count of relocations to segments of this module
+    ///
+    pub fn internal_relocation_count(&self) -> usize {
+        self.count_relocations(|t| matches!(t, RelocationType::Internal(_)))
+    }
+    ///
+    /// Count of relocations to imported names and ordinals
+    ///
+    pub fn import_relocation_count(&self) -> usize {
+        self.count_relocations(|t| {
+            matches!(
+                t,
+                RelocationType::ImportName(_) | RelocationType::ImportOrdinal(_)
+            )
+        })
+    }
+    ///
+    /// Count of OS fixups of floating-point instructions
+    ///
+    pub fn fpu_fixup_count(&self) -> usize {
+        self.count_relocations(|t| matches!(t, RelocationType::OSFixup(_)))
+    }
+
+    fn count_relocations(&self, kind: impl Fn(&RelocationType) -> bool) -> usize {
+        self.relocs
+            .rel_entries
+            .iter()
+            .filter(|e| kind(&e.rel_type))
+            .count()
+    }
+
     pub fn read_data<T: Read + Seek>(&mut self, reader: &mut T) -> io::Result<()> {
         if self.header.sector_base == 0 {
             return Ok(());
         }
 
-        let data_offset = self.header.data_offset(self.shift_count as u64);
-        let data_length = self.header.data_length();
+        let data_offset = self.file_offset();
+        let data_length = self.header.physical_size();
 
         reader.seek(SeekFrom::Start(data_offset))?;
         let mut data = vec![0; data_length as usize];
@@ -99,19 +187,70 @@ impl DllImport {
         name: PascalString,
         ordinal: u16,
         file_pointer: u64,
+        seg_offset: u16,
     ) -> Self {
         Self {
             dll_name,
             name,
             ordinal,
             file_pointer,
+            seg_offset,
         }
     }
+    ///
+    /// Procedure is imported by ordinal: it has no name
+    ///
+    pub fn is_by_ordinal(&self) -> bool {
+        self.ordinal != 0 && self.name.is_empty()
+    }
+    pub fn is_by_name(&self) -> bool {
+        !self.is_by_ordinal()
+    }
+    ///
+    /// Procedure name or `#ordinal` (like `#5`) for imports by ordinal
+    ///
+    pub fn display_symbol(&self) -> String {
+        match self.is_by_ordinal() {
+            true => format!("#{}", self.ordinal),
+            false => self.name.to_string_lossy(),
+        }
+    }
+    pub fn dll_name_str(&self) -> String {
+        self.dll_name.to_string_lossy()
+    }
+}
+///
+/// Imports are the same if they are pointing to the same procedure
+/// of the same module. Call sites and file pointers are not compared.
+///
+impl PartialEq for DllImport {
+    fn eq(&self, other: &Self) -> bool {
+        self.dll_name == other.dll_name && self.name == other.name && self.ordinal == other.ordinal
+    }
+}
+impl Eq for DllImport {}
+impl Hash for DllImport {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dll_name.hash(state);
+        self.name.hash(state);
+        self.ordinal.hash(state);
+    }
+}
+///
+/// One unique import procedure with all places
+/// in segments which are referencing it.
+///
+#[derive(Debug, Clone)]
+pub struct ImportReferences {
+    pub import: DllImport,
+    /// `(segment number, offset in segment)` of every relocation
+    pub sites: Vec<(i32, u16)>,
 }
 
 /// ### Imports extraction from segmented module
 /// Read [it](https://alexeytolstopyatov.github.io/notes/2025/09/23/ne-imptab.html) please
 /// if you really need to know how to define dynamic imports
+#[derive(Debug, Clone)]
 pub struct ImportsTable {
     pub seg_number: i32,
     pub imp_list: Vec<DllImport>,
@@ -119,28 +258,56 @@ pub struct ImportsTable {
 impl ImportsTable {
     /// Reads run-time import symbols based on relocations,
     /// module-reference table values and known segment's number.
+    /// `imp_tab` and `mod_tab` are absolute file offsets (`e_lfanew` + pointer).
     pub fn read<T: Read + Seek>(
         reader: &mut T,
         rel_tab: &RelocationTable,
-        imp_tab: u32,
-        mod_tab: u32,
+        imp_tab: u64,
+        mod_tab: u64,
         seg_number: i32,
+    ) -> io::Result<Self> {
+        Self::read_with_modules(reader, rel_tab, imp_tab, mod_tab, seg_number, &mut HashMap::new())
+    }
+    ///
+    /// Reads imports like [ImportsTable::read], but module names are
+    /// taken from `modules` (keyed by offset in imported names table)
+    /// and read only when they are met for the first time. Imports of
+    /// every segment which are read with the same `modules` share
+    /// one copy of each module name.
+    ///
+    pub fn read_with_modules<T: Read + Seek>(
+        reader: &mut T,
+        rel_tab: &RelocationTable,
+        imp_tab: u64,
+        mod_tab: u64,
+        seg_number: i32,
+        modules: &mut HashMap<u16, PascalString>,
     ) -> io::Result<Self> {
         let mut imp_list = Vec::new();
 
         for reloc in &rel_tab.rel_entries {
             match &reloc.rel_type {
                 RelocationType::ImportName(import_name) => {
-                    if let Some(import) =
-                        Self::read_import_name(reader, import_name, imp_tab, mod_tab)?
-                    {
+                    if let Some(import) = Self::read_import_name(
+                        reader,
+                        import_name,
+                        imp_tab,
+                        mod_tab,
+                        reloc.rel_seg_ptr,
+                        modules,
+                    )? {
                         imp_list.push(import);
                     }
                 }
                 RelocationType::ImportOrdinal(import_ord) => {
-                    if let Some(import) =
-                        Self::read_import_ordinal(reader, import_ord, imp_tab, mod_tab)?
-                    {
+                    if let Some(import) = Self::read_import_ordinal(
+                        reader,
+                        import_ord,
+                        imp_tab,
+                        mod_tab,
+                        reloc.rel_seg_ptr,
+                        modules,
+                    )? {
                         imp_list.push(import);
                     }
                 }
@@ -154,11 +321,27 @@ impl ImportsTable {
         })
     }
 
+    ///
+    /// Returns every imported procedure of this segment once,
+    /// in order of the first relocation which references it.
+    /// Named imports are compared by module and procedure name,
+    /// ordinal imports by module name and ordinal.
+    ///
+    pub fn deduplicated(&self) -> Vec<&DllImport> {
+        let mut seen = HashSet::new();
+        self.imp_list
+            .iter()
+            .filter(|import| seen.insert(*import))
+            .collect()
+    }
+
     fn read_import_name<T: Read + Seek>(
         reader: &mut T,
         import_name: &crate::exe286::segrelocs::ImportName,
-        imp_tab: u32,
-        mod_tab: u32,
+        imp_tab: u64,
+        mod_tab: u64,
+        seg_offset: u16,
+        modules: &mut HashMap<u16, PascalString>,
     ) -> io::Result<Option<DllImport>> {
         let mod_offset = Self::read_module_offset(reader, mod_tab, import_name.imp_mod_index)?;
         let mod_offset = match mod_offset {
@@ -166,22 +349,25 @@ impl ImportsTable {
             None => return Ok(None),
         };
 
-        let dll_name = Self::read_module_str(reader, imp_tab, mod_offset)?;
+        let dll_name = Self::interned_module_str(reader, imp_tab, mod_offset, modules)?;
         let proc_name = Self::read_procedure_str(reader, imp_tab, import_name.imp_offset)?;
 
         Ok(Some(DllImport::new(
             dll_name,
             proc_name,
             0,
-            (imp_tab + import_name.imp_offset as u32) as u64,
+            imp_tab + import_name.imp_offset as u64,
+            seg_offset,
         )))
     }
 
     fn read_import_ordinal<T: Read + Seek>(
         reader: &mut T,
         import_ord: &crate::exe286::segrelocs::ImportOrdinal,
-        imp_tab: u32,
-        mod_tab: u32,
+        imp_tab: u64,
+        mod_tab: u64,
+        seg_offset: u16,
+        modules: &mut HashMap<u16, PascalString>,
     ) -> io::Result<Option<DllImport>> {
         let mod_offset = Self::read_module_offset(reader, mod_tab, import_ord.imp_mod_index)?;
         let mod_offset = match mod_offset {
@@ -189,23 +375,30 @@ impl ImportsTable {
             None => return Ok(None),
         };
 
-        let dll_name = Self::read_module_str(reader, imp_tab, mod_offset)?;
+        let dll_name = Self::interned_module_str(reader, imp_tab, mod_offset, modules)?;
 
         Ok(Some(DllImport::new(
             dll_name,
             PascalString::empty(),
             import_ord.imp_ordinal,
             reader.stream_position()?,
+            seg_offset,
         )))
     }
 
     fn read_module_offset<T: Read + Seek>(
         reader: &mut T,
-        mod_tab: u32,
+        mod_tab: u64,
         imp_mod: u16,
     ) -> io::Result<Option<u16>> {
-        let mod_offset_ptr = mod_tab + 2 * (imp_mod - 1) as u32;
-        reader.seek(SeekFrom::Start(mod_offset_ptr as u64))?;
+        let Some(index) = imp_mod.checked_sub(1) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Import relocation refers to module #0",
+            ));
+        };
+        let mod_offset_ptr = mod_tab + 2 * index as u64;
+        reader.seek(SeekFrom::Start(mod_offset_ptr))?;
 
         let mut mod_offset_buf = [0; 2];
         reader.read_exact(&mut mod_offset_buf)?;
@@ -218,13 +411,27 @@ impl ImportsTable {
         })
     }
 
+    fn interned_module_str<T: Read + Seek>(
+        reader: &mut T,
+        imp_tab: u64,
+        mod_offset: u16,
+        modules: &mut HashMap<u16, PascalString>,
+    ) -> io::Result<PascalString> {
+        if let Some(name) = modules.get(&mod_offset) {
+            return Ok(name.clone());
+        }
+        let name = Self::read_module_str(reader, imp_tab, mod_offset)?;
+        modules.insert(mod_offset, name.clone());
+        Ok(name)
+    }
+
     fn read_module_str<T: Read + Seek>(
         reader: &mut T,
-        imp_tab: u32,
+        imp_tab: u64,
         mod_offset: u16,
     ) -> io::Result<PascalString> {
-        let mod_ptr = imp_tab + mod_offset as u32;
-        reader.seek(SeekFrom::Start(mod_ptr as u64))?;
+        let mod_ptr = imp_tab + mod_offset as u64;
+        reader.seek(SeekFrom::Start(mod_ptr))?;
 
         let mut mod_len = 0;
         reader.read_exact(std::slice::from_mut(&mut mod_len))?;
@@ -237,11 +444,11 @@ impl ImportsTable {
 
     fn read_procedure_str<T: Read + Seek>(
         reader: &mut T,
-        imp_tab: u32,
+        imp_tab: u64,
         imp_offset: u16,
     ) -> io::Result<PascalString> {
-        let proc_ptr = imp_tab + imp_offset as u32;
-        reader.seek(SeekFrom::Start(proc_ptr as u64))?;
+        let proc_ptr = imp_tab + imp_offset as u64;
+        reader.seek(SeekFrom::Start(proc_ptr))?;
 
         let mut proc_len = 0;
         reader.read_exact(std::slice::from_mut(&mut proc_len))?;
@@ -271,16 +478,16 @@ impl ImportsTable {
 /// //      |                 |
 /// //      |                 +-----> Based on flags and SEG_HASMASK (0x0007) byte
 /// //  Segments with offset = 0      defines the rules for each segment in table.
-/// //  are .BSS prototypes           flags & HASMASK = 1 -> .CODE16 segment
-/// //  because there's no iterated                     0 -> .DATA16 segment
-/// //  or compressed segments       (flags & PRELOAD) + (flags & HASMASK)
+/// //  are .BSS prototypes           flags & HASMASK = 0 -> .CODE16 segment
+/// //  because there's no iterated                     1 -> .DATA16 segment
+/// //  or compressed segments       (flags & READONLY) for data segments
 /// //                                                 0 -> .DATA16  (read-write)
 /// //                                                 1 -> .RDATA16 (read-only)
 /// ```
 /// Every segment has a rights to contain own relocations table,
 /// because this way to imagine the segments table is most simple.
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SegmentHeader {
     pub sector_base: u16,
     pub sector_length: u16,
@@ -296,6 +503,7 @@ pub struct SegmentHeader {
 /// memory model is flat. Segmented memory model is significant
 /// thing.
 ///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NeSegmentRights {
     /// Rights of 16-bit .code segment
     ///  - READABLE
@@ -318,17 +526,25 @@ pub enum NeSegmentRights {
 }
 pub const SEG_HASMASK: u16 = 0x0007;
 ///
+/// Segment type stored in the `SEG_HASMASK` bits equals 1 for data segments
+/// and 0 for code segments.
+///
+pub const SEG_DATA: u16 = 0x0001;
+///
 /// Segment marked as moveable can be moved into another segment
 /// after application loads into Windows memory.
 ///
 pub const SEG_MOVABLE: u16 = 0x0010;
 ///
-/// Data segments having this flag are read-only
 /// All segments marked as SEG_PRELOAD are loads in memory before
 /// Windows loader prepares to run application.
 ///
 pub const SEG_PRELOAD: u16 = 0x0040;
 ///
+/// Execute-only for code segments and read-only for data segments.
+///
+pub const SEG_READONLY: u16 = 0x0080;
+///
 /// If byte-mask of segment OR SEG_RELOCS gives true -
 /// next following data of segment is will be huge table of
 /// segment relocations. Per-segment relocations is very important tables
@@ -340,6 +556,16 @@ pub const SEG_PRELOAD: u16 = 0x0040;
 ///
 pub const SEG_RELOCS: u16 = 0x0100;
 ///
+/// Conforming code segment (OS/2). `NEWEXE.H` keeps it in the 9th bit:
+/// the 12th bit is already taken by [SEG_DISCARD].
+///
+pub const SEG_CONFORMING: u16 = 0x0200;
+///
+/// Descriptor privilege level (ring) which loader sets
+/// for the segment. Two bits starting from the 10th.
+///
+pub const SEG_DPL: u16 = 0x0C00;
+///
 /// If segment marked as discardable - it can be unloaded
 /// after application runs.
 ///
@@ -353,7 +579,7 @@ impl SegmentHeader {
     pub fn read<TRead: Read>(r: &mut TRead) -> io::Result<Self> {
         let mut buf = [0; 0x8];
         r.read_exact(&mut buf)?;
-        let get_u16 = |pos| u16::from_le_bytes(buf[pos..pos + 2].try_into().unwrap());
+        let get_u16 = |pos: usize| u16::from_le_bytes([buf[pos], buf[pos + 1]]);
 
         Ok(Self {
             sector_base: get_u16(0),
@@ -362,6 +588,12 @@ impl SegmentHeader {
             min_alloc: get_u16(6),
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for word in [self.sector_base, self.sector_length, self.flags, self.min_alloc] {
+            w.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
     ///
     /// Compares all byte-mask with current flags of
     /// segment rights.
@@ -371,10 +603,10 @@ impl SegmentHeader {
             return NeSegmentRights::BSS;
         }
 
-        match (self.flags & SEG_HASMASK) != 0 {
-            true => NeSegmentRights::CODE,
-            false => {
-                if (self.flags & SEG_PRELOAD) != 0 {
+        match self.is_data() {
+            false => NeSegmentRights::CODE,
+            true => {
+                if self.is_read_only() {
                     NeSegmentRights::RDATA
                 } else {
                     NeSegmentRights::DATA
@@ -386,34 +618,143 @@ impl SegmentHeader {
     /// Remember the NE Header `e_align` field??
     /// This is a main reason of usage this field. Per-segment relocations
     /// depend hard on sector shifting
+    /// (see [Segment::sector_to_file_offset]).
     ///
     pub fn data_offset(&self, alignment: u64) -> u64 {
-        (self.sector_base as u64) << alignment
+        let alignment = u16::try_from(alignment).unwrap_or(u16::MAX);
+        Segment::sector_to_file_offset(self.sector_base, alignment)
     }
-
-    pub fn data_length(&self) -> u64 {
+    ///
+    /// Length of segment data in file, bytes. Zero `sector_length`
+    /// of segment with data means 64KB (`0x10000`): the length doesn't
+    /// fit into 16-bit field, so NE spec reserves zero for it.
+    /// Segments without data (`sector_base == 0`) have nothing in file at all.
+    ///
+    pub fn physical_size(&self) -> u64 {
         if self.sector_length == 0 {
             0x10000
         } else {
             self.sector_length as u64
         }
     }
-
-    pub fn min_alloc(&self) -> u64 {
+    #[deprecated(since = "0.1.2", note = "use `physical_size()` instead")]
+    pub fn data_length(&self) -> u64 {
+        self.physical_size()
+    }
+    ///
+    /// Memory loader allocates for segment, bytes. Zero `min_alloc`
+    /// means 64KB (`0x10000`) by the same rule as [SegmentHeader::physical_size].
+    /// Allocation is never less than data in file.
+    ///
+    pub fn min_alloc_bytes(&self) -> u64 {
         if self.min_alloc == 0 {
             0x10000
         } else {
             self.min_alloc as u64
         }
     }
+    ///
+    /// Same as [SegmentHeader::min_alloc_bytes]
+    ///
+    pub fn min_alloc(&self) -> u64 {
+        self.min_alloc_bytes()
+    }
+    ///
+    /// Segment is followed by per-segment relocations table
+    ///
+    pub fn has_relocations(&self) -> bool {
+        (self.flags & SEG_RELOCS) != 0
+    }
+    #[deprecated(since = "0.1.2", note = "name is inverted, use `has_relocations()` instead")]
     pub fn relocations_stripped(&self) -> bool {
-        (self.flags & SEG_RELOCS) == 0
+        !self.has_relocations()
+    }
+    pub fn is_data(&self) -> bool {
+        (self.flags & SEG_HASMASK) == SEG_DATA
+    }
+    pub fn is_moveable(&self) -> bool {
+        (self.flags & SEG_MOVABLE) != 0
+    }
+    pub fn is_preload(&self) -> bool {
+        (self.flags & SEG_PRELOAD) != 0
+    }
+    pub fn is_discardable(&self) -> bool {
+        (self.flags & SEG_DISCARD) != 0
+    }
+    ///
+    /// Only data segments may be read-only. The same bit
+    /// in code segment means "execute-only".
+    ///
+    pub fn is_read_only(&self) -> bool {
+        self.is_data() && (self.flags & SEG_READONLY) != 0
+    }
+    ///
+    /// Descriptor privilege level of the segment (0..=3)
+    ///
+    pub fn ring(&self) -> u8 {
+        ((self.flags & SEG_DPL) >> 10) as u8
+    }
+    ///
+    /// Same as [SegmentHeader::ring]: OS/2 1.x device drivers and
+    /// `IOPL` segments are getting ring 2 by it.
+    ///
+    pub fn dpl(&self) -> u8 {
+        self.ring()
+    }
+    ///
+    /// Code segment may be called from less privileged rings
+    /// without changing the current privilege level
+    ///
+    pub fn is_conforming(&self) -> bool {
+        !self.is_data() && (self.flags & SEG_CONFORMING) != 0
+    }
+    ///
+    /// x86 descriptor access byte which loader of `os` builds for the segment:
+    /// `present | DPL | code/data | type`. The accessed bit is always clear.
+    ///
+    /// OS/2 takes DPL, conforming and execute-only bits from flags.
+    /// Windows ignores them: every segment is a readable ring 3 descriptor
+    /// and only data segments may be read-only.
+    ///
+    pub fn descriptor_access_byte(&self, os: &OS) -> u8 {
+        const PRESENT: u8 = 0x80;
+        const CODE_OR_DATA: u8 = 0x10;
+        const EXECUTABLE: u8 = 0x08;
+        const CONFORMING: u8 = 0x04;
+        // readable for code, writable for data
+        const READ_WRITE: u8 = 0x02;
+
+        let is_os2 = matches!(os, OS::Os2);
+        let dpl = if is_os2 { self.dpl() } else { 3 };
+        let mut access = PRESENT | (dpl << 5) | CODE_OR_DATA;
+
+        match self.is_data() {
+            true if !self.is_read_only() => access |= READ_WRITE,
+            true => {}
+            false => {
+                access |= EXECUTABLE;
+                let execute_only = (self.flags & SEG_READONLY) != 0;
+                if !is_os2 || !execute_only {
+                    access |= READ_WRITE;
+                }
+                if is_os2 && self.is_conforming() {
+                    access |= CONFORMING;
+                }
+            }
+        }
+        access
+    }
+}
+impl Readable<SegmentHeader> for SegmentHeader {
+    fn read<R: Read>(r: &mut R) -> io::Result<SegmentHeader> {
+        Self::read(r)
     }
 }
 
 /// > This scheme is custom!
 ///
 /// It's not include in official documentation.
+#[derive(Debug, Clone)]
 pub struct DllImport {
     /// ### Module's Name
     /// Module's name after linker distorts and becomes `INVALIDCASE`
@@ -425,6 +766,8 @@ pub struct DllImport {
     /// Microsoft official manual for "Segmented Executables" module names implicitly
     /// casts to a `@0` record in `ResidentNames` table. That's main reason why `@0`
     /// ordinal is a reserved value.
+    ///
+    /// Imports of one module read together share the name.
     pub dll_name: PascalString,
     ///
     /// ### Procedure's Name
@@ -441,4 +784,6 @@ pub struct DllImport {
     ///
     pub ordinal: u16,
     pub file_pointer: u64,
+    /// Offset of the relocation (call site) in the importing segment
+    pub seg_offset: u16,
 }
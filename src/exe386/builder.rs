@@ -0,0 +1,540 @@
+//! This module builds linear executables (LX) from descriptions
+//! of objects, exports and imports. Tables are placed the way
+//! LINK386 places them, loader section first:
+//! ```text
+//! +------------------------+ <-- 0
+//! | DOS stub               | see [crate::exe::builder::MzBuilder]
+//! +------------------------+ <-- e_lfanew = 0x40
+//! | LX header              |
+//! | object table           | <-+
+//! | object page map        |   | loader section (e32_ldrsize)
+//! | resident names         |   |
+//! | entry table            | <-+
+//! | fixup page table       | <-+
+//! | fixup record table     |   | fixup section (e32_fixupsize)
+//! | import module names    |   |
+//! | import procedure names | <-+
+//! | non-resident names     |
+//! +------------------------+ <-- e32_datapage
+//! | pages of objects       |
+//! +------------------------+
+//! ```
+//! Module is little-endian, every page except the last one
+//! of object is a whole page of 4096 bytes.
+//! ```rust
+//! use os2omf::exe386::builder::{ExportSpec, ImportSpec, LxBuilder, ObjectSpec};
+//! use os2omf::exe386::LinearExecutableLayout;
+//! use os2omf::omf::coment::ImportEntry;
+//! use std::io::Cursor;
+//!
+//! let mut builder = LxBuilder::new("HELLO");
+//! // call [DosWrite]; ret
+//! builder.objects.push(ObjectSpec::code(vec![0xFF, 0x15, 0, 0, 0, 0, 0xC3]));
+//! builder.exports.push(ExportSpec::new("HELLO", 1, 1, 0));
+//! builder.imports.push(ImportSpec::new("DOSCALLS", ImportEntry::Ordinal(282), 1, 2));
+//!
+//! let module = builder.build()?;
+//! let layout = LinearExecutableLayout::read_from(&mut Cursor::new(&module))?;
+//! assert_eq!(layout.import_table.imports()[0].display_symbol(), "#282");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::exe::builder::MzBuilder;
+use crate::exe286::nrestab::{NonResidentNameEntry, NonResidentNameTable};
+use crate::exe386::enttab::{BundleType, Entry, Entry32, EntryBundle, EntryTable};
+use crate::exe386::frectab::{
+    FixupRecord, FixupRecordsTable, FixupTarget, FixupTargetImportedName,
+    FixupTargetImportedOrdinal,
+};
+use crate::exe386::header::{LX_MAGIC, LinearExecutableHeader};
+use crate::exe386::objpagetab::{LXObjectPageHeader, ObjectPage, ObjectPagesTable};
+use crate::exe386::objtab::{
+    OBJ_BIG, OBJ_EXECUTABLE, OBJ_READABLE, OBJ_WRITEABLE, Object, ObjectsTable,
+};
+use crate::exe386::resntab::{LxResidentNameTable, ResidentNameEntry};
+use crate::omf::coment::ImportEntry;
+use crate::types::{ByteOrder, PascalString};
+use bytemuck::Zeroable;
+use std::io::{self, Write};
+
+/// Offset of LX header after the DOS stub
+const LX_OFFSET: u32 = 0x40;
+/// Size of memory page of i386
+pub const PAGE_SIZE: u32 = 0x1000;
+/// Objects are loaded from this address, every one in its own 64K
+pub const OBJECT_BASE: u32 = 0x10000;
+/// Fixup source is 32-bit offset
+pub const SOURCE_OFFSET32: u8 = 0x07;
+
+///
+/// Object of module. Bytes of `virtual_size` after `data`
+/// are zeros made by loader.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectSpec {
+    pub data: Vec<u8>,
+    pub virtual_size: u32,
+    /// Object flags, see [crate::exe386::objtab]
+    pub flags: u32,
+}
+
+impl ObjectSpec {
+    pub fn code(data: Vec<u8>) -> Self {
+        Self {
+            virtual_size: data.len() as u32,
+            data,
+            flags: (OBJ_READABLE | OBJ_EXECUTABLE | OBJ_BIG) as u32,
+        }
+    }
+    pub fn data(data: Vec<u8>) -> Self {
+        Self {
+            flags: (OBJ_READABLE | OBJ_WRITEABLE | OBJ_BIG) as u32,
+            ..Self::code(data)
+        }
+    }
+}
+
+///
+/// Exported 32-bit entry `object:offset` with its ordinal and name
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSpec {
+    pub name: String,
+    pub ordinal: u16,
+    /// 1-based object number
+    pub object: u16,
+    pub offset: u32,
+    /// Entry flags: `0x01` exported, `0x02` uses shared data
+    pub flags: u8,
+    /// Name is kept in resident names table, else in non-resident one
+    pub resident: bool,
+}
+
+impl ExportSpec {
+    ///
+    /// Resident exported entry
+    ///
+    pub fn new(name: &str, ordinal: u16, object: u16, offset: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            ordinal,
+            object,
+            offset,
+            flags: 0x01,
+            resident: true,
+        }
+    }
+}
+
+///
+/// Dynamic link which patches `offset` of `object`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSpec {
+    /// Name of DLL
+    pub module: String,
+    pub entry: ImportEntry,
+    /// 1-based object number of the site
+    pub object: u16,
+    pub offset: u32,
+    /// Source type of fixup, see [crate::exe386::frectab::FixupFlags]
+    pub source: u8,
+}
+
+impl ImportSpec {
+    pub fn new(module: &str, entry: ImportEntry, object: u16, offset: u32) -> Self {
+        Self {
+            module: module.to_string(),
+            entry,
+            object,
+            offset,
+            source: SOURCE_OFFSET32,
+        }
+    }
+}
+
+///
+/// Linear executable to build. Fields are copied into the header as is,
+/// tables, pointers and sizes are computed by [LxBuilder::write].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LxBuilder {
+    pub module_name: String,
+    /// First name of non-resident names table
+    pub description: String,
+    /// Raw `e32_mflags`, see [crate::exe386::header::ModuleFlags]
+    pub flags: u32,
+    /// Raw `e32_cpu`, see [crate::exe386::header::CPU]
+    pub cpu: u16,
+    /// Raw `e32_os`, see [crate::exe386::header::OS]
+    pub os: u16,
+    /// Pages are aligned by `1 << page_shift` bytes in file
+    pub page_shift: u32,
+    /// Entry point as 1-based object number and offset
+    pub cs: u32,
+    pub eip: u32,
+    pub ss: u32,
+    pub esp: u32,
+    pub heap_size: u32,
+    pub stack_size: u32,
+    pub objects: Vec<ObjectSpec>,
+    pub exports: Vec<ExportSpec>,
+    pub imports: Vec<ImportSpec>,
+}
+
+impl LxBuilder {
+    ///
+    /// OS/2 library module for i386 without objects, exports and imports
+    ///
+    pub fn new(module_name: &str) -> Self {
+        Self {
+            module_name: module_name.to_string(),
+            description: module_name.to_string(),
+            flags: 0x00008000,
+            cpu: 0x02,
+            os: 0x01,
+            page_shift: 0,
+            cs: 0,
+            eip: 0,
+            ss: 0,
+            esp: 0,
+            heap_size: 0,
+            stack_size: 0,
+            objects: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+        }
+    }
+    ///
+    /// Writes DOS stub, header, tables and pages
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let order = ByteOrder::Little;
+        if self.page_shift > 12 {
+            return Err(invalid_input(format!(
+                "Page shift {} is larger than page",
+                self.page_shift
+            )));
+        }
+        let (objects, pages) = self.objects()?;
+        let mut header = self.header();
+        header.e32_objcnt = objects.len() as u32;
+        header.e32_mpages = pages.len() as u32;
+
+        // pages are addressed from e32_datapage, so the map
+        // doesn't depend on the tables before pages
+        let alignment = 1_usize << self.page_shift;
+        let mut data = Vec::new();
+        let mut page_map = ObjectPagesTable { pages: Vec::new() };
+        for page in &pages {
+            data.resize(data.len().next_multiple_of(alignment), 0);
+            page_map.pages.push(ObjectPage::LXPageFormat(LXObjectPageHeader {
+                page_offset: (data.len() >> self.page_shift) as u32,
+                data_size: page.len() as u16,
+                flags: 0,
+            }));
+            data.extend_from_slice(page);
+        }
+
+        // loader section
+        let mut loader = Vec::new();
+        ObjectsTable {
+            objects: objects.clone(),
+        }
+        .write(&mut loader, order)?;
+        let objmap = loader.len();
+        page_map.write(&mut loader, order)?;
+        let restab = loader.len();
+        self.resident_names()?.write_with_order(&mut loader, order)?;
+        let enttab = loader.len();
+        self.entries()?.write(&mut loader, order)?;
+
+        // fixup section
+        let (records, modules, procedures) = self.fixups(&objects)?;
+        let mut record_table = Vec::new();
+        let page_table = records.write(&mut record_table, order, pages.len() as u32)?;
+        let mut fixups = Vec::new();
+        page_table.write(&mut fixups, order)?;
+        let frectab = fixups.len();
+        fixups.extend_from_slice(&record_table);
+        let impmod = fixups.len();
+        fixups.extend_from_slice(&modules);
+        let impproc = fixups.len();
+        fixups.extend_from_slice(&procedures);
+
+        let mut non_resident = Vec::new();
+        self.non_resident_names()?.write_with_order(&mut non_resident, order)?;
+
+        let objtab = 0xC4_usize;
+        let fpagetab = objtab + loader.len();
+        let nrestab = fpagetab + fixups.len();
+        let datapage = (LX_OFFSET as usize + nrestab + non_resident.len()).next_multiple_of(alignment);
+        header.e32_objtab = objtab as u32;
+        header.e32_objmap = (objtab + objmap) as u32;
+        header.e32_rsrctab = (objtab + restab) as u32;
+        header.e32_restab = (objtab + restab) as u32;
+        header.e32_enttab = (objtab + enttab) as u32;
+        header.e32_ldrsize = loader.len() as u32;
+        header.e32_fpagetab = fpagetab as u32;
+        header.e32_frectab = (fpagetab + frectab) as u32;
+        header.e32_impmod = (fpagetab + impmod) as u32;
+        header.e32_impmodcnt = self.modules().len() as u32;
+        header.e32_impproc = (fpagetab + impproc) as u32;
+        header.e32_fixupsize = fixups.len() as u32;
+        header.e32_nrestab = (LX_OFFSET as usize + nrestab) as u32;
+        header.e32_cbnrestab = non_resident.len() as u32;
+        header.e32_datapage = datapage as u32;
+
+        let mut stub = MzBuilder::new(Vec::new());
+        stub.lfanew = LX_OFFSET;
+        let mut file = stub.build()?;
+        file.resize(LX_OFFSET as usize, 0);
+        header.write(&mut file)?;
+        file.extend_from_slice(&loader);
+        file.extend_from_slice(&fixups);
+        file.extend_from_slice(&non_resident);
+        file.resize(datapage, 0);
+        file.extend_from_slice(&data);
+        w.write_all(&file)
+    }
+    ///
+    /// Whole module in a buffer
+    ///
+    pub fn build(&self) -> io::Result<Vec<u8>> {
+        let mut module = Vec::new();
+        self.write(&mut module)?;
+        Ok(module)
+    }
+
+    fn header(&self) -> LinearExecutableHeader {
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LX_MAGIC;
+        header.e32_cpu = self.cpu;
+        header.e32_os = self.os;
+        header.e32_mflags = self.flags;
+        header.e32_cs = self.cs;
+        header.e32_eip = self.eip;
+        header.e32_ss = self.ss;
+        header.e32_esp = self.esp;
+        header.e32_pagesize = PAGE_SIZE;
+        header.e32_pageshift_or_lastpage = self.page_shift;
+        header.e32_heapsize = self.heap_size;
+        header.e32_stacksize = self.stack_size;
+        header
+    }
+    ///
+    /// Object table records and data of every page in order of page map
+    ///
+    fn objects(&self) -> io::Result<(Vec<Object>, Vec<&[u8]>)> {
+        let mut objects = Vec::with_capacity(self.objects.len());
+        let mut pages: Vec<&[u8]> = Vec::new();
+        for (i, spec) in self.objects.iter().enumerate() {
+            let virtual_size = spec.virtual_size.max(spec.data.len() as u32);
+            let span = (virtual_size as u64).next_multiple_of(0x10000).max(0x10000);
+            let virtual_addr = OBJECT_BASE as u64 + i as u64 * span;
+            let virtual_addr = u32::try_from(virtual_addr).map_err(|_| {
+                invalid_input(format!("Object #{} is out of address space", i + 1))
+            })?;
+            let map_index = pages.len() as u32 + 1;
+            pages.extend(spec.data.chunks(PAGE_SIZE as usize));
+            objects.push(Object {
+                virtual_size,
+                virtual_addr,
+                flags: spec.flags,
+                map_index,
+                map_size: pages.len() as u32 + 1 - map_index,
+                _reserved: 0,
+            });
+        }
+        Ok((objects, pages))
+    }
+    ///
+    /// Names of imported modules in order of the first import
+    ///
+    fn modules(&self) -> Vec<&str> {
+        let mut modules: Vec<&str> = Vec::new();
+        for import in &self.imports {
+            if !modules.contains(&import.module.as_str()) {
+                modules.push(&import.module);
+            }
+        }
+        modules
+    }
+    ///
+    /// Fixup records of imports sorted by pages, import module
+    /// names table and import procedure names table
+    ///
+    fn fixups(&self, objects: &[Object]) -> io::Result<(FixupRecordsTable, Vec<u8>, Vec<u8>)> {
+        let modules = self.modules();
+        let mut module_names = Vec::new();
+        for module in &modules {
+            push_name(&mut module_names, module)?;
+        }
+        // offset 0 of procedure names is never a name
+        let mut procedures = vec![0_u8];
+        let mut offsets: Vec<(&str, u32)> = Vec::new();
+
+        let mut records = Vec::with_capacity(self.imports.len());
+        for import in &self.imports {
+            let index = (import.object as usize).wrapping_sub(1);
+            let (Some(object), Some(spec)) = (objects.get(index), self.objects.get(index)) else {
+                return Err(invalid_input(format!(
+                    "Import of {} refers to missing object #{}",
+                    import.module, import.object
+                )));
+            };
+            let site_end = import.offset as u64 + site_len(import.source);
+            if site_end > spec.data.len() as u64 {
+                return Err(invalid_input(format!(
+                    "Import site 0x{:X}..0x{:X} is out of object #{} data",
+                    import.offset, site_end, import.object
+                )));
+            }
+            let module_ordinal = modules.iter().position(|m| *m == import.module).unwrap_or(0) as u16 + 1;
+            let target_data = match &import.entry {
+                ImportEntry::Ordinal(ordinal) => FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal {
+                    module_ordinal,
+                    import_ordinal: *ordinal as u32,
+                }),
+                ImportEntry::Name(name) => {
+                    let offset = match offsets.iter().find(|(n, _)| n == name) {
+                        Some((_, offset)) => *offset,
+                        None => {
+                            let offset = procedures.len() as u32;
+                            push_name(&mut procedures, name)?;
+                            offsets.push((name, offset));
+                            offset
+                        }
+                    };
+                    FixupTarget::ImportedName(FixupTargetImportedName {
+                        module_ordinal,
+                        procedure_name_offset: offset,
+                    })
+                }
+            };
+            let mut record = FixupRecord {
+                page: object.map_index + import.offset / PAGE_SIZE,
+                source: import.source,
+                target_flags: 0,
+                source_offset_or_count: (import.offset % PAGE_SIZE) as u16,
+                target_data,
+                additive_value: None,
+                source_offset_list: None,
+            };
+            record.compact_flags();
+            records.push(record);
+        }
+        records.sort_by_key(|r| r.page);
+        Ok((FixupRecordsTable { records }, module_names, procedures))
+    }
+    ///
+    /// Ordinals follow one-by-one from `@1`: entries of the same object
+    /// make one bundle, gaps are unused bundles
+    ///
+    fn entries(&self) -> io::Result<EntryTable> {
+        let mut exports: Vec<&ExportSpec> = self.exports.iter().collect();
+        exports.sort_by_key(|e| e.ordinal);
+        if let Some(pair) = exports.windows(2).find(|p| p[0].ordinal == p[1].ordinal) {
+            return Err(invalid_input(format!(
+                "Export {} has ordinal @{} of another export",
+                pair[1].name, pair[1].ordinal
+            )));
+        }
+        if let Some(export) = exports.iter().find(|e| e.ordinal == 0) {
+            return Err(invalid_input(format!("Export {} has ordinal @0", export.name)));
+        }
+
+        let mut bundles: Vec<EntryBundle> = Vec::new();
+        let mut next = 1_u16;
+        for export in exports {
+            while next < export.ordinal {
+                let count = (export.ordinal - next).min(u8::MAX as u16);
+                bundles.push(EntryBundle {
+                    first_ordinal: next,
+                    count: count as u8,
+                    bundle_type: BundleType::Unused,
+                    object: 0,
+                    entries: vec![Entry::Unused; count as usize],
+                });
+                next += count;
+            }
+            let entry = Entry::Entry32(Entry32 {
+                flags: export.flags,
+                offset: export.offset,
+            });
+            match bundles.last_mut() {
+                Some(bundle)
+                    if bundle.bundle_type == BundleType::Entry32
+                        && bundle.object == export.object
+                        && bundle.count < u8::MAX =>
+                {
+                    bundle.count += 1;
+                    bundle.entries.push(entry);
+                }
+                _ => bundles.push(EntryBundle {
+                    first_ordinal: export.ordinal,
+                    count: 1,
+                    bundle_type: BundleType::Entry32,
+                    object: export.object,
+                    entries: vec![entry],
+                }),
+            }
+            next = export.ordinal.wrapping_add(1);
+        }
+        Ok(EntryTable { bundles })
+    }
+
+    fn resident_names(&self) -> io::Result<LxResidentNameTable> {
+        let mut entries = vec![ResidentNameEntry {
+            name: pascal(&self.module_name)?,
+            ordinal: 0,
+        }];
+        for export in self.exports.iter().filter(|e| e.resident) {
+            entries.push(ResidentNameEntry {
+                name: pascal(&export.name)?,
+                ordinal: export.ordinal,
+            });
+        }
+        Ok(LxResidentNameTable { entries })
+    }
+
+    fn non_resident_names(&self) -> io::Result<NonResidentNameTable> {
+        let mut entries = vec![NonResidentNameEntry {
+            name: pascal(&self.description)?,
+            ordinal: 0,
+        }];
+        for export in self.exports.iter().filter(|e| !e.resident) {
+            entries.push(NonResidentNameEntry {
+                name: pascal(&export.name)?,
+                ordinal: export.ordinal,
+            });
+        }
+        Ok(NonResidentNameTable { entries })
+    }
+}
+
+/// Bytes which fixup of `source` type writes at the site
+fn site_len(source: u8) -> u64 {
+    match source & 0x0F {
+        0x00 => 1,
+        0x02 | 0x05 => 2,
+        0x06 => 6,
+        _ => 4,
+    }
+}
+
+fn push_name(table: &mut Vec<u8>, name: &str) -> io::Result<()> {
+    let name = pascal(name)?;
+    table.push(name.pascal_len());
+    table.extend_from_slice(name.as_bytes());
+    Ok(())
+}
+
+fn pascal(name: &str) -> io::Result<PascalString> {
+    let length = u8::try_from(name.len())
+        .map_err(|_| invalid_input(format!("Name {} is longer than 255 bytes", name)))?;
+    Ok(PascalString::new(length, name.as_bytes().to_vec()))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
@@ -1,5 +1,6 @@
 //! This module represents Module Directives Table for Linear executables
 use crate::exe386::header::LinearExecutableHeader;
+use crate::types::{bounded_capacity, table_accessors, ByteOrder, FieldCursor};
 use bytemuck::{Pod, Zeroable};
 use std::io;
 use std::io::{Read, Seek, SeekFrom};
@@ -20,13 +21,23 @@ use std::io::{Read, Seek, SeekFrom};
 /// fields in the linear EXE header referencing the module format directives table are zero.
 ///
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 pub struct ModuleDirectiveRecord {
     pub directive_number: u16,
     pub data_length: u16,
     pub data_offset: u32,
 }
 
+impl ModuleDirectiveRecord {
+    pub fn read<R: Read>(reader: &mut R, order: ByteOrder) -> io::Result<Self> {
+        Ok(Self {
+            directive_number: order.read_u16(reader)?,
+            data_length: order.read_u16(reader)?,
+            data_offset: order.read_u32(reader)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ModuleDirective {
     pub directive_type: DirectiveType,
@@ -80,9 +91,11 @@ pub struct ObjectVerification {
     pub virtual_size: u32,
 }
 
+#[derive(Debug, Clone)]
 pub struct ModuleDirectivesTable {
     pub directives: Vec<ModuleDirective>,
 }
+table_accessors!(ModuleDirectivesTable, directives, ModuleDirective);
 
 impl ModuleDirectivesTable {
     pub fn empty() -> Self {
@@ -90,24 +103,26 @@ impl ModuleDirectivesTable {
             directives: Vec::new(),
         }
     }
+    ///
+    /// Reads `e32_dircnt` directives of table at `e32_dirtab`, which is
+    /// relative to the LE/LX header at `e_lfanew` (`0` without DOS header).
+    ///
     pub fn read<R: Read + Seek>(
         reader: &mut R,
         header: &LinearExecutableHeader,
         e_lfanew: u64,
     ) -> io::Result<Self> {
-        if header.e32_impmod == 0 || header.e32_impmodcnt == 0 {
+        if header.e32_dirtab == 0 || header.e32_dircnt == 0 {
             return Ok(Self {
                 directives: Vec::new(),
             });
         }
 
-        reader.seek(SeekFrom::Start(header.e32_impmod as u64 + e_lfanew))?;
+        reader.seek(SeekFrom::Start(e_lfanew + header.e32_dirtab as u64))?;
 
-        let mut directives = Vec::with_capacity(header.e32_impmodcnt as usize);
-        for _ in 0..header.e32_impmodcnt {
-            let mut entry_buf = [0_u8; 8];
-            reader.read_exact(&mut entry_buf)?;
-            let entry: ModuleDirectiveRecord = bytemuck::pod_read_unaligned(&entry_buf);
+        let mut directives = Vec::with_capacity(bounded_capacity(header.e32_dircnt as usize));
+        for _ in 0..header.e32_dircnt {
+            let entry = ModuleDirectiveRecord::read(reader, header.byte_order())?;
 
             // Directive data
             let directive_type = DirectiveType::from(entry.directive_number);
@@ -115,7 +130,7 @@ impl ModuleDirectivesTable {
 
             let data_offset = if entry.directive_number & 0x8000 != 0 {
                 // Resident table - offset from header
-                header.e32_magic as u64 + entry.data_offset as u64
+                e_lfanew + entry.data_offset as u64
             } else {
                 // Non-resident table - offset from file start
                 entry.data_offset as u64
@@ -135,7 +150,15 @@ impl ModuleDirectivesTable {
         Ok(Self { directives })
     }
 
-    pub fn read_verify_record(directive: &ModuleDirective) -> io::Result<VerifyRecord> {
+    ///
+    /// Decodes data of verify record directive in byte order of module
+    /// ([LinearExecutableHeader::byte_order]). Module and object records
+    /// cut by the end of data are `InvalidData`.
+    ///
+    pub fn read_verify_record(
+        directive: &ModuleDirective,
+        order: ByteOrder,
+    ) -> io::Result<VerifyRecord> {
         if !matches!(directive.directive_type, DirectiveType::VerifyRecord) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -144,53 +167,47 @@ impl ModuleDirectivesTable {
         }
 
         let data = &directive.data;
-        if data.len() < 2 {
-            return Err(io::Error::new(
+        // every record is checked against the rest of data before it's decoded
+        let cut = |what: &str, offset: usize| {
+            io::Error::new(
                 io::ErrorKind::InvalidData,
-                "Verify record too short",
-            ));
+                format!(
+                    "Verify record: {} at 0x{:X} is out of {} bytes",
+                    what,
+                    offset,
+                    data.len()
+                ),
+            )
+        };
+        if data.len() < 2 {
+            return Err(cut("count of modules", 0));
         }
-
-        let entry_count = u16::from_le_bytes([data[0], data[1]]) as usize;
-        let mut dependencies = Vec::with_capacity(entry_count);
+        let mut f = FieldCursor::new(data, order);
+        let entry_count = f.u16() as usize;
+        let mut dependencies = Vec::with_capacity(bounded_capacity(entry_count));
         let mut offset = 2;
 
         for _ in 0..entry_count {
-            if offset + 8 > data.len() {
-                break;
+            if offset + 6 > data.len() {
+                return Err(cut("module record", offset));
             }
-
-            let module_ordinal = u16::from_le_bytes([data[offset], data[offset + 1]]);
-            let version = u16::from_le_bytes([data[offset + 2], data[offset + 3]]);
-            let module_object_count = u16::from_le_bytes([data[offset + 4], data[offset + 5]]);
+            let module_ordinal = f.u16();
+            let version = f.u16();
+            let module_object_count = f.u16();
             offset += 6;
 
-            let mut object_verifications = Vec::with_capacity(module_object_count as usize);
+            let mut object_verifications =
+                Vec::with_capacity(bounded_capacity(module_object_count as usize));
             for _ in 0..module_object_count {
-                if offset + 8 > data.len() {
-                    break;
+                if offset + 10 > data.len() {
+                    return Err(cut("object record", offset));
                 }
-
-                let object_number = u16::from_le_bytes([data[offset], data[offset + 1]]);
-                let base_address = u32::from_le_bytes([
-                    data[offset + 2],
-                    data[offset + 3],
-                    data[offset + 4],
-                    data[offset + 5],
-                ]);
-                let virtual_size = u32::from_le_bytes([
-                    data[offset + 6],
-                    data[offset + 7],
-                    data[offset + 8],
-                    data[offset + 9],
-                ]);
-                offset += 10;
-
                 object_verifications.push(ObjectVerification {
-                    object_number,
-                    base_address,
-                    virtual_size,
+                    object_number: f.u16(),
+                    base_address: f.u32(),
+                    virtual_size: f.u32(),
                 });
+                offset += 10;
             }
 
             dependencies.push(ModuleDependency {
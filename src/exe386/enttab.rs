@@ -1,16 +1,22 @@
 //! This module represents structure and methods of EntryTable
+use crate::types::readable::Readable;
+use crate::types::{table_accessors, ByteOrder};
+use std::collections::HashMap;
 use std::io;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EntryBundle {
+    /// Ordinal of the first entry in bundle
+    pub first_ordinal: u16,
     pub count: u8,
     pub bundle_type: BundleType,
+    /// Object of entries. Reserved word (zero) of forwarder bundles.
     pub object: u16,
     pub entries: Vec<Entry>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BundleType {
     Unused,
     Entry16,
@@ -32,38 +38,39 @@ impl BundleType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EntryTable {
     pub bundles: Vec<EntryBundle>,
 }
+table_accessors!(EntryTable, bundles, EntryBundle);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Entry16 {
     pub flags: u8,
     pub offset: u16,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Entry32 {
     pub flags: u8,
     pub offset: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EntryCallGate {
     pub flags: u8,
     pub offset: u16,
     pub callgate_selector: u16,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EntryForwarder {
     pub flags: u8,
     pub module_ordinal: u16,
     pub offset_or_ordinal: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Entry {
     Unused,
     Entry16(Entry16),
@@ -73,8 +80,9 @@ pub enum Entry {
 }
 
 impl EntryTable {
-    pub fn read<T: Read + Seek>(reader: &mut T, enttab: u64) -> io::Result<Self> {
+    pub fn read<T: Read + Seek>(reader: &mut T, enttab: u64, order: ByteOrder) -> io::Result<Self> {
         let mut bundles = Vec::new();
+        let mut ordinal: u16 = 1;
         reader.seek(SeekFrom::Start(enttab))?;
 
         loop {
@@ -90,33 +98,31 @@ impl EntryTable {
             reader.read_exact(&mut type_buf)?;
             let bundle_type = BundleType::from(type_buf[0]);
 
-            let object =
-                if bundle_type != BundleType::Unused && bundle_type != BundleType::Forwarder {
-                    let mut obj_buf = [0_u8; 2];
-                    reader.read_exact(&mut obj_buf)?;
-                    u16::from_le_bytes(obj_buf)
-                } else {
-                    0
-                };
+            // forwarders have the reserved word here instead of object
+            let object = if bundle_type != BundleType::Unused {
+                order.read_u16(reader)?
+            } else {
+                0
+            };
 
             let mut entries = Vec::with_capacity(count as usize);
             for _ in 0..count {
                 let entry = match bundle_type {
                     BundleType::Unused => Entry::Unused,
                     BundleType::Entry16 => {
-                        let entry_data = Entry16::read(reader)?;
+                        let entry_data = Entry16::read(reader, order)?;
                         Entry::Entry16(entry_data)
                     }
                     BundleType::Entry286CallGate => {
-                        let entry_data = EntryCallGate::read(reader)?;
+                        let entry_data = EntryCallGate::read(reader, order)?;
                         Entry::EntryCallGate(entry_data)
                     }
                     BundleType::Entry32 => {
-                        let entry_data = Entry32::read(reader)?;
+                        let entry_data = Entry32::read(reader, order)?;
                         Entry::Entry32(entry_data)
                     }
                     BundleType::Forwarder => {
-                        let entry_data = EntryForwarder::read(reader)?;
+                        let entry_data = EntryForwarder::read(reader, order)?;
                         Entry::EntryForwarder(entry_data)
                     }
                     BundleType::Unknown(unknown_type) => {
@@ -130,84 +136,231 @@ impl EntryTable {
             }
 
             bundles.push(EntryBundle {
+                first_ordinal: ordinal,
                 count,
                 bundle_type,
                 object,
                 entries,
             });
+            ordinal = ordinal.wrapping_add(count as u16);
         }
 
         Ok(EntryTable { bundles })
     }
+    ///
+    /// Writes bundles as they are and zero byte which ends the table.
+    /// Count of bundle is the count of its entries.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        for bundle in &self.bundles {
+            let count = u8::try_from(bundle.entries.len())
+                .ok()
+                .filter(|count| *count != 0)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Bundle @{} of {} entries can't be written",
+                            bundle.first_ordinal,
+                            bundle.entries.len()
+                        ),
+                    )
+                })?;
+            let bundle_type = match bundle.bundle_type {
+                BundleType::Unused => 0x00,
+                BundleType::Entry16 => 0x01,
+                BundleType::Entry286CallGate => 0x02,
+                BundleType::Entry32 => 0x03,
+                BundleType::Forwarder => 0x04,
+                BundleType::Unknown(n) => n,
+            };
+            w.write_all(&[count, bundle_type])?;
+            if bundle.bundle_type == BundleType::Unused {
+                continue;
+            }
+            order.write_u16(w, bundle.object)?;
+            for entry in &bundle.entries {
+                match entry {
+                    Entry::Unused => {}
+                    Entry::Entry16(e) => e.write(w, order)?,
+                    Entry::Entry32(e) => e.write(w, order)?,
+                    Entry::EntryCallGate(e) => e.write(w, order)?,
+                    Entry::EntryForwarder(e) => e.write(w, order)?,
+                }
+            }
+        }
+        w.write_all(&[0])
+    }
+    ///
+    /// Count of entries of every bundle type in the table.
+    /// Unused bundles are counted too: they're holes in ordinals.
+    ///
+    pub fn entry_count_by_type(&self) -> HashMap<BundleType, usize> {
+        let mut counts = HashMap::new();
+        for bundle in &self.bundles {
+            *counts.entry(bundle.bundle_type).or_insert(0) += bundle.count as usize;
+        }
+        counts
+    }
+    ///
+    /// Count of entries which are really exported (not in `Unused` bundles)
+    ///
+    pub fn export_count(&self) -> usize {
+        self.bundles
+            .iter()
+            .filter(|b| b.bundle_type != BundleType::Unused)
+            .map(|b| b.entries.len())
+            .sum()
+    }
+    pub fn bundle_count(&self) -> usize {
+        self.bundles.len()
+    }
+    ///
+    /// Some entries are forwarded to procedures of other modules
+    ///
+    pub fn has_forwarders(&self) -> bool {
+        self.bundles
+            .iter()
+            .any(|b| b.bundle_type == BundleType::Forwarder && b.count != 0)
+    }
+    ///
+    /// Entry by its ordinal (`@1` is the first entry) with the bundle
+    /// which holds it: object of entry is the object of bundle.
+    ///
+    pub fn by_ordinal(&self, ordinal: u16) -> Option<(&EntryBundle, &Entry)> {
+        self.bundles.iter().find_map(|b| {
+            let index = ordinal.checked_sub(b.first_ordinal)? as usize;
+            b.entries.get(index).map(|entry| (b, entry))
+        })
+    }
 }
 
 impl Entry16 {
-    pub fn read<T: Read>(reader: &mut T) -> io::Result<Self> {
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> io::Result<Self> {
         let mut flags_buf = [0_u8];
         reader.read_exact(&mut flags_buf)?;
 
-        let mut offset_buf = [0_u8; 2];
-        reader.read_exact(&mut offset_buf)?;
-
         Ok(Entry16 {
             flags: flags_buf[0],
-            offset: u16::from_le_bytes(offset_buf),
+            offset: order.read_u16(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        w.write_all(&[self.flags])?;
+        order.write_u16(w, self.offset)
+    }
+    ///
+    /// Names of set `flags` bits: "exported", "single data"
+    ///
+    pub fn flags_description(&self) -> String {
+        entry_flags(self.flags)
+    }
+}
+impl Readable<Entry16> for Entry16 {
+    fn read<R: Read>(r: &mut R) -> io::Result<Entry16> {
+        Self::read(r, ByteOrder::Little)
+    }
 }
 
 impl Entry32 {
-    pub fn read<T: Read>(reader: &mut T) -> io::Result<Self> {
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> io::Result<Self> {
         let mut flags_buf = [0_u8];
         reader.read_exact(&mut flags_buf)?;
 
-        let mut offset_buf = [0_u8; 4];
-        reader.read_exact(&mut offset_buf)?;
-
         Ok(Entry32 {
             flags: flags_buf[0],
-            offset: u32::from_le_bytes(offset_buf),
+            offset: order.read_u32(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        w.write_all(&[self.flags])?;
+        order.write_u32(w, self.offset)
+    }
+    ///
+    /// Names of set `flags` bits: "exported", "single data"
+    ///
+    pub fn flags_description(&self) -> String {
+        entry_flags(self.flags)
+    }
+}
+impl Readable<Entry32> for Entry32 {
+    fn read<R: Read>(r: &mut R) -> io::Result<Entry32> {
+        Self::read(r, ByteOrder::Little)
+    }
 }
 
 impl EntryCallGate {
-    pub fn read<T: Read>(reader: &mut T) -> io::Result<Self> {
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> io::Result<Self> {
         let mut flags_buf = [0_u8];
         reader.read_exact(&mut flags_buf)?;
 
-        let mut offset_buf = [0_u8; 2];
-        reader.read_exact(&mut offset_buf)?;
-
-        let mut callgate_buf = [0_u8; 2];
-        reader.read_exact(&mut callgate_buf)?;
-
         Ok(EntryCallGate {
             flags: flags_buf[0],
-            offset: u16::from_le_bytes(offset_buf),
-            callgate_selector: u16::from_le_bytes(callgate_buf),
+            offset: order.read_u16(reader)?,
+            callgate_selector: order.read_u16(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        w.write_all(&[self.flags])?;
+        order.write_u16(w, self.offset)?;
+        order.write_u16(w, self.callgate_selector)
+    }
+    ///
+    /// `callgate_selector` is a selector of 286 call gate which is
+    /// reserved by loader: 16-bit code calls the entry through it.
+    ///
+    pub fn description(&self) -> String {
+        format!(
+            "CallGate[selector=0x{:04X} offset=0x{:04X} flags=0x{:02X}]",
+            self.callgate_selector, self.offset, self.flags
+        )
+    }
+    ///
+    /// Names of set `flags` bits: "exported", "single data"
+    ///
+    pub fn flags_description(&self) -> String {
+        entry_flags(self.flags)
+    }
+}
+impl Readable<EntryCallGate> for EntryCallGate {
+    fn read<R: Read>(r: &mut R) -> io::Result<EntryCallGate> {
+        Self::read(r, ByteOrder::Little)
+    }
 }
 
 impl EntryForwarder {
-    pub fn read<T: Read>(reader: &mut T) -> io::Result<Self> {
-        let mut reserved_buf = [0u8; 2];
-        reader.read_exact(&mut reserved_buf)?;
-
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> io::Result<Self> {
         let mut flags_buf = [0_u8];
         reader.read_exact(&mut flags_buf)?;
 
-        let mut module_ordinal_buf = [0_u8; 2];
-        reader.read_exact(&mut module_ordinal_buf)?;
-
-        let mut offset_or_ordinal_buf = [0_u8; 4];
-        reader.read_exact(&mut offset_or_ordinal_buf)?;
-
         Ok(EntryForwarder {
             flags: flags_buf[0],
-            module_ordinal: u16::from_le_bytes(module_ordinal_buf),
-            offset_or_ordinal: u32::from_le_bytes(offset_or_ordinal_buf),
+            module_ordinal: order.read_u16(reader)?,
+            offset_or_ordinal: order.read_u32(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        w.write_all(&[self.flags])?;
+        order.write_u16(w, self.module_ordinal)?;
+        order.write_u32(w, self.offset_or_ordinal)
+    }
+}
+impl Readable<EntryForwarder> for EntryForwarder {
+    fn read<R: Read>(r: &mut R) -> io::Result<EntryForwarder> {
+        Self::read(r, ByteOrder::Little)
+    }
+}
+
+///
+/// Names of entry flags: `0x01` exported, `0x02` uses single (shared) data segment.
+/// Empty when neither is set.
+///
+fn entry_flags(flags: u8) -> String {
+    let names = [(0x01, "exported"), (0x02, "single data")];
+    let active: Vec<&str> = names
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, n)| *n)
+        .collect();
+    active.join(", ")
 }
@@ -1,15 +1,16 @@
 //! This module represents structure and methods of FixupPages table
 use crate::exe386::frectab::FixupRecord;
 use crate::exe386::header::LinearExecutableHeader;
+use crate::types::{bounded_capacity, ByteOrder};
 use std::io;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupPageTable {
     pub page_offsets: Vec<u32>,
     pub end_of_fixup_records: u32,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupRecordTable {
     pub records: Vec<FixupRecord>,
 }
@@ -31,11 +32,10 @@ impl FixupPageTable {
         // records = fpages + 1 (needed end marker too)
         let entry_count = header.e32_mpages as usize + 1;
 
-        let mut page_offsets = Vec::with_capacity(entry_count);
+        let order = header.byte_order();
+        let mut page_offsets = Vec::with_capacity(bounded_capacity(entry_count));
         for _ in 0..entry_count {
-            let mut buf = [0_u8; 4];
-            reader.read_exact(&mut buf)?;
-            page_offsets.push(u32::from_le_bytes(buf));
+            page_offsets.push(order.read_u32(reader)?);
         }
 
         let end_of_fixup_records = page_offsets.pop().ok_or_else(|| {
@@ -50,4 +50,40 @@ impl FixupPageTable {
             end_of_fixup_records,
         })
     }
+
+    ///
+    /// Writes offset of every page and the end marker
+    ///
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        for offset in &self.page_offsets {
+            order.write_u32(w, *offset)?;
+        }
+        order.write_u32(w, self.end_of_fixup_records)
+    }
+    ///
+    /// Returns `(start, end)` offsets of fixup records of page
+    /// `page_idx` (0-based) relative to the fixup record table.
+    /// End of the last page is `end_of_fixup_records`.
+    ///
+    pub fn page_range(&self, page_idx: usize) -> Option<(u32, u32)> {
+        let start = *self.page_offsets.get(page_idx)?;
+        let end = self
+            .page_offsets
+            .get(page_idx + 1)
+            .copied()
+            .unwrap_or(self.end_of_fixup_records);
+        Some((start, end))
+    }
+    ///
+    /// Returns count of pages described by table
+    ///
+    pub fn total_pages(&self) -> usize {
+        self.page_offsets.len()
+    }
+    ///
+    /// Returns `true` if page `page_idx` (0-based) has any fixup record
+    ///
+    pub fn has_fixups_for_page(&self, page_idx: usize) -> bool {
+        matches!(self.page_range(page_idx), Some((start, end)) if end > start)
+    }
 }
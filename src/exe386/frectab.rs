@@ -1,9 +1,13 @@
 //! This module represents API of Fixup records table
 use crate::exe386::fpagetab::FixupPageTable;
-use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom};
+use crate::exe386::objtab::Object;
+use crate::types::{table_accessors, ByteOrder};
+use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupRecord {
+    /// Logical page number (1-based) where the source of fixup is
+    pub page: u32,
     pub source: u8,
     pub target_flags: u8,
     pub source_offset_or_count: u16,
@@ -12,7 +16,121 @@ pub struct FixupRecord {
     pub source_offset_list: Option<Vec<u16>>,
 }
 
-#[derive(Debug, Clone)]
+impl FixupRecord {
+    ///
+    /// Sets flag bits of `source` and `target_flags` by the contents
+    /// of record: target type, source list, additive value and the smallest
+    /// widths of object or module number, target offset and ordinal.
+    /// Source type and alias bits of `source` are kept.
+    ///
+    pub fn compact_flags(&mut self) {
+        let (target_type, number, wide) = match &self.target_data {
+            FixupTarget::Internal(t) => (0x00, t.object_number, t.target_offset.unwrap_or(0) > 0xFFFF),
+            FixupTarget::ImportedOrdinal(t) => (0x01, t.module_ordinal, t.import_ordinal > 0xFFFF),
+            FixupTarget::ImportedName(t) => (0x02, t.module_ordinal, t.procedure_name_offset > 0xFFFF),
+            FixupTarget::FixupViaEntryTable(t) => (0x03, t.entry_number, false),
+        };
+        let mut flags = target_type;
+        if let Some(additive) = self.additive_value {
+            flags |= 0x04;
+            if additive > 0xFFFF {
+                flags |= 0x20;
+            }
+        }
+        if wide {
+            flags |= 0x10;
+        }
+        if number > 0xFF {
+            flags |= 0x40;
+        }
+        if matches!(&self.target_data, FixupTarget::ImportedOrdinal(t) if t.import_ordinal <= 0xFF) {
+            flags |= 0x80;
+        }
+        self.target_flags = flags;
+
+        self.source &= !0x20;
+        if let Some(list) = &self.source_offset_list {
+            self.source |= 0x20;
+            self.source_offset_or_count = list.len() as u16;
+        }
+    }
+    ///
+    /// Writes record in the compact form described by its flags.
+    /// Values which don't fit into widths set by flags are errors,
+    /// see [FixupRecord::compact_flags].
+    ///
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        let flags = FixupFlags::from_bytes(self.source, self.target_flags);
+        w.write_all(&[self.source, self.target_flags])?;
+
+        match (&self.source_offset_list, flags.has_source_list) {
+            (Some(list), true) => {
+                let count = u8::try_from(list.len())
+                    .map_err(|_| invalid_record(format!("{} sources in one list", list.len())))?;
+                w.write_all(&[count])?;
+            }
+            (None, false) => order.write_u16(w, self.source_offset_or_count)?,
+            _ => return Err(invalid_record("source list and flags don't match".to_string())),
+        }
+
+        let number = |w: &mut W, value: u16| match flags.is_16bit_object_module {
+            true => order.write_u16(w, value),
+            false => match u8::try_from(value) {
+                Ok(value) => w.write_all(&[value]),
+                Err(_) => Err(invalid_record(format!("number {} takes 16 bits", value))),
+            },
+        };
+        let offset = |w: &mut W, value: u32, wide: bool| match wide {
+            true => order.write_u32(w, value),
+            false => match u16::try_from(value) {
+                Ok(value) => order.write_u16(w, value),
+                Err(_) => Err(invalid_record(format!("value 0x{:X} takes 32 bits", value))),
+            },
+        };
+        match (&self.target_data, flags.target_type) {
+            (FixupTarget::Internal(t), 0x00) => {
+                number(w, t.object_number)?;
+                if flags.source_type != 0x02 {
+                    offset(w, t.target_offset.unwrap_or(0), flags.is_32bit_target)?;
+                }
+            }
+            (FixupTarget::ImportedOrdinal(t), 0x01) => {
+                number(w, t.module_ordinal)?;
+                match flags.is_8bit_ordinal {
+                    true => match u8::try_from(t.import_ordinal) {
+                        Ok(ordinal) => w.write_all(&[ordinal])?,
+                        Err(_) => {
+                            return Err(invalid_record(format!("ordinal {} takes 16 bits", t.import_ordinal)));
+                        }
+                    },
+                    false => offset(w, t.import_ordinal, flags.is_32bit_target)?,
+                }
+            }
+            (FixupTarget::ImportedName(t), 0x02) => {
+                number(w, t.module_ordinal)?;
+                offset(w, t.procedure_name_offset, flags.is_32bit_target)?;
+            }
+            (FixupTarget::FixupViaEntryTable(t), 0x03) => number(w, t.entry_number)?,
+            _ => return Err(invalid_record("target and flags don't match".to_string())),
+        }
+
+        match (self.additive_value, flags.has_additive) {
+            (Some(additive), true) => offset(w, additive, flags.is_32bit_additive)?,
+            (None, false) => {}
+            _ => return Err(invalid_record("additive value and flags don't match".to_string())),
+        }
+        for source in self.source_offset_list.iter().flatten() {
+            order.write_u16(w, *source)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid_record(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("Fixup record can't be written: {}", message))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FixupTarget {
     Internal(FixupTargetInternal),
     ImportedOrdinal(FixupTargetImportedOrdinal),
@@ -20,30 +138,90 @@ pub enum FixupTarget {
     FixupViaEntryTable(FixupTargetEntryTable),
 }
 
-#[derive(Debug, Clone)]
+impl FixupTarget {
+    ///
+    /// Target is a procedure of imported module (by ordinal or by name)
+    ///
+    pub fn is_import(&self) -> bool {
+        self.module_ordinal().is_some()
+    }
+    ///
+    /// 1-based index in import module names table. `None` for targets
+    /// in this module: internal ones and ones via entry table.
+    ///
+    pub fn module_ordinal(&self) -> Option<u16> {
+        match self {
+            FixupTarget::ImportedOrdinal(t) => Some(t.module_ordinal),
+            FixupTarget::ImportedName(t) => Some(t.module_ordinal),
+            _ => None,
+        }
+    }
+    ///
+    /// Ordinal of procedure imported by ordinal
+    ///
+    pub fn imported_ordinal(&self) -> Option<u32> {
+        match self {
+            FixupTarget::ImportedOrdinal(t) => Some(t.import_ordinal),
+            _ => None,
+        }
+    }
+    ///
+    /// Offset of name in import procedure names table for procedure imported by name
+    ///
+    pub fn imported_name_offset(&self) -> Option<u32> {
+        match self {
+            FixupTarget::ImportedName(t) => Some(t.procedure_name_offset),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Target in the object of the same module.
+/// Selector fixups (source type `0x02`) have no offset: loader puts
+/// only the selector of target object into the source location.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupTargetInternal {
+    /// 1-based number of target object
     pub object_number: u16,
+    /// Offset in target object, `None` for selector fixups
     pub target_offset: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+impl FixupTargetInternal {
+    ///
+    /// Fixup writes selector of target object without offset
+    ///
+    pub fn is_selector_only(&self) -> bool {
+        self.target_offset.is_none()
+    }
+    ///
+    /// 1-based number of target object in objects table
+    ///
+    pub fn target_object_number(&self) -> u16 {
+        self.object_number
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupTargetImportedOrdinal {
     pub module_ordinal: u16,
     pub import_ordinal: u32, // <-- might be 8/16/32-bit
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupTargetImportedName {
     pub module_ordinal: u16,
     pub procedure_name_offset: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupTargetEntryTable {
     pub entry_number: u16,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupFlags {
     pub has_source_list: bool,
     pub has_additive: bool,
@@ -70,46 +248,257 @@ impl FixupFlags {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FixupRecordsTable {
     pub records: Vec<FixupRecord>,
 }
+table_accessors!(FixupRecordsTable, records, FixupRecord);
 
 impl FixupRecordsTable {
+    ///
+    /// Reads records of every logical page. With `rayon` feature the
+    /// section is read at once and pages are decoded in parallel
+    /// ([FixupRecordsTable::read_parallel]), else one-by-one
+    /// ([FixupRecordsTable::read_sequential]). Results are the same.
+    ///
     pub fn read<R: Read + Seek>(
         reader: &mut R,
         fixup_page_table: &FixupPageTable,
         fixup_record_table_offset: u64,
+        order: ByteOrder,
+    ) -> io::Result<Self> {
+        #[cfg(feature = "rayon")]
+        let table = Self::read_parallel(reader, fixup_page_table, fixup_record_table_offset, order);
+        #[cfg(not(feature = "rayon"))]
+        let table = Self::read_sequential(reader, fixup_page_table, fixup_record_table_offset, order);
+        table
+    }
+    ///
+    /// Reads records page by page straight from `reader`
+    ///
+    pub fn read_sequential<R: Read + Seek>(
+        reader: &mut R,
+        fixup_page_table: &FixupPageTable,
+        fixup_record_table_offset: u64,
+        order: ByteOrder,
     ) -> io::Result<Self> {
         let mut records = Vec::new();
+
+        for logical_page in 0..fixup_page_table.total_pages() {
+            let Some(range) = fixup_page_table.page_range(logical_page) else {
+                break;
+            };
+            records.extend(Self::read_page(
+                reader,
+                fixup_record_table_offset,
+                range,
+                logical_page,
+                order,
+            )?);
+        }
+
+        Ok(Self { records })
+    }
+    ///
+    /// Reads the whole record section into memory and decodes
+    /// pages concurrently. Records are kept in order of pages.
+    ///
+    #[cfg(feature = "rayon")]
+    pub fn read_parallel<R: Read + Seek>(
+        reader: &mut R,
+        fixup_page_table: &FixupPageTable,
+        fixup_record_table_offset: u64,
+        order: ByteOrder,
+    ) -> io::Result<Self> {
+        use rayon::prelude::*;
+
+        let section_len = fixup_page_table
+            .page_offsets
+            .iter()
+            .copied()
+            .chain([fixup_page_table.end_of_fixup_records])
+            .max()
+            .unwrap_or(0);
+        // truncated section fails on the same record as sequential reading
+        let mut section = Vec::new();
         reader.seek(SeekFrom::Start(fixup_record_table_offset))?;
+        reader.take(section_len as u64).read_to_end(&mut section)?;
 
-        for (logical_page, &page_offset) in fixup_page_table.page_offsets.as_slice().iter().enumerate() {
-            let record_offset = fixup_record_table_offset + page_offset as u64;
-            reader.seek(SeekFrom::Start(record_offset))?;
+        let pages: Vec<Vec<FixupRecord>> = (0..fixup_page_table.total_pages())
+            .into_par_iter()
+            .map(|logical_page| {
+                let range = fixup_page_table.page_range(logical_page).unwrap_or((0, 0));
+                Self::read_page(&mut std::io::Cursor::new(&section), 0, range, logical_page, order)
+            })
+            .collect::<io::Result<_>>()?;
 
-            // I can read records till next page offset!
-            // For elsewhere it throws unexpected problems
+        Ok(Self {
+            records: pages.into_iter().flatten().collect(),
+        })
+    }
+    ///
+    /// Records of page `logical_page` (0-based) in `(start, end)` range
+    /// relative to the record table at `table_offset` of `reader`
+    ///
+    fn read_page<R: Read + Seek>(
+        reader: &mut R,
+        table_offset: u64,
+        (start, end): (u32, u32),
+        logical_page: usize,
+        order: ByteOrder,
+    ) -> io::Result<Vec<FixupRecord>> {
+        let mut records = Vec::new();
+        reader.seek(SeekFrom::Start(table_offset + start as u64))?;
 
-            let next_offset = fixup_page_table
-                .page_offsets
-                .as_slice()
-                .get(logical_page + 1)
-                .copied()
-                .unwrap_or(fixup_page_table.end_of_fixup_records);
+        // I can read records till next page offset!
+        // For elsewhere it throws unexpected problems
 
-            while reader.stream_position()? < fixup_record_table_offset + next_offset as u64 {
-                if let Some(record) = Self::read_single_fixup_record(reader)? {
-                    records.push(record);
-                } else {
-                    break;
-                }
+        while reader.stream_position()? < table_offset + end as u64 {
+            if let Some(mut record) = Self::read_single_fixup_record(reader, order)? {
+                record.page = logical_page as u32 + 1;
+                records.push(record);
+            } else {
+                break;
             }
         }
+        Ok(records)
+    }
+    ///
+    /// Yields `(page_index, record)` pairs (`page_index` is 0-based) one
+    /// by one without buffering the table. Only the reader and current
+    /// position are kept; iteration stops after the first error.
+    ///
+    pub fn iter_page_records<'a, R: Read + Seek + 'a>(
+        reader: R,
+        fixup_page_table: &'a FixupPageTable,
+        base_offset: u64,
+        order: ByteOrder,
+    ) -> impl Iterator<Item = io::Result<(usize, FixupRecord)>> + 'a {
+        FixupRecordsIter {
+            reader,
+            fixup_page_table,
+            base_offset,
+            order,
+            logical_page: 0,
+            position: None,
+        }
+    }
 
-        Ok(Self { records })
+    ///
+    /// Writes records of `pages` logical pages and returns the fixup page
+    /// table which points to them. Records must be sorted by pages.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder, pages: u32) -> io::Result<FixupPageTable> {
+        let mut buf = Vec::new();
+        let mut page_offsets = Vec::with_capacity(pages as usize);
+        let mut records = self.records.iter().peekable();
+
+        for page in 1..=pages {
+            page_offsets.push(buf.len() as u32);
+            while let Some(record) = records.next_if(|r| r.page == page) {
+                record.write(&mut buf, order)?;
+            }
+        }
+        if let Some(record) = records.next() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Fixup record of page #{} is out of {} pages or out of order",
+                    record.page, pages
+                ),
+            ));
+        }
+
+        w.write_all(&buf)?;
+        Ok(FixupPageTable {
+            page_offsets,
+            end_of_fixup_records: buf.len() as u32,
+        })
+    }
+    ///
+    /// Patches loaded `object_data` of the object number `object_idx` (1-based)
+    /// by internal fixups which sources are in pages of this object.
+    ///
+    /// `object_bases` holds load address of every object of module
+    /// (index 0 for object #1). Target address is
+    /// `object_bases[target] + target_offset + additive`.
+    ///
+    /// Selectors can't be known without a loader, so selector fixups (`0x02`)
+    /// are skipped and far pointers (`0x03`, `0x06`) get only their offset part.
+    ///
+    pub fn apply_internal_fixups(
+        &self,
+        object_data: &mut [u8],
+        object_idx: usize,
+        object: &Object,
+        object_bases: &[u32],
+        page_size: u32,
+    ) -> io::Result<()> {
+        let first_page = object.map_index;
+        let last_page = object.map_index.saturating_add(object.map_size);
+        let object_base = object_bases.get(object_idx.wrapping_sub(1)).copied().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("No load address for object #{}", object_idx),
+            )
+        })?;
+
+        for record in &self.records {
+            if record.page < first_page || record.page >= last_page {
+                continue;
+            }
+            let internal = match &record.target_data {
+                FixupTarget::Internal(internal) => internal,
+                _ => continue,
+            };
+            let target_base = object_bases
+                .get((internal.object_number as usize).wrapping_sub(1))
+                .copied()
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Fixup targets unknown object #{}", internal.object_number),
+                    )
+                })?;
+            let target = target_base
+                .wrapping_add(internal.target_offset.unwrap_or(0))
+                .wrapping_add(record.additive_value.unwrap_or(0));
+
+            let page_start = (record.page - first_page) as i64 * page_size as i64;
+            let offsets = match &record.source_offset_list {
+                Some(list) => list.iter().map(|&o| o as i16).collect(),
+                None => vec![record.source_offset_or_count as i16],
+            };
+
+            for offset in offsets {
+                // source could cross page boundary, then offset is negative
+                let site = page_start + offset as i64;
+                let address = object_base.wrapping_add(site as u32);
+                Self::patch(object_data, site, record.source & 0x0F, target, address);
+            }
+        }
+
+        Ok(())
     }
 
-    fn read_single_fixup_record<R: Read>(reader: &mut R) -> io::Result<Option<FixupRecord>> {
+    fn patch(data: &mut [u8], site: i64, source_type: u8, target: u32, address: u32) {
+        let (width, value) = match source_type {
+            0x00 => (1, target & 0xFF),
+            0x03 | 0x05 => (2, target & 0xFFFF),
+            0x06 | 0x07 => (4, target),
+            // self-relative: distance from the end of 32-bit operand
+            0x08 => (4, target.wrapping_sub(address.wrapping_add(4))),
+            _ => return,
+        };
+        // parts of fixup outside of object data belong to the neighbour object
+        if site < 0 || site as usize + width > data.len() {
+            return;
+        }
+        let site = site as usize;
+        data[site..site + width].copy_from_slice(&value.to_le_bytes()[..width]);
+    }
+
+    fn read_single_fixup_record<R: Read>(reader: &mut R, order: ByteOrder) -> io::Result<Option<FixupRecord>> {
         let mut source_buf = [0_u8];
 
         reader.read_exact(&mut source_buf)?;
@@ -127,21 +516,15 @@ impl FixupRecordsTable {
             reader.read_exact(&mut count_buf)?;
             count_buf[0] as u16
         } else {
-            let mut offset_buf = [0_u8; 2];
-            reader.read_exact(&mut offset_buf)?;
-            u16::from_le_bytes(offset_buf)
+            order.read_u16(reader)?
         };
 
-        let target_data = Self::read_target_data(reader, &flags)?;
+        let target_data = Self::read_target_data(reader, &flags, order)?;
         let additive_value = if flags.has_additive {
             Some(if flags.is_32bit_additive {
-                let mut additive_buf = [0_u8; 4];
-                reader.read_exact(&mut additive_buf)?;
-                u32::from_le_bytes(additive_buf)
+                order.read_u32(reader)?
             } else {
-                let mut additive_buf = [0_u8; 2];
-                reader.read_exact(&mut additive_buf)?;
-                u16::from_le_bytes(additive_buf) as u32
+                order.read_u16(reader)? as u32
             })
         } else {
             None
@@ -151,9 +534,7 @@ impl FixupRecordsTable {
             let count = source_offset_or_count as usize;
             let mut list = Vec::with_capacity(count);
             for _ in 0..count {
-                let mut offset_buf = [0_u8; 2];
-                reader.read_exact(&mut offset_buf)?;
-                list.push(u16::from_le_bytes(offset_buf));
+                list.push(order.read_u16(reader)?);
             }
             Some(list)
         } else {
@@ -161,6 +542,7 @@ impl FixupRecordsTable {
         };
 
         Ok(Some(FixupRecord {
+            page: 0,
             source,
             target_flags,
             source_offset_or_count,
@@ -170,12 +552,12 @@ impl FixupRecordsTable {
         }))
     }
 
-    fn read_target_data<R: Read>(reader: &mut R, flags: &FixupFlags) -> io::Result<FixupTarget> {
+    fn read_target_data<R: Read>(reader: &mut R, flags: &FixupFlags, order: ByteOrder) -> io::Result<FixupTarget> {
         match flags.target_type {
-            0x00 => Self::read_internal_target(reader, flags),
-            0x01 => Self::read_imported_ordinal_target(reader, flags),
-            0x02 => Self::read_imported_name_target(reader, flags),
-            0x03 => Self::read_entry_table_target(reader, flags),
+            0x00 => Self::read_internal_target(reader, flags, order),
+            0x01 => Self::read_imported_ordinal_target(reader, flags, order),
+            0x02 => Self::read_imported_name_target(reader, flags, order),
+            0x03 => Self::read_entry_table_target(reader, flags, order),
             _ => Err(Error::new(
                 ErrorKind::InvalidData,
                 format!("Unknown target type: 0x{:02x}", flags.target_type),
@@ -186,12 +568,11 @@ impl FixupRecordsTable {
     fn read_internal_target<R: Read>(
         reader: &mut R,
         flags: &FixupFlags,
+        order: ByteOrder,
     ) -> io::Result<FixupTarget> {
         let object_number = match flags.is_16bit_object_module {
             true => {
-                let mut obj_buf = [0_u8; 2];
-                reader.read_exact(&mut obj_buf)?;
-                u16::from_le_bytes(obj_buf)
+                order.read_u16(reader)?
             }
             false => {
                 let mut obj_buf = [0_u8];
@@ -200,17 +581,14 @@ impl FixupRecordsTable {
             }
         };
 
+        // selector fixups have no offset in target object
         let target_offset = if flags.source_type != 0x02 {
             Some(match flags.is_32bit_target {
                 true => {
-                    let mut offset_buf = [0_u8; 4];
-                    reader.read_exact(&mut offset_buf)?;
-                    u32::from_le_bytes(offset_buf)
+                    order.read_u32(reader)?
                 }
                 false => {
-                    let mut offset_buf = [0_u8; 2];
-                    reader.read_exact(&mut offset_buf)?;
-                    u16::from_le_bytes(offset_buf) as u32
+                    order.read_u16(reader)? as u32
                 }
             })
         } else {
@@ -226,12 +604,11 @@ impl FixupRecordsTable {
     fn read_imported_ordinal_target<R: Read>(
         reader: &mut R,
         flags: &FixupFlags,
+        order: ByteOrder,
     ) -> io::Result<FixupTarget> {
         let module_ordinal = match flags.is_16bit_object_module {
             true => {
-                let mut mod_buf = [0_u8; 2];
-                reader.read_exact(&mut mod_buf)?;
-                u16::from_le_bytes(mod_buf)
+                order.read_u16(reader)?
             }
             false => {
                 let mut mod_buf = [0_u8];
@@ -245,13 +622,9 @@ impl FixupRecordsTable {
             reader.read_exact(&mut ordinal_buf)?;
             ordinal_buf[0] as u32
         } else if flags.is_32bit_target {
-            let mut ordinal_buf = [0_u8; 4];
-            reader.read_exact(&mut ordinal_buf)?;
-            u32::from_le_bytes(ordinal_buf)
+            order.read_u32(reader)?
         } else {
-            let mut ordinal_buf = [0_u8; 2];
-            reader.read_exact(&mut ordinal_buf)?;
-            u16::from_le_bytes(ordinal_buf) as u32
+            order.read_u16(reader)? as u32
         };
 
         Ok(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal {
@@ -263,12 +636,11 @@ impl FixupRecordsTable {
     fn read_imported_name_target<R: Read>(
         reader: &mut R,
         flags: &FixupFlags,
+        order: ByteOrder,
     ) -> io::Result<FixupTarget> {
         let module_ordinal = match flags.is_16bit_object_module {
             true => {
-                let mut mod_buf = [0_u8; 2];
-                reader.read_exact(&mut mod_buf)?;
-                u16::from_le_bytes(mod_buf)
+                order.read_u16(reader)?
             }
             false => {
                 let mut mod_buf = [0_u8];
@@ -279,14 +651,10 @@ impl FixupRecordsTable {
 
         let procedure_name_offset = match flags.is_32bit_target {
             true => {
-                let mut offset_buf = [0_u8; 4];
-                reader.read_exact(&mut offset_buf)?;
-                u32::from_le_bytes(offset_buf)
+                order.read_u32(reader)?
             }
             false => {
-                let mut offset_buf = [0_u8; 2];
-                reader.read_exact(&mut offset_buf)?;
-                u16::from_le_bytes(offset_buf) as u32
+                order.read_u16(reader)? as u32
             }
         };
 
@@ -299,12 +667,11 @@ impl FixupRecordsTable {
     fn read_entry_table_target<R: Read>(
         reader: &mut R,
         flags: &FixupFlags,
+        order: ByteOrder,
     ) -> io::Result<FixupTarget> {
         let entry_number = match flags.is_16bit_object_module {
             true => {
-                let mut entry_buf = [0_u8; 2];
-                reader.read_exact(&mut entry_buf)?;
-                u16::from_le_bytes(entry_buf)
+                order.read_u16(reader)?
             }
             false => {
                 let mut entry_buf = [0_u8];
@@ -318,3 +685,63 @@ impl FixupRecordsTable {
         }))
     }
 }
+
+///
+/// State of [FixupRecordsTable::iter_page_records]. `position` is
+/// the `(current, end)` file offsets inside of `logical_page`,
+/// `None` before the page is entered.
+///
+struct FixupRecordsIter<'a, R> {
+    reader: R,
+    fixup_page_table: &'a FixupPageTable,
+    base_offset: u64,
+    order: ByteOrder,
+    logical_page: usize,
+    position: Option<(u64, u64)>,
+}
+
+impl<R: Read + Seek> Iterator for FixupRecordsIter<'_, R> {
+    type Item = io::Result<(usize, FixupRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (current, end) = match self.position {
+                Some((current, end)) if current < end => (current, end),
+                Some(_) => {
+                    self.logical_page += 1;
+                    self.position = None;
+                    continue;
+                }
+                None => {
+                    let (start, end) = self.fixup_page_table.page_range(self.logical_page)?;
+                    let position = (self.base_offset + start as u64, self.base_offset + end as u64);
+                    self.position = Some(position);
+                    position
+                }
+            };
+            if current >= end {
+                continue;
+            }
+
+            let record = self
+                .reader
+                .seek(SeekFrom::Start(current))
+                .and_then(|_| FixupRecordsTable::read_single_fixup_record(&mut self.reader, self.order))
+                .and_then(|record| Ok((record, self.reader.stream_position()?)));
+            match record {
+                Ok((Some(mut record), next)) => {
+                    record.page = self.logical_page as u32 + 1;
+                    self.position = Some((next, end));
+                    return Some(Ok((self.logical_page, record)));
+                }
+                // unknown record ends the page, like in `read`
+                Ok((None, _)) => self.position = Some((end, end)),
+                Err(e) => {
+                    self.logical_page = self.fixup_page_table.total_pages();
+                    self.position = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
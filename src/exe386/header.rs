@@ -12,13 +12,11 @@
 //!  - Get raw file pointer to IA-32 protected-mode exec header.
 //!  - Get all IA-32 protected-mode header. (expecting `LE` or `LX`)
 //!
-//! ```rust
+//! ```rust,no_run
 //! use std::fs::File;
 //! use std::io::{BufReader, Read, Seek, SeekFrom};
 //! use os2omf::exe386::header::LinearExecutableHeader;
 //!
-//! use std::ptr::read;
-//!
 //! const NEXT_SIGNATURE_PTR: u64 = 0x3C;
 //!
 //! let file_str = "<put here path to FLAT executable>";
@@ -29,13 +27,12 @@
 //! let mut next_ptr_buffer = [0_u8; 4]; // e_lfanew is DWORD typed field
 //! file_buf.read_exact(&mut next_ptr_buffer)?;
 //!
-//!
-//! let next_ptr = u64::from_le_bytes(next_ptr_buffer);
+//! let next_ptr = u32::from_le_bytes(next_ptr_buffer) as u64;
 //! file_buf.seek(SeekFrom::Start(next_ptr))?;
 //!
 //! // finally!
 //! let exec_flat = LinearExecutableHeader::read(&mut file_buf)?;
-//!
+//! # Ok::<(), std::io::Error>(())
 //! ```
 //!
 //! Now we've got the intel protected-mode FLAT header.
@@ -50,12 +47,15 @@
 //!  - Make sure this signature belongs to IBM FLAT executable.
 //!  - Read next whole following data.
 //!
+use crate::types::readable::Readable;
+use crate::types::{ByteOrder, FieldCursor};
 use bytemuck::{Pod, Zeroable};
-use std::io::{Error, ErrorKind, Read};
+use std::fmt;
+use std::io::{Error, ErrorKind, Read, Write};
 
 pub const LX_MAGIC: u16 = 0x584C;
 pub const LX_CIGAM: u16 = 0x4C58;
-pub const LE_MAGIC: u16 = 0x455C;
+pub const LE_MAGIC: u16 = 0x454C;
 pub const LE_CIGAM: u16 = 0x4C45;
 ///
 /// Linear Executable format is undocumented format
@@ -87,7 +87,7 @@ pub const LE_CIGAM: u16 = 0x4C45;
 /// Most of reverse-engineering materials about IBM OS/2 OMFs could have been applied
 /// to Microsoft OS/2 `LE` objects.
 #[repr(C)]
-#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Pod, Zeroable)]
 pub struct LinearExecutableHeader {
     pub e32_magic: u16,
     pub e32_border: u8,
@@ -106,6 +106,9 @@ pub struct LinearExecutableHeader {
     /// Depends on target format:
     ///  - `e32_page_shift` as u32 - LX linked project
     ///  - `e32_lastpage_size` (count bytes at last page) - LE linked project
+    ///
+    /// Use [LinearExecutableHeader::page_shift] or
+    /// [LinearExecutableHeader::bytes_on_last_page] instead of guessing.
     pub e32_pageshift_or_lastpage: u32,
     pub e32_fixupsize: u32,
     pub e32_fixupsum: u32,
@@ -144,22 +147,176 @@ pub struct LinearExecutableHeader {
 }
 
 impl LinearExecutableHeader {
+    ///
+    /// Reads header in byte order set by `e32_border` and `e32_worder`.
+    /// Signature is two characters, so it doesn't depend on byte order.
+    ///
     pub fn read<T: Read>(r: &mut T) -> Result<Self, Error> {
         let mut buf = [0; 184]; // 184+12 = 200
         r.read_exact(&mut buf)?;
 
-        let header: &LinearExecutableHeader = bytemuck::try_from_bytes(&buf)
-            .map_err(|_| Error::new(ErrorKind::InvalidData, "Unable to cast bytes into header"))?;
+        let magic = u16::from_le_bytes([buf[0], buf[1]]);
+        if !matches!(magic, LX_MAGIC | LX_CIGAM | LE_MAGIC | LE_CIGAM) {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Invalid magic 0x{:X}", magic)));
+        }
+        let order = match (ByteOrder::from(buf[2]), ByteOrder::from(buf[3])) {
+            (Some(border), Some(worder)) if border == worder => border,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unsupported byte order {} and word order {}", buf[2], buf[3]),
+                ))
+            }
+        };
 
-        if !header.invalid_magic() {
-            return Err(Error::new(ErrorKind::InvalidData, format!("Invalid magic 0x{:X}", header.e32_magic)));
+        let mut f = FieldCursor::new(&buf, order);
+        Ok(Self {
+            e32_magic: u16::from_le_bytes(f.array()),
+            e32_border: f.u8(),
+            e32_worder: f.u8(),
+            e32_level: f.u32(),
+            e32_cpu: f.u16(),
+            e32_os: f.u16(),
+            e32_ver: f.u32(),
+            e32_mflags: f.u32(),
+            e32_mpages: f.u32(),
+            e32_cs: f.u32(),
+            e32_eip: f.u32(),
+            e32_ss: f.u32(),
+            e32_esp: f.u32(),
+            e32_pagesize: f.u32(),
+            e32_pageshift_or_lastpage: f.u32(),
+            e32_fixupsize: f.u32(),
+            e32_fixupsum: f.u32(),
+            e32_ldrsize: f.u32(),
+            e32_ldrsum: f.u32(),
+            e32_objtab: f.u32(),
+            e32_objcnt: f.u32(),
+            e32_objmap: f.u32(),
+            e32_itermap: f.u32(),
+            e32_rsrctab: f.u32(),
+            e32_rsrccnt: f.u32(),
+            e32_restab: f.u32(),
+            e32_enttab: f.u32(),
+            e32_dirtab: f.u32(),
+            e32_dircnt: f.u32(),
+            e32_fpagetab: f.u32(),
+            e32_frectab: f.u32(),
+            e32_impmod: f.u32(),
+            e32_impmodcnt: f.u32(),
+            e32_impproc: f.u32(),
+            e32_pagesum: f.u32(),
+            e32_datapage: f.u32(),
+            e32_preload: f.u32(),
+            e32_nrestab: f.u32(),
+            e32_cbnrestab: f.u32(),
+            e32_nressum: f.u32(),
+            e32_autodata: f.u32(),
+            e32_debuginfo: f.u32(),
+            e32_debuglen: f.u32(),
+            e32_instpreload: f.u32(),
+            e32_instdemand: f.u32(),
+            e32_heapsize: f.u32(),
+            e32_stacksize: f.u32(),
+            e32_res3: f.array(),
+        })
+    }
+    ///
+    /// Writes 0xC4 bytes of header in its byte order.
+    /// Reserved bytes after `e32_res3` are zeros.
+    ///
+    pub fn write<W: Write>(&self, w: &mut W) -> Result<(), Error> {
+        let order = self.byte_order();
+        let mut buf = Vec::with_capacity(0xC4);
+        buf.extend_from_slice(&self.e32_magic.to_le_bytes());
+        buf.extend_from_slice(&[self.e32_border, self.e32_worder]);
+        order.write_u32(&mut buf, self.e32_level)?;
+        order.write_u16(&mut buf, self.e32_cpu)?;
+        order.write_u16(&mut buf, self.e32_os)?;
+        for value in [
+            self.e32_ver,
+            self.e32_mflags,
+            self.e32_mpages,
+            self.e32_cs,
+            self.e32_eip,
+            self.e32_ss,
+            self.e32_esp,
+            self.e32_pagesize,
+            self.e32_pageshift_or_lastpage,
+            self.e32_fixupsize,
+            self.e32_fixupsum,
+            self.e32_ldrsize,
+            self.e32_ldrsum,
+            self.e32_objtab,
+            self.e32_objcnt,
+            self.e32_objmap,
+            self.e32_itermap,
+            self.e32_rsrctab,
+            self.e32_rsrccnt,
+            self.e32_restab,
+            self.e32_enttab,
+            self.e32_dirtab,
+            self.e32_dircnt,
+            self.e32_fpagetab,
+            self.e32_frectab,
+            self.e32_impmod,
+            self.e32_impmodcnt,
+            self.e32_impproc,
+            self.e32_pagesum,
+            self.e32_datapage,
+            self.e32_preload,
+            self.e32_nrestab,
+            self.e32_cbnrestab,
+            self.e32_nressum,
+            self.e32_autodata,
+            self.e32_debuginfo,
+            self.e32_debuglen,
+            self.e32_instpreload,
+            self.e32_instdemand,
+            self.e32_heapsize,
+            self.e32_stacksize,
+        ] {
+            order.write_u32(&mut buf, value)?;
         }
-        
-        if !header.le_byte_ordering() {
-            return Err(Error::new(ErrorKind::InvalidData, "Only Little endian linked modules are supported!"))
+        buf.extend_from_slice(&self.e32_res3);
+        buf.resize(0xC4, 0);
+        w.write_all(&buf)
+    }
+    ///
+    /// Order of bytes in all multibyte fields of module
+    /// (header and tables). Words are in the same order.
+    ///
+    pub fn byte_order(&self) -> ByteOrder {
+        match self.e32_border {
+            0 => ByteOrder::Little,
+            _ => ByteOrder::Big,
         }
-        
-        Ok(*header)
+    }
+    ///
+    /// Target processor decoded from `e32_cpu`
+    ///
+    pub fn cpu(&self) -> CPU {
+        CPU::from(self.e32_cpu)
+    }
+    ///
+    /// Target operating system decoded from `e32_os`
+    ///
+    pub fn target_os(&self) -> OS {
+        OS::from(self.e32_os)
+    }
+    ///
+    /// Version of module data (`e32_ver`) set by the linker
+    /// or by `/VERSION` option. It's not a version of format.
+    ///
+    pub fn module_version(&self) -> u32 {
+        self.e32_ver
+    }
+    ///
+    /// Format level (`e32_level`). Only level 0 exists,
+    /// loader refuses modules of any other level.
+    ///
+    pub fn format_level(&self) -> u32 {
+        self.e32_level
     }
     pub fn external_relocs_stripped(&self) -> bool {
         self.e32_mflags & 0x00000020 != 0
@@ -208,7 +365,8 @@ impl LinearExecutableHeader {
         }
     }
     ///
-    /// Be carefully: Only little endian-ordered files are supported!
+    /// Returns `true` if bytes and words of module are little-endian
+    /// (see [LinearExecutableHeader::byte_order])
     ///
     pub fn le_byte_ordering(&self) -> bool {
         if self.e32_border == 0 && self.e32_worder == 0 {
@@ -216,6 +374,62 @@ impl LinearExecutableHeader {
         }
         false
     }
+    ///
+    /// Module is linked in LX format (OS/2 2.x+ modules).
+    /// Object page map records are 8 bytes and `e32_pageshift_or_lastpage`
+    /// is a shift of page offsets (see [LinearExecutableHeader::page_shift]).
+    ///
+    pub fn is_lx(&self) -> bool {
+        self.e32_magic == LX_MAGIC || self.e32_magic == LX_CIGAM
+    }
+    ///
+    /// Module is linked in LE format (Windows VxDs, DOS extenders, OS/2 2.0 betas).
+    /// Object page map records are 4 bytes and `e32_pageshift_or_lastpage`
+    /// is a size of the last page (see [LinearExecutableHeader::bytes_on_last_page]).
+    ///
+    pub fn is_le(&self) -> bool {
+        self.e32_magic == LE_MAGIC || self.e32_magic == LE_CIGAM
+    }
+    ///
+    /// Shift of page offsets in object page map. Only LX modules have it,
+    /// for LE modules returns 0 (pages are not shifted).
+    ///
+    pub fn page_shift(&self) -> u32 {
+        match self.is_lx() {
+            true => self.e32_pageshift_or_lastpage,
+            false => 0,
+        }
+    }
+    ///
+    /// Count of used bytes of the last page. Only LE modules have it,
+    /// for LX modules returns `e32_pagesize` (every page is whole).
+    ///
+    pub fn bytes_on_last_page(&self) -> u32 {
+        match self.is_le() {
+            true => self.e32_pageshift_or_lastpage,
+            false => self.e32_pagesize,
+        }
+    }
+    ///
+    /// Offset of page data section. `e32_datapage` is counted from the
+    /// start of file, not from the LE/LX header: `base` is where the
+    /// file image starts (`0` for standalone modules).
+    ///
+    pub fn data_pages_offset(&self, base: u64) -> u64 {
+        base + self.e32_datapage as u64
+    }
+    ///
+    /// Count of pages loader reads when the module is loaded (`e32_preload`)
+    ///
+    pub fn preload_page_count(&self) -> u32 {
+        self.e32_preload
+    }
+    ///
+    /// Count of per-instance pages loaded on demand (`e32_instdemand`)
+    ///
+    pub fn demand_page_count(&self) -> u32 {
+        self.e32_instdemand
+    }
     /// Matches `e32_magic` with program-constants
     /// declared higher in `exe386::header`
     pub fn invalid_magic(&self) -> bool {
@@ -226,35 +440,186 @@ impl LinearExecutableHeader {
         }
     }
 }
-#[derive(Debug, Clone)]
-#[repr(u16)]
+impl Readable<LinearExecutableHeader> for LinearExecutableHeader {
+    fn read<R: Read>(r: &mut R) -> Result<LinearExecutableHeader, Error> {
+        Self::read(r)
+    }
+}
+///
+/// Short summary of the header for humans.
+/// Use fields of header for machine processing.
+/// Format level is printed only if it's not zero.
+///
+/// ```text
+/// LX executable module
+///   CPU:          i386
+///   OS:           OS/2 2.x
+///   Module type:  DLL
+///   Pages:        12 (4096 bytes each)
+///   Objects:      3
+///   Entry point:  0001:00000010
+///   Flags:        internal fixups applied, per-process init
+/// ```
+///
+impl fmt::Display for LinearExecutableHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magic = match self.e32_magic {
+            LX_MAGIC | LX_CIGAM => "LX",
+            LE_MAGIC | LE_CIGAM => "LE",
+            _ => "??",
+        };
+        let flags = self.module_flags();
+        let names = [
+            (flags.internal_fixups, "internal fixups applied"),
+            (flags.external_fixups, "external fixups applied"),
+            (flags.pm_windowing_incompat, "PM incompatible"),
+            (flags.pm_windowing_compat, "PM compatible"),
+            (flags.not_loadable, "not loadable"),
+            (flags.multi_cpu_unsafe, "multi-CPU unsafe"),
+            (flags.per_process_init, "per-process init"),
+            (flags.per_process_term, "per-process term"),
+        ];
+        let active: Vec<&str> = names.iter().filter(|(set, _)| *set).map(|(_, n)| *n).collect();
+
+        writeln!(f, "{} executable module", magic)?;
+        if self.format_level() != 0 {
+            writeln!(f, "  Level:        {} (not loadable)", self.format_level())?;
+        }
+        writeln!(f, "  CPU:          {}", self.cpu())?;
+        writeln!(f, "  OS:           {}", self.target_os())?;
+        writeln!(f, "  Module type:  {:?}", self.module_type())?;
+        writeln!(f, "  Pages:        {} ({} bytes each)", self.e32_mpages, self.e32_pagesize)?;
+        writeln!(f, "  Objects:      {}", self.e32_objcnt)?;
+        writeln!(f, "  Entry point:  {:04X}:{:08X}", self.e32_cs, self.e32_eip)?;
+        match active.is_empty() {
+            true => write!(f, "  Flags:        none"),
+            false => write!(f, "  Flags:        {}", active.join(", ")),
+        }
+    }
+}
+///
+/// Target processor (`e32_cpu`). Values are reserved by IBM specification,
+/// RISC ones were never shipped but appear in toolkits headers.
+///
+/// NE modules keep processor in `e_flags` bits instead
+/// (see [crate::exe286::header::CPU]), values aren't the same.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CPU {
     /// Intel 286 and higher
-    I286 = 0x0001,
+    I286,
     /// Intel 386 and higher
-    I386 = 0x0002,
+    I386,
     /// Intel 486 and higher
-    I486 = 0x0003,
+    I486,
+    /// Intel Pentium (586) and higher
+    Pentium,
+    /// Intel i860 XR (N10)
+    I860N10,
+    /// Intel i860 XP (N11)
+    I860N11,
+    /// MIPS Mark I (R2000, R3000)
+    MipsMarkI,
+    /// MIPS Mark II (R6000)
+    MipsMarkII,
+    /// MIPS Mark III (R4000)
+    MipsMarkIII,
+    /// Not set or unknown by this crate
+    Unknown(u16),
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OS {
-    /// Flag not set: any OS supported or nothing at all.
-    Unknown = 0,
     /// OS/2 2.0+
-    Os2v2 = 0x0001,
+    Os2v2,
     /// Windows without 32-bit support
-    Windows286 = 0x0002,
+    Windows286,
     /// DOS 4.0+
-    Dos4 = 0x0003,
+    Dos4,
     /// Windows with support of 32-bit code execution
     ///
     /// Be carefully: `Win386` is not `Win32s`. Win32s is a subsystem
     /// of Windows COFF/PE 32-bit executables for 16-bit Windows 3x.
-    Windows386 = 0x0004,
+    Windows386,
     /// I can't find any information about it
-    PersonalityNeural = 0x0005,
+    PersonalityNeural,
+    /// Flag not set (any OS supported or nothing at all) or unknown by this crate
+    Unknown(u16),
+}
+impl CPU {
+    ///
+    /// Lossy conversion: unknown values become [CPU::Unknown].
+    /// Use `CPU::try_from` to refuse them.
+    ///
+    pub fn from(cpu: u16) -> Self {
+        match cpu {
+            0x0001 => CPU::I286,
+            0x0002 => CPU::I386,
+            0x0003 => CPU::I486,
+            0x0004 => CPU::Pentium,
+            0x0020 => CPU::I860N10,
+            0x0021 => CPU::I860N11,
+            0x0040 => CPU::MipsMarkI,
+            0x0041 => CPU::MipsMarkII,
+            0x0042 => CPU::MipsMarkIII,
+            n => CPU::Unknown(n),
+        }
+    }
+}
+impl TryFrom<u16> for CPU {
+    type Error = u16;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match CPU::from(value) {
+            CPU::Unknown(n) => Err(n),
+            cpu => Ok(cpu),
+        }
+    }
+}
+impl fmt::Display for CPU {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CPU::Unknown(0) => "unknown",
+            CPU::Unknown(n) => return write!(f, "unknown (0x{:04X})", n),
+            CPU::I286 => "i286",
+            CPU::I386 => "i386",
+            CPU::I486 => "i486",
+            CPU::Pentium => "Pentium",
+            CPU::I860N10 => "i860 (N10)",
+            CPU::I860N11 => "i860 (N11)",
+            CPU::MipsMarkI => "MIPS Mark I",
+            CPU::MipsMarkII => "MIPS Mark II",
+            CPU::MipsMarkIII => "MIPS Mark III",
+        };
+        f.write_str(name)
+    }
+}
+impl OS {
+    pub fn from(os: u16) -> Self {
+        match os {
+            0x0001 => OS::Os2v2,
+            0x0002 => OS::Windows286,
+            0x0003 => OS::Dos4,
+            0x0004 => OS::Windows386,
+            0x0005 => OS::PersonalityNeural,
+            n => OS::Unknown(n),
+        }
+    }
+}
+impl fmt::Display for OS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OS::Unknown(0) => "unknown",
+            OS::Unknown(n) => return write!(f, "unknown (0x{:04X})", n),
+            OS::Os2v2 => "OS/2 2.x",
+            OS::Windows286 => "Windows (286)",
+            OS::Dos4 => "DOS 4.x",
+            OS::Windows386 => "Windows (386)",
+            OS::PersonalityNeural => "Personality Neutral",
+        };
+        f.write_str(name)
+    }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleFlags {
     /// External fixups *has been applied*
     pub external_fixups: bool,
@@ -292,7 +657,7 @@ pub struct ModuleFlags {
     pub per_process_init: bool,
 }
 /// Possible declared by IBM manual types of loadable modules
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[repr(u32)]
 pub enum ModuleType {
     /// Executable
@@ -2,7 +2,9 @@
 //! for extraction run-time imports from module
 use crate::exe386::frectab::{FixupRecord, FixupTarget};
 use crate::types::PascalString;
-use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom, Write};
 
 #[derive(Debug)]
 pub enum ImportError {
@@ -11,26 +13,106 @@ pub enum ImportError {
     InvalidStringLength(u8),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImportData<'fixup_recs> {
     pub imp_mod_offset: u64,
+    /// Count of import module names (`e32_impmodcnt`)
+    pub imp_mod_count: u32,
     pub imp_proc_offset: u64,
+    /// Bytes of procedure names table: it is the last table
+    /// of fixup section (`e32_fpagetab + e32_fixupsize - e32_impproc`)
+    pub imp_proc_size: u64,
     pub fixup_records: &'fixup_recs Vec<FixupRecord>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ImportRelocationsTable {
+    modules: Vec<PascalString>,
     imports: Vec<DllImport>,
+    procedures: Vec<u8>,
+}
+///
+/// Unique imported procedure and count of fixups
+/// which are referencing it.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportUsage {
+    pub import: DllImport,
+    pub fixup_count: usize,
 }
 
 impl ImportRelocationsTable {
     pub fn imports(&self) -> &[DllImport] {
-        &self.imports.as_slice()
+        self.imports.as_slice()
+    }
+    ///
+    /// All import module names declared in the module.
+    /// Modules might be here even if no fixup references them.
+    ///
+    pub fn modules(&self) -> &[PascalString] {
+        self.modules.as_slice()
+    }
+    ///
+    /// Name at `offset` of import procedure names table. Fixups and
+    /// forwarders by name point here, but not every name has a fixup.
+    /// `None` if name is outside of the table.
+    ///
+    pub fn procedure_name(&self, offset: u32) -> Option<PascalString> {
+        let offset = offset as usize;
+        let len = *self.procedures.get(offset)?;
+        let name = self.procedures.get(offset + 1..offset + 1 + len as usize)?;
+        Some(PascalString::new(len, name.to_vec()))
+    }
+    ///
+    /// Every imported procedure once, keyed by module and name or ordinal.
+    /// Order of first reference is kept.
+    ///
+    pub fn unique_imports(&self) -> Vec<ImportUsage> {
+        let mut unique: Vec<ImportUsage> = Vec::new();
+        let mut positions: HashMap<&DllImport, usize> = HashMap::new();
+
+        for import in &self.imports {
+            match positions.get(import) {
+                Some(&i) => unique[i].fixup_count += 1,
+                None => {
+                    positions.insert(import, unique.len());
+                    unique.push(ImportUsage {
+                        import: import.clone(),
+                        fixup_count: 1,
+                    });
+                }
+            }
+        }
+
+        unique
+    }
+    ///
+    /// Every imported procedure once. Use it when you are building
+    /// a dependency graph: `imports()` holds one record per fixup.
+    ///
+    pub fn deduplicated_imports(&self) -> Vec<DllImport> {
+        self.unique_imports()
+            .into_iter()
+            .map(|usage| usage.import)
+            .collect()
+    }
+
+    ///
+    /// Writes import module names table. Procedure names are written
+    /// by [crate::exe386::builder::LxBuilder].
+    ///
+    pub fn write_modules<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for module in &self.modules {
+            w.write_all(&[module.pascal_len()])?;
+            w.write_all(module.as_bytes())?;
+        }
+        Ok(())
     }
 
     fn read_modules<T: Read + Seek>(
         reader: &mut T,
         imp_mod_offset: u64,
+        imp_mod_count: u32,
     ) -> io::Result<Vec<PascalString>> {
         if imp_mod_offset == 0 {
             return Ok(Vec::new());
@@ -40,13 +122,10 @@ impl ImportRelocationsTable {
         reader.seek(SeekFrom::Start(imp_mod_offset))?;
 
         let mut modules = Vec::new();
-
-        loop {
+        // Procedures table may follow right after modules table
+        // and starts with non-zero byte. Don't try to find zero terminator.
+        for _ in 0..imp_mod_count {
             let len = Self::read_byte(reader)?;
-            if len == 0 {
-                break;
-            }
-
             let name_bytes = Self::read_bytes(reader, len as usize)?;
             modules.push(PascalString::new(len, name_bytes));
         }
@@ -55,6 +134,29 @@ impl ImportRelocationsTable {
         Ok(modules)
     }
 
+    fn read_procedures<T: Read + Seek>(
+        reader: &mut T,
+        imp_proc_offset: u64,
+        imp_proc_size: u64,
+    ) -> io::Result<Vec<u8>> {
+        if imp_proc_offset == 0 || imp_proc_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let original_pos = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(imp_proc_offset))?;
+
+        // size is from header: keep only bytes which file has
+        let mut procedures = Vec::new();
+        reader
+            .by_ref()
+            .take(imp_proc_size)
+            .read_to_end(&mut procedures)?;
+
+        reader.seek(SeekFrom::Start(original_pos))?;
+        Ok(procedures)
+    }
+
     fn read_pascal_string<T: Read>(reader: &mut T) -> io::Result<PascalString> {
         let len = Self::read_byte(reader)?;
         if len == 0 {
@@ -83,12 +185,16 @@ impl ImportRelocationsTable {
         modules: &[PascalString],
         imp_proc_offset: u64,
     ) -> Result<DllImport, Error> {
-        let module_index = name_target.module_ordinal - 1;
+        let module_index = name_target.module_ordinal.wrapping_sub(1);
         let module_name = modules
             .get(module_index as usize)
-            .ok_or_else(|| ImportError::InvalidModuleOrdinal(module_index))
-            .unwrap()
-            .clone();
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Module at {} index is invalid", module_index),
+                )
+            })
+            .cloned()?;
 
         let procedure_ptr = imp_proc_offset + name_target.procedure_name_offset as u64;
 
@@ -111,7 +217,7 @@ impl ImportRelocationsTable {
         ordinal_target: &crate::exe386::frectab::FixupTargetImportedOrdinal,
         modules: &[PascalString],
     ) -> Result<DllImport, Error> {
-        let module_index = ordinal_target.module_ordinal - 1;
+        let module_index = ordinal_target.module_ordinal.wrapping_sub(1);
         let module_name = modules
             .get(module_index as usize)
             .ok_or_else(|| {
@@ -119,8 +225,8 @@ impl ImportRelocationsTable {
                     ErrorKind::InvalidData,
                     format!("Module at {} index is invalid", module_index),
                 )
-            })?
-            .clone();
+            })
+            .cloned()?;
 
         Ok(DllImport::ImportOrdinal(DllImportOrdinal {
             module_index,
@@ -130,7 +236,16 @@ impl ImportRelocationsTable {
     }
 
     pub fn read<T: Read + Seek>(reader: &mut T, import_data: ImportData) -> Result<Self, Error> {
-        let modules = Self::read_modules(reader, import_data.imp_mod_offset)?;
+        let modules = Self::read_modules(
+            reader,
+            import_data.imp_mod_offset,
+            import_data.imp_mod_count,
+        )?;
+        let procedures = Self::read_procedures(
+            reader,
+            import_data.imp_proc_offset,
+            import_data.imp_proc_size,
+        )?;
         let mut imports = Vec::new();
 
         for record in import_data.fixup_records {
@@ -161,7 +276,11 @@ impl ImportRelocationsTable {
             }
         }
 
-        Ok(Self { imports })
+        Ok(Self {
+            modules,
+            imports,
+            procedures,
+        })
     }
 }
 
@@ -185,19 +304,81 @@ impl DllImport {
             DllImport::ImportOrdinal(import) => import.module_index,
         }
     }
+    ///
+    /// Procedure name if procedure is imported by name
+    ///
+    pub fn import_name(&self) -> Option<&PascalString> {
+        match self {
+            DllImport::ImportName(import) => Some(&import.import_name),
+            DllImport::ImportOrdinal(_) => None,
+        }
+    }
+    ///
+    /// Procedure ordinal if procedure is imported by ordinal
+    ///
+    pub fn import_ordinal(&self) -> Option<u32> {
+        match self {
+            DllImport::ImportName(_) => None,
+            DllImport::ImportOrdinal(import) => Some(import.import_ordinal),
+        }
+    }
+    pub fn is_by_name(&self) -> bool {
+        matches!(self, DllImport::ImportName(_))
+    }
+    pub fn is_by_ordinal(&self) -> bool {
+        matches!(self, DllImport::ImportOrdinal(_))
+    }
+    ///
+    /// Procedure name or `#ordinal` (like `#282`) for imports by ordinal
+    ///
+    pub fn display_symbol(&self) -> String {
+        match self {
+            DllImport::ImportName(import) => import.import_name.to_string_lossy(),
+            DllImport::ImportOrdinal(import) => format!("#{}", import.import_ordinal),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+///
+/// Imports are equal when they are pointing to the same procedure:
+/// the same module and the same name (by offset in procedures table) or ordinal.
+///
+impl PartialEq for DllImport {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DllImport::ImportName(a), DllImport::ImportName(b)) => {
+                a.module_index == b.module_index && a.import_name_offset == b.import_name_offset
+            }
+            (DllImport::ImportOrdinal(a), DllImport::ImportOrdinal(b)) => {
+                a.module_index == b.module_index && a.import_ordinal == b.import_ordinal
+            }
+            _ => false,
+        }
+    }
+}
+impl Eq for DllImport {}
+impl Hash for DllImport {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            DllImport::ImportName(i) => (0_u8, i.module_index, i.import_name_offset).hash(state),
+            DllImport::ImportOrdinal(i) => (1_u8, i.module_index, i.import_ordinal).hash(state),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DllImportName {
     pub module_index: u16,
+    /// Shared by all imports of the module read from one table
     pub module_name: PascalString,
     pub import_name_offset: u32,
     pub import_name: PascalString,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DllImportOrdinal {
     pub module_index: u16,
+    /// Shared by all imports of the module read from one table
     pub module_name: PascalString,
     pub import_ordinal: u32,
 }
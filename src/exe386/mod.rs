@@ -23,7 +23,7 @@
 //! Fixup tables tells us "what pointers needs to resolve in runtime?" and object
 //! pages holds data about executable code and data which will be loaded in memory.
 //! 
-//! ```rust
+//! ```rust,no_run
 //! use os2omf::exe386::LinearExecutableLayout;
 //! 
 //! let file_str = "<put here your flat_exec path>.DLL";
@@ -34,22 +34,40 @@
 //! let public_exports = layout.non_resident_names.entries; // <-- not.
 //! 
 //! let imports = layout.import_table.imports(); // names and ordinals of dynamic imports
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Table pointers
+//! Pointers of loader and fixup sections are relative to the LE/LX header
+//! (`e_lfanew`, or `0` when module has no DOS header). Structures which
+//! aren't kept in memory are addressed from the start of file:
+//! ```text
+//! e32_objtab, e32_objmap, e32_rsrctab, e32_restab, e32_enttab,
+//! e32_dirtab, e32_fpagetab, e32_frectab, e32_impmod, e32_impproc   e_lfanew + pointer
+//! e32_datapage, e32_nrestab, e32_debuginfo                        pointer
 //! ```
+//! Every table reader takes the absolute `u64` file offset computed
+//! like that and seeks to it by itself.
 //!
-use crate::exe::MzHeader;
+use crate::exe::{MzHeader, LX_HEADER_LEN};
 use crate::exe286::nrestab::NonResidentNameTable;
-use crate::exe286::resntab::ResidentNameTable;
 use crate::exe386::dirtab::ModuleDirectivesTable;
-use crate::exe386::enttab::EntryTable;
+use crate::exe386::enttab::{BundleType, Entry, EntryTable};
 use crate::exe386::fpagetab::FixupPageTable;
-use crate::exe386::frectab::FixupRecordsTable;
-use crate::exe386::header::LinearExecutableHeader;
+use crate::exe386::frectab::{FixupRecordsTable, FixupTarget};
+use crate::exe386::header::{LinearExecutableHeader, OS};
 use crate::exe386::imptab::{ImportData, ImportRelocationsTable};
-use crate::exe386::objpagetab::ObjectPagesTable;
-use crate::exe386::objtab::ObjectsTable;
+use crate::exe386::objpagetab::{ObjectPage, ObjectPagesTable};
+use crate::exe386::objtab::{Object, ObjectsTable};
+use crate::exe386::resntab::LxResidentNameTable;
+use crate::types::PascalString;
+use crate::anomaly::{self, Anomaly, Severity};
+use crate::regions::{self, Region, RegionKind};
+use std::fmt;
 use std::fs::File;
 use std::io::{BufReader, Error, ErrorKind, Read, Seek, SeekFrom};
 
+pub mod builder;
 pub mod dirtab;
 pub mod enttab;
 pub mod fpagetab;
@@ -61,8 +79,16 @@ pub mod objpagetab;
 pub mod objtab;
 pub mod resntab;
 pub mod vxd;
+pub mod w3;
+pub mod w4;
 
+/// Address space of OS/2 process, no object is larger
+const MAX_OBJECT_SIZE: u64 = 512 << 20;
+
+#[derive(Debug, Clone)]
 pub struct LinearExecutableLayout {
+    /// DOS header if module has DOS compatibility
+    pub dos_header: Option<MzHeader>,
     pub header: LinearExecutableHeader,
     pub object_table: ObjectsTable,
     pub object_pages: ObjectPagesTable,
@@ -72,7 +98,107 @@ pub struct LinearExecutableLayout {
     pub import_table: ImportRelocationsTable,
     pub module_directives_table: ModuleDirectivesTable,
     pub non_resident_names: NonResidentNameTable,
-    pub resident_names: ResidentNameTable,
+    pub resident_names: LxResidentNameTable,
+}
+///
+/// `object:offset` address of module with its virtual address
+/// (object is loaded at `virtual_addr` of object table)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectAddress {
+    /// 1-based object number
+    pub object: u32,
+    pub offset: u32,
+    pub virtual_address: u32,
+}
+///
+/// Internal fixup (target type `0x00`) joined with the page of its
+/// source. Records with source lists give one relocation per source.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InternalRelocation {
+    /// Logical page number (1-based) where the source is
+    pub source_page: u32,
+    /// 1-based object which owns `source_page`, `None` if no object does
+    pub source_object: Option<u32>,
+    /// Offset in source page. Negative when the source starts in previous page
+    pub source_offset: i16,
+    /// Lower nibble of source byte: `0x07` offset32, `0x08` self32 ...
+    pub source_type: u8,
+    /// 1-based number of target object
+    pub target_object: u16,
+    /// Offset in target object, `None` for selector fixups
+    pub target_offset: Option<u32>,
+    pub additive: Option<u32>,
+}
+///
+/// Where the fixup via entry table (target type `0x03`) really points.
+/// Modules call their own exports this way position-independently.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedFixupTarget {
+    /// Entry is in object (1-based) of this module
+    Object { object: u16, offset: u32 },
+    /// Entry is forwarded to the procedure of imported module.
+    /// `offset_or_ordinal` is an ordinal when `by_ordinal` is set,
+    /// else offset of name in import procedure names table.
+    Forwarder {
+        module_ordinal: u16,
+        module: Option<PascalString>,
+        by_ordinal: bool,
+        offset_or_ordinal: u32,
+    },
+    /// Entry is missing or unused. Some packers leave such fixups
+    Dangling,
+}
+///
+/// Fixup via entry table with its resolved target
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedEntryFixup {
+    /// Index of record in `fixup_records_table`
+    pub record: usize,
+    pub entry_number: u16,
+    pub target: ResolvedFixupTarget,
+}
+///
+/// Pages of one object split by the way loader reads them
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectPreload {
+    /// 1-based object number
+    pub object: u32,
+    pub preload_pages: u32,
+    pub demand_pages: u32,
+    /// Bytes of preload pages in file
+    pub preload_bytes: u64,
+    /// Object has `OBJ_HAS_PRELOAD` flag
+    pub marked_preload: bool,
+}
+///
+/// Preload pages of module and their split between objects.
+/// See [LinearExecutableLayout::preload_summary].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadSummary {
+    /// Count of preload pages (`e32_preload`)
+    pub preload_pages: u32,
+    /// Bytes of all preload pages in file
+    pub preload_bytes: u64,
+    pub objects: Vec<ObjectPreload>,
+}
+///
+/// All internal relocations of module.
+///
+/// When internal fixups are stripped (`internal_relocs_stripped`) and
+/// every object has its preferred load address, targets are already
+/// written into object pages: `rebase_only` is set, and relocations
+/// left in the file are needed only when objects are loaded elsewhere.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalRelocations {
+    pub rebase_only: bool,
+    pub relocations: Vec<InternalRelocation>,
 }
 
 impl LinearExecutableLayout {
@@ -82,20 +208,23 @@ impl LinearExecutableLayout {
     /// Then first header instead of DOS header will be Linear Executable header
     /// and all relative pointers what set in header becomes absolute
     ///
-    fn define_base_offset<T: Read>(reader: &mut T) -> Option<u64> {
+    pub(crate) fn define_base_offset<T: Read + Seek>(reader: &mut T) -> Option<(Option<MzHeader>, u64)> {
+        reader.seek(SeekFrom::Start(0)).ok()?;
         let maybe_header = MzHeader::read(reader);
         match maybe_header {
             Ok(h) => {
-                return Some(h.e_lfanew as u64)
+                return Some((Some(h), h.e_lfanew as u64))
             },
             Err(..) => {
                 // ignore for 1st time
             }
         }
 
+        // DOS header has eaten some bytes, LE/LX header starts at 0 too
+        reader.seek(SeekFrom::Start(0)).ok()?;
         let maybe_header = LinearExecutableHeader::read(reader);
         match maybe_header {
-            Ok(_) => Some(0),
+            Ok(_) => Some((None, 0)),
             Err(..) => None,
         }
     }
@@ -105,55 +234,95 @@ impl LinearExecutableLayout {
     pub fn get(path: &str) -> Result<Self, Error> {
         let file = File::open(path)?;
         let mut reader = BufReader::new(file);
-
-        let base_offset = match Self::define_base_offset(&mut reader) {
-            Some(offset) => offset,
+        Self::read_from(&mut reader)
+    }
+    ///
+    /// Reads module from the start of `reader`, with or without DOS header
+    ///
+    pub fn read_from<T: Read + Seek>(reader: &mut T) -> Result<Self, Error> {
+        let (dos_header, base_offset) = match Self::define_base_offset(reader) {
+            Some(base) => base,
             None => Err(Error::new(ErrorKind::InvalidInput, "Could not determine base offset"))?,
         };
+        if let Some(dos_header) = &dos_header {
+            let file_len = reader.seek(SeekFrom::End(0))?;
+            dos_header.validate_lfanew(file_len, LX_HEADER_LEN)?;
+        }
+        Self::read_layout(reader, dos_header, base_offset)
+    }
+    ///
+    /// Reads module which LE/LX header is at `header_offset` of the reader.
+    /// Use it for modules embedded in other files (e.g. W3 archives,
+    /// see [w3::W3Archive]): DOS header is not read and stays `None`.
+    ///
+    /// Header pointers are relative to `header_offset` as usual,
+    /// pointers to pages and non-resident names stay absolute.
+    ///
+    pub fn read_at<T: Read + Seek>(reader: &mut T, header_offset: u64) -> Result<Self, Error> {
+        Self::read_layout(reader, None, header_offset)
+    }
+
+    fn read_layout<T: Read + Seek>(
+        reader: &mut T,
+        dos_header: Option<MzHeader>,
+        base_offset: u64,
+    ) -> Result<Self, Error> {
         reader.seek(SeekFrom::Start(base_offset))?;
-        let header = LinearExecutableHeader::read(&mut reader)?;
+        let header = LinearExecutableHeader::read(reader)?;
 
         let offset = |ptr: u32| -> u64 { ptr as u64 + base_offset };
+        let order = header.byte_order();
 
         let object_pages = ObjectPagesTable::read(
-            &mut reader,
+            reader,
             offset(header.e32_objmap),
             header.e32_mpages,
-            header.e32_pageshift_or_lastpage,
             header.e32_magic,
+            order,
         )?;
         let object_table = ObjectsTable::read(
-            &mut reader,
+            reader,
             offset(header.e32_objtab),
-            header.e32_objcnt
+            header.e32_objcnt,
+            order,
         )?;
         let entry_table = EntryTable::read(
-            &mut reader,
-            offset(header.e32_enttab)
+            reader,
+            offset(header.e32_enttab),
+            order,
         )?;
-        let resident_names = ResidentNameTable::read(
-            &mut reader,
-            offset(header.e32_restab)
+        let resident_names = LxResidentNameTable::read_with_order(
+            reader,
+            offset(header.e32_restab),
+            order,
         )?;
-        let non_resident_names = NonResidentNameTable::read(
-            &mut reader,
-            header.e32_nrestab
+        let non_resident_names = NonResidentNameTable::read_with_order(
+            reader,
+            header.e32_nrestab as u64,
+            order,
         )?;
         let fixup_page_table = FixupPageTable::read(
-            &mut reader,
+            reader,
             offset(header.e32_fpagetab),
             &header
         )?;
         let fixup_records_table = FixupRecordsTable::read(
-            &mut reader,
+            reader,
             &fixup_page_table,
-            offset(header.e32_frectab)
+            offset(header.e32_frectab),
+            order,
         )?;
         let import_table = ImportRelocationsTable::read(
-            &mut reader,
+            reader,
             ImportData {
                 imp_mod_offset: offset(header.e32_impmod),
+                imp_mod_count: header.e32_impmodcnt,
                 imp_proc_offset: offset(header.e32_impproc),
+                imp_proc_size: match (header.e32_fpagetab, header.e32_impproc) {
+                    (0, _) | (_, 0) => 0,
+                    (fpagetab, impproc) => (fpagetab as u64 + header.e32_fixupsize as u64)
+                        .saturating_sub(impproc as u64),
+                },
                 fixup_records: &fixup_records_table.records,
             },
         )?;
@@ -161,13 +330,14 @@ impl LinearExecutableLayout {
         let mut module_directives_table = ModuleDirectivesTable::empty();
         if header.e32_dirtab != 0 {
             module_directives_table = ModuleDirectivesTable::read(
-                &mut reader,
+                reader,
                 &header,
                 base_offset
             )?;
         }
 
         Ok(Self {
+            dos_header,
             header,
             object_table,
             object_pages,
@@ -180,4 +350,611 @@ impl LinearExecutableLayout {
             non_resident_names
         })
     }
+    ///
+    /// Operating system module is linked for (`e32_os`)
+    ///
+    pub fn expected_os(&self) -> OS {
+        self.header.target_os()
+    }
+    ///
+    /// Entry point `e32_cs:e32_eip`. Libraries without initialization
+    /// routine and drivers have no entry object: it's `NotFound` error.
+    ///
+    pub fn entry_point(&self) -> Result<ObjectAddress, Error> {
+        self.object_address("Entry point", self.header.e32_cs, self.header.e32_eip)
+    }
+    ///
+    /// Initial stack `e32_ss:e32_esp`. Libraries use stack of the
+    /// caller and have no stack object: it's `NotFound` error.
+    ///
+    pub fn initial_stack(&self) -> Result<ObjectAddress, Error> {
+        self.object_address("Stack", self.header.e32_ss, self.header.e32_esp)
+    }
+    ///
+    /// Automatic data object (`e32_autodata`) with its 1-based number
+    ///
+    pub fn auto_data_object(&self) -> Result<(usize, &Object), Error> {
+        let number = self.header.e32_autodata;
+        let object = self.object("Automatic data", number)?;
+        Ok((number as usize, object))
+    }
+    pub fn heap_size(&self) -> u32 {
+        self.header.e32_heapsize
+    }
+    pub fn stack_size(&self) -> u32 {
+        self.header.e32_stacksize
+    }
+    ///
+    /// Per-instance pages of module data as `(preload, demand)` counts
+    /// (`e32_instpreload`, `e32_instdemand`). Libraries with instance
+    /// data get their own copy of these pages in every process.
+    ///
+    pub fn instance_pages(&self) -> (u32, u32) {
+        (self.header.e32_instpreload, self.header.e32_instdemand)
+    }
+    fn object_address(&self, what: &str, object: u32, offset: u32) -> Result<ObjectAddress, Error> {
+        let virtual_address = self.object(what, object)?.virtual_addr.wrapping_add(offset);
+        Ok(ObjectAddress {
+            object,
+            offset,
+            virtual_address,
+        })
+    }
+    ///
+    /// Object by 1-based `number` from the header field of `what`
+    ///
+    fn object(&self, what: &str, number: u32) -> Result<&Object, Error> {
+        if number == 0 {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} object is not set (0)", what),
+            ));
+        }
+        if number > self.header.e32_objcnt {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} object #{} is out of objects count {}",
+                    what, number, self.header.e32_objcnt
+                ),
+            ));
+        }
+        self.object_table.objects.get(number as usize - 1).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{} object #{} is missing in object table", what, number),
+            )
+        })
+    }
+    ///
+    /// Image of object `number` (1-based) made of its pages, read from
+    /// the same `reader` layout was read from. Image stops at the last
+    /// byte present in file: zero-filled and invalid pages between pages
+    /// of data are zeros, the rest up to `virtual_size` isn't allocated.
+    /// Iterated (packed) pages are not unpacked: `InvalidData`.
+    /// Pages out of file, pages larger than the whole file and objects
+    /// larger than 512MB of process address space are `InvalidData` too.
+    ///
+    pub fn read_object_data<T: Read + Seek>(
+        &self,
+        reader: &mut T,
+        number: u32,
+    ) -> Result<Vec<u8>, Error> {
+        let object = self.object("Requested", number)?;
+        let header = &self.header;
+        let page_size = header.e32_pagesize as u64;
+        let image_size = (object.map_size as u64 * page_size).max(object.virtual_size as u64);
+        if image_size > MAX_OBJECT_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Object #{} takes 0x{:X} bytes of memory", number, image_size),
+            ));
+        }
+        let file_len = reader.seek(SeekFrom::End(0))?;
+        let mut image = Vec::new();
+        // offset of the current page in image
+        let mut start = 0_u64;
+
+        for index in 0..object.map_size {
+            let page_number = object.map_index.saturating_add(index);
+            let page = page_number
+                .checked_sub(1)
+                .and_then(|i| self.object_pages.pages.get(i as usize))
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Page #{} of object #{} is out of page map", page_number, number),
+                    )
+                })?;
+            let (flags, location) = match page {
+                ObjectPage::LXPageFormat(p) => {
+                    let offset = (p.page_offset as u64)
+                        .checked_shl(header.page_shift())
+                        .unwrap_or(u64::MAX);
+                    (p.flags, (offset, p.data_size as u64))
+                }
+                ObjectPage::LEPageFormat(p) => {
+                    // high word of page number, then its low byte
+                    let high = u16::from_le_bytes([p.page_number[0], p.page_number[1]]) as u64;
+                    let file_page = ((high << 8) | p.page_number[2] as u64).saturating_sub(1);
+                    let len = match page_number == header.e32_mpages {
+                        true => header.bytes_on_last_page() as u64,
+                        false => page_size,
+                    };
+                    (p.flags as u16, (file_page * page_size, len))
+                }
+            };
+
+            match flags {
+                // legal physical page
+                0 => {
+                    let (offset, len) = location;
+                    let len = len.min(page_size);
+                    let at = self.data_pages_offset().saturating_add(offset);
+                    if at.saturating_add(len) > file_len {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "Page #{} at 0x{:X}..0x{:X} is out of {} bytes of file",
+                                page_number,
+                                at,
+                                at.saturating_add(len),
+                                file_len
+                            ),
+                        ));
+                    }
+                    if start > image.len() as u64 && page_size > file_len {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Pages of 0x{:X} bytes are larger than file of {} bytes", page_size, file_len),
+                        ));
+                    }
+                    reader.seek(SeekFrom::Start(at))?;
+                    image.resize(start as usize, 0);
+                    image.resize((start + len) as usize, 0);
+                    reader.read_exact(&mut image[start as usize..])?;
+                }
+                // invalid, zero-filled and range of pages have no data
+                2..=4 => {}
+                flags => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Page #{} has unsupported flags 0x{:X}", page_number, flags),
+                    ))
+                }
+            }
+            start += page_size;
+        }
+
+        image.truncate(object.virtual_size as usize);
+        Ok(image)
+    }
+    ///
+    /// Objects which hold resources with their 1-based numbers,
+    /// the same numbers the resource table and fixups are using.
+    ///
+    pub fn resource_objects(&self) -> Vec<(usize, &Object)> {
+        self.object_table
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.is_resource())
+            .map(|(i, object)| (i + 1, object))
+            .collect()
+    }
+    ///
+    /// Internal fixups of all pages with source objects and types.
+    /// Cross-object references are the ones which `source_object`
+    /// differs from `target_object`.
+    ///
+    pub fn internal_relocations(&self) -> InternalRelocations {
+        let owner = |page: u32| {
+            self.object_table
+                .iter()
+                .position(|o| page >= o.map_index && page - o.map_index < o.map_size)
+                .map(|i| i as u32 + 1)
+        };
+        let mut relocations = Vec::new();
+        for record in &self.fixup_records_table.records {
+            let FixupTarget::Internal(target) = &record.target_data else {
+                continue;
+            };
+            let offsets = match &record.source_offset_list {
+                Some(list) => list.clone(),
+                None => vec![record.source_offset_or_count],
+            };
+            let source_object = owner(record.page);
+            relocations.extend(offsets.into_iter().map(|offset| InternalRelocation {
+                source_page: record.page,
+                source_object,
+                source_offset: offset as i16,
+                source_type: record.source & 0x0F,
+                target_object: target.object_number,
+                target_offset: target.target_offset,
+                additive: record.additive_value,
+            }));
+        }
+
+        let preferred = self.object_table.iter().all(|o| o.virtual_addr != 0);
+        InternalRelocations {
+            rebase_only: self.header.internal_relocs_stripped() && preferred,
+            relocations,
+        }
+    }
+    ///
+    /// Fixups via entry table (target type `0x03`) translated through
+    /// entry table of module to `object:offset` or to the forwarder.
+    /// Entry numbers out of table are `Dangling`, not errors:
+    /// [LinearExecutableLayout::validate] warns about them.
+    ///
+    pub fn resolve_entry_fixups(&self) -> Vec<ResolvedEntryFixup> {
+        self.fixup_records_table
+            .iter()
+            .enumerate()
+            .filter_map(|(record, fixup)| match &fixup.target_data {
+                FixupTarget::FixupViaEntryTable(t) => Some(ResolvedEntryFixup {
+                    record,
+                    entry_number: t.entry_number,
+                    target: self.resolve_entry(t.entry_number),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+    fn resolve_entry(&self, ordinal: u16) -> ResolvedFixupTarget {
+        let Some((bundle, entry)) = self.entry_table.by_ordinal(ordinal) else {
+            return ResolvedFixupTarget::Dangling;
+        };
+        let object = bundle.object;
+        match entry {
+            Entry::Entry16(e) => ResolvedFixupTarget::Object { object, offset: e.offset as u32 },
+            Entry::Entry32(e) => ResolvedFixupTarget::Object { object, offset: e.offset },
+            Entry::EntryCallGate(e) => ResolvedFixupTarget::Object { object, offset: e.offset as u32 },
+            Entry::EntryForwarder(e) => ResolvedFixupTarget::Forwarder {
+                module_ordinal: e.module_ordinal,
+                module: (e.module_ordinal as usize)
+                    .checked_sub(1)
+                    .and_then(|i| self.import_table.modules().get(i))
+                    .cloned(),
+                by_ordinal: e.flags & 0x01 != 0,
+                offset_or_ordinal: e.offset_or_ordinal,
+            },
+            Entry::Unused => ResolvedFixupTarget::Dangling,
+        }
+    }
+    ///
+    /// Start of data pages section (`e32_datapage`). The pointer is
+    /// counted from the start of file even if module has DOS header.
+    ///
+    pub fn data_pages_offset(&self) -> u64 {
+        self.header.data_pages_offset(0)
+    }
+    ///
+    /// End of loader and fixup sections: the boundary between tables
+    /// loader keeps in memory and the rest of file (non-resident names,
+    /// data pages, debug information).
+    ///
+    pub fn loader_section_end(&self) -> u64 {
+        let header = &self.header;
+        let base = self.dos_header.map(|h| h.e_lfanew as u64).unwrap_or(0);
+        let loader_end = header.e32_objtab as u64 + header.e32_ldrsize as u64;
+        let fixup_end = match header.e32_fpagetab {
+            0 => 0,
+            fpagetab => fpagetab as u64 + header.e32_fixupsize as u64,
+        };
+        base + loader_end.max(fixup_end)
+    }
+    ///
+    /// How module was tuned for loading. Linker puts preload pages first:
+    /// pages `1..=e32_preload` of the page map are read when module
+    /// is loaded, others on the first access. Every object gets the
+    /// count of its pages in both sets and bytes of its preload pages.
+    ///
+    pub fn preload_summary(&self) -> PreloadSummary {
+        let preload = self.header.preload_page_count();
+        let objects: Vec<ObjectPreload> = self
+            .object_table
+            .iter()
+            .enumerate()
+            .map(|(i, object)| {
+                // pages out of page map are neither preloaded nor demanded
+                let map_end = self.object_pages.pages.len() as u32 + 1;
+                let end = object.map_index.checked_add(object.map_size).unwrap_or(map_end).min(map_end);
+                let pages = object.map_index.max(1)..end;
+                let preload_bytes = pages
+                    .clone()
+                    .filter(|number| *number <= preload)
+                    .filter_map(|number| {
+                        let page = self.object_pages.pages.get(number.checked_sub(1)? as usize)?;
+                        self.page_in_file(number, page)
+                    })
+                    .map(|(_, len)| len)
+                    .sum();
+                let preload_pages = pages.clone().filter(|number| *number <= preload).count() as u32;
+                ObjectPreload {
+                    object: i as u32 + 1,
+                    preload_pages,
+                    demand_pages: pages.len() as u32 - preload_pages,
+                    preload_bytes,
+                    marked_preload: object.is_preload(),
+                }
+            })
+            .collect();
+
+        PreloadSummary {
+            preload_pages: preload,
+            preload_bytes: objects.iter().map(|o| o.preload_bytes).sum(),
+            objects,
+        }
+    }
+    ///
+    /// Byte ranges of DOS part (if exists), LE/LX header, loader and
+    /// fixup section tables, object pages, names and debug information.
+    /// See [crate::regions] for details.
+    ///
+    pub fn regions(&self) -> Vec<Region> {
+        let mut list = match &self.dos_header {
+            Some(dos_header) => regions::dos_regions(dos_header),
+            None => Vec::new(),
+        };
+        let header = &self.header;
+        let base = self.dos_header.map(|h| h.e_lfanew as u64).unwrap_or(0);
+        let table = |ptr: u32, len: u64, name: &str| {
+            Region::new(base + ptr as u64, len, RegionKind::Table, name)
+        };
+
+        list.push(Region::new(base, 0xC4, RegionKind::Header, "LE/LX header"));
+        list.push(table(header.e32_objtab, header.e32_objcnt as u64 * 24, "Object table"));
+        let page_record = if header.is_lx() { 8 } else { 4 };
+        list.push(table(header.e32_objmap, header.e32_mpages as u64 * page_record, "Object page map"));
+        if header.e32_rsrccnt != 0 {
+            list.push(table(header.e32_rsrctab, header.e32_rsrccnt as u64 * 14, "Resource table"));
+        }
+        let resident_len = self
+            .resident_names
+            .entries
+            .iter()
+            .map(|e| 3 + e.name.len() as u64)
+            .sum::<u64>()
+            + 1;
+        list.push(table(header.e32_restab, resident_len, "Resident names table"));
+        list.push(table(header.e32_enttab, self.entry_table_len(), "Entry table"));
+        if header.e32_dirtab != 0 {
+            list.push(table(header.e32_dirtab, header.e32_dircnt as u64 * 8, "Module directives table"));
+        }
+        if header.e32_fpagetab != 0 {
+            list.push(table(header.e32_fpagetab, (header.e32_mpages as u64 + 1) * 4, "Fixup page table"));
+            list.push(table(
+                header.e32_frectab,
+                self.fixup_page_table.end_of_fixup_records as u64,
+                "Fixup record table",
+            ));
+        }
+        let modules_len = self
+            .import_table
+            .modules()
+            .iter()
+            .map(|m| 1 + m.len() as u64)
+            .sum::<u64>();
+        list.push(table(header.e32_impmod, modules_len, "Import module names table"));
+        // Procedure names are the last table of fixup section
+        let fixup_end = header.e32_fpagetab as u64 + header.e32_fixupsize as u64;
+        list.push(table(
+            header.e32_impproc,
+            fixup_end.saturating_sub(header.e32_impproc as u64),
+            "Import procedure names table",
+        ));
+        if header.e32_nrestab != 0 {
+            list.push(Region::new(
+                header.e32_nrestab as u64,
+                header.e32_cbnrestab as u64,
+                RegionKind::Table,
+                "Non-resident names table",
+            ));
+        }
+        if header.e32_debuginfo != 0 {
+            list.push(Region::new(
+                header.e32_debuginfo as u64,
+                header.e32_debuglen as u64,
+                RegionKind::Debug,
+                "Debug information",
+            ));
+        }
+        list.extend(self.page_regions());
+
+        regions::finish(list)
+    }
+    ///
+    /// Looks for structural anomalies: tables out of file or over each other,
+    /// names of missing entries, objects with pages out of object page map.
+    /// `file_len` is the length of the file layout was read from.
+    ///
+    pub fn validate(&self, file_len: u64) -> Vec<Anomaly> {
+        let mut anomalies: Vec<Anomaly> = self
+            .dos_header
+            .and_then(|h| anomaly::check_lfanew(h.e_lfanew))
+            .into_iter()
+            .collect();
+        anomalies.extend(anomaly::check_regions(&self.regions(), file_len));
+
+        let base = self.dos_header.map(|h| h.e_lfanew as u64).unwrap_or(0);
+        let resident = self.resident_names.entries.iter().map(|e| (&e.name, e.ordinal));
+        let non_resident = self.non_resident_names.entries.iter().map(|e| (&e.name, e.ordinal));
+        for (name, ordinal) in resident.chain(non_resident) {
+            if ordinal == 0 || self.has_entry(ordinal) {
+                continue;
+            }
+            anomalies.push(Anomaly::new(
+                Severity::Warning,
+                base + self.header.e32_enttab as u64,
+                format!(
+                    "Name {} refers to missing entry @{}",
+                    String::from_utf8_lossy(name.as_bytes()),
+                    ordinal
+                ),
+            ));
+        }
+
+        for fixup in self.resolve_entry_fixups() {
+            if fixup.target != ResolvedFixupTarget::Dangling {
+                continue;
+            }
+            anomalies.push(Anomaly::new(
+                Severity::Warning,
+                base + self.header.e32_frectab as u64,
+                format!(
+                    "Fixup record #{} refers to missing entry @{}",
+                    fixup.record, fixup.entry_number
+                ),
+            ));
+        }
+
+        for (i, object) in self.object_table.objects.iter().enumerate() {
+            let last_page = object.map_index as u64 + object.map_size as u64;
+            if object.map_size != 0 && last_page > self.header.e32_mpages as u64 + 1 {
+                anomalies.push(Anomaly::new(
+                    Severity::Error,
+                    base + self.header.e32_objtab as u64 + i as u64 * 24,
+                    format!(
+                        "Object #{} pages {}..{} exceed page count {}",
+                        i + 1,
+                        object.map_index,
+                        last_page - 1,
+                        self.header.e32_mpages
+                    ),
+                ));
+            }
+        }
+
+        anomalies
+    }
+    ///
+    /// Entry with `ordinal` exists and is not unused
+    ///
+    fn has_entry(&self, ordinal: u16) -> bool {
+        self.entry_table.bundles.iter().any(|b| {
+            b.bundle_type != BundleType::Unused
+                && ordinal >= b.first_ordinal
+                && ((ordinal - b.first_ordinal) as usize) < b.entries.len()
+        })
+    }
+    ///
+    /// Size of entry table in bytes counted as
+    /// [EntryTable::read] consumes it (with zero terminator).
+    ///
+    fn entry_table_len(&self) -> u64 {
+        let mut len = 1;
+        for bundle in &self.entry_table.bundles {
+            len += match bundle.bundle_type {
+                BundleType::Unused => 2,
+                _ => 4,
+            };
+            let entry_size = match bundle.bundle_type {
+                BundleType::Entry16 => 3,
+                BundleType::Entry286CallGate | BundleType::Entry32 => 5,
+                BundleType::Forwarder => 7,
+                _ => 0,
+            };
+            len += bundle.entries.len() as u64 * entry_size;
+        }
+        len
+    }
+    ///
+    /// Physical pages of objects. Pages without data in file
+    /// (zero-filled, invalid, range) are not regions.
+    ///
+    fn page_regions(&self) -> Vec<Region> {
+        let mut list = Vec::new();
+
+        for (i, page) in self.object_pages.pages.iter().enumerate() {
+            let number = i as u32 + 1;
+            let Some((start, len)) = self.page_in_file(number, page) else {
+                continue;
+            };
+            let object = self
+                .object_table
+                .objects
+                .iter()
+                .position(|o| number >= o.map_index && number < o.map_index.saturating_add(o.map_size))
+                .map(|o| format!("Object #{} ", o + 1))
+                .unwrap_or_default();
+            let name = format!("{}page #{}", object, number);
+            list.push(Region::new(start, len, RegionKind::Data, &name));
+        }
+        list
+    }
+    ///
+    /// File offset and length of page `number` (1-based) data.
+    /// Pages without data in file (zero-filled, invalid, range) are `None`.
+    ///
+    fn page_in_file(&self, number: u32, page: &ObjectPage) -> Option<(u64, u64)> {
+        let header = &self.header;
+        let (offset, len) = match page {
+            ObjectPage::LXPageFormat(p) => {
+                // 2 - invalid, 3 - zero filled, 4 - range of pages
+                if matches!(p.flags, 2..=4) {
+                    return None;
+                }
+                let offset = (p.page_offset as u64).checked_shl(header.page_shift())?;
+                (offset, p.data_size as u64)
+            }
+            ObjectPage::LEPageFormat(p) => {
+                // high word of page number, then its low byte
+                let high = u16::from_le_bytes([p.page_number[0], p.page_number[1]]) as u32;
+                let page_number = (high << 8) | p.page_number[2] as u32;
+                if page_number == 0 {
+                    return None;
+                }
+                let len = match number == header.e32_mpages {
+                    true => header.bytes_on_last_page(),
+                    false => header.e32_pagesize,
+                };
+                ((page_number as u64 - 1) * header.e32_pagesize as u64, len as u64)
+            }
+        };
+        Some((self.data_pages_offset() + offset, len))
+    }
+}
+///
+/// Short summary of the module: name, format and counts of parsed records.
+/// Use [LinearExecutableHeader] `Display` for header details.
+/// ```text
+/// LX module DOSCALLS
+///   CPU:          i386
+///   OS:           OS/2 2.x
+///   Objects:      3
+///   Pages:        12
+///   Entries:      140
+///   Names:        1 resident, 139 non-resident
+///   Modules:      2
+///   Imports:      25
+///   Fixups:       310
+/// ```
+///
+impl fmt::Display for LinearExecutableLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = if self.header.is_lx() { "LX" } else { "LE" };
+        let name = self
+            .resident_names
+            .iter()
+            .find(|e| e.ordinal == 0)
+            .map(|e| e.name.to_string_lossy())
+            .unwrap_or_default();
+        let entries: usize = self.entry_table.iter().map(|b| b.entries.len()).sum();
+
+        writeln!(f, "{} module {}", format, name)?;
+        writeln!(f, "  CPU:          {}", self.header.cpu())?;
+        writeln!(f, "  OS:           {}", self.header.target_os())?;
+        writeln!(f, "  Objects:      {}", self.object_table.len())?;
+        writeln!(f, "  Pages:        {}", self.object_pages.len())?;
+        writeln!(f, "  Entries:      {}", entries)?;
+        writeln!(
+            f,
+            "  Names:        {} resident, {} non-resident",
+            self.resident_names.len(),
+            self.non_resident_names.len()
+        )?;
+        writeln!(f, "  Modules:      {}", self.import_table.modules().len())?;
+        writeln!(f, "  Imports:      {}", self.import_table.imports().len())?;
+        write!(f, "  Fixups:       {}", self.fixup_records_table.len())
+    }
 }
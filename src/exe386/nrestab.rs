@@ -3,7 +3,7 @@ use crate::types::PascalString;
 use std::io;
 use std::io::Read;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NonResidentNameEntry {
     pub name: PascalString,
     pub ordinal: u16,
@@ -1,28 +1,51 @@
+//! This module represents structure and methods of object page table
+//! (object page map). It's the only representation of pages in crate.
+//!
+//! Every object of module takes `map_size` records of this table
+//! starting from `map_index` (1-based). Record format depends on magic:
+//! ```
+//! // LE record (4 bytes)            LX record (8 bytes)
+//! // +--------+--------+-------+    +-------------+-----------+-------+
+//! // | HIGH   | LOW    | FLAGS |    | PAGE_OFFSET | DATA_SIZE | FLAGS |
+//! // | 2 bytes| 1 byte | 1 byte|    | 4 bytes     | 2 bytes   | 2 bytes
+//! // +--------+--------+-------+    +-------------+-----------+-------+
+//! ```
+//! LE record holds 1-based page number in file: page data is at
+//! `e32_datapage + (number - 1) * e32_pagesize`.
+//! LX record holds offset of page data shifted by `e32_pageshift`:
+//! page data is at `e32_datapage + (page_offset << e32_pageshift)`
+//! and only `data_size` bytes are in file.
+//!
+//! LX records are read in byte order of module. LE modules are
+//! little-endian only, so LE records are kept as they are in file.
 use crate::exe386::header::{LE_CIGAM, LE_MAGIC, LX_CIGAM, LX_MAGIC};
+use crate::types::readable::Readable;
+use crate::types::{bounded_capacity, table_accessors, ByteOrder, FieldCursor};
 use bytemuck::{Pod, Zeroable};
 use std::io;
-use std::io::{Error, Read, Seek, SeekFrom};
+use std::io::{Error, Read, Seek, SeekFrom, Write};
 
 type U24 = [u8; 3];
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectPagesTable {
     pub pages: Vec<ObjectPage>,
 }
-#[derive(Debug)]
+table_accessors!(ObjectPagesTable, pages, ObjectPage);
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ObjectPage {
     LEPageFormat(LEObjectPageHeader),
     LXPageFormat(LXObjectPageHeader),
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 pub struct LEObjectPageHeader {
     pub page_number: U24,
     pub flags: u8,
 }
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Pod, Zeroable)]
 pub struct LXObjectPageHeader {
     pub page_offset: u32,
     pub data_size: u16,
@@ -35,39 +58,69 @@ pub struct LXObjectPageData {
     pub number: u32,
 }
 impl ObjectPagesTable {
+    ///
+    /// Reads `pages_count` (`e32_mpages`) records of object page map
+    /// by absolute `obj_map` offset. Reader position before the call
+    /// doesn't matter. Page shift isn't needed to read the records,
+    /// it's applied to LX page offsets by [LXObjectPageHeader::read_page_data].
+    ///
     pub fn read<T: Read + Seek>(
         reader: &mut T,
         obj_map: u64,
         pages_count: u32,
-        pages_shift: u32,
         magic: u16,
+        order: ByteOrder,
     ) -> io::Result<Self> {
-        let mut pages = Vec::<ObjectPage>::with_capacity(pages_count as usize);
+        let mut pages = Vec::<ObjectPage>::with_capacity(bounded_capacity(pages_count as usize));
         reader.seek(SeekFrom::Start(obj_map))?;
 
         if magic == LX_CIGAM || magic == LX_MAGIC {
-            Self::fill_lx_pages(reader, &mut pages, pages_shift)
-        };
+            Self::fill_lx_pages(reader, &mut pages, pages_count, order)?;
+        }
         if magic == LE_MAGIC || magic == LE_CIGAM {
-            Self::fill_le_pages(reader, &mut pages, pages_count)
-        };
+            Self::fill_le_pages(reader, &mut pages, pages_count)?;
+        }
 
         Ok(Self { pages })
     }
-    pub fn fill_lx_pages<T: Read>(reader: &mut T, pages: &mut Vec<ObjectPage>, pages_count: u32) {
+    ///
+    /// Writes the map: LX records in byte order of module,
+    /// LE records as they are in file
+    ///
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        for page in &self.pages {
+            match page {
+                ObjectPage::LEPageFormat(page) => w.write_all(bytemuck::bytes_of(page))?,
+                ObjectPage::LXPageFormat(page) => page.write(w, order)?,
+            }
+        }
+        Ok(())
+    }
+    pub fn fill_lx_pages<T: Read>(
+        reader: &mut T,
+        pages: &mut Vec<ObjectPage>,
+        pages_count: u32,
+        order: ByteOrder,
+    ) -> io::Result<()> {
         for _ in 0..pages_count {
-            let entry = LXObjectPageHeader::read(reader).unwrap();
+            let entry = LXObjectPageHeader::read(reader, order)?;
             pages.push(ObjectPage::LXPageFormat(entry));
         }
+        Ok(())
     }
-    pub fn fill_le_pages<T: Read>(reader: &mut T, pages: &mut Vec<ObjectPage>, pages_count: u32) {
+    pub fn fill_le_pages<T: Read>(
+        reader: &mut T,
+        pages: &mut Vec<ObjectPage>,
+        pages_count: u32,
+    ) -> io::Result<()> {
         for _ in 0..pages_count {
-            let entry: LEObjectPageHeader = LEObjectPageHeader::read(reader).unwrap();
+            let entry: LEObjectPageHeader = LEObjectPageHeader::read(reader)?;
             pages.push(ObjectPage::LEPageFormat(entry));
         }
+        Ok(())
     }
 }
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PageFlags {
     pub is_legal_physical: bool,
     pub is_iterated: bool,
@@ -92,13 +145,28 @@ impl LEObjectPageHeader {
         Ok(bytemuck::pod_read_unaligned(&buffer))
     }
 }
+impl Readable<LEObjectPageHeader> for LEObjectPageHeader {
+    fn read<R: Read>(r: &mut R) -> io::Result<LEObjectPageHeader> {
+        Self::read(r)
+    }
+}
 
 impl LXObjectPageHeader {
-    pub fn read<T: Read>(reader: &mut T) -> Result<Self, Error> {
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> Result<Self, Error> {
         let mut buffer = [0_u8; 8];
         reader.read_exact(&mut buffer)?;
 
-        Ok(bytemuck::pod_read_unaligned(&buffer))
+        let mut f = FieldCursor::new(&buffer, order);
+        Ok(Self {
+            page_offset: f.u32(),
+            data_size: f.u16(),
+            flags: f.u16(),
+        })
+    }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> io::Result<()> {
+        order.write_u32(w, self.page_offset)?;
+        order.write_u16(w, self.data_size)?;
+        order.write_u16(w, self.flags)
     }
     pub fn read_page_data<R: Read + Seek>(
         reader: &mut R,
@@ -117,7 +185,16 @@ impl LXObjectPageHeader {
         }
 
         // find real offset using page_shift
-        let actual_offset = data_pages_offset + ((page_entry.page_offset as u64) << page_shift);
+        let actual_offset = (page_entry.page_offset as u64)
+            .checked_shl(page_shift)
+            .filter(|offset| offset >> page_shift == page_entry.page_offset as u64)
+            .and_then(|offset| offset.checked_add(data_pages_offset))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Page offset 0x{:X} << {} is out of file", page_entry.page_offset, page_shift),
+                )
+            })?;
         reader.seek(SeekFrom::Start(actual_offset))?;
 
         let mut data = vec![0_u8; page_entry.data_size as usize];
@@ -130,3 +207,8 @@ impl LXObjectPageHeader {
         })
     }
 }
+impl Readable<LXObjectPageHeader> for LXObjectPageHeader {
+    fn read<R: Read>(r: &mut R) -> io::Result<LXObjectPageHeader> {
+        Self::read(r, ByteOrder::Little)
+    }
+}
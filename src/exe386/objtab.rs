@@ -4,11 +4,13 @@
 //! 
 //! Objects are unnamed and permissions of them `LNK386.EXE` puts in characteristics.
 //! Field which named `flags` stores characteristics for each object.
+use crate::types::readable::Readable;
+use crate::types::{table_accessors, ByteOrder, FieldCursor};
 use bytemuck::{Pod, Zeroable};
-use std::io::{Error, Read, Seek, SeekFrom};
+use std::io::{Error, Read, Seek, SeekFrom, Write};
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zeroable, Pod)]
 pub struct Object {
     pub virtual_size: u32,
     pub virtual_addr: u32,
@@ -18,6 +20,33 @@ pub struct Object {
     pub _reserved: u32,
 }
 impl Object {
+    pub fn read<T: Read>(reader: &mut T, order: ByteOrder) -> Result<Self, Error> {
+        let mut buf = [0; 24];
+        reader.read_exact(&mut buf)?;
+
+        let mut f = FieldCursor::new(&buf, order);
+        Ok(Self {
+            virtual_size: f.u32(),
+            virtual_addr: f.u32(),
+            flags: f.u32(),
+            map_index: f.u32(),
+            map_size: f.u32(),
+            _reserved: f.u32(),
+        })
+    }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> Result<(), Error> {
+        for value in [
+            self.virtual_size,
+            self.virtual_addr,
+            self.flags,
+            self.map_index,
+            self.map_size,
+            self._reserved,
+        ] {
+            order.write_u32(w, value)?;
+        }
+        Ok(())
+    }
     pub fn get_object_rights(&self) -> LXObjectRights {
         if self.virtual_size == 0 {
             return LXObjectRights::BSS;
@@ -30,6 +59,23 @@ impl Object {
             _ => LXObjectRights::RDATA,
         }
     }
+    ///
+    /// Object holds resources data (see [OBJ_RESOURCE])
+    ///
+    pub fn is_resource(&self) -> bool {
+        self.flags & OBJ_RESOURCE as u32 != 0
+    }
+    ///
+    /// Object is marked to be loaded with module (see [OBJ_HAS_PRELOAD])
+    ///
+    pub fn is_preload(&self) -> bool {
+        self.flags & OBJ_HAS_PRELOAD as u32 != 0
+    }
+}
+impl Readable<Object> for Object {
+    fn read<R: Read>(r: &mut R) -> Result<Object, Error> {
+        Self::read(r, ByteOrder::Little)
+    }
 }
 pub const OBJ_READABLE: u16 =        0x0001;
 pub const OBJ_WRITEABLE: u16 =       0x0002;
@@ -48,6 +94,7 @@ pub const OBJ_ALIAS_REQUIRED: u16 =  0x1000;
 pub const OBJ_BIG: u16 =             0x2000;
 pub const OBJ_CONFORMING: u16 =      0x4000;
 pub const OBJ_IOPL: u16 =            0x8000;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LXObjectRights {
     /// Rights of "code32" section
     ///  - READ
@@ -69,23 +116,49 @@ pub enum LXObjectRights {
     /// Non-readable object
     SETTER = 7,
 }
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectsTable {
     pub objects: Vec<Object>,
 }
+table_accessors!(ObjectsTable, objects, Object);
 impl ObjectsTable {
     pub fn read<T: Read + Seek>(
         reader: &mut T,
         objtab: u64,
         count: u32,
+        order: ByteOrder,
     ) -> Result<ObjectsTable, Error> {
         let mut objects = Vec::<Object>::new();
         reader.seek(SeekFrom::Start(objtab))?;
         for _ in 0..count {
-            let mut caught_obj = [0; 24];
-            reader.read_exact(&mut caught_obj)?;
-            objects.push(bytemuck::cast(caught_obj));
+            objects.push(Object::read(reader, order)?);
         }
 
         Ok(ObjectsTable { objects })
     }
+    pub fn write<W: Write>(&self, w: &mut W, order: ByteOrder) -> Result<(), Error> {
+        for object in &self.objects {
+            object.write(w, order)?;
+        }
+        Ok(())
+    }
+    ///
+    /// Lowest virtual address and the end of the highest object
+    /// (`virtual_addr + virtual_size`). Objects without virtual size are skipped.
+    /// Returns `None` if there are no such objects.
+    ///
+    pub fn virtual_memory_span(&self) -> Option<(u32, u32)> {
+        let sized = self.objects.iter().filter(|o| o.virtual_size > 0);
+        let start = sized.clone().map(|o| o.virtual_addr).min()?;
+        let end = sized
+            .map(|o| o.virtual_addr.saturating_add(o.virtual_size))
+            .max()?;
+        Some((start, end))
+    }
+    ///
+    /// Sum of virtual sizes of all objects
+    ///
+    pub fn total_virtual_size(&self) -> u64 {
+        self.objects.iter().map(|o| o.virtual_size as u64).sum()
+    }
 }
@@ -1,37 +1,20 @@
-//! This module represents API of resident names table
-use crate::types::PascalString;
-use std::io;
-use std::io::Read;
+//! This module represents API of resident names table.
+//! Table has the same records as resident names of NE modules
+//! (see [crate::exe286::resntab]), only the pointer to it differs:
+//! ```text
+//! NE: e_lfanew    + e_resn_tab
+//! LX: base_offset + e32_restab
+//! ```
+//! `base_offset` is `e_lfanew` of DOS header or `0` when module
+//! has no DOS compatibility (see [crate::exe386::LinearExecutableLayout]).
+//! Both `read` functions take the absolute file offset computed like that,
+//! and LX ordinals follow the byte order of module (`read_with_order`).
+use crate::exe286::resntab::ResidentNameTable;
 
-#[derive(Debug, Clone)]
-pub struct ResidentNameEntry {
-    pub name: PascalString,
-    pub ordinal: u16,
-}
+pub use crate::exe286::resntab::ResidentNameEntry;
 
-impl ResidentNameEntry {
-    pub fn read<TRead: Read>(r: &mut TRead) -> io::Result<Option<Self>> {
-        let len = {
-            let mut len = 0;
-            r.read_exact(std::slice::from_mut(&mut len))?;
-            len
-        };
-        if len == 0 {
-            return Ok(None);
-        }
-        let name = {
-            let mut name = vec![0; len as usize];
-            r.read_exact(name.as_mut_slice())?;
-            name
-        };
-        let index = {
-            let mut buf = [0; 2];
-            r.read_exact(&mut buf)?;
-            u16::from_le_bytes(buf)
-        };
-        Ok(Some(Self {
-            name: PascalString::new(len, name),
-            ordinal: index,
-        }))
-    }
-}
+///
+/// Resident names of linear executable. First name is the module name
+/// with ordinal `0`, others are exported entries kept in memory.
+///
+pub type LxResidentNameTable = ResidentNameTable;
@@ -14,13 +14,34 @@ use bytemuck::{Pod, Zeroable};
 #[repr(C, packed(1))]
 #[derive(Copy, Clone, PartialEq, Debug, Pod, Zeroable)]
 pub struct VxDHeader {
+    /// Offset of Windows resources (see [VxDRsrcHeader]) in the resource
+    /// object of module. Zero if driver has no resources.
     pub e32_win_rsrc_offset: u32,
+    /// Size of Windows resources. Zero if driver has no resources.
     pub e32_win_rsrc_size: u32,
     pub e32_device_id: u16,
     pub e32_ddk_major: u16,
     pub e32_ddk_minor: u16,
 }
 
+impl VxDHeader {
+    ///
+    /// Reads the header at `offset` of the first executable object data.
+    /// `None` if header doesn't fit in `data`.
+    ///
+    pub fn from_object_data(data: &[u8], offset: u32) -> Option<Self> {
+        let start = offset as usize;
+        let bytes = data.get(start..start.checked_add(size_of::<Self>())?)?;
+        bytemuck::try_from_bytes::<Self>(bytes).ok().copied()
+    }
+    ///
+    /// Driver has Windows resources (version information)
+    ///
+    pub fn has_resources(&self) -> bool {
+        self.e32_win_rsrc_offset != 0 && self.e32_win_rsrc_size != 0
+    }
+}
+
 ///
 /// This structure is a marker of Windows VMM virtual drivers
 /// Mostly embeds in VXD drivers built using
@@ -46,3 +67,14 @@ pub struct VxDRsrcHeader {
     // and embedded into Windows drivers)
     // pub rsrc_version_info: Win32VersionInfo
 }
+
+impl VxDRsrcHeader {
+    ///
+    /// Reads the resource header at the start of `data`.
+    /// `None` if `data` is too short.
+    ///
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let bytes = data.get(..size_of::<Self>())?;
+        bytemuck::try_from_bytes::<Self>(bytes).ok().copied()
+    }
+}
@@ -0,0 +1,159 @@
+//! This module represents W3 archives: `WIN386.EXE` of Windows 3.x
+//! and uncompressed `VMM32.VXD`. Archive is a DOS program which `e_lfanew`
+//! points to the W3 header instead of protected-mode module header.
+//! W3 header is a directory of virtual device drivers in LE format
+//! which are laying in the same file one by one.
+//! ```text
+//! +-------------------+ <-- e_lfanew
+//! | W3 header         | magic, version, count of modules
+//! +-------------------+
+//! | VMM     | offset  | 8-char name, LE header offset, module size
+//! | DOSMGR  | offset  |
+//! | ...     |         |
+//! +-------------------+
+//! | LE module #1      |
+//! +-------------------+
+//! | ...               |
+//! ```
+//! Embedded modules keep pages and non-resident names pointers
+//! as absolute file offsets in the archive, so they are read in place.
+//! ```rust,no_run
+//! use os2omf::exe386::w3::W3Archive;
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! let mut reader = BufReader::new(File::open("put here WIN386.EXE path")?);
+//! let archive = W3Archive::read(&mut reader)?;
+//!
+//! for (i, module) in archive.modules().iter().enumerate() {
+//!     let layout = archive.open_module(&mut reader, i)?;
+//!     println!("{} {}", module.name, layout.header.e32_objcnt);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! W4 archives (compressed `VMM32.VXD` of Windows 95/98) have another
+//! magic. Read them through [crate::exe386::w4::W4Reader].
+use crate::exe::MzHeader;
+use crate::exe386::LinearExecutableLayout;
+use std::io::{self, Read, Seek, SeekFrom};
+
+pub const W3_MAGIC: u16 = 0x3357;
+pub const W4_MAGIC: u16 = 0x3457;
+
+///
+/// Directory of W3 archive
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct W3Archive {
+    /// Windows version of archive (e.g. `0x030A` for Windows 3.1)
+    pub version: u16,
+    /// Absolute file pointer to W3 header
+    pub header_offset: u64,
+    modules: Vec<W3Module>,
+}
+///
+/// One directory record: embedded LE module
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct W3Module {
+    /// Module name (e.g. `VMM`), padded by spaces in file
+    pub name: String,
+    /// Absolute file pointer to LE header of module
+    pub header_offset: u32,
+    /// Size of module in bytes
+    pub size: u32,
+}
+
+impl W3Archive {
+    /// W3 header: magic, version, count of modules and 10 reserved bytes
+    const HEADER_SIZE: u64 = 16;
+    /// Directory record: 8-char name, offset and size
+    const RECORD_SIZE: usize = 16;
+
+    ///
+    /// Reads DOS header and W3 directory which `e_lfanew` points to.
+    /// Reader position before the call doesn't matter.
+    ///
+    pub fn read<T: Read + Seek>(reader: &mut T) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let dos_header = MzHeader::read(reader)?;
+        if !dos_header.has_valid_magic() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a valid DOS header",
+            ));
+        }
+        let header_offset = dos_header.e_lfanew as u64;
+        reader.seek(SeekFrom::Start(header_offset))?;
+
+        let mut header = [0_u8; Self::HEADER_SIZE as usize];
+        reader.read_exact(&mut header)?;
+        let magic = u16::from_le_bytes([header[0], header[1]]);
+        match magic {
+            W3_MAGIC => {}
+            W4_MAGIC => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "W4 archive is compressed, read it through W4Reader",
+                ));
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid W3 magic 0x{:04X}", magic),
+                ));
+            }
+        }
+        let version = u16::from_le_bytes([header[2], header[3]]);
+        let count = u16::from_le_bytes([header[4], header[5]]);
+
+        let mut modules = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let mut record = [0_u8; Self::RECORD_SIZE];
+            reader.read_exact(&mut record)?;
+
+            let name = String::from_utf8_lossy(&record[0..8])
+                .trim_end_matches([' ', '\0'])
+                .to_string();
+            modules.push(W3Module {
+                name,
+                header_offset: u32::from_le_bytes([record[8], record[9], record[10], record[11]]),
+                size: u32::from_le_bytes([record[12], record[13], record[14], record[15]]),
+            });
+        }
+
+        Ok(Self {
+            version,
+            header_offset,
+            modules,
+        })
+    }
+    pub fn modules(&self) -> &[W3Module] {
+        self.modules.as_slice()
+    }
+    ///
+    /// Module record by name. Names are compared ignoring case.
+    ///
+    pub fn find(&self, name: &str) -> Option<&W3Module> {
+        self.modules
+            .iter()
+            .find(|module| module.name.eq_ignore_ascii_case(name))
+    }
+    ///
+    /// Reads embedded module number `index` (from 0) from the same
+    /// reader the archive was read from.
+    ///
+    pub fn open_module<T: Read + Seek>(
+        &self,
+        reader: &mut T,
+        index: usize,
+    ) -> io::Result<LinearExecutableLayout> {
+        let module = self.modules.get(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Archive has no module #{}", index),
+            )
+        })?;
+        LinearExecutableLayout::read_at(reader, module.header_offset as u64)
+    }
+}
@@ -0,0 +1,361 @@
+//! This module represents W4 archives: compressed `VMM32.VXD` of Windows 95/98.
+//! W4 archive is a W3 archive (see [crate::exe386::w3]) which everything
+//! starting from `e_lfanew` is split into chunks and compressed.
+//! ```text
+//! +-------------------+ <-- e_lfanew
+//! | W4 header         | magic, version, chunk size, count of chunks, "DS"
+//! +-------------------+
+//! | chunk pointers    | absolute file pointer to every chunk
+//! +-------------------+
+//! | chunk #1          | W3 header and modules after decompression
+//! | ...               |
+//! ```
+//! [W4Reader] decompresses chunks on demand and looks like the original
+//! W3 archive file, so W3 directory and modules are read on top of it:
+//! ```rust,no_run
+//! use os2omf::exe386::w3::W3Archive;
+//! use os2omf::exe386::w4::W4Reader;
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! let file = BufReader::new(File::open("put here VMM32.VXD path")?);
+//! let mut reader = W4Reader::new(file)?;
+//! let archive = W3Archive::read(&mut reader)?;
+//!
+//! for module in archive.modules() {
+//!     println!("{}", module.name);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Chunks are compressed by DoubleSpace scheme (MRCI1), which is named
+//! by "DS" signature in the header. Chunk which takes the whole chunk size
+//! in file is stored as is. Broken chunk tables and compressed data
+//! are errors of the read, not panics.
+use crate::exe::MzHeader;
+use crate::exe386::w3::W4_MAGIC;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Signature of DoubleSpace compression method
+pub const DS_SIGNATURE: u16 = 0x5344;
+
+///
+/// W4 header and table of chunk pointers
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct W4Header {
+    pub version: u16,
+    /// Size of decompressed chunk. The last one may be shorter.
+    pub chunk_size: u16,
+    /// Absolute file pointers to compressed chunks
+    pub chunk_offsets: Vec<u32>,
+}
+
+impl W4Header {
+    ///
+    /// Reads W4 header at `offset` (`e_lfanew` of DOS header)
+    ///
+    pub fn read<T: Read + Seek>(reader: &mut T, offset: u64) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut buf = [0_u8; 16];
+        reader.read_exact(&mut buf)?;
+        let word = |pos: usize| u16::from_le_bytes([buf[pos], buf[pos + 1]]);
+
+        if word(0) != W4_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid W4 magic 0x{:04X}", word(0)),
+            ));
+        }
+        if word(8) != DS_SIGNATURE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown W4 compression method 0x{:04X}", word(8)),
+            ));
+        }
+        let chunk_size = word(4);
+        if chunk_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "W4 chunk size is zero",
+            ));
+        }
+
+        let mut chunk_offsets = Vec::with_capacity(word(6) as usize);
+        let mut pointer = [0_u8; 4];
+        for _ in 0..word(6) {
+            reader.read_exact(&mut pointer)?;
+            chunk_offsets.push(u32::from_le_bytes(pointer));
+        }
+
+        Ok(Self {
+            version: word(2),
+            chunk_size,
+            chunk_offsets,
+        })
+    }
+}
+
+///
+/// Decompressed view of W4 archive. Bytes before `e_lfanew`
+/// (DOS header and stub) are read as is, everything after them
+/// is the decompressed chunks.
+///
+pub struct W4Reader<T: Read + Seek> {
+    inner: T,
+    header: W4Header,
+    /// Start of chunks data in the decompressed view
+    base: u64,
+    file_len: u64,
+    len: u64,
+    position: u64,
+    cache: Option<(usize, Vec<u8>)>,
+}
+
+impl<T: Read + Seek> W4Reader<T> {
+    pub fn new(mut inner: T) -> io::Result<Self> {
+        inner.seek(SeekFrom::Start(0))?;
+        let dos_header = MzHeader::read(&mut inner)?;
+        if !dos_header.has_valid_magic() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a valid DOS header",
+            ));
+        }
+        let base = dos_header.e_lfanew as u64;
+        let header = W4Header::read(&mut inner, base)?;
+        let file_len = inner.seek(SeekFrom::End(0))?;
+
+        let mut reader = Self {
+            inner,
+            header,
+            base,
+            file_len,
+            len: base,
+            position: 0,
+            cache: None,
+        };
+        // only the last chunk knows the length of decompressed data
+        if let Some(last) = reader.header.chunk_offsets.len().checked_sub(1) {
+            let last_len = reader.chunk(last)?.len() as u64;
+            reader.len = base + last as u64 * reader.header.chunk_size as u64 + last_len;
+        }
+        Ok(reader)
+    }
+    pub fn header(&self) -> &W4Header {
+        &self.header
+    }
+    ///
+    /// Length of decompressed archive
+    ///
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn chunk(&mut self, index: usize) -> io::Result<&[u8]> {
+        if !matches!(&self.cache, Some((cached, _)) if *cached == index) {
+            let data = self.decompress(index)?;
+            self.cache = Some((index, data));
+        }
+        match &self.cache {
+            Some((_, data)) => Ok(data.as_slice()),
+            None => unreachable!(),
+        }
+    }
+
+    fn decompress(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        let offsets = &self.header.chunk_offsets;
+        let start = offsets[index] as u64;
+        let end = offsets
+            .get(index + 1)
+            .map_or(self.file_len, |next| *next as u64);
+        if start > end || end > self.file_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "W4 chunk #{} is out of file (0x{:X}..0x{:X})",
+                    index, start, end
+                ),
+            ));
+        }
+
+        let mut data = vec![0_u8; (end - start) as usize];
+        self.inner.seek(SeekFrom::Start(start))?;
+        self.inner.read_exact(&mut data)?;
+
+        let chunk_size = self.header.chunk_size as usize;
+        if data.len() >= chunk_size {
+            data.truncate(chunk_size);
+            return Ok(data);
+        }
+        decompress_chunk(&data, chunk_size)
+    }
+}
+
+impl<T: Read + Seek> Read for W4Reader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.position < self.base {
+            let count = buf.len().min((self.base - self.position) as usize);
+            self.inner.seek(SeekFrom::Start(self.position))?;
+            let count = self.inner.read(&mut buf[..count])?;
+            self.position += count as u64;
+            return Ok(count);
+        }
+
+        let chunk_size = self.header.chunk_size as u64;
+        let index = ((self.position - self.base) / chunk_size) as usize;
+        let skip = ((self.position - self.base) % chunk_size) as usize;
+        let chunk = self.chunk(index)?;
+        let available = chunk.get(skip..).unwrap_or_default();
+        if available.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("W4 chunk #{} is shorter than chunk size", index),
+            ));
+        }
+
+        let count = buf.len().min(available.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<T: Read + Seek> Seek for W4Reader<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Seek before the start of W4 archive",
+            )),
+        }
+    }
+}
+
+/// Offset of long back reference which marks end of block or data
+const DS_END_MARK: usize = 0x113F;
+/// Data is compressed by blocks (sectors) of this size
+const DS_BLOCK: usize = 512;
+
+///
+/// Decompresses DoubleSpace (MRCI1) compressed `data` up to `limit` bytes.
+///
+/// Data is a bit stream read from the lowest bit of every byte.
+/// Two bits select the token:
+///  - `01`, `10` - literal with 7 bits of the value and high bit `0` or `1`;
+///  - `00` - back reference with 6-bit offset;
+///  - `11` `0` - back reference with 8-bit offset plus 64;
+///  - `11` `1` - back reference with 12-bit offset plus 320.
+///
+/// Back reference offset is followed by the length: `n` zero bits, one bit
+/// and `n` bits of `value` make the length `2^n + 1 + value`.
+/// Offset `0x113F` ends every 512-byte block and the data.
+///
+pub fn decompress_chunk(data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    let mut bits = BitReader::new(data);
+    let mut out = Vec::with_capacity(limit);
+
+    while out.len() < limit {
+        let offset = match bits.read(2)? {
+            0 => bits.read(6)? as usize,
+            1 => {
+                out.push(bits.read(7)? as u8);
+                continue;
+            }
+            2 => {
+                out.push(bits.read(7)? as u8 | 0x80);
+                continue;
+            }
+            _ => match bits.read(1)? {
+                0 => bits.read(8)? as usize + 64,
+                _ => bits.read(12)? as usize + 320,
+            },
+        };
+        if offset == DS_END_MARK {
+            // end of block is followed by next block if there's data
+            if out.len() % DS_BLOCK == 0 && bits.has_bytes() {
+                continue;
+            }
+            break;
+        }
+
+        let mut n = 0;
+        while bits.read(1)? == 0 {
+            n += 1;
+            if n > 8 {
+                return Err(ds_error(bits.position(), "length is too long"));
+            }
+        }
+        let length = (1 << n) + 1 + bits.read(n)? as usize;
+
+        if offset == 0 || offset > out.len() {
+            return Err(ds_error(bits.position(), "reference is out of data"));
+        }
+        if out.len() + length > limit {
+            return Err(ds_error(bits.position(), "data is longer than chunk"));
+        }
+        let from = out.len() - offset;
+        // overlapped references are repeating bytes
+        for i in 0..length {
+            out.push(out[from + i]);
+        }
+    }
+
+    Ok(out)
+}
+
+fn ds_error(bit: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Compressed data at bit {}: {}", bit, message),
+    )
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit: 0 }
+    }
+    fn read(&mut self, count: u32) -> io::Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = self
+                .data
+                .get(self.bit / 8)
+                .ok_or_else(|| ds_error(self.bit, "unexpected end of data"))?;
+            value |= (((byte >> (self.bit % 8)) & 1) as u32) << i;
+            self.bit += 1;
+        }
+        Ok(value)
+    }
+    fn position(&self) -> usize {
+        self.bit
+    }
+    ///
+    /// Whole bytes are left after the current one
+    ///
+    fn has_bytes(&self) -> bool {
+        self.bit.div_ceil(8) < self.data.len()
+    }
+}
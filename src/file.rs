@@ -0,0 +1,117 @@
+//! This module represents an executable of any supported format.
+//! Use it when you don't know what is in the file: signature
+//! at `e_lfanew` decides which layout is read.
+//! ```rust,no_run
+//! use os2omf::file::ExecutableFile;
+//!
+//! let file = ExecutableFile::open("put here any exe or dll path")?;
+//! println!("{}", file);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! With `mmap` feature [ExecutableFile::open_mmap] maps the file
+//! instead of seeking in buffered file: reading is faster when many
+//! files are read one-by-one. Layouts own all their data, so nothing
+//! borrows the map after reading.
+use crate::anomaly::Anomaly;
+use crate::exe::{self, ExtensionType, MzExecutableLayout};
+use crate::exe286::NewExecutableLayout;
+use crate::exe386::LinearExecutableLayout;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+
+///
+/// Parsed executable module
+///
+#[derive(Debug, Clone)]
+pub enum ExecutableFile {
+    /// Plain DOS program or DOS program with unknown extension
+    Mz(MzExecutableLayout),
+    /// Segmented 16-bit New Executable
+    Ne(NewExecutableLayout),
+    /// Linear Executable (LE or LX), with or without DOS header
+    Lx(LinearExecutableLayout),
+}
+
+impl ExecutableFile {
+    ///
+    /// Reads the file by path. Files without DOS header
+    /// are read as linear executables.
+    ///
+    pub fn open(path: &str) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read_from(&mut reader)
+    }
+    ///
+    /// Maps the file by path into memory and reads it from the map.
+    /// Files which can't be mapped (like ones on network file systems)
+    /// are read through [BufReader] as [ExecutableFile::open] does.
+    /// File truncated by another process while it's read kills the
+    /// process with `SIGBUS` on Unix.
+    ///
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map lives only while the module is read and nothing
+        // is kept from it. If the file is changed meanwhile by another
+        // process, layout is read from changed bytes. If it is truncated,
+        // reading pages past the new end raises SIGBUS and kills the
+        // process: use `open` for files which may be cut while read.
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(map) => Self::read_from_slice(&map),
+            Err(_) => Self::read_from(&mut BufReader::new(file)),
+        }
+    }
+    ///
+    /// Reads the executable from the whole file image in memory
+    ///
+    pub fn read_from_slice(image: &[u8]) -> io::Result<Self> {
+        Self::read_from(&mut Cursor::new(image))
+    }
+    ///
+    /// Reads the executable from `reader`, which starts at the start of file
+    ///
+    pub fn read_from<T: Read + Seek>(reader: &mut T) -> io::Result<Self> {
+        let extension = match exe::extension_type_at(reader) {
+            Ok(extension) => extension,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                return LinearExecutableLayout::read_from(reader).map(ExecutableFile::Lx);
+            }
+            Err(e) => return Err(e),
+        };
+
+        match extension {
+            Some(ExtensionType::Ne) => NewExecutableLayout::read_from(reader).map(ExecutableFile::Ne),
+            Some(ExtensionType::Le | ExtensionType::Lx) => {
+                LinearExecutableLayout::read_from(reader).map(ExecutableFile::Lx)
+            }
+            Some(ExtensionType::UnknownExtended { .. }) | None => {
+                MzExecutableLayout::read_from(reader).map(ExecutableFile::Mz)
+            }
+        }
+    }
+    ///
+    /// Structural anomalies of the layout (see [crate::anomaly]).
+    /// DOS programs which `e_lfanew` points inside the header or past
+    /// the end of file are read as plain DOS programs and warned here.
+    ///
+    pub fn validate(&self, file_len: u64) -> Vec<Anomaly> {
+        match self {
+            ExecutableFile::Mz(layout) => layout.validate(file_len),
+            ExecutableFile::Ne(layout) => layout.validate(file_len),
+            ExecutableFile::Lx(layout) => layout.validate(file_len),
+        }
+    }
+}
+///
+/// Summary of the layout. DOS programs are printed by their header.
+///
+impl fmt::Display for ExecutableFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutableFile::Mz(layout) => write!(f, "{}", layout.header),
+            ExecutableFile::Ne(layout) => write!(f, "{}", layout),
+            ExecutableFile::Lx(layout) => write!(f, "{}", layout),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! This module answers quick questions about a module without
+//! reading the whole layout: only DOS header, NE/LE/LX header and
+//! the one table which holds the answer are read. Fixups, relocations,
+//! imports and segments are not touched.
+//! ```rust,no_run
+//! use os2omf::inspect;
+//!
+//! let path = "put here OS/2 or Windows module path";
+//! println!("{} module {:?} exports {} entries",
+//!     inspect::format(path)?,
+//!     inspect::module_name(path)?,
+//!     inspect::export_count(path)?);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Every function has a `read_*` twin which takes any `Read + Seek` source.
+use crate::exe::{extension_type_at, ExtensionType, MzHeader};
+use crate::exe286::header::NewExecutableHeader;
+use crate::exe286::resntab::ResidentNameTable;
+use crate::exe386::header::LinearExecutableHeader;
+use crate::exe386::resntab::LxResidentNameTable;
+use crate::{exe286, exe386};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+///
+/// Format of module: `"MZ"`, `"NE"`, `"LE"` or `"LX"`, or signature of
+/// known extension which is not read (like `"P3"` of PharLap programs)
+///
+pub fn format(path: &str) -> io::Result<String> {
+    read_format(&mut BufReader::new(File::open(path)?))
+}
+///
+/// Name of module (resident name with ordinal `0`).
+/// Plain DOS programs have no name: `None`.
+///
+pub fn module_name(path: &str) -> io::Result<Option<String>> {
+    read_module_name(&mut BufReader::new(File::open(path)?))
+}
+///
+/// Count of exported entries of entry table.
+/// Plain DOS programs export nothing: `0`.
+///
+pub fn export_count(path: &str) -> io::Result<usize> {
+    read_export_count(&mut BufReader::new(File::open(path)?))
+}
+
+pub fn read_format<T: Read + Seek>(reader: &mut T) -> io::Result<String> {
+    let name = match locate(reader)? {
+        (None, _) => "MZ",
+        (Some(ExtensionType::Ne), _) => "NE",
+        (Some(ExtensionType::Le), _) => "LE",
+        (Some(ExtensionType::Lx), _) => "LX",
+        (Some(ExtensionType::UnknownExtended { signature, .. }), _) => {
+            return Ok(String::from_utf8_lossy(&signature).into_owned());
+        }
+    };
+    Ok(name.to_string())
+}
+
+pub fn read_module_name<T: Read + Seek>(reader: &mut T) -> io::Result<Option<String>> {
+    let names = match locate(reader)? {
+        (None | Some(ExtensionType::UnknownExtended { .. }), _) => return Ok(None),
+        (Some(ExtensionType::Ne), offset) => {
+            let header = NewExecutableHeader::read(reader, offset as u32)?;
+            ResidentNameTable::read(reader, offset + header.e_resn_tab as u64)?
+        }
+        (Some(_), offset) => {
+            reader.seek(SeekFrom::Start(offset))?;
+            let header = LinearExecutableHeader::read(reader)?;
+            LxResidentNameTable::read_with_order(
+                reader,
+                offset + header.e32_restab as u64,
+                header.byte_order(),
+            )?
+        }
+    };
+    Ok(names
+        .entries
+        .iter()
+        .find(|e| e.ordinal == 0)
+        .map(|e| e.name.to_string_lossy()))
+}
+
+pub fn read_export_count<T: Read + Seek>(reader: &mut T) -> io::Result<usize> {
+    match locate(reader)? {
+        (None | Some(ExtensionType::UnknownExtended { .. }), _) => Ok(0),
+        (Some(ExtensionType::Ne), offset) => {
+            let header = NewExecutableHeader::read(reader, offset as u32)?;
+            let entries = exe286::enttab::EntryTable::read(
+                reader,
+                offset + header.e_ent_tab as u64,
+                header.e_cb_ent,
+            )?;
+            Ok(entries.export_count())
+        }
+        (Some(_), offset) => {
+            reader.seek(SeekFrom::Start(offset))?;
+            let header = LinearExecutableHeader::read(reader)?;
+            let entries = exe386::enttab::EntryTable::read(
+                reader,
+                offset + header.e32_enttab as u64,
+                header.byte_order(),
+            )?;
+            Ok(entries.export_count())
+        }
+    }
+}
+///
+/// Protected-mode header and its offset. LE/LX modules may have
+/// no DOS header: then LE/LX header is at the start of file.
+///
+fn locate<T: Read + Seek>(reader: &mut T) -> io::Result<(Option<ExtensionType>, u64)> {
+    reader.seek(SeekFrom::Start(0))?;
+    if let Ok(header) = MzHeader::read(reader) {
+        return Ok((extension_type_at(reader)?, header.e_lfanew as u64));
+    }
+
+    reader.seek(SeekFrom::Start(0))?;
+    let header = LinearExecutableHeader::read(reader).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidData, "Not MZ, NE, LE or LX module")
+    })?;
+    Ok((ExtensionType::from(header.e32_magic), 0))
+}
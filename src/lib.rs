@@ -12,64 +12,3927 @@
 //!  - `LE (mod exe386)` Microsoft OS/2 2.0+ and Windows 9x VxDs 16-32-bit
 //!  - `LX (mod exe386)` IBM OS/2 2.0-4.5 16-32-bit executables
 //!
+//! and object modules:
+//!  - `OMF (mod omf)` Intel/Microsoft/IBM 16-32-bit object modules (`.OBJ`)
+//!
 //!
 //! ### Issues
 //! List what has written here is temporary, I hope.
 //! I really want to fix all known problems and specially warn you about most serious of them.
 //! 
-//! - Crate works correctly only with `LittleEndian` linked files;
 //! - Some of the structures are undocumented;
 //! - No correct data-container for values (the worst for cross-platform compilation);
 //! - No support for VxD files yet (specific VxD structures);
-//! - No support for resources blocks. (can't read resource table yet)
+//! - No support for resources of LE/LX modules yet.
+//!
+//! ### Untrusted input
+//! Readers never panic on broken or hostile files: every inconsistency
+//! is an `io::Error` (mostly `InvalidData` or `UnexpectedEof`), and garbage
+//! counts from file fail on EOF before anything huge is allocated.
+//! Parsing modules deny `unwrap` and `expect`, and the rare exceptions
+//! are allowed one-by-one with the reason why they can't fail.
+//! Fuzz targets for MZ, NE and LE/LX readers are in `fuzz/`.
 //!
 
+/// Structural anomalies of executables
+pub mod anomaly;
+/// Module-definition files writers
+pub mod def;
+/// Human-readable dumps of executables
+pub mod dump;
 /// 16-bit DOS Executables
+#[deny(clippy::unwrap_used, clippy::expect_used)]
 pub mod exe;
 /// Segmented 16-bit New Executables 
+#[deny(clippy::unwrap_used, clippy::expect_used)]
 pub mod exe286;
 /// Microsoft-IBM 16-32-bit Linear Executables
+#[deny(clippy::unwrap_used, clippy::expect_used)]
 pub mod exe386;
+/// Executable of any supported format
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+pub mod file;
+/// Format, name and exports count of module without reading its layout
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+pub mod inspect;
+/// Linker-style map reports writers
+pub mod map;
+/// Object Module Format (.OBJ) records
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+pub mod omf;
+/// In-place patching of header fields
+pub mod patch;
+/// Byte ranges of structures in the file
+pub mod regions;
 /// Support of specific types
+#[deny(clippy::unwrap_used, clippy::expect_used)]
 pub mod types;
 
+///
+/// Layouts and tables own all parsed data and keep no reader,
+/// so they may be shared between threads. APIs which need the file
+/// again take the reader as a parameter. This doesn't compile if
+/// any of them gets `Rc`, `Cell` or a reader handle inside.
+///
+fn _assert_send_sync() {
+    fn assert<T: Send + Sync>() {}
+
+    assert::<file::ExecutableFile>();
+    assert::<exe::MzExecutableLayout>();
+    assert::<exe286::NewExecutableLayout>();
+    assert::<exe386::LinearExecutableLayout>();
+    assert::<exe386::w3::W3Archive>();
+    assert::<omf::OmfModule>();
+    assert::<omf::library::Library>();
+
+    assert::<exe::reltab::MzRelocationTable>();
+    assert::<exe286::enttab::EntryTable>();
+    assert::<exe286::modtab::ModuleReferencesTable>();
+    assert::<exe286::nrestab::NonResidentNameTable>();
+    assert::<exe286::resntab::ResidentNameTable>();
+    assert::<exe286::rsrctab::ResourceTable>();
+    assert::<exe286::segrelocs::RelocationTable>();
+    assert::<exe286::segtab::ImportsTable>();
+    assert::<exe286::segtab::Segment>();
+    assert::<exe386::dirtab::ModuleDirectivesTable>();
+    assert::<exe386::enttab::EntryTable>();
+    assert::<exe386::fpagetab::FixupPageTable>();
+    assert::<exe386::frectab::FixupRecordsTable>();
+    assert::<exe386::imptab::ImportRelocationsTable>();
+    assert::<exe386::objpagetab::ObjectPagesTable>();
+    assert::<exe386::objtab::ObjectsTable>();
+    assert::<exe386::resntab::LxResidentNameTable>();
+}
+
 #[cfg(test)]
 mod exe_386_tests {
     use crate::exe386;
+    use crate::exe386::ObjectAddress;
+    use crate::exe386::enttab::{BundleType, Entry};
+    use crate::exe386::fpagetab::FixupPageTable;
+    use crate::exe386::frectab::{
+        FixupRecord, FixupRecordsTable, FixupTarget, FixupTargetImportedName,
+        FixupTargetImportedOrdinal, FixupTargetInternal,
+    };
+    use crate::exe386::header::{LinearExecutableHeader, CPU, LE_MAGIC, LX_MAGIC, OS};
+    use crate::exe386::imptab::{ImportData, ImportRelocationsTable};
+    use crate::exe386::objpagetab::{LXObjectPageHeader, ObjectPage, ObjectPagesTable};
+    use crate::exe386::objtab::{Object, ObjectsTable};
+    use crate::exe386::resntab::{LxResidentNameTable, ResidentNameEntry};
+    use crate::exe386::builder::{ExportSpec, ImportSpec, LxBuilder, ObjectSpec};
+    use crate::omf::coment::ImportEntry;
+    use crate::anomaly::Severity;
+    use crate::types::{ByteOrder, PascalString};
+    use bytemuck::Zeroable;
+    use crate::{inspect, patch};
+    use std::io::{Cursor, ErrorKind, Seek, SeekFrom};
+
+    fn fixup(target_data: FixupTarget) -> FixupRecord {
+        FixupRecord {
+            page: 1,
+            source: 0x07,
+            target_flags: 0x00,
+            source_offset_or_count: 0,
+            target_data,
+            additive_value: None,
+            source_offset_list: None,
+        }
+    }
+
+    fn empty_layout() -> exe386::LinearExecutableLayout {
+        let no_records = vec![];
+        let import_table = ImportRelocationsTable::read(
+            &mut Cursor::new(vec![]),
+            ImportData { imp_mod_offset: 0, imp_mod_count: 0, imp_proc_offset: 0, imp_proc_size: 0, fixup_records: &no_records },
+        )
+        .unwrap();
+        exe386::LinearExecutableLayout {
+            dos_header: None,
+            header: LinearExecutableHeader::zeroed(),
+            object_table: ObjectsTable { objects: vec![] },
+            object_pages: ObjectPagesTable { pages: vec![] },
+            entry_table: exe386::enttab::EntryTable { bundles: vec![] },
+            fixup_page_table: FixupPageTable { page_offsets: vec![], end_of_fixup_records: 0 },
+            fixup_records_table: FixupRecordsTable { records: vec![] },
+            import_table,
+            module_directives_table: exe386::dirtab::ModuleDirectivesTable::empty(),
+            non_resident_names: crate::exe286::nrestab::NonResidentNameTable { entries: vec![] },
+            resident_names: crate::exe286::resntab::ResidentNameTable { entries: vec![] },
+        }
+    }
 
     #[test]
-    fn e386_header() {
-        let path = "D:\\TEST\\MS_OS220\\DOSCALL1.DLL";
-        let layout = exe386::LinearExecutableLayout::get(path);
+    fn e386_fixup_target_accessors() {
+        let by_ordinal = FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal { module_ordinal: 2, import_ordinal: 282 });
+        let by_name = FixupTarget::ImportedName(FixupTargetImportedName { module_ordinal: 1, procedure_name_offset: 0x10 });
+        let internal = FixupTarget::Internal(FixupTargetInternal { object_number: 1, target_offset: Some(0) });
+        let via_entry = FixupTarget::FixupViaEntryTable(exe386::frectab::FixupTargetEntryTable { entry_number: 1 });
 
-        match layout {
-            Ok(res) => assert!(true, "{:?}", res.header),
-            Err(e) => assert!(false, "{:?}", e),
+        assert_eq!((by_ordinal.module_ordinal(), by_ordinal.imported_ordinal(), by_ordinal.imported_name_offset()), (Some(2), Some(282), None));
+        assert_eq!((by_name.module_ordinal(), by_name.imported_ordinal(), by_name.imported_name_offset()), (Some(1), None, Some(0x10)));
+        assert!(by_ordinal.is_import() && by_name.is_import());
+        for target in [internal, via_entry] {
+            assert!(!target.is_import());
+            assert_eq!((target.module_ordinal(), target.imported_ordinal(), target.imported_name_offset()), (None, None, None));
         }
     }
 
     #[test]
-    fn e386_enttab() {
-        let path = "D:\\TEST\\MS_OS220\\DOSCALL1.DLL";
-        let layout = exe386::LinearExecutableLayout::get(path);
+    fn e386_internal_relocations() {
+        let object = |virtual_addr, map_index| Object { virtual_addr, map_index, map_size: 1, ..Object::zeroed() };
+        let mut layout = empty_layout();
+        layout.object_table.objects = vec![object(0x10000, 1), object(0x20000, 2)];
+        layout.fixup_records_table.records = vec![
+            // code of page 1 refers to data of object 2 twice
+            FixupRecord {
+                source_offset_list: Some(vec![0x10, 0xFFFE]),
+                additive_value: Some(4),
+                ..fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 2, target_offset: Some(0x20) }))
+            },
+            fixup(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal { module_ordinal: 1, import_ordinal: 1 })),
+            FixupRecord {
+                page: 3,
+                source: 0x02,
+                ..fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 1, target_offset: None }))
+            },
+        ];
 
-        match layout {
-            Ok(res) => {
-                assert!(true, "{:?}", res.entry_table.bundles);
+        let internal = layout.internal_relocations();
+        assert!(!internal.rebase_only);
+        assert_eq!(internal.relocations.len(), 3);
+        assert_eq!(internal.relocations[0], exe386::InternalRelocation {
+            source_page: 1,
+            source_object: Some(1),
+            source_offset: 0x10,
+            source_type: 0x07,
+            target_object: 2,
+            target_offset: Some(0x20),
+            additive: Some(4),
+        });
+        // source crosses the start of page
+        assert_eq!(internal.relocations[1].source_offset, -2);
+        // page 3 belongs to no object
+        assert_eq!((internal.relocations[2].source_object, internal.relocations[2].source_type), (None, 0x02));
+
+        layout.header.e32_mflags |= 0x10;
+        assert!(layout.internal_relocations().rebase_only);
+        layout.object_table.objects[1].virtual_addr = 0;
+        assert!(!layout.internal_relocations().rebase_only);
+    }
+
+    #[test]
+    fn e386_resolve_entry_fixups() {
+        use crate::exe386::enttab::{Entry, Entry16, EntryBundle, EntryForwarder};
+        use crate::exe386::frectab::FixupTargetEntryTable;
+        use crate::exe386::ResolvedFixupTarget;
+        let via_entry = |entry_number| fixup(FixupTarget::FixupViaEntryTable(FixupTargetEntryTable { entry_number }));
+        let records = vec![via_entry(1), via_entry(9)];
+        let mut layout = empty_layout();
+        layout.import_table = ImportRelocationsTable::read(
+            &mut Cursor::new(b"\0\x03DOS".to_vec()),
+            ImportData { imp_mod_offset: 1, imp_mod_count: 1, imp_proc_offset: 5, imp_proc_size: 0, fixup_records: &records },
+        )
+        .unwrap();
+        layout.entry_table.bundles = vec![
+            EntryBundle { first_ordinal: 1, count: 1, bundle_type: BundleType::Entry16, object: 2, entries: vec![Entry::Entry16(Entry16 { flags: 1, offset: 0x40 })] },
+            EntryBundle { first_ordinal: 2, count: 1, bundle_type: BundleType::Unused, object: 0, entries: vec![Entry::Unused] },
+            EntryBundle {
+                first_ordinal: 3,
+                count: 1,
+                bundle_type: BundleType::Forwarder,
+                object: 0,
+                entries: vec![Entry::EntryForwarder(EntryForwarder { flags: 1, module_ordinal: 1, offset_or_ordinal: 282 })],
             },
-            Err(e) => assert!(false, "{:?}", e),
+        ];
+        layout.fixup_records_table.records = vec![
+            via_entry(1),
+            fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 1, target_offset: Some(0) })),
+            via_entry(3),
+            via_entry(2),
+            via_entry(9),
+        ];
+
+        let resolved = layout.resolve_entry_fixups();
+        let targets: Vec<(usize, &ResolvedFixupTarget)> = resolved.iter().map(|f| (f.record, &f.target)).collect();
+        assert_eq!(targets, [
+            (0, &ResolvedFixupTarget::Object { object: 2, offset: 0x40 }),
+            (2, &ResolvedFixupTarget::Forwarder { module_ordinal: 1, module: Some(PascalString::new(3, b"DOS".to_vec())), by_ordinal: true, offset_or_ordinal: 282 }),
+            (3, &ResolvedFixupTarget::Dangling),
+            (4, &ResolvedFixupTarget::Dangling),
+        ]);
+
+        // dangling entries are warnings of validation
+        let warnings: Vec<String> = layout
+            .validate(0)
+            .into_iter()
+            .filter(|a| a.severity == Severity::Warning && a.message.starts_with("Fixup record"))
+            .map(|a| a.message)
+            .collect();
+        assert_eq!(warnings, ["Fixup record #3 refers to missing entry @2", "Fixup record #4 refers to missing entry @9"]);
+    }
+
+    #[test]
+    fn e386_resource_objects() {
+        let object = |flags| Object { flags, virtual_size: 0x1000, ..Object::zeroed() };
+        let mut layout = empty_layout();
+        layout.object_table.objects = vec![object(0x2005), object(0x2009), object(0x0003), object(0x0019)];
+
+        assert!(!layout.object_table[0].is_resource() && layout.object_table[1].is_resource());
+        let numbers: Vec<usize> = layout.resource_objects().iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, [2, 4]);
+        assert_eq!(layout.resource_objects()[1].1.flags, 0x0019);
+    }
+
+    #[test]
+    fn e386_vxd_headers() {
+        use crate::exe386::vxd::{VxDHeader, VxDRsrcHeader};
+
+        let mut data = vec![0xCC; 4];
+        data.extend_from_slice(&0x200_u32.to_le_bytes());
+        data.extend_from_slice(&0x80_u32.to_le_bytes());
+        data.extend_from_slice(&[0x34, 0x12, 0x04, 0x00, 0x0A, 0x00]);
+
+        let header = VxDHeader::from_object_data(&data, 4).unwrap();
+        assert_eq!({ header.e32_win_rsrc_offset }, 0x200);
+        assert_eq!({ header.e32_device_id }, 0x1234);
+        assert_eq!(({ header.e32_ddk_major }, { header.e32_ddk_minor }), (4, 10));
+        assert!(header.has_resources());
+        assert!(VxDHeader::from_object_data(&data, 5).is_none());
+        assert!(VxDHeader::from_object_data(&data, u32::MAX).is_none());
+
+        let rsrc = VxDRsrcHeader::from_bytes(&[0x10, 0xFF, 0x01, 0x00, 0x30, 0x00, 0x5C, 0x01, 0xAA]).unwrap();
+        assert_eq!((rsrc.rsrc_type, { rsrc.rsrc_ordinal }, { rsrc.rsrc_length }), (0x10, 1, 0x15C));
+        assert!(VxDRsrcHeader::from_bytes(&[0x10, 0xFF]).is_none());
+    }
+
+    #[test]
+    fn e386_w3_archive() {
+        use crate::exe386::w3::W3Archive;
+
+        // LE module with empty tables: entry and resident names tables
+        // are pointing to zero byte right after the header
+        let header_len = size_of::<LinearExecutableHeader>() as u32;
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LE_MAGIC;
+        header.e32_enttab = header_len;
+        header.e32_restab = header_len;
+        let mut module = bytemuck::bytes_of(&header).to_vec();
+        module.push(0);
+
+        let mut file = vec![0_u8; 0x40];
+        file[0..2].copy_from_slice(b"MZ");
+        file[0x3C..0x40].copy_from_slice(&0x40_u32.to_le_bytes());
+        file.extend_from_slice(b"W3");
+        file.extend_from_slice(&0x030A_u16.to_le_bytes());
+        file.extend_from_slice(&2_u16.to_le_bytes());
+        file.extend_from_slice(&[0; 10]);
+        let first = 0x40 + 16 + 2 * 16;
+        for (name, offset) in [(b"VMM     ", first), (b"DOSMGR  ", first + module.len())] {
+            file.extend_from_slice(name);
+            file.extend_from_slice(&(offset as u32).to_le_bytes());
+            file.extend_from_slice(&(module.len() as u32).to_le_bytes());
         }
+        file.extend_from_slice(&module);
+        file.extend_from_slice(&module);
+
+        let mut reader = Cursor::new(file);
+        let archive = W3Archive::read(&mut reader).unwrap();
+        assert_eq!((archive.version, archive.header_offset), (0x030A, 0x40));
+        assert_eq!(archive.modules().len(), 2);
+        assert_eq!(archive.modules()[1].name, "DOSMGR");
+        assert_eq!(archive.find("vmm").unwrap().header_offset, first as u32);
+
+        let layout = archive.open_module(&mut reader, 1).unwrap();
+        assert_eq!(layout.header.e32_magic, LE_MAGIC);
+        assert!(layout.dos_header.is_none() && layout.entry_table.is_empty());
+        assert!(archive.open_module(&mut reader, 2).is_err());
+
+        reader.get_mut()[0x40..0x42].copy_from_slice(b"W4");
+        assert!(W3Archive::read(&mut reader).is_err());
     }
+
     #[test]
-    fn e386_imports() {
-        let path = "D:\\TEST\\ARCA\\BDCALLS.DLL";
-        let layout = exe386::LinearExecutableLayout::get(path);
+    fn e386_read_from_cursor() {
+        use crate::file::ExecutableFile;
 
-        match layout {
-            Ok(res) => {
-                assert!(true, "{:?}", res.import_table.imports());
+        let header_len = size_of::<LinearExecutableHeader>() as u32;
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LX_MAGIC;
+        header.e32_enttab = header_len;
+        header.e32_restab = header_len;
+        let mut module = bytemuck::bytes_of(&header).to_vec();
+        module.push(0);
+
+        // without DOS header pointers are from the start of file
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&module)).unwrap();
+        assert!(layout.dos_header.is_none());
+        assert_eq!(layout.header.e32_magic, LX_MAGIC);
+
+        let mut file = vec![0_u8; 0x40];
+        file[0..2].copy_from_slice(b"MZ");
+        file[0x3C..0x40].copy_from_slice(&0x40_u32.to_le_bytes());
+        file.extend_from_slice(&module);
+        let mut reader = Cursor::new(file);
+        // position of reader doesn't matter
+        reader.seek(SeekFrom::End(0)).unwrap();
+        let layout = exe386::LinearExecutableLayout::read_from(&mut reader).unwrap();
+        assert_eq!(layout.dos_header.unwrap().e_lfanew, 0x40);
+
+        assert!(matches!(ExecutableFile::read_from(&mut reader).unwrap(), ExecutableFile::Lx(_)));
+        assert!(matches!(ExecutableFile::read_from(&mut Cursor::new(&module)).unwrap(), ExecutableFile::Lx(_)));
+    }
+
+    /// DoubleSpace bit stream: values are written from the lowest bit
+    fn ds_stream(fields: &[(u32, u32)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut bit = 0;
+        for &(value, count) in fields {
+            for i in 0..count {
+                if bit % 8 == 0 {
+                    bytes.push(0);
+                }
+                *bytes.last_mut().unwrap() |= (((value >> i) & 1) as u8) << (bit % 8);
+                bit += 1;
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn e386_w4_decompress() {
+        use crate::exe386::w4::decompress_chunk;
+
+        let end = [(3, 2), (1, 1), (0xFFF, 12)];
+        // "AB", then offset 2 length 6 (n = 2, value = 1), literal 0xC1, end
+        let mut fields = vec![(1, 2), (0x41, 7), (1, 2), (0x42, 7)];
+        fields.extend([(0, 2), (2, 6), (0, 1), (0, 1), (1, 1), (1, 2)]);
+        fields.extend([(2, 2), (0x41, 7)]);
+        fields.extend(end);
+        let data = ds_stream(&fields);
+
+        assert_eq!(decompress_chunk(&data, 0x100).unwrap(), b"ABABABAB\xC1");
+        // output is never longer than chunk
+        assert_eq!(decompress_chunk(&data, 2).unwrap(), b"AB");
+        assert!(decompress_chunk(&data, 5).is_err());
+        // reference before the data start and cut stream
+        assert!(decompress_chunk(&ds_stream(&[(0, 2), (1, 6), (1, 1)]), 0x100).is_err());
+        assert!(decompress_chunk(&data[..2], 0x100).is_err());
+    }
+
+    #[test]
+    fn e386_w4_reader() {
+        use crate::exe386::w3::W3Archive;
+        use crate::exe386::w4::W4Reader;
+        use std::io::Read;
+
+        let mut file = vec![0_u8; 0x40];
+        file[0..2].copy_from_slice(b"MZ");
+        file[0x3C..0x40].copy_from_slice(&0x40_u32.to_le_bytes());
+        file.extend_from_slice(b"W4");
+        file.extend_from_slice(&0x0400_u16.to_le_bytes());
+        file.extend_from_slice(&16_u16.to_le_bytes()); // chunk size
+        file.extend_from_slice(&2_u16.to_le_bytes()); // chunks
+        file.extend_from_slice(b"DS");
+        file.extend_from_slice(&[0; 6]);
+        let first = 0x40 + 16 + 8;
+        file.extend_from_slice(&(first as u32).to_le_bytes());
+        file.extend_from_slice(&(first as u32 + 16).to_le_bytes());
+        // stored chunk: W3 header without modules
+        file.extend_from_slice(b"W3\x00\x04\x00\x00");
+        file.extend_from_slice(&[0; 10]);
+        // compressed chunk: "xyxyx"
+        file.extend(ds_stream(&[(1, 2), (0x78, 7), (1, 2), (0x79, 7), (0, 2), (2, 6), (0, 1), (1, 1), (0, 1), (3, 2), (1, 1), (0xFFF, 12)]));
+
+        let mut reader = W4Reader::new(Cursor::new(file)).unwrap();
+        assert_eq!(reader.header().chunk_offsets.len(), 2);
+        assert_eq!(reader.len(), 0x40 + 16 + 5);
+
+        let archive = W3Archive::read(&mut reader).unwrap();
+        assert_eq!((archive.version, archive.modules().len()), (0x0400, 0));
+
+        let mut tail = Vec::new();
+        reader.seek(SeekFrom::End(-7)).unwrap();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, b"\0\0xyxyx");
+
+        // chunk pointer out of file
+        let mut broken = reader.into_inner().into_inner();
+        broken[0x54..0x58].copy_from_slice(&0x1000_u32.to_le_bytes());
+        assert!(W4Reader::new(Cursor::new(broken)).is_err());
+    }
+
+    #[test]
+    fn e386_virtual_memory_span() {
+        let object = |virtual_addr, virtual_size| Object { virtual_addr, virtual_size, ..Object::zeroed() };
+        let table = ObjectsTable {
+            objects: vec![object(0x20000, 0x800), object(0x10000, 0x1000), object(0x90000, 0)],
+        };
+        assert_eq!(table.virtual_memory_span(), Some((0x10000, 0x20800)));
+        assert_eq!(table.total_virtual_size(), 0x1800);
+
+        assert_eq!(ObjectsTable { objects: vec![] }.virtual_memory_span(), None);
+        assert_eq!(ObjectsTable { objects: vec![object(0x10000, 0)] }.virtual_memory_span(), None);
+    }
+
+    #[test]
+    fn e386_table_accessors() {
+        let object = |virtual_addr| Object { virtual_addr, ..Object::zeroed() };
+        let table = ObjectsTable {
+            objects: vec![object(0x10000), object(0x20000)],
+        };
+        assert_eq!((table.len(), table.is_empty()), (2, false));
+        assert_eq!(table[1].virtual_addr, 0x20000);
+        assert_eq!(table.get(0).map(|o| o.virtual_addr), Some(0x10000));
+        assert!(table.get(2).is_none());
+
+        let mut addresses = Vec::new();
+        for object in &table {
+            addresses.push(object.virtual_addr);
+        }
+        assert_eq!(addresses, table.iter().map(|o| o.virtual_addr).collect::<Vec<_>>());
+        assert_eq!(table.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn e386_entry_descriptions() {
+        use crate::exe386::enttab::{Entry16, Entry32, EntryCallGate};
+        let gate = EntryCallGate { flags: 0x03, offset: 0x1234, callgate_selector: 0x0F8F };
+        assert_eq!(gate.description(), "CallGate[selector=0x0F8F offset=0x1234 flags=0x03]");
+        assert_eq!(gate.flags_description(), "exported, single data");
+        assert_eq!(Entry16 { flags: 0x01, offset: 0 }.flags_description(), "exported");
+        assert_eq!(Entry32 { flags: 0x02, offset: 0 }.flags_description(), "single data");
+        assert_eq!(Entry32 { flags: 0, offset: 0 }.flags_description(), "");
+    }
+
+    #[test]
+    fn e386_forwarder_bundle() {
+        // one reserved word in bundle header, then 7-byte entries
+        let mut entries = vec![2, 4, 0, 0];
+        entries.extend_from_slice(&[0x01, 1, 0, 0x1A, 0x01, 0, 0]);
+        entries.extend_from_slice(&[0x00, 2, 0, 0x10, 0, 0, 0]);
+        entries.extend_from_slice(&[1, 3, 1, 0, 0x01, 0x20, 0, 0, 0]);
+        entries.push(0);
+        let table = exe386::enttab::EntryTable::read(&mut Cursor::new(&entries), 0, ByteOrder::Little).unwrap();
+
+        assert_eq!(table.bundle_count(), 2);
+        let forwarders: Vec<_> = table.bundles[0].entries.iter().map(|e| match e {
+            Entry::EntryForwarder(f) => (f.flags, f.module_ordinal, f.offset_or_ordinal),
+            e => panic!("{:?} is not a forwarder", e),
+        }).collect();
+        assert_eq!(forwarders, [(1, 1, 282), (0, 2, 0x10)]);
+        assert!(matches!(table.by_ordinal(3), Some((_, Entry::Entry32(e))) if e.offset == 0x20));
+
+        let mut written = Vec::new();
+        table.write(&mut written, ByteOrder::Little).unwrap();
+        assert_eq!(written, entries);
+    }
+
+    #[test]
+    fn e386_entry_statistics() {
+        let mut entries = vec![2, 3, 1, 0];
+        entries.extend_from_slice(&[0x01, 0x10, 0, 0, 0, 0x01, 0x20, 0, 0, 0]);
+        entries.extend_from_slice(&[3, 0]);
+        entries.extend_from_slice(&[1, 4, 0, 0, 0, 1, 0, 5, 0, 0, 0]);
+        entries.push(0);
+        let table = exe386::enttab::EntryTable::read(&mut Cursor::new(&entries), 0, ByteOrder::Little).unwrap();
+
+        assert_eq!(table.bundle_count(), 3);
+        assert!(table.has_forwarders());
+        let counts = table.entry_count_by_type();
+        assert_eq!(counts[&BundleType::Entry32], 2);
+        assert_eq!(counts[&BundleType::Unused], 3);
+        assert_eq!(counts[&BundleType::Forwarder], 1);
+        assert!(!counts.contains_key(&BundleType::Entry16));
+        assert_eq!(table.bundles[2].first_ordinal, 6);
+
+        let empty = exe386::enttab::EntryTable { bundles: vec![] };
+        assert!(!empty.has_forwarders() && empty.entry_count_by_type().is_empty());
+    }
+
+    #[test]
+    fn e386_header_format() {
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LX_MAGIC;
+        header.e32_pagesize = 4096;
+        header.e32_pageshift_or_lastpage = 2;
+        assert!(header.is_lx() && !header.is_le());
+        assert_eq!((header.page_shift(), header.bytes_on_last_page()), (2, 4096));
+
+        header.e32_magic = u16::from_le_bytes(*b"LE");
+        header.e32_pageshift_or_lastpage = 0x200;
+        assert_eq!(header.e32_magic, LE_MAGIC);
+        assert!(header.is_le() && !header.is_lx());
+        assert_eq!((header.page_shift(), header.bytes_on_last_page()), (0, 0x200));
+    }
+
+    #[test]
+    fn e386_header_display() {
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LX_MAGIC;
+        header.e32_cpu = 2;
+        header.e32_os = 1;
+        header.e32_mflags = 0x0000_8010;
+        header.e32_mpages = 12;
+        header.e32_pagesize = 4096;
+        header.e32_objcnt = 3;
+        header.e32_cs = 1;
+        header.e32_eip = 0x10;
+
+        let expected = "LX executable module\n  \
+                        CPU:          i386\n  \
+                        OS:           OS/2 2.x\n  \
+                        Module type:  DLL\n  \
+                        Pages:        12 (4096 bytes each)\n  \
+                        Objects:      3\n  \
+                        Entry point:  0001:00000010\n  \
+                        Flags:        internal fixups applied";
+        assert_eq!(header.to_string(), expected);
+    }
+
+    /// Raw header bytes as linker writes them
+    fn raw_header(magic: &[u8; 2], level: u32, cpu: u16, os: u16, version: u32) -> Vec<u8> {
+        let mut raw = vec![0_u8; 184];
+        raw[0..2].copy_from_slice(magic);
+        raw[4..8].copy_from_slice(&level.to_le_bytes());
+        raw[8..10].copy_from_slice(&cpu.to_le_bytes());
+        raw[10..12].copy_from_slice(&os.to_le_bytes());
+        raw[12..16].copy_from_slice(&version.to_le_bytes());
+        raw
+    }
+
+    #[test]
+    fn e386_cpu_and_os() {
+        // Windows 3.x VxD
+        let vxd = LinearExecutableHeader::read(&mut Cursor::new(raw_header(b"LE", 0, 2, 4, 0))).unwrap();
+        assert_eq!((vxd.cpu(), vxd.target_os()), (CPU::I386, OS::Windows386));
+        assert_eq!(vxd.format_level(), 0);
+
+        // OS/2 2.x DLL with /VERSION:1.2 (major in high word)
+        let dll = LinearExecutableHeader::read(&mut Cursor::new(raw_header(b"LX", 0, 3, 1, 0x0001_0002))).unwrap();
+        assert_eq!((dll.cpu(), dll.target_os()), (CPU::I486, OS::Os2v2));
+        assert_eq!(dll.module_version(), 0x0001_0002);
+
+        let mut header = LinearExecutableHeader::zeroed();
+        for (e32_cpu, cpu) in [(4, CPU::Pentium), (0x21, CPU::I860N11), (0x42, CPU::MipsMarkIII), (0x99, CPU::Unknown(0x99))] {
+            header.e32_cpu = e32_cpu;
+            assert_eq!(header.cpu(), cpu);
+        }
+        header.e32_os = 9;
+        assert_eq!(header.target_os(), OS::Unknown(9));
+        assert_eq!(header.cpu().to_string(), "unknown (0x0099)");
+
+        let odd = LinearExecutableHeader::read(&mut Cursor::new(raw_header(b"LX", 1, 2, 1, 0))).unwrap();
+        assert_eq!(odd.format_level(), 1);
+        assert!(odd.to_string().contains("Level:        1 (not loadable)"));
+    }
+
+    #[test]
+    fn e386_big_endian() {
+        let mut raw = raw_header(b"LX", 0, 0, 0, 0);
+        raw[2] = 1;
+        raw[3] = 1;
+        raw[8..10].copy_from_slice(&2_u16.to_be_bytes());
+        raw[10..12].copy_from_slice(&1_u16.to_be_bytes());
+        raw[0x28..0x2C].copy_from_slice(&4096_u32.to_be_bytes()); // e32_pagesize
+        raw[0x40..0x44].copy_from_slice(&0x00C4_u32.to_be_bytes()); // e32_objtab
+        let header = LinearExecutableHeader::read(&mut Cursor::new(&raw)).unwrap();
+        assert_eq!(header.byte_order(), ByteOrder::Big);
+        assert_eq!((header.cpu(), header.target_os()), (CPU::I386, OS::Os2v2));
+        assert_eq!((header.e32_pagesize, header.e32_objtab), (4096, 0xC4));
+        assert!(header.is_lx());
+
+        // mixed byte and word order
+        raw[3] = 0;
+        assert!(LinearExecutableHeader::read(&mut Cursor::new(&raw)).is_err());
+
+        let object: Vec<u8> = [0x1000_u32, 0x10000, 0x2005, 1, 1, 0].iter().flat_map(|d| d.to_be_bytes()).collect();
+        let object = Object::read(&mut Cursor::new(&object), ByteOrder::Big).unwrap();
+        assert_eq!((object.virtual_size, object.virtual_addr, object.flags), (0x1000, 0x10000, 0x2005));
+
+        let page = [0, 0, 0, 2, 0x10, 0, 0, 1];
+        let page = LXObjectPageHeader::read(&mut Cursor::new(&page), ByteOrder::Big).unwrap();
+        assert_eq!((page.page_offset, page.data_size, page.flags), (2, 0x1000, 1));
+
+        // bundle of one 32-bit entry in object #1
+        let entries = [1, 3, 0, 1, 0x01, 0, 0, 0x12, 0x34, 0];
+        let table = exe386::enttab::EntryTable::read(&mut Cursor::new(&entries), 0, ByteOrder::Big).unwrap();
+        assert_eq!(table.bundles[0].object, 1);
+        assert!(matches!(table.bundles[0].entries[0], exe386::enttab::Entry::Entry32(e) if e.offset == 0x1234));
+
+        // internal 32-bit offset fixup at 0x0010 to object #2 offset 0x00000100
+        let mut file = Vec::new();
+        file.extend_from_slice(&[0x07, 0x10, 0x00, 0x10, 0x02, 0x00, 0x00, 0x01, 0x00]);
+        let pages = FixupPageTable { page_offsets: vec![0], end_of_fixup_records: file.len() as u32 };
+        let records = FixupRecordsTable::read(&mut Cursor::new(&file), &pages, 0, ByteOrder::Big).unwrap();
+        assert_eq!(records.records[0].source_offset_or_count, 0x0010);
+        assert!(matches!(
+            &records.records[0].target_data,
+            FixupTarget::Internal(t) if t.object_number == 2 && t.target_offset == Some(0x100)
+        ));
+    }
+
+    #[test]
+    fn e386_fixup_page_ranges() {
+        let table = FixupPageTable {
+            page_offsets: vec![0, 0, 0x10],
+            end_of_fixup_records: 0x18,
+        };
+
+        assert_eq!(table.total_pages(), 3);
+        assert_eq!(table.page_range(0), Some((0, 0)));
+        assert_eq!(table.page_range(1), Some((0, 0x10)));
+        assert_eq!(table.page_range(2), Some((0x10, 0x18)));
+        assert_eq!(table.page_range(3), None);
+        assert!(!table.has_fixups_for_page(0));
+        assert!(table.has_fixups_for_page(1));
+        assert!(table.has_fixups_for_page(2));
+        assert!(!table.has_fixups_for_page(3));
+    }
+
+    #[test]
+    fn e386_selector_fixups() {
+        // selector of object #2 at 0x0010, offset16 of 3:0x0040 at 0x0020
+        let file = [0x02, 0x00, 0x10, 0x00, 0x02, 0x05, 0x00, 0x20, 0x00, 0x03, 0x40, 0x00];
+        let pages = FixupPageTable { page_offsets: vec![0], end_of_fixup_records: file.len() as u32 };
+        let table = FixupRecordsTable::read(&mut Cursor::new(&file), &pages, 0, ByteOrder::Little).unwrap();
+
+        let targets: Vec<&FixupTargetInternal> = table
+            .iter()
+            .filter_map(|record| match &record.target_data {
+                FixupTarget::Internal(target) => Some(target),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(targets.len(), 2);
+        assert!(targets[0].is_selector_only());
+        assert_eq!(targets[0].target_object_number(), 2);
+        assert!(!targets[1].is_selector_only());
+        assert_eq!((targets[1].target_object_number(), targets[1].target_offset), (3, Some(0x40)));
+    }
+
+    #[test]
+    fn e386_apply_internal_fixups() {
+        let internal = |object_number, target_offset| {
+            FixupTarget::Internal(FixupTargetInternal {
+                object_number,
+                target_offset: Some(target_offset),
+            })
+        };
+        let mut offset32 = fixup(internal(2, 0x10));
+        offset32.page = 2;
+        offset32.source_offset_or_count = 0x04;
+        let mut relative = fixup(internal(1, 0x00));
+        relative.source = 0x28;
+        relative.source_offset_or_count = 2;
+        relative.source_offset_list = Some(vec![0x08, 0x0C]);
+        let mut foreign = fixup(internal(1, 0x00));
+        foreign.page = 3;
+
+        let table = FixupRecordsTable {
+            records: vec![offset32, relative, foreign],
+        };
+        let object = Object {
+            virtual_size: 0x20,
+            virtual_addr: 0x10000,
+            flags: 0x2005,
+            map_index: 1,
+            map_size: 2,
+            _reserved: 0,
+        };
+        let mut data = vec![0_u8; 0x20];
+
+        table
+            .apply_internal_fixups(&mut data, 1, &object, &[0x10000, 0x20000], 0x10)
+            .unwrap();
+
+        assert_eq!(&data[0x14..0x18], &0x20010_u32.to_le_bytes());
+        assert_eq!(&data[0x08..0x0C], &(0x10000_u32.wrapping_sub(0x1000C)).to_le_bytes());
+        assert_eq!(&data[0x0C..0x10], &(0x10000_u32.wrapping_sub(0x10010)).to_le_bytes());
+        assert_eq!(&data[0x00..0x04], &[0; 4]);
+    }
+
+    #[test]
+    fn e386_object_pages_seek() {
+        let file = vec![0xEE, 0xEE, 0xEE, 0xEE, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00];
+        let mut reader = Cursor::new(file);
+        reader.seek(SeekFrom::End(0)).unwrap();
+
+        let table = ObjectPagesTable::read(&mut reader, 4, 2, LE_MAGIC, ByteOrder::Little).unwrap();
+
+        assert_eq!(table.pages.len(), 2);
+        match &table.pages[1] {
+            ObjectPage::LEPageFormat(page) => assert_eq!(page.page_number, [0x00, 0x00, 0x02]),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn e386_lx_object_pages_count() {
+        // e32_mpages = 2 records of LX map
+        let mut file = vec![0xEE; 4];
+        for (offset, size) in [(1_u32, 0x200_u16), (2, 0x10)] {
+            file.extend_from_slice(&offset.to_le_bytes());
+            file.extend_from_slice(&size.to_le_bytes());
+            file.extend_from_slice(&0_u16.to_le_bytes());
+        }
+        let mut reader = Cursor::new(file);
+
+        let table = ObjectPagesTable::read(&mut reader, 4, 2, LX_MAGIC, ByteOrder::Little).unwrap();
+        assert_eq!(table.pages.len(), 2);
+        assert_eq!(table.pages[1], ObjectPage::LXPageFormat(LXObjectPageHeader { page_offset: 2, data_size: 0x10, flags: 0 }));
+
+        // truncated map is an error, not a panic
+        assert!(ObjectPagesTable::read(&mut reader, 4, 3, LX_MAGIC, ByteOrder::Little).is_err());
+    }
+
+    #[test]
+    fn e386_dump() {
+        use crate::dump;
+        use crate::exe286::nrestab::NonResidentNameTable;
+        use crate::exe286::resntab::{ResidentNameEntry, ResidentNameTable};
+        use crate::exe386::dirtab::ModuleDirectivesTable;
+        use crate::exe386::enttab::{Entry, Entry32, EntryBundle, EntryForwarder, EntryTable};
+        use crate::file::ExecutableFile;
+        use crate::types::PascalString;
+
+        let records = vec![
+            FixupRecord {
+                source_offset_or_count: 0x10,
+                additive_value: Some(4),
+                ..fixup(FixupTarget::Internal(FixupTargetInternal {
+                    object_number: 1,
+                    target_offset: Some(0x100),
+                }))
             },
-            Err(e) => assert!(false, "{:?}", e),
+            FixupRecord {
+                source_offset_list: Some(vec![0x20, 0x30]),
+                ..fixup(FixupTarget::ImportedName(FixupTargetImportedName {
+                    module_ordinal: 1,
+                    procedure_name_offset: 0,
+                }))
+            },
+        ];
+        let import_table = ImportRelocationsTable::read(
+            &mut Cursor::new(b"\0\x03DOS\x04Open".to_vec()),
+            ImportData {
+                imp_mod_offset: 1,
+                imp_mod_count: 1,
+                imp_proc_offset: 5,
+                imp_proc_size: 5,
+                fixup_records: &records,
+            },
+        )
+        .unwrap();
+
+        let mut header = LinearExecutableHeader::zeroed();
+        header.e32_magic = LX_MAGIC;
+        let layout = exe386::LinearExecutableLayout {
+            dos_header: None,
+            header,
+            object_table: ObjectsTable {
+                objects: vec![Object {
+                    virtual_addr: 0x10000,
+                    virtual_size: 0x1000,
+                    flags: 0x2005,
+                    map_size: 1,
+                    ..Object::zeroed()
+                }],
+            },
+            object_pages: ObjectPagesTable { pages: vec![] },
+            entry_table: EntryTable {
+                bundles: vec![
+                    EntryBundle {
+                        first_ordinal: 1,
+                        count: 1,
+                        bundle_type: BundleType::Entry32,
+                        object: 1,
+                        entries: vec![Entry::Entry32(Entry32 { flags: 1, offset: 0x10 })],
+                    },
+                    EntryBundle {
+                        first_ordinal: 2,
+                        count: 1,
+                        bundle_type: BundleType::Forwarder,
+                        object: 0,
+                        entries: vec![Entry::EntryForwarder(EntryForwarder {
+                            flags: 1,
+                            module_ordinal: 1,
+                            offset_or_ordinal: 12,
+                        })],
+                    },
+                ],
+            },
+            fixup_page_table: FixupPageTable {
+                page_offsets: vec![],
+                end_of_fixup_records: 0,
+            },
+            fixup_records_table: FixupRecordsTable { records: records.clone() },
+            import_table,
+            module_directives_table: ModuleDirectivesTable::empty(),
+            non_resident_names: NonResidentNameTable { entries: vec![] },
+            resident_names: ResidentNameTable {
+                entries: vec![ResidentNameEntry {
+                    name: PascalString::new(4, b"INIT".to_vec()),
+                    ordinal: 1,
+                }],
+            },
+        };
+
+        let file = ExecutableFile::Lx(layout);
+        let mut out = Vec::new();
+        dump::write_objects(&file, &mut out).unwrap();
+        dump::write_exports(&file, &mut out).unwrap();
+        dump::write_imports(&file, &mut out).unwrap();
+        dump::write_fixups(&file, &mut out).unwrap();
+        dump::write_resources(&file, &mut out).unwrap();
+
+        let expected = "\
+Objects
+ no. base     size     pages flags
+   1 00010000 00001000 1     readable, executable, 32-bit
+Exports
+ ord obj  offset   name
+   1 0001 00000010 INIT
+   2 fwd  DOS.12
+Imports
+ module procedure references
+ DOS    Open      1
+Fixups
+ page offset type     target
+    1 0010   offset32 0001:00000100 + 0x4
+    1 0020   offset32 DOS.Open
+    1 0030   offset32 DOS.Open
+Resources
+ not supported for linear executables
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn e386_import_module_names_shared() {
+        // 100k fixups of one module hold one name, not 100k copies
+        let file = b"\0\x08DOSCALLS".to_vec();
+        let records: Vec<FixupRecord> = (0..100_000)
+            .map(|i| fixup(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal { module_ordinal: 1, import_ordinal: i % 300 + 1 })))
+            .collect();
+        let table = ImportRelocationsTable::read(
+            &mut Cursor::new(file),
+            ImportData { imp_mod_offset: 1, imp_mod_count: 1, imp_proc_offset: 10, imp_proc_size: 0, fixup_records: &records },
+        )
+        .unwrap();
+
+        // characters of the name are where the module table keeps them
+        let shared = table.modules()[0].as_bytes();
+        assert!(table.imports().iter().all(|i| std::ptr::eq(i.module_name().as_bytes(), shared)));
+        assert_eq!(table.imports()[99_999].module_name().to_string(), "DOSCALLS");
+        assert_eq!(table.modules()[0].to_string(), "DOSCALLS");
+    }
+
+    #[test]
+    fn e386_import_modules() {
+        // padding, 2 module names, and procedure names right after them
+        let file = b"\0\x03DOS\x03PMW\x04Open".to_vec();
+        let by_name = || {
+            fixup(FixupTarget::ImportedName(FixupTargetImportedName {
+                module_ordinal: 1,
+                procedure_name_offset: 0,
+            }))
+        };
+        let records = vec![
+            by_name(),
+            fixup(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal {
+                module_ordinal: 2,
+                import_ordinal: 7,
+            })),
+            by_name(),
+        ];
+
+        let table = ImportRelocationsTable::read(
+            &mut Cursor::new(file),
+            ImportData {
+                imp_mod_offset: 1,
+                imp_mod_count: 2,
+                imp_proc_offset: 9,
+                imp_proc_size: 5,
+                fixup_records: &records,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(table.modules().len(), 2);
+        assert_eq!(table.modules()[1].to_string(), "PMW");
+        assert_eq!(table.imports().len(), 3);
+        // name table is kept: names are found without fixups, and not past its end
+        assert_eq!(table.procedure_name(0).unwrap().to_string(), "Open");
+        assert_eq!(table.procedure_name(1), None);
+        assert_eq!(table.procedure_name(5), None);
+
+        let unique = table.unique_imports();
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].fixup_count, 2);
+        assert_eq!(unique[1].fixup_count, 1);
+        assert_eq!(table.deduplicated_imports().len(), 2);
+        assert_eq!(table.imports()[0], table.imports()[2]);
+        assert_ne!(table.imports()[0], table.imports()[1]);
+
+        let (open, ordinal) = (&table.imports()[0], &table.imports()[1]);
+        assert!(open.is_by_name() && !open.is_by_ordinal());
+        assert_eq!(open.import_name().unwrap().to_string(), "Open");
+        assert_eq!(open.import_ordinal(), None);
+        assert_eq!(open.display_symbol(), "Open");
+        assert!(ordinal.is_by_ordinal() && ordinal.import_name().is_none());
+        assert_eq!(ordinal.import_ordinal(), Some(7));
+        assert_eq!(ordinal.display_symbol(), "#7");
+    }
+
+    fn lx_builder_fixture() -> LxBuilder {
+        let mut builder = LxBuilder::new("HELLO");
+        builder.description = "Hello library".to_string();
+        // code takes two pages
+        builder.objects.push(ObjectSpec::code(vec![0x90; 0x1800]));
+        builder.objects.push(ObjectSpec { virtual_size: 0x100, ..ObjectSpec::data(b"Hello".to_vec()) });
+        builder.exports.push(ExportSpec::new("Hello", 1, 1, 0x10));
+        builder.exports.push(ExportSpec::new("Hello2", 2, 1, 0x20));
+        builder.exports.push(ExportSpec { resident: false, ..ExportSpec::new("Greeting", 5, 2, 0) });
+        builder.imports.push(ImportSpec::new("DOSCALLS", ImportEntry::Ordinal(282), 1, 2));
+        builder.imports.push(ImportSpec::new("MSG", ImportEntry::Ordinal(5), 1, 0x1004));
+        builder.imports.push(ImportSpec::new("DOSCALLS", ImportEntry::Name("DosWrite".to_string()), 1, 0x10));
+        builder
+    }
+
+    #[test]
+    fn e386_mutated_files_dont_panic() {
+        let original = lx_builder_fixture().build().unwrap();
+        let mut seed: u32 = 0x0386_0386;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            seed >> 8
+        };
+        for _ in 0..2000 {
+            let mut file = original.clone();
+            for _ in 0..1 + next() % 4 {
+                // header and loader section are where the pointers are
+                let at = next() as usize % file.len().min(0x400);
+                file[at] = next() as u8;
+            }
+            if next() % 8 == 0 {
+                file.truncate(next() as usize % file.len());
+            }
+            if let Ok(layout) = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)) {
+                let _ = layout.validate(file.len() as u64);
+                for object in 1..=layout.object_table.objects.len() as u32 {
+                    let _ = layout.read_object_data(&mut Cursor::new(&file), object);
+                }
+                let _ = layout.regions();
+            }
+        }
+    }
+
+    #[test]
+    fn e386_preload_summary() {
+        let file = lx_builder_fixture().build().unwrap();
+        let mut layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        // builder writes no preload pages: everything is loaded on demand
+        let summary = layout.preload_summary();
+        assert_eq!((summary.preload_pages, summary.preload_bytes), (0, 0));
+        assert_eq!(summary.objects.iter().map(|o| (o.preload_pages, o.demand_pages)).collect::<Vec<_>>(), [(0, 2), (0, 1)]);
+
+        // code object is preloaded, data object waits
+        layout.header.e32_preload = 2;
+        layout.object_table.objects[0].flags |= exe386::objtab::OBJ_HAS_PRELOAD as u32;
+        let summary = layout.preload_summary();
+        assert_eq!((summary.preload_pages, summary.preload_bytes), (2, 0x1800));
+        assert_eq!(summary.objects[0], exe386::ObjectPreload { object: 1, preload_pages: 2, demand_pages: 0, preload_bytes: 0x1800, marked_preload: true });
+        assert_eq!((summary.objects[1].preload_pages, summary.objects[1].demand_pages, summary.objects[1].marked_preload), (0, 1, false));
+
+        // page map of untrusted object is clamped to the page table
+        layout.header.e32_preload = u32::MAX;
+        layout.object_table.objects[1].map_size = u32::MAX;
+        let summary = layout.preload_summary();
+        assert_eq!((summary.objects[1].preload_pages, summary.objects[1].demand_pages), (1, 0));
+        layout.object_table.objects[1].map_index = u32::MAX;
+        assert_eq!(layout.preload_summary().objects[1].preload_pages, 0);
+
+        // sections
+        let header = &layout.header;
+        assert_eq!(layout.data_pages_offset(), header.e32_datapage as u64);
+        assert_eq!(&file[layout.data_pages_offset() as usize..][..4], &[0x90; 4]);
+        assert_eq!(layout.loader_section_end(), 0x40 + (header.e32_fpagetab + header.e32_fixupsize) as u64);
+        assert!(layout.loader_section_end() <= layout.data_pages_offset());
+    }
+
+    #[test]
+    fn e386_readable() {
+        use crate::exe386::enttab::{Entry16, Entry32, EntryCallGate, EntryForwarder};
+        use crate::exe386::objpagetab::LEObjectPageHeader;
+        use crate::types::readable::Readable;
+
+        fn read<T: Readable<T>>(bytes: &[u8]) -> std::io::Result<T> {
+            T::read(&mut Cursor::new(bytes))
         }
+        let file = lx_builder_fixture().build().unwrap();
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        let base = layout.dos_header.unwrap().e_lfanew as usize;
+        assert_eq!(read::<LinearExecutableHeader>(&file[base..]).unwrap().e32_objtab, layout.header.e32_objtab);
+
+        let objtab = base + layout.header.e32_objtab as usize;
+        assert_eq!(read::<Object>(&file[objtab..]).unwrap(), layout.object_table.objects[0]);
+        let mut bytes = Vec::new();
+        let page = LXObjectPageHeader { page_offset: 0x12345, data_size: 0x1000, flags: 1 };
+        page.write(&mut bytes, ByteOrder::Little).unwrap();
+        assert_eq!(read::<LXObjectPageHeader>(&bytes).unwrap(), page);
+        let le_page = read::<LEObjectPageHeader>(&[0, 0, 7, 0]).unwrap();
+        assert_eq!(le_page.flags, 0);
+
+        let entry16 = Entry16 { flags: 1, offset: 0x1234 };
+        let entry32 = Entry32 { flags: 3, offset: 0x12345678 };
+        let gate = EntryCallGate { flags: 1, offset: 0x10, callgate_selector: 0x4B };
+        let forwarder = EntryForwarder { flags: 1, module_ordinal: 2, offset_or_ordinal: 282 };
+        let mut bytes = Vec::new();
+        entry16.write(&mut bytes, ByteOrder::Little).unwrap();
+        assert_eq!(read::<Entry16>(&bytes).unwrap(), entry16);
+        bytes.clear();
+        entry32.write(&mut bytes, ByteOrder::Little).unwrap();
+        assert_eq!(read::<Entry32>(&bytes).unwrap(), entry32);
+        bytes.clear();
+        gate.write(&mut bytes, ByteOrder::Little).unwrap();
+        assert_eq!(read::<EntryCallGate>(&bytes).unwrap(), gate);
+        bytes.clear();
+        forwarder.write(&mut bytes, ByteOrder::Little).unwrap();
+        assert_eq!(read::<EntryForwarder>(&bytes).unwrap(), forwarder);
+        // flags, module ordinal, procedure ordinal: no reserved word
+        assert_eq!(read::<EntryForwarder>(&[1, 2, 0, 0x1A, 0x01, 0, 0]).unwrap(), forwarder);
+
+        // short input is EOF, not a half-filled structure
+        assert_eq!(read::<Entry32>(&bytes[..2]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn e386_module_directives() {
+        use crate::exe386::dirtab::{DirectiveType, ModuleDirective, ModuleDirectivesTable};
+        let mut file = lx_builder_fixture().build().unwrap();
+        let mut header = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap().header;
+        // builder writes no directives, and import modules are not them
+        assert_eq!((header.e32_dirtab, header.e32_dircnt), (0, 0));
+        assert_ne!(header.e32_impmodcnt, 0);
+
+        // verify record is resident (from header), language info is not (from file start)
+        let dirtab = file.len() as u32;
+        let verify = dirtab + 16;
+        let language = dirtab + 20;
+        for (number, length, offset) in [(0x8001_u16, 4_u16, verify - 0x40), (0x0002, 3, language)] {
+            file.extend_from_slice(&number.to_le_bytes());
+            file.extend_from_slice(&length.to_le_bytes());
+            file.extend_from_slice(&offset.to_le_bytes());
+        }
+        file.extend_from_slice(&[0, 0, 0xAA, 0xBB, b'C', b'+', b'+']);
+        header.e32_dirtab = dirtab - 0x40;
+        header.e32_dircnt = 2;
+        header.write(&mut Cursor::new(&mut file[0x40..])).unwrap();
+
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        let directives = &layout.module_directives_table.directives;
+        assert_eq!(directives.len(), 2);
+        assert!(matches!(directives[0].directive_type, DirectiveType::VerifyRecord));
+        assert_eq!(directives[0].data, [0, 0, 0xAA, 0xBB]);
+        assert!(matches!(directives[1].directive_type, DirectiveType::LanguageInfo));
+        assert_eq!(directives[1].data, b"C++");
+        let order = layout.header.byte_order();
+        assert_eq!(ModuleDirectivesTable::read_verify_record(&directives[0], order).unwrap().module_dependencies.len(), 0);
+
+        // one module of one object: 16 bytes are 2 short of object record, which used to index past them
+        let mut data = vec![1, 0, 5, 0, 2, 0, 1, 0];
+        data.extend_from_slice(&[1, 0, 0, 0, 1, 0, 0, 0]);
+        let truncated = ModuleDirective { directive_type: DirectiveType::VerifyRecord, data: data.clone() };
+        let error = ModuleDirectivesTable::read_verify_record(&truncated, order).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        data.extend_from_slice(&[0x10, 0]);
+        let complete = ModuleDirective { directive_type: DirectiveType::VerifyRecord, data };
+        let record = ModuleDirectivesTable::read_verify_record(&complete, order).unwrap();
+        let object = &record.module_dependencies[0].object_verifications[0];
+        assert_eq!((object.object_number, object.base_address, object.virtual_size), (1, 0x10000, 0x100000));
+        // in big-endian module the same bytes are 0x100 modules, far more than 18 bytes hold
+        assert!(ModuleDirectivesTable::read_verify_record(&complete, ByteOrder::Big).is_err());
+    }
+
+    #[test]
+    fn e386_builder_round_trip() {
+        let file = lx_builder_fixture().build().unwrap();
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        let header = &layout.header;
+
+        assert!(header.is_lx() && header.le_byte_ordering());
+        assert_eq!((header.e32_objcnt, header.e32_mpages, header.e32_impmodcnt), (2, 3, 2));
+        assert_eq!(header.e32_ldrsize, header.e32_fpagetab - header.e32_objtab);
+        assert_eq!(header.e32_fixupsize, header.e32_nrestab - 0x40 - header.e32_fpagetab);
+        // first page of code object, no pages are preloaded
+        assert_eq!(&file[header.data_pages_offset(0) as usize..][..4], &[0x90; 4]);
+        assert_eq!((header.preload_page_count(), header.demand_page_count()), (0, 0));
+        let mut preloaded = *header;
+        preloaded.e32_preload = 3;
+        preloaded.e32_instdemand = 1;
+        assert_eq!((preloaded.preload_page_count(), preloaded.demand_page_count()), (3, 1));
+        assert_eq!(preloaded.data_pages_offset(0x1000), 0x1000 + header.e32_datapage as u64);
+
+        let objects: Vec<(u32, u32, u32, u32)> = layout.object_table.iter().map(|o| (o.virtual_addr, o.virtual_size, o.map_index, o.map_size)).collect();
+        assert_eq!(objects, vec![(0x10000, 0x1800, 1, 2), (0x20000, 0x100, 3, 1)]);
+        // e32_mpages records of map
+        let pages: Vec<(u32, u16, u16)> = layout.object_pages.iter().map(|p| match p {
+            ObjectPage::LXPageFormat(p) => (p.page_offset, p.data_size, p.flags),
+            other => panic!("{:?}", other),
+        }).collect();
+        assert_eq!(pages, vec![(0, 0x1000, 0), (0x1000, 0x800, 0), (0x1800, 5, 0)]);
+
+        let bundles: Vec<(u16, BundleType, u16, usize)> = layout.entry_table.iter().map(|b| (b.first_ordinal, b.bundle_type, b.object, b.entries.len())).collect();
+        assert_eq!(bundles, vec![(1, BundleType::Entry32, 1, 2), (3, BundleType::Unused, 0, 2), (5, BundleType::Entry32, 2, 1)]);
+        let names: Vec<(u16, String)> = layout.resident_names.iter().map(|e| (e.ordinal, e.name.to_string_lossy())).collect();
+        assert_eq!(names, vec![(0, "HELLO".to_string()), (1, "Hello".to_string()), (2, "Hello2".to_string())]);
+        let names: Vec<(u16, String)> = layout.non_resident_names.iter().map(|e| (e.ordinal, e.name.to_string_lossy())).collect();
+        assert_eq!(names, vec![(0, "Hello library".to_string()), (5, "Greeting".to_string())]);
+
+        // records are sorted by pages, 8-bit ordinal is compact
+        let records: Vec<(u32, u16, u8)> = layout.fixup_records_table.iter().map(|r| (r.page, r.source_offset_or_count, r.target_flags)).collect();
+        assert_eq!(records, vec![(1, 2, 0x01), (1, 0x10, 0x02), (2, 4, 0x81)]);
+        let imports: Vec<(String, String)> = layout.import_table.imports().iter().map(|i| (i.module_name().to_string_lossy(), i.display_symbol())).collect();
+        assert_eq!(imports, vec![
+            ("DOSCALLS".to_string(), "#282".to_string()),
+            ("DOSCALLS".to_string(), "DosWrite".to_string()),
+            ("MSG".to_string(), "#5".to_string()),
+        ]);
+        assert!(layout.validate(file.len() as u64).iter().all(|a| a.severity != Severity::Error));
+    }
+
+    #[test]
+    fn e386_builder_import_site() {
+        let mut builder = LxBuilder::new("SITE");
+        builder.objects.push(ObjectSpec::code(vec![0x90; 6]));
+        // 32-bit offset at 4 needs bytes 4..8
+        builder.imports.push(ImportSpec::new("DOSCALLS", ImportEntry::Ordinal(282), 1, 4));
+        let error = builder.build().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Import site 0x4..0x8 is out of object #1 data");
+
+        builder.imports[0].offset = 2;
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn e386_tables_write_read() {
+        let file = lx_builder_fixture().build().unwrap();
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        let order = ByteOrder::Little;
+
+        let mut big_endian = layout.header;
+        big_endian.e32_border = 1;
+        big_endian.e32_worder = 1;
+        for header in [layout.header, big_endian] {
+            let mut buf = Vec::new();
+            header.write(&mut buf).unwrap();
+            assert_eq!(buf.len(), 0xC4);
+            assert_eq!(LinearExecutableHeader::read(&mut Cursor::new(&buf)).unwrap(), header);
+        }
+
+        let mut buf = Vec::new();
+        layout.object_table.write(&mut buf, order).unwrap();
+        assert_eq!(ObjectsTable::read(&mut Cursor::new(&buf), 0, 2, order).unwrap(), layout.object_table);
+
+        let mut buf = Vec::new();
+        layout.entry_table.write(&mut buf, order).unwrap();
+        assert_eq!(exe386::enttab::EntryTable::read(&mut Cursor::new(&buf), 0, order).unwrap(), layout.entry_table);
+
+        let mut buf = Vec::new();
+        layout.resident_names.write_with_order(&mut buf, ByteOrder::Big).unwrap();
+        assert_eq!(LxResidentNameTable::read_with_order(&mut Cursor::new(&buf), 0, ByteOrder::Big).unwrap(), layout.resident_names);
+
+        let mut buf = Vec::new();
+        let page_table = layout.fixup_records_table.write(&mut buf, order, layout.header.e32_mpages).unwrap();
+        assert_eq!(page_table, layout.fixup_page_table);
+        assert_eq!(FixupRecordsTable::read(&mut Cursor::new(&buf), &page_table, 0, order).unwrap(), layout.fixup_records_table);
+
+        let mut buf = Vec::new();
+        layout.import_table.write_modules(&mut buf).unwrap();
+        assert_eq!(buf, b"\x08DOSCALLS\x03MSG");
+    }
+
+    /// 64 pages of fixup records after 0x20 bytes of other data
+    fn fixup_blob() -> (FixupRecordsTable, FixupPageTable, Vec<u8>) {
+        let mut records = Vec::new();
+        for page in 1..=64_u32 {
+            for i in 0..page % 5 {
+                let mut record = match i {
+                    0 => fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 1, target_offset: Some(page * 0x10) })),
+                    1 => fixup(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal { module_ordinal: 2, import_ordinal: page })),
+                    2 => fixup(FixupTarget::ImportedName(FixupTargetImportedName { module_ordinal: 1, procedure_name_offset: page })),
+                    _ => fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 2, target_offset: None })),
+                };
+                record.page = page;
+                record.source_offset_or_count = (i * 0x100) as u16;
+                if i == 3 {
+                    // selector fixup of two sites
+                    record.source = 0x02;
+                    record.source_offset_list = Some(vec![0x10, 0x20]);
+                }
+                record.compact_flags();
+                records.push(record);
+            }
+        }
+        let table = FixupRecordsTable { records };
+        let mut blob = vec![0xEE; 0x20];
+        let page_table = table.write(&mut blob, ByteOrder::Little, 64).unwrap();
+        (table, page_table, blob)
+    }
+
+    #[test]
+    fn e386_fixup_records_sequential() {
+        let (table, page_table, blob) = fixup_blob();
+        let read = FixupRecordsTable::read_sequential(&mut Cursor::new(&blob), &page_table, 0x20, ByteOrder::Little).unwrap();
+        assert_eq!(read, table);
+        assert_eq!(FixupRecordsTable::read(&mut Cursor::new(&blob), &page_table, 0x20, ByteOrder::Little).unwrap(), table);
+    }
+
+    #[test]
+    fn e386_fixup_records_streaming() {
+        let (table, page_table, blob) = fixup_blob();
+        let streamed: Vec<(usize, FixupRecord)> = FixupRecordsTable::iter_page_records(Cursor::new(&blob), &page_table, 0x20, ByteOrder::Little)
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+        assert_eq!(streamed.len(), table.records.len());
+        for ((page, record), expected) in streamed.iter().zip(&table.records) {
+            assert_eq!(*page as u32 + 1, expected.page);
+            assert_eq!(record, expected);
+        }
+
+        // error is yielded once, then iteration stops
+        let truncated = &blob[..blob.len() - 3];
+        let results: Vec<_> = FixupRecordsTable::iter_page_records(Cursor::new(truncated), &page_table, 0x20, ByteOrder::Little).collect();
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert_eq!(results.last().unwrap().as_ref().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn e386_fixup_records_parallel() {
+        let (table, page_table, blob) = fixup_blob();
+        let parallel = FixupRecordsTable::read_parallel(&mut Cursor::new(&blob), &page_table, 0x20, ByteOrder::Little).unwrap();
+        let sequential = FixupRecordsTable::read_sequential(&mut Cursor::new(&blob), &page_table, 0x20, ByteOrder::Little).unwrap();
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel, table);
+
+        // truncated section fails both ways
+        let truncated = &blob[..blob.len() - 3];
+        let parallel = FixupRecordsTable::read_parallel(&mut Cursor::new(truncated), &page_table, 0x20, ByteOrder::Little).unwrap_err();
+        let sequential = FixupRecordsTable::read_sequential(&mut Cursor::new(truncated), &page_table, 0x20, ByteOrder::Little).unwrap_err();
+        assert_eq!(parallel.kind(), sequential.kind());
+    }
+
+    #[test]
+    fn e386_fixup_record_flags() {
+        let mut record = fixup(FixupTarget::ImportedOrdinal(FixupTargetImportedOrdinal { module_ordinal: 300, import_ordinal: 0x12345 }));
+        record.additive_value = Some(0x10000);
+        record.source_offset_list = Some(vec![0x10, 0x20, 0xFFFE]);
+        record.compact_flags();
+        // list, 16-bit module, 32-bit ordinal and 32-bit additive
+        assert_eq!((record.source, record.target_flags, record.source_offset_or_count), (0x27, 0x75, 3));
+
+        let mut internal = fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 2, target_offset: Some(0x10) }));
+        internal.compact_flags();
+        assert_eq!(internal.target_flags, 0x00);
+        let mut selector = fixup(FixupTarget::Internal(FixupTargetInternal { object_number: 1, target_offset: None }));
+        selector.source = 0x02;
+        selector.page = 2;
+
+        for order in [ByteOrder::Little, ByteOrder::Big] {
+            let table = FixupRecordsTable { records: vec![record.clone(), internal.clone(), selector.clone()] };
+            let mut buf = Vec::new();
+            let page_table = table.write(&mut buf, order, 2).unwrap();
+            assert_eq!(page_table.page_offsets, vec![0, 26]);
+            assert_eq!(FixupRecordsTable::read(&mut Cursor::new(&buf), &page_table, 0, order).unwrap(), table);
+        }
+
+        // values which don't fit into widths of flags
+        let mut wide = internal.clone();
+        wide.target_data = FixupTarget::Internal(FixupTargetInternal { object_number: 2, target_offset: Some(0x10000) });
+        assert!(wide.write(&mut Vec::new(), ByteOrder::Little).is_err());
+        let mut listed = internal.clone();
+        listed.source_offset_list = Some(vec![0]);
+        assert!(listed.write(&mut Vec::new(), ByteOrder::Little).is_err());
+        let unsorted = FixupRecordsTable { records: vec![selector, internal] };
+        assert!(unsorted.write(&mut Vec::new(), ByteOrder::Little, 2).is_err());
+    }
+
+    #[test]
+    fn e386_patch_header_fields() {
+        let original = lx_builder_fixture().build().unwrap();
+        let mut file = Cursor::new(original.clone());
+
+        patch::set_lx_stack_size(&mut file, 0x8000).unwrap();
+        patch::set_lx_heap_size(&mut file, 0x2000).unwrap();
+        patch::set_lx_entry(&mut file, 2, 0x40).unwrap();
+
+        // only e32_cs, e32_eip, e32_heapsize and e32_stacksize are changed
+        let patched = file.get_ref().clone();
+        let changed: Vec<usize> = (0..original.len()).filter(|&i| original[i] != patched[i]).collect();
+        assert_eq!(changed, vec![0x40 + 0x18, 0x40 + 0x1C, 0x40 + 0xA9, 0x40 + 0xAD]);
+
+        let layout = exe386::LinearExecutableLayout::read_from(&mut file).unwrap();
+        let mut expected = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&original)).unwrap().header;
+        expected.e32_cs = 2;
+        expected.e32_eip = 0x40;
+        expected.e32_heapsize = 0x2000;
+        expected.e32_stacksize = 0x8000;
+        assert_eq!(layout.header, expected);
+
+        // no object #3
+        let error = patch::set_lx_entry(&mut file, 3, 0).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(file.get_ref(), &patched);
+    }
+
+    #[test]
+    fn e386_cpu_try_from() {
+        assert_eq!(CPU::try_from(2), Ok(CPU::I386));
+        assert_eq!(CPU::try_from(0x42), Ok(CPU::MipsMarkIII));
+        assert_eq!(CPU::try_from(5), Err(5));
+        assert_eq!(CPU::from(5), CPU::Unknown(5));
+        assert_eq!(CPU::try_from(3).unwrap().to_string(), "i486");
+    }
+
+    #[test]
+    fn e386_expected_os() {
+        for (os, expected, name) in [(1, OS::Os2v2, "OS/2 2.x"), (4, OS::Windows386, "Windows (386)")] {
+            let mut builder = lx_builder_fixture();
+            builder.os = os;
+            let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&builder.build().unwrap())).unwrap();
+            assert_eq!(layout.expected_os(), expected);
+            assert_eq!(layout.expected_os().to_string(), name);
+        }
+    }
+
+    #[test]
+    fn e386_entry_point_and_stack() {
+        // program: entry in code object, stack in data object
+        let mut builder = lx_builder_fixture();
+        builder.flags = 0;
+        (builder.cs, builder.eip, builder.ss, builder.esp) = (1, 0x10, 2, 0x100);
+        (builder.heap_size, builder.stack_size) = (0x1000, 0x2000);
+        let mut layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&builder.build().unwrap())).unwrap();
+
+        assert_eq!(layout.entry_point().unwrap(), ObjectAddress { object: 1, offset: 0x10, virtual_address: 0x10010 });
+        assert_eq!(layout.initial_stack().unwrap(), ObjectAddress { object: 2, offset: 0x100, virtual_address: 0x20100 });
+        assert_eq!((layout.heap_size(), layout.stack_size()), (0x1000, 0x2000));
+        layout.header.e32_autodata = 2;
+        layout.header.e32_instpreload = 1;
+        let (number, object) = layout.auto_data_object().unwrap();
+        assert_eq!((number, object.virtual_addr), (2, 0x20000));
+        assert_eq!(layout.instance_pages(), (1, 0));
+
+        layout.header.e32_cs = 3;
+        assert_eq!(layout.entry_point().unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // library: no initialization routine, stack of the caller, no DGROUP
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&lx_builder_fixture().build().unwrap())).unwrap();
+        assert_eq!(layout.entry_point().unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(layout.initial_stack().unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(layout.auto_data_object().unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn e386_read_object_data() {
+        let file = lx_builder_fixture().build().unwrap();
+        let mut reader = Cursor::new(&file);
+        let layout = exe386::LinearExecutableLayout::read_from(&mut reader).unwrap();
+
+        // layout is shared between threads, reader is passed by caller
+        let shared = std::sync::Arc::new(layout);
+        let images: Vec<Vec<u8>> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (1..=2).map(|number| {
+                let layout = shared.clone();
+                let file = &file;
+                scope.spawn(move || layout.read_object_data(&mut Cursor::new(file), number).unwrap())
+            }).collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        assert_eq!(images[0], vec![0x90; 0x1800]);
+        // 0x100 bytes of object, only 5 of them are in file
+        assert_eq!(images[1], b"Hello");
+
+        assert_eq!(shared.read_object_data(&mut reader, 3).unwrap_err().kind(), ErrorKind::InvalidData);
+        let mut packed = (*shared).clone();
+        if let ObjectPage::LXPageFormat(page) = &mut packed.object_pages.pages[2] {
+            page.flags = 1;
+        }
+        assert_eq!(packed.read_object_data(&mut reader, 2).unwrap_err().kind(), ErrorKind::InvalidData);
+        // zero-filled page
+        if let ObjectPage::LXPageFormat(page) = &mut packed.object_pages.pages[2] {
+            page.flags = 3;
+        }
+        assert_eq!(packed.read_object_data(&mut reader, 2).unwrap(), Vec::<u8>::new());
+
+        // page data past the end of file, page size larger than file
+        let mut truncated = (*shared).clone();
+        if let ObjectPage::LXPageFormat(page) = &mut truncated.object_pages.pages[2] {
+            page.data_size = file.len() as u16;
+        }
+        assert_eq!(truncated.read_object_data(&mut reader, 2).unwrap_err().kind(), ErrorKind::InvalidData);
+        let mut sparse = (*shared).clone();
+        if let ObjectPage::LXPageFormat(page) = &mut sparse.object_pages.pages[0] {
+            page.flags = 3;
+        }
+        let image = sparse.read_object_data(&mut reader, 1).unwrap();
+        assert_eq!((&image[..0x1000], &image[0x1000..]), (&[0; 0x1000][..], &[0x90; 0x800][..]));
+        sparse.header.e32_pagesize = 0x1000_0000;
+        assert_eq!(sparse.read_object_data(&mut reader, 1).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn e386_inspect() {
+        let file = lx_builder_fixture().build().unwrap();
+        let mut reader = Cursor::new(&file);
+        assert_eq!(inspect::read_format(&mut reader).unwrap(), "LX");
+        assert_eq!(inspect::read_module_name(&mut reader).unwrap(), Some("HELLO".to_string()));
+        assert_eq!(inspect::read_export_count(&mut reader).unwrap(), 3);
+
+        // without DOS header
+        let mut reader = Cursor::new(&file[0x40..]);
+        assert_eq!(inspect::read_format(&mut reader).unwrap(), "LX");
+        assert_eq!(inspect::read_module_name(&mut reader).unwrap(), Some("HELLO".to_string()));
+
+        let mut le = file.clone();
+        le[0x40..0x42].copy_from_slice(&LE_MAGIC.to_le_bytes());
+        assert_eq!(inspect::read_format(&mut Cursor::new(&le)).unwrap(), "LE");
+        assert_eq!(inspect::read_format(&mut Cursor::new(vec![0; 0x100])).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn e386_strip_debug() {
+        let original = lx_builder_fixture().build().unwrap();
+        let layout = exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&original)).unwrap();
+        let nrestab = layout.header.e32_nrestab as usize;
+
+        // HLL symbols between loader sections and non-resident names
+        let debug = b"NB04 symbols".to_vec();
+        let mut image = original.clone();
+        image.splice(nrestab..nrestab, debug.iter().copied());
+        let mut header = layout.header;
+        header.e32_debuginfo = nrestab as u32;
+        header.e32_debuglen = debug.len() as u32;
+        header.e32_nrestab += debug.len() as u32;
+        header.e32_datapage += debug.len() as u32;
+        let mut with_debug = Vec::new();
+        header.write(&mut with_debug).unwrap();
+        image[0x40..0x40 + with_debug.len()].copy_from_slice(&with_debug);
+
+        assert_eq!(patch::strip_debug_image(&mut image).unwrap(), Some(debug));
+        assert_eq!(image, original);
+        assert_eq!(patch::strip_debug_image(&mut image).unwrap(), None);
+
+        // appended to the end of file without pointer in header
+        let mut image = original.clone();
+        image.extend_from_slice(b"NB09");
+        image.extend_from_slice(&[0xAA; 8]);
+        image.extend_from_slice(b"NB09");
+        image.extend_from_slice(&20_u32.to_le_bytes());
+        assert_eq!(patch::strip_debug_image(&mut image).unwrap().map(|d| d.len()), Some(20));
+        assert_eq!(image, original);
+
+        // cut would take page data away
+        let mut image = original.clone();
+        header = layout.header;
+        header.e32_debuginfo = header.e32_datapage;
+        header.e32_debuglen = 4;
+        with_debug.clear();
+        header.write(&mut with_debug).unwrap();
+        image[0x40..0x40 + with_debug.len()].copy_from_slice(&with_debug);
+        let before = image.clone();
+        assert_eq!(patch::strip_debug_image(&mut image).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(image, before);
+    }
+
+    #[test]
+    fn e386_extract_debug() {
+        let original = lx_builder_fixture().build().unwrap();
+        let dir = std::env::temp_dir().join(format!("os2omf-extract-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let module = dir.join("HELLO.DLL");
+        let symbols = dir.join("HELLO.SYM");
+
+        let mut image = original.clone();
+        image.extend_from_slice(b"NB04NB04");
+        image.extend_from_slice(&12_u32.to_le_bytes());
+        std::fs::write(&module, &image).unwrap();
+
+        assert!(patch::extract_debug(module.to_str().unwrap(), symbols.to_str().unwrap()).unwrap());
+        assert_eq!(std::fs::read(&module).unwrap(), original);
+        assert_eq!(std::fs::read(&symbols).unwrap(), image[original.len()..]);
+        assert!(!patch::strip_debug(module.to_str().unwrap()).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn e386_patch_refuses_le() {
+        let mut le = lx_builder_fixture().build().unwrap();
+        le[0x40..0x42].copy_from_slice(&LE_MAGIC.to_le_bytes());
+        let mut file = Cursor::new(le.clone());
+
+        for result in [
+            patch::set_lx_stack_size(&mut file, 0x8000),
+            patch::set_lx_heap_size(&mut file, 0x2000),
+            patch::set_lx_entry(&mut file, 1, 0),
+        ] {
+            assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+        assert_eq!(file.get_ref(), &le);
+
+        // NE patchers don't take LX header for NE one
+        let mut file = Cursor::new(lx_builder_fixture().build().unwrap());
+        assert_eq!(patch::set_ne_flags(&mut file, 0).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn e386_resident_names_offset() {
+        // 0x10 bytes before LX header, e32_restab = 4
+        let mut file = vec![0xEE_u8; 0x14];
+        file.extend_from_slice(b"\x04INIT\x00\x01\x00");
+        let base_offset = 0x10_u64;
+
+        let table = LxResidentNameTable::read(&mut Cursor::new(&file), base_offset + 4).unwrap();
+        assert_eq!(table.iter().map(|e| e.ordinal).collect::<Vec<_>>(), vec![0x0100]);
+        assert_eq!(table.entries[0].name.to_string_lossy(), "INIT");
+
+        // ordinals of big-endian modules
+        let table = LxResidentNameTable::read_with_order(&mut Cursor::new(&file), base_offset + 4, ByteOrder::Big).unwrap();
+        assert_eq!(table.entries, vec![ResidentNameEntry { name: PascalString::new(4, b"INIT".to_vec()), ordinal: 1 }]);
+    }
+
+    #[test]
+    #[ignore = "needs DOSCALL1.DLL of OS/2 in D:\\TEST"]
+    fn e386_header() {
+        let path = "D:\\TEST\\MS_OS220\\DOSCALL1.DLL";
+        let layout = exe386::LinearExecutableLayout::get(path);
+
+        match layout {
+            Ok(res) => assert!(true, "{:?}", res.header),
+            Err(e) => assert!(false, "{:?}", e),
+        }
+    }
+
+    #[test]
+    #[ignore = "needs DOSCALL1.DLL of OS/2 in D:\\TEST"]
+    fn e386_enttab() {
+        let path = "D:\\TEST\\MS_OS220\\DOSCALL1.DLL";
+        let layout = exe386::LinearExecutableLayout::get(path);
+
+        match layout {
+            Ok(res) => {
+                assert!(true, "{:?}", res.entry_table.bundles);
+            },
+            Err(e) => assert!(false, "{:?}", e),
+        }
+    }
+    #[test]
+    #[ignore = "needs BDCALLS.DLL of OS/2 in D:\\TEST"]
+    fn e386_imports() {
+        let path = "D:\\TEST\\ARCA\\BDCALLS.DLL";
+        let layout = exe386::LinearExecutableLayout::get(path);
+
+        match layout {
+            Ok(res) => {
+                assert!(true, "{:?}", res.import_table.imports());
+            },
+            Err(e) => assert!(false, "{:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod exe_286_tests {
+    use crate::exe::builder::MzBuilder;
+    use crate::exe::{extension_type_at, ExtensionType, MzExecutableLayout, MzHeader};
+    use crate::exe::reltab::{FarPointer, MzRelocationTable};
+    use crate::exe286::enttab::EntryTable;
+    use crate::exe286::header::{DataSegment, ExtendedFlags, NewExecutableHeader, CPU, OS};
+    use crate::exe286::modtab::ModuleReferencesTable;
+    use crate::exe286::enttab::{Entry, FixedEntry, MoveableEntry, ENTRY_CONSTANT, MOVEABLE_ENTRY_MAGIC};
+    use crate::exe286::nrestab::{NonResidentNameEntry, NonResidentNameTable};
+    use crate::exe286::resntab::{ResidentNameEntry, ResidentNameTable};
+    use crate::exe286::resources::{
+        Bitmap, DialogClass, DialogTemplate, Font, FontDir, GroupIcon, Icon, Menu, MenuItem, MAX_MENU_DEPTH, MessageKind,
+        MessageTable, NameOrOrdinal, Os2Bitmap, Os2BitmapArray, Os2BitmapKind, VersionInfo,
+    };
+    use crate::exe286::rsrctab::{
+        self, ResourceId, ResourceTable, OS2_RT_MESSAGE, OS2_RT_POINTER, RT_GROUP_ICON, RT_ICON,
+    };
+    use crate::exe286::segrelocs::{
+        ChainEnd, FPUFixup, FPUFixupType, ImportName, ImportOrdinal, InternalFixup, RelocationEntry, RelocationTable,
+        RelocationType,
+    };
+    use crate::exe286::segtab::{DllImport, ImportsTable, Segment, SegmentHeader, SEG_DATA, SEG_RELOCS};
+    use crate::exe286::builder::{ExportSpec, ImportSpec, NeBuilder, SegmentSpec};
+    use crate::exe286::{NewExecutableLayout, SegmentedAddress};
+    use crate::omf::coment::ImportEntry;
+    use crate::dump;
+    use crate::file::ExecutableFile;
+    use crate::anomaly::Severity;
+    use crate::inspect;
+    use crate::patch;
+    use crate::regions::{self, RegionKind};
+    use crate::types::{NameTable, PascalString};
+    use bytemuck::Zeroable;
+    use std::io::{Cursor, ErrorKind};
+
+    fn segment_record(sector_base: u16, sector_length: u16, flags: u16) -> Vec<u8> {
+        [sector_base, sector_length, flags, 0]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect()
+    }
+
+    fn empty_layout() -> NewExecutableLayout {
+        NewExecutableLayout {
+            dos_header: MzHeader::zeroed(),
+            new_header: NewExecutableHeader::zeroed(),
+            ent_tab: EntryTable { entries: vec![] },
+            seg_tab: vec![],
+            nres_tab: NonResidentNameTable { entries: vec![] },
+            resn_tab: ResidentNameTable { entries: vec![] },
+            mod_tab: ModuleReferencesTable { m_offsets: vec![] },
+            imp_tab: vec![],
+            rsrc_tab: ResourceTable::empty(),
+            rsrc_error: None,
+        }
+    }
+
+    fn internal_reloc(address_type: u8, additive: bool, site: u16, offset: u16) -> RelocationEntry {
+        RelocationEntry {
+            rel_rtp: 0,
+            rel_atp: address_type,
+            rel_add: additive,
+            rel_seg_ptr: site,
+            rel_type: RelocationType::Internal(InternalFixup {
+                int_seg: 1,
+                int_mov: false,
+                int_offset: offset,
+            }),
+        }
+    }
+
+    fn pascal(s: &str) -> PascalString {
+        PascalString::new(s.len() as u8, s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn e286_readable() {
+        use crate::types::readable::Readable;
+
+        let file = ne_builder_fixture().build().unwrap();
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        let mut reader = Cursor::new(&file);
+        assert_eq!(<MzHeader as Readable<MzHeader>>::read(&mut reader).unwrap().e_lfanew, layout.dos_header.e_lfanew);
+
+        // trait reads from current position, inherent reader seeks to e_lfanew
+        let base = layout.dos_header.e_lfanew as usize;
+        let header = <NewExecutableHeader as Readable<NewExecutableHeader>>::read(&mut Cursor::new(&file[base..])).unwrap();
+        assert_eq!(header.e_seg_tab, layout.new_header.e_seg_tab);
+        let segtab = base + header.e_seg_tab as usize;
+        let segment = <SegmentHeader as Readable<SegmentHeader>>::read(&mut Cursor::new(&file[segtab..])).unwrap();
+        assert_eq!(segment.physical_size(), layout.seg_tab[0].header.physical_size());
+    }
+
+    #[test]
+    fn e286_segment_sizes() {
+        let header = SegmentHeader { sector_base: 1, sector_length: 0x1234, flags: 0, min_alloc: 0x2000 };
+        assert_eq!((header.physical_size(), header.min_alloc_bytes(), header.min_alloc()), (0x1234, 0x2000, 0x2000));
+
+        // zero means 64KB in both fields
+        let full = SegmentHeader { sector_length: 0, min_alloc: 0, ..header };
+        assert_eq!((full.physical_size(), full.min_alloc_bytes(), full.min_alloc()), (0x10000, 0x10000, 0x10000));
+        #[allow(deprecated)]
+        let old = full.data_length();
+        assert_eq!(old, 0x10000);
+    }
+
+    #[test]
+    fn e286_segment_64k_relocs() {
+        // segments table at 0, segment data at sector 1 (shift 4) with 64K length
+        let mut file = segment_record(1, 0, SEG_RELOCS);
+        file.resize(0x10 + 0x10000, 0xAA);
+        file.extend_from_slice(&1_u16.to_le_bytes());
+        file.extend_from_slice(&[0x03, 0x00, 0x10, 0x00, 0x02, 0x00, 0x04, 0x00]);
+
+        let mut reader = Cursor::new(file);
+        let segment = Segment::read(&mut reader, 4).unwrap();
+
+        assert_eq!(segment.relocs.rel_entries.len(), 1);
+        assert_eq!(segment.relocs.rel_entries[0].rel_seg_ptr, 0x0010);
+        assert_eq!(reader.position(), 8);
+    }
+
+    #[test]
+    fn e286_segment_bss_relocs() {
+        let file = segment_record(0, 0x100, SEG_RELOCS);
+        let segment = Segment::read(&mut Cursor::new(file), 4).unwrap();
+
+        assert!(segment.relocs.rel_entries.is_empty());
+    }
+
+    #[test]
+    fn e286_segment_relocs_past_eof() {
+        let file = segment_record(0x100, 0x100, SEG_RELOCS);
+
+        assert!(Segment::read(&mut Cursor::new(file), 4).is_err());
+    }
+
+    #[test]
+    fn e286_sector_to_file_offset() {
+        assert_eq!(Segment::sector_to_file_offset(3, 0), 3 * 512);
+        assert_eq!(Segment::sector_to_file_offset(3, Segment::DEFAULT_SECTOR_SHIFT), 3 * 512);
+        assert_eq!(Segment::sector_to_file_offset(3, 4), 0x30);
+        assert_eq!(Segment::sector_to_file_offset(0xFFFF, 16), 0xFFFF_0000);
+        assert_eq!(Segment::sector_to_file_offset(1, 64), u64::MAX);
+
+        let segment = Segment::read(&mut Cursor::new(segment_record(2, 0x10, 0)), 0).unwrap();
+        assert_eq!(segment.shift_count, Segment::DEFAULT_SECTOR_SHIFT);
+        assert_eq!(segment.file_offset(), 0x400);
+        assert_eq!(segment.header.data_offset(0), 0x400);
+    }
+
+    #[test]
+    fn e286_linear_address() {
+        // load base paragraph 0x1234 plus sector 2 of 512 bytes
+        let segment = Segment::read(&mut Cursor::new(segment_record(2, 0x10, 0)), 0).unwrap();
+        assert_eq!(segment.linear_address(0x1234), 0x12340 + 0x400);
+        assert_eq!(segment.linear_address(0), 0x400);
+        // image loaded at the top of real-mode memory goes past 1MB
+        assert_eq!(segment.linear_address(0xFFFF), 0xFFFF0 + 0x400);
+
+        // every segment is at its file offset from the load base, whatever alignment is
+        for (sector_base, shift) in [(1, 4), (0x20, 4), (3, 9), (0x7FFF, 9)] {
+            let record = segment_record(sector_base, 0x10, 0);
+            let segment = Segment::read(&mut Cursor::new(record), shift).unwrap();
+            assert_eq!(
+                segment.linear_address(0x2000) - segment.linear_address(0),
+                0x20000
+            );
+            assert_eq!(segment.linear_address(0) as u64, segment.file_offset());
+        }
+    }
+
+    #[test]
+    fn e286_data_segment_flags() {
+        let mut header = NewExecutableHeader::zeroed();
+
+        header.e_flags = 0x0001;
+        assert!(matches!(header.module_flags().data_segment, DataSegment::Single));
+        header.e_flags = 0x8302;
+        assert!(matches!(header.module_flags().data_segment, DataSegment::Multiple));
+        header.e_flags = 0x0000;
+        assert!(matches!(header.module_flags().data_segment, DataSegment::No));
+    }
+
+    #[test]
+    fn e286_segment_flags() {
+        // flags | data | moveable | preload | discard | read-only | ring | relocs
+        let table = [
+            (0x1D50_u16, false, true, true, true, false, 3, true), // moveable discardable code
+            (0x0D51, true, true, true, false, false, 3, true), // automatic data segment
+            (0x0C50, false, true, true, false, false, 3, false), // code without relocations
+            (0x0CD1, true, true, true, false, true, 3, false), // read-only data
+            (0x0000, false, false, false, false, false, 0, false), // fixed ring 0 code
+        ];
+
+        for (flags, data, moveable, preload, discard, read_only, ring, relocs) in table {
+            let header = SegmentHeader {
+                sector_base: 1,
+                sector_length: 0,
+                flags,
+                min_alloc: 0,
+            };
+            assert_eq!(header.is_data(), data, "0x{:04X}", flags);
+            assert_eq!(header.is_moveable(), moveable, "0x{:04X}", flags);
+            assert_eq!(header.is_preload(), preload, "0x{:04X}", flags);
+            assert_eq!(header.is_discardable(), discard, "0x{:04X}", flags);
+            assert_eq!(header.is_read_only(), read_only, "0x{:04X}", flags);
+            assert_eq!(header.ring(), ring, "0x{:04X}", flags);
+            assert_eq!(header.has_relocations(), relocs, "0x{:04X}", flags);
+        }
+    }
+
+    #[test]
+    fn e286_segment_descriptor() {
+        let header = |flags| SegmentHeader { sector_base: 1, sector_length: 0, flags, min_alloc: 0 };
+        // ring 2 conforming execute-only code of OS/2 driver
+        let driver = header(0x0A80);
+        assert_eq!(driver.dpl(), 2);
+        assert!(driver.is_conforming());
+        assert_eq!(driver.descriptor_access_byte(&OS::Os2), 0xDC);
+        assert_eq!(driver.descriptor_access_byte(&OS::Windows286), 0xFA);
+        // ring 3 read-only and writable data
+        assert!(!header(0x0E81).is_conforming());
+        assert_eq!(header(0x0C81).descriptor_access_byte(&OS::Os2), 0xF0);
+        assert_eq!(header(0x0C01).descriptor_access_byte(&OS::Os2), 0xF2);
+        assert_eq!(header(0x0001).descriptor_access_byte(&OS::Windows386), 0xF2);
+    }
+
+    #[test]
+    fn e286_imports_aggregated() {
+        let by_name = |offset| DllImport::new(pascal("KERNEL"), pascal("GETPROCADDRESS"), 0, 0, offset);
+        let by_ordinal = |offset| DllImport::new(pascal("USER"), PascalString::empty(), 5, 0, offset);
+
+        let mut layout = empty_layout();
+        layout.imp_tab = vec![
+            ImportsTable {
+                seg_number: 1,
+                imp_list: vec![by_name(0x10), by_ordinal(0x20), by_name(0x30)],
+            },
+            ImportsTable {
+                seg_number: 2,
+                imp_list: vec![by_name(0x04)],
+            },
+        ];
+
+        let imports = layout.imports();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].sites, vec![(1, 0x10), (1, 0x30), (2, 0x04)]);
+        assert_eq!(imports[1].import.ordinal, 5);
+        assert_eq!(imports[1].sites, vec![(1, 0x20)]);
+    }
+
+    #[test]
+    fn e286_import_module_names_shared() {
+        let mut builder = NeBuilder::new("MANY");
+        builder.segments.push(SegmentSpec::code(vec![0; 0x800]));
+        builder.segments.push(SegmentSpec::code(vec![0; 0x800]));
+        for i in 0..300_u16 {
+            builder.imports.push(ImportSpec::new("KERNEL", ImportEntry::Ordinal(i + 1), 1 + i % 2, i / 2 * 8));
+        }
+        builder.imports.push(ImportSpec::new("USER", ImportEntry::Name("MessageBox".to_string()), 2, 0x7F0));
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(builder.build().unwrap())).unwrap();
+
+        let imports: Vec<&DllImport> = layout.imp_tab.iter().flat_map(|t| &t.imp_list).collect();
+        assert_eq!(imports.len(), 301);
+        let (kernel, user): (Vec<&DllImport>, Vec<&DllImport>) = imports.into_iter().partition(|i| i.dll_name_str() == "KERNEL");
+        // imports of both segments share one name
+        assert_eq!(kernel.len(), 300);
+        assert!(kernel.iter().all(|i| std::ptr::eq(i.dll_name.as_bytes(), kernel[0].dll_name.as_bytes())));
+        assert_eq!(user[0].dll_name.to_string(), "USER");
+
+        // imports made by hand own their names
+        let by_hand = DllImport::new(pascal("KERNEL"), PascalString::empty(), 1, 0, 0);
+        assert_eq!(&by_hand, kernel[0]);
+    }
+
+    #[test]
+    fn e286_import_from_module_zero() {
+        let mut file = ne_builder_fixture().build().unwrap();
+        // far pointer at 1 imports KERNEL.70: module index is 1-based
+        let record = [3, 1, 1, 0, 1, 0, 70, 0];
+        let at = file.windows(8).position(|w| w == record).unwrap();
+        file[at + 4] = 0;
+
+        let err = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("module #0"));
+    }
+
+    #[test]
+    fn e286_mutated_files_dont_panic() {
+        let original = ne_builder_fixture().build().unwrap();
+        let mut seed: u32 = 0x0286_0286;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            seed >> 8
+        };
+        for _ in 0..2000 {
+            let mut file = original.clone();
+            for _ in 0..1 + next() % 4 {
+                let at = next() as usize % file.len();
+                file[at] = next() as u8;
+            }
+            if next() % 8 == 0 {
+                file.truncate(next() as usize % file.len());
+            }
+            let _ = MzExecutableLayout::read_from(&mut Cursor::new(&file));
+            let _ = ExecutableFile::read_from_slice(&file);
+            if let Ok(mut layout) = NewExecutableLayout::read_from(&mut Cursor::new(&file)) {
+                let _ = layout.read_segments_data(&mut Cursor::new(&file));
+                let _ = layout.validate(file.len() as u64);
+                let _ = layout.imports();
+                let _ = layout.regions();
+            }
+        }
+    }
+
+    #[test]
+    fn e286_imports_deduplicated() {
+        let by_name = |name, offset| DllImport::new(pascal("KERNEL"), pascal(name), 0, 0, offset);
+        let by_ordinal = |ordinal, offset| DllImport::new(pascal("USER"), PascalString::empty(), ordinal, 0, offset);
+
+        let first = ImportsTable {
+            seg_number: 1,
+            imp_list: vec![
+                by_name("GLOBALALLOC", 0x10),
+                by_ordinal(5, 0x20),
+                by_name("GLOBALALLOC", 0x30),
+                by_ordinal(5, 0x40),
+                by_ordinal(6, 0x50),
+            ],
+        };
+        assert!(first.imp_list[0].is_by_name() && !first.imp_list[0].is_by_ordinal());
+        assert!(first.imp_list[1].is_by_ordinal() && !first.imp_list[1].is_by_name());
+        assert_eq!(first.imp_list[0].display_symbol(), "GLOBALALLOC");
+        assert_eq!(first.imp_list[1].display_symbol(), "#5");
+        assert_eq!(first.imp_list[1].dll_name_str(), "USER");
+
+        let unique = first.deduplicated();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(unique[0].seg_offset, 0x10);
+        assert_eq!(unique[1].ordinal, 5);
+        assert_eq!(unique[2].ordinal, 6);
+
+        let mut layout = empty_layout();
+        layout.imp_tab = vec![
+            first,
+            ImportsTable {
+                seg_number: 2,
+                imp_list: vec![by_name("GLOBALFREE", 0x04), by_ordinal(5, 0x08)],
+            },
+        ];
+        let all = layout.all_imports_deduplicated();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3].name.to_string(), "GLOBALFREE");
+    }
+
+    #[test]
+    fn e286_apply_internal_fixups() {
+        let segments = vec![Segment {
+            header: SegmentHeader {
+                sector_base: 2,
+                sector_length: 0x10,
+                flags: 0,
+                min_alloc: 0x10,
+            },
+            shift_count: 4,
+            data: None,
+            relocs: RelocationTable { rel_entries: vec![] },
+        }];
+        let relocs = RelocationTable {
+            rel_entries: vec![
+                internal_reloc(0x02, false, 0x00, 0x0010), // selector chain 0x00 -> 0x04
+                internal_reloc(0x05, true, 0x08, 0x0010),  // additive offset
+                internal_reloc(0x03, false, 0x0A, 0x0010), // far pointer
+            ],
+        };
+        let mut data = vec![
+            0x04, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+            0x05, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        relocs.apply_internal_fixups(&mut data, &segments, 0x1000).unwrap();
+
+        // segment at file offset 0x20 loaded at 0x1000:0000 -> paragraph 0x1002
+        assert_eq!(&data[0..2], &[0x02, 0x10]);
+        assert_eq!(&data[4..6], &[0x02, 0x10]);
+        assert_eq!(&data[8..10], &[0x15, 0x00]);
+        assert_eq!(&data[10..14], &[0x10, 0x00, 0x02, 0x10]);
+    }
+
+    #[test]
+    fn e286_resolve_internal_fixup() {
+        let mut layout = empty_layout();
+        layout.ent_tab.entries = vec![
+            (1, Entry::Fixed(FixedEntry { segment: 2, flags: 1, offset: 0x40 })),
+            (2, Entry::Moveable(MoveableEntry { flags: 1, magic: [0xCD, 0x3F], segment: 3, offset: 0x80 })),
+            (3, Entry::Unused),
+            (4, Entry::Fixed(FixedEntry { segment: ENTRY_CONSTANT, flags: 0, offset: 7 })),
+        ];
+        let fixup = |int_seg: u8, int_offset| InternalFixup { int_seg, int_mov: int_seg == 0xFF, int_offset };
+
+        assert_eq!(layout.resolve_internal_fixup(&fixup(1, 0x10)), Some((1, 0x10)));
+        assert_eq!(layout.resolve_internal_fixup(&fixup(0xFF, 1)), Some((2, 0x40)));
+        assert_eq!(layout.resolve_internal_fixup(&fixup(0xFF, 2)), Some((3, 0x80)));
+        assert_eq!(layout.resolve_internal_fixup(&fixup(0xFF, 3)), None);
+        assert_eq!(layout.resolve_internal_fixup(&fixup(0xFF, 4)), None);
+        assert_eq!(layout.resolve_internal_fixup(&fixup(0xFF, 9)), None);
+    }
+
+    #[test]
+    fn e286_relocation_sites() {
+        let data = [0x04, 0x00, 0x00, 0x00, 0x06, 0x00, 0xFF, 0xFF];
+        let chain = internal_reloc(0x02, false, 0x00, 0);
+        let additive = internal_reloc(0x02, true, 0x00, 0);
+
+        assert!(!chain.is_additive() && additive.is_additive());
+        assert_eq!(chain.sites(&data).unwrap(), vec![0x00, 0x04, 0x06]);
+        assert_eq!(additive.sites(&data).unwrap(), vec![0x00]);
+        assert!(internal_reloc(0x02, false, 0x07, 0).sites(&data).is_err());
+    }
+
+    #[test]
+    fn e286_segment_relocation_counts() {
+        let mut segment = Segment {
+            header: SegmentHeader { sector_base: 1, sector_length: 0x20, flags: SEG_RELOCS, min_alloc: 0x20 },
+            shift_count: 9,
+            data: None,
+            relocs: RelocationTable { rel_entries: vec![] },
+        };
+        assert!(!segment.has_relocations());
+        assert_eq!(segment.relocation_count(), 0);
+
+        let mut import = internal_reloc(0x03, false, 0x10, 0);
+        import.rel_type = RelocationType::ImportOrdinal(ImportOrdinal { imp_mod_index: 1, imp_ordinal: 5 });
+        let mut by_name = internal_reloc(0x03, false, 0x14, 0);
+        by_name.rel_type = RelocationType::ImportName(ImportName { imp_mod_index: 1, imp_offset: 1 });
+        let mut fpu = internal_reloc(0x05, true, 0x18, 0);
+        fpu.rel_type = RelocationType::OSFixup(FPUFixup { osf_type: FPUFixupType::FiArqqFjArqq, osf_padd: 0 });
+        segment.relocs.rel_entries = vec![internal_reloc(0x02, false, 0x00, 0), import, by_name, fpu, internal_reloc(0x05, true, 0x04, 0)];
+
+        assert!(segment.has_relocations());
+        assert_eq!(segment.relocation_count(), 5);
+        assert_eq!(segment.internal_relocation_count(), 2);
+        assert_eq!(segment.import_relocation_count(), 2);
+        assert_eq!(segment.fpu_fixup_count(), 1);
+    }
+
+    #[test]
+    fn e286_relocation_chains() {
+        // 0x00 -> 0x04 -> 0x06 -> end, 0x02 -> 0x0A is outside of segment
+        let data = [0x04, 0x00, 0x0A, 0x00, 0x06, 0x00, 0xFF, 0xFF];
+        let relocs = RelocationTable {
+            rel_entries: vec![
+                internal_reloc(0x02, false, 0x00, 0),
+                internal_reloc(0x05, true, 0x04, 0),
+                internal_reloc(0x02, false, 0x02, 0),
+            ],
+        };
+
+        assert_eq!(relocs.chain_sites(&data), vec![vec![0x00, 0x04, 0x06], vec![0x04], vec![0x02]]);
+        let chains = relocs.chains(&data);
+        assert!(!chains[0].is_abnormal() && !chains[1].is_abnormal());
+        assert_eq!(chains[2].end, ChainEnd::OutOfBounds(0x0A));
+
+        let chain = internal_reloc(0x02, false, 0x02, 0).chain(&[0x00, 0x00, 0x02, 0x00]);
+        assert_eq!((chain.sites, chain.end), (vec![0x02], ChainEnd::Cycle(0x02)));
+    }
+
+    #[test]
+    fn e286_write_def() {
+        let fixed = Entry::Fixed(FixedEntry {
+            segment: 1,
+            flags: 1,
+            offset: 0,
+        });
+        let mut layout = empty_layout();
+        layout.new_header.e_flags = 0x8002;
+        layout.ent_tab.entries = vec![(1, fixed.clone()), (2, Entry::Unused), (3, fixed.clone()), (4, fixed)];
+        layout.resn_tab.entries = vec![
+            ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("WEP"), ordinal: 1 },
+        ];
+        layout.nres_tab.entries = vec![
+            NonResidentNameEntry { name: pascal("Sample library"), ordinal: 0 },
+            NonResidentNameEntry { name: pascal("Sample.Call"), ordinal: 3 },
+        ];
+
+        let mut def = Vec::new();
+        crate::def::write_ne_def(&layout, &mut def).unwrap();
+
+        let expected = "LIBRARY SAMPLE\n\
+                        DESCRIPTION 'Sample library'\n\
+                        EXPORTS\n    \
+                        WEP @1 RESIDENTNAME\n    \
+                        'Sample.Call' @3\n    \
+                        ORDINAL_4 @4 NONAME\n";
+        assert_eq!(String::from_utf8(def).unwrap(), expected);
+    }
+
+    #[test]
+    fn e286_write_map() {
+        let mut layout = empty_layout();
+        layout.new_header.e_csip = 0x0001_0010;
+        layout.seg_tab = vec![Segment {
+            header: SegmentHeader {
+                sector_base: 1,
+                sector_length: 0x20,
+                flags: 0x0C50,
+                min_alloc: 0x120,
+            },
+            shift_count: 4,
+            data: None,
+            relocs: RelocationTable { rel_entries: vec![] },
+        }];
+        layout.ent_tab.entries = vec![
+            (1, Entry::Fixed(FixedEntry { segment: 1, flags: 1, offset: 0x40 })),
+            (2, Entry::Unused),
+            (3, Entry::Fixed(FixedEntry { segment: 1, flags: 1, offset: 0x10 })),
+        ];
+        layout.resn_tab.entries = vec![
+            ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("WEP"), ordinal: 3 },
+        ];
+
+        let mut map = Vec::new();
+        crate::map::write_ne_map(&layout, &mut map).unwrap();
+
+        let expected = " SAMPLE\n\n \
+                        Start     Length     Class\n \
+                        0001:0000 00120H     CODE\n\n  \
+                        Address         Publics by Value\n\n \
+                        0001:0010       WEP\n \
+                        0001:0040       @1\n\n\
+                        Program entry point at 0001:0010\n";
+        assert_eq!(String::from_utf8(map).unwrap(), expected);
+    }
+
+    #[test]
+    fn e286_entry_table_sizes() {
+        // fixed bundle of 1 entry, then truncated bundle header
+        let file = vec![0x01, 0x01, 0x01, 0x10, 0x00, 0x02];
+
+        assert!(EntryTable::read(&mut Cursor::new(&file), 0, 6).is_err());
+        assert_eq!(EntryTable::read_lenient(&mut Cursor::new(&file), 0, 6).unwrap().entries.len(), 1);
+        // no zero terminator: stops exactly at declared size
+        assert_eq!(EntryTable::read(&mut Cursor::new(&file), 0, 5).unwrap().entries.len(), 1);
+        assert!(EntryTable::read(&mut Cursor::new(&file), 0, 1).is_err());
+    }
+
+    #[test]
+    fn e286_moveable_entry_magic() {
+        // moveable bundle of 2 entries: INT 3Fh thunk, then zeros
+        let file = vec![0x02, 0xFF, 0x01, 0xCD, 0x3F, 0x02, 0x10, 0x00, 0x01, 0x00, 0x00, 0x02, 0x20, 0x00, 0x00];
+        let valid = MoveableEntry { flags: 1, magic: MOVEABLE_ENTRY_MAGIC, segment: 2, offset: 0x10 };
+        let invalid = MoveableEntry { magic: [0, 0], offset: 0x20, ..valid };
+        assert!(valid.validate_magic() && valid.is_valid());
+        assert!(!invalid.validate_magic() && !invalid.is_valid());
+
+        // wrong thunk is read as it is: validate() of layout warns about it
+        let table = EntryTable::read(&mut Cursor::new(&file), 0, 14).unwrap();
+        assert_eq!(table.entries, vec![(1, Entry::Moveable(valid)), (2, Entry::Moveable(invalid))]);
+        assert_eq!(EntryTable::read_lenient(&mut Cursor::new(&file), 0, 14).unwrap(), table);
+
+        let mut layout = empty_layout();
+        layout.ent_tab = table;
+        let warnings: Vec<String> = layout.validate(0).into_iter().filter(|a| a.severity == Severity::Warning).map(|a| a.message).collect();
+        assert!(warnings.contains(&"Moveable entry @2 has no INT 3Fh thunk: 00 00".to_string()));
+        assert!(!warnings.iter().any(|w| w.contains("@1")));
+    }
+
+    #[test]
+    fn e286_entry_ordinals() {
+        // 2 unused entries, then fixed bundle of 1 entry
+        let file = vec![0x02, 0x00, 0x01, 0x01, 0x01, 0x10, 0x00, 0x00, 0x00];
+        let table = EntryTable::read(&mut Cursor::new(&file), 0, 9).unwrap();
+
+        assert_eq!(table.entries.iter().map(|(o, _)| *o).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(matches!(table.by_ordinal(2), Some(Entry::Unused)));
+        assert!(matches!(table.by_ordinal(3), Some(Entry::Fixed(e)) if e.offset == 0x10));
+        assert!(table.by_ordinal(4).is_none());
+    }
+
+    #[test]
+    fn e286_entry_counts() {
+        // 5 unused entries, then fixed bundle of 3 entries in segment #1
+        let mut file = vec![0x05, 0x00, 0x03, 0x01];
+        file.extend_from_slice(&[0x01, 0x10, 0x00, 0x01, 0x20, 0x00, 0x01, 0x30, 0x00]);
+        let table = EntryTable::read(&mut Cursor::new(&file), 0, file.len() as u16).unwrap();
+
+        assert_eq!(table.entry_count(), 8);
+        assert_eq!(table.export_count(), 3);
+        assert!(!table.is_empty());
+        assert!(matches!(table.get_entry(5), Some(Entry::Unused)));
+        assert!(matches!(table.get_entry(7), Some(Entry::Fixed(e)) if e.offset == 0x20));
+        assert!(table.get_entry(0).is_none() && table.get_entry(9).is_none());
+        assert!(EntryTable { entries: vec![] }.is_empty());
+    }
+
+    #[test]
+    fn e286_segment_for_entry() {
+        let mut layout = empty_layout();
+        layout.seg_tab = [0x10, 0x20]
+            .iter()
+            .map(|&sector_base| Segment {
+                header: SegmentHeader { sector_base, sector_length: 0x10, flags: 0, min_alloc: 0 },
+                shift_count: 4,
+                data: None,
+                relocs: RelocationTable { rel_entries: vec![] },
+            })
+            .collect();
+        let fixed = |segment| Entry::Fixed(FixedEntry { segment, flags: 1, offset: 0 });
+        layout.ent_tab.entries = vec![
+            (1, fixed(2)),
+            (2, Entry::Unused),
+            (3, Entry::Moveable(MoveableEntry { flags: 1, magic: [0xCD, 0x3F], segment: 1, offset: 0 })),
+            (4, fixed(ENTRY_CONSTANT)),
+            (5, fixed(7)),
+        ];
+
+        assert_eq!(layout.segment_for_entry(0).unwrap().header.sector_base, 0x20);
+        assert!(layout.segment_for_entry(1).is_none());
+        assert_eq!(layout.segment_for_entry(2).unwrap().header.sector_base, 0x10);
+        assert!(layout.segment_for_entry(3).is_none());
+        assert!(layout.segment_for_entry(4).is_none());
+        assert!(layout.segment_for_entry(5).is_none());
+        assert_eq!(layout.segment_for_ordinal(3).unwrap().header.sector_base, 0x10);
+        assert!(layout.segment_for_ordinal(9).is_none());
+    }
+
+    #[test]
+    fn e286_entry_table_garbage() {
+        let mut seed: u32 = 0x1234_5678;
+        for size in [0_usize, 1, 2, 3, 7, 64, 513, 4096, 0xFFFF] {
+            let file: Vec<u8> = (0..size)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (seed >> 16) as u8
+                })
+                .collect();
+            let _ = EntryTable::read(&mut Cursor::new(&file), 0, size as u16);
+            let _ = EntryTable::read_lenient(&mut Cursor::new(&file), 0, size as u16);
+        }
+    }
+
+    #[test]
+    fn e286_regions() {
+        let mut layout = empty_layout();
+        layout.dos_header.e_lfanew = 0x80;
+        layout.dos_header.e_cp = 1;
+        layout.dos_header.e_cblp = 0x80;
+        layout.dos_header.e_cparhdr = 4;
+        layout.new_header.e_cseg = 1;
+        layout.new_header.e_seg_tab = 0x40;
+        layout.new_header.e_rsrc_tab = 0x48;
+        layout.new_header.e_resn_tab = 0x48;
+        layout.new_header.e_mod_tab = 0x52;
+        layout.new_header.e_imp_tab = 0x52;
+        layout.new_header.e_ent_tab = 0x54;
+        layout.new_header.e_cb_ent = 4;
+        layout.resn_tab.entries = vec![ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 }];
+        layout.seg_tab = vec![Segment {
+            header: SegmentHeader { sector_base: 0x10, sector_length: 0x20, flags: SEG_RELOCS, min_alloc: 0 },
+            shift_count: 4,
+            data: None,
+            relocs: RelocationTable { rel_entries: vec![] },
+        }];
+
+        let regions = layout.regions();
+        let spans: Vec<(u64, u64, RegionKind)> = regions.iter().map(|r| (r.start, r.len, r.kind)).collect();
+
+        assert_eq!(
+            spans,
+            [
+                (0x00, 0x40, RegionKind::Header),
+                (0x40, 0x40, RegionKind::DosStub),
+                (0x80, 0x40, RegionKind::Header),
+                (0xC0, 0x08, RegionKind::Table),
+                (0xC8, 0x0A, RegionKind::Table),
+                (0xD2, 0x02, RegionKind::Table),
+                (0xD4, 0x04, RegionKind::Table),
+                (0xD8, 0x28, RegionKind::Unclaimed),
+                (0x100, 0x20, RegionKind::Data),
+                (0x120, 0x02, RegionKind::Relocations),
+            ]
+        );
+        let at = regions::regions_at(&regions, 0xC4);
+        assert_eq!(at.len(), 1);
+        assert_eq!(at[0].name, "Segment table");
+        assert!(regions::regions_at(&regions, 0x122).is_empty());
+    }
+
+    #[test]
+    fn e286_fastload_area() {
+        let mut layout = empty_layout();
+        let header = &mut layout.new_header;
+        header.e_os = 0x02;
+        header.e_align = 4;
+        header.e_ret_thunk = 0x10;
+        header.e_segref_thunk = 0x02;
+        assert_eq!(header.fastload_area(), None);
+
+        header.e_flag_others = 0x08;
+        assert_eq!(header.fastload_area(), Some((0x100, 0x20)));
+        let regions = layout.regions();
+        let area = regions.iter().find(|r| r.name == "Fast-load area").unwrap();
+        assert_eq!((area.start, area.len, area.kind), (0x100, 0x20, RegionKind::Data));
+
+        // OS/2 keeps thunk offsets in the same fields
+        layout.new_header.e_os = 0x01;
+        assert_eq!(layout.new_header.fastload_area(), None);
+    }
+
+    #[test]
+    fn e286_family_api_bound() {
+        let mut layout = empty_layout();
+        layout.new_header.e_os = 0x01;
+        layout.dos_header.e_cparhdr = 4;
+        layout.dos_header.e_cp = 4;
+        layout.dos_header.e_cblp = 0x40;
+        layout.dos_header.e_crlc = 12;
+        layout.imp_tab = vec![ImportsTable {
+            seg_number: 1,
+            imp_list: vec![DllImport::new(pascal("DOSCALLS"), PascalString::empty(), 5, 0, 0)],
+        }];
+
+        // DOS program with table of emulated modules
+        let mut file = vec![0_u8; 0x640];
+        file[0x300..0x308].copy_from_slice(b"DOSCALLS");
+        let mut reader = Cursor::new(file);
+        assert!(layout.is_family_api_bound(&mut reader).unwrap());
+        assert_eq!(layout.family_api_loader(&mut reader).unwrap(), Some((0x40, 0x600)));
+
+        // large DOS program without the loader
+        let mut other = Cursor::new(vec![0x90_u8; 0x640]);
+        assert!(!layout.is_family_api_bound(&mut other).unwrap());
+        assert_eq!(layout.family_api_loader(&mut other).unwrap(), None);
+        // signature out of DOS program
+        let mut header_only = Cursor::new([&b"DOSCALLS"[..], &[0; 0x638]].concat());
+        assert!(!layout.is_family_api_bound(&mut header_only).unwrap());
+
+        // plain "cannot be run in DOS mode" stub
+        let mut stub = layout.clone();
+        (stub.dos_header.e_cp, stub.dos_header.e_crlc) = (1, 0);
+        assert!(!stub.is_family_api_bound(&mut reader).unwrap());
+        assert_eq!(stub.family_api_loader(&mut reader).unwrap(), None);
+
+        let mut library = layout.clone();
+        library.new_header.e_flags = 0x8000 | 0x0002;
+        assert!(!library.is_family_api_bound(&mut reader).unwrap());
+
+        layout.new_header.e_os = 0x02;
+        assert!(!layout.is_family_api_bound(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn e286_headers_fixture() {
+        // Fields are laid out with distinct bytes, so swapped
+        // halves or host byte order show up as wrong values.
+        let mut mz = vec![0u8; 0x40];
+        mz[0..2].copy_from_slice(b"MZ");
+        mz[0x02..0x04].copy_from_slice(&[0x34, 0x12]); // e_cblp
+        mz[0x12..0x14].copy_from_slice(&[0xCD, 0xAB]); // e_crc
+        mz[0x18..0x1A].copy_from_slice(&[0x40, 0x00]); // e_lfarlc
+        mz[0x1C..0x1E].copy_from_slice(&[0x01, 0x02]); // e_res[0]
+        mz[0x3A..0x3C].copy_from_slice(&[0x03, 0x04]); // e_res2[9]
+        mz[0x3C..0x40].copy_from_slice(&[0x80, 0x00, 0x00, 0x00]); // e_lfanew
+
+        let mut ne = vec![0u8; 0x40];
+        ne[0..2].copy_from_slice(b"NE");
+        ne[0x02] = 5; // e_link_maj
+        ne[0x03] = 10; // e_link_min
+        ne[0x04..0x06].copy_from_slice(&[0x78, 0x56]); // e_ent_tab
+        ne[0x08..0x0C].copy_from_slice(&[0x78, 0x56, 0x34, 0x12]); // e_load_crc
+        ne[0x14..0x18].copy_from_slice(&[0x00, 0x01, 0x02, 0x00]); // e_csip
+        ne[0x2C..0x30].copy_from_slice(&[0x00, 0x04, 0x00, 0x00]); // e_nres_tab
+        ne[0x36] = 2; // e_os
+        ne[0x3E..0x40].copy_from_slice(&[0x0A, 0x03]); // expected_win_ver
+
+        let mut image = mz.clone();
+        image.resize(0x80, 0);
+        image.extend_from_slice(&ne);
+        let mut reader = Cursor::new(image);
+
+        let mz = MzHeader::read(&mut reader).unwrap();
+        assert_eq!(mz.e_magic, 0x5A4D);
+        assert_eq!(mz.e_cblp, 0x1234);
+        assert_eq!(mz.e_crc, 0xABCD);
+        assert_eq!(mz.e_lfarlc, 0x40);
+        assert_eq!(mz.e_res[0], 0x0201);
+        assert_eq!(mz.e_res2[9], 0x0403);
+        assert_eq!(mz.e_lfanew, 0x80);
+
+        let ne = NewExecutableHeader::read(&mut reader, mz.e_lfanew).unwrap();
+        assert_eq!(&ne.e_magic, b"NE");
+        assert_eq!(ne.linker_version(), (5, 10));
+        assert_eq!(ne.e_ent_tab, 0x5678);
+        assert_eq!(ne.e_load_crc, 0x1234_5678);
+        assert_eq!(ne.e_csip, 0x0002_0100);
+        assert_eq!(ne.e_nres_tab, 0x400);
+        assert_eq!(ne.target_os(), OS::Windows286);
+        assert_eq!(ne.expected_windows_version(), Some((3, 10)));
+    }
+
+    #[test]
+    fn e286_header_display() {
+        let mut header = NewExecutableHeader::zeroed();
+        header.e_magic = *b"NE";
+        header.e_link_maj = 5;
+        header.e_link_min = 10;
+        header.e_flags = 0x0001;
+        header.e_cseg = 2;
+        header.e_cmod = 1;
+        header.e_os = 2;
+        header.expected_win_ver = [10, 3];
+        header.e_csip = 0x0001_0010;
+        header.e_sssp = 0x0002_0400;
+        header.e_heap = 1024;
+
+        let expected = "NE executable module\n  \
+                        Linker:       5.10\n  \
+                        Segments:     2\n  \
+                        Modules:      1\n  \
+                        OS:           Windows\n  \
+                        Windows:      3.10\n  \
+                        Entry point:  0001:0010\n  \
+                        Stack:        0002:0400\n  \
+                        Heap:         1024\n  \
+                        Flags:        SINGLEDATA";
+        assert_eq!(header.to_string(), expected);
+    }
+
+    #[test]
+    fn e286_layout_display() {
+        let mut layout = empty_layout();
+        layout.new_header.e_os = 1;
+        layout.resn_tab.entries = vec![ResidentNameEntry {
+            name: pascal("SAMPLE"),
+            ordinal: 0,
+        }];
+        layout.seg_tab = vec![Segment::read(&mut Cursor::new(segment_record(0, 0x10, 0)), 4).unwrap()];
+        layout.imp_tab = vec![ImportsTable {
+            seg_number: 1,
+            imp_list: vec![DllImport::new(pascal("DOSCALLS"), PascalString::empty(), 5, 0, 0)],
+        }];
+
+        let expected = "NE module SAMPLE\n  \
+                        OS:           OS/2\n  \
+                        Segments:     1\n  \
+                        Entries:      0\n  \
+                        Names:        1 resident, 0 non-resident\n  \
+                        Modules:      0\n  \
+                        Imports:      1\n  \
+                        Resources:    0";
+        assert_eq!(layout.to_string(), expected);
+
+        // parsed structures are comparable for snapshot tests
+        let copy = layout.clone();
+        assert_eq!(copy.seg_tab[0].header, layout.seg_tab[0].header);
+        assert_eq!(copy.resn_tab, layout.resn_tab);
+        assert_eq!(copy.new_header, layout.new_header);
+    }
+
+    #[test]
+    fn e286_dump() {
+        let mut layout = empty_layout();
+        layout.new_header.e_os = 2;
+        let mut code = Segment::read(&mut Cursor::new(segment_record(2, 0x40, 0x0050)), 4).unwrap();
+        code.header.flags |= SEG_RELOCS;
+        code.relocs = RelocationTable {
+            rel_entries: vec![
+                internal_reloc(3, false, 0x10, 0x0100),
+                RelocationEntry {
+                    rel_rtp: 2,
+                    rel_atp: 3,
+                    rel_add: false,
+                    rel_seg_ptr: 0x20,
+                    rel_type: RelocationType::ImportName(ImportName {
+                        imp_mod_index: 1,
+                        imp_offset: 1,
+                    }),
+                },
+            ],
+        };
+        let data = Segment::read(&mut Cursor::new(segment_record(0, 0, 0x0081)), 4).unwrap();
+        layout.seg_tab = vec![code, data];
+        layout.imp_tab = vec![ImportsTable {
+            seg_number: 1,
+            imp_list: vec![DllImport::new(pascal("KERNEL"), pascal("GLOBALALLOC"), 0, 0, 0x20)],
+        }];
+        layout.ent_tab.entries = vec![
+            (1, Entry::Fixed(FixedEntry { segment: 1, flags: 1, offset: 0x10 })),
+            (2, Entry::Unused),
+            (3, Entry::Fixed(FixedEntry { segment: ENTRY_CONSTANT, flags: 1, offset: 0x1234 })),
+        ];
+        layout.resn_tab.entries = vec![
+            ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("WEP"), ordinal: 1 },
+        ];
+        layout.rsrc_tab.types = vec![rsrctab::ResourceType {
+            type_id: ResourceId::Integer(RT_ICON),
+            resources: vec![rsrctab::Resource {
+                id: ResourceId::Integer(1),
+                offset: 0x200,
+                length: 0x30,
+                flags: 0x0030,
+                data: vec![],
+            }],
+        }];
+
+        let file = ExecutableFile::Ne(layout);
+        let mut out = Vec::new();
+        for section in [dump::write_objects, dump::write_exports, dump::write_imports] {
+            section(&file, &mut out).unwrap();
+        }
+        dump::write_fixups(&file, &mut out).unwrap();
+        dump::write_resources(&file, &mut out).unwrap();
+
+        let expected = "\
+Segments
+ no. type offset   file  mem   flags
+   1 CODE 00000020 00040 10000 moveable, preload, relocations
+   2 DATA 00000000 00000 10000 read-only
+Exports
+ ord seg  offset name
+   1 0001 0010   WEP
+   3 abs  1234
+Imports
+ module procedure   references
+ KERNEL GLOBALALLOC 1
+Fixups
+ segment 1
+  offset type     target
+  0010   far16:16 0001:0100
+  0020   far16:16 KERNEL.GLOBALALLOC
+Resources
+ type id offset   length flags
+ ICON 1  00000200 00030  moveable, pure
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn e286_target_os() {
+        let mut header = NewExecutableHeader::zeroed();
+        header.expected_win_ver = [10, 3];
+        header.e_flag_others = 0x0008;
+
+        header.e_os = 2;
+        assert_eq!(header.target_os(), OS::Windows286);
+        assert_eq!(header.expected_windows_version(), Some((3, 10)));
+        assert!(matches!(header.extended_flags(), ExtendedFlags::Windows(f) if f.fastload_area));
+
+        header.e_os = 1;
+        assert_eq!(header.target_os(), OS::Os2);
+        assert_eq!(header.expected_windows_version(), None);
+        assert!(matches!(header.extended_flags(), ExtendedFlags::Os2(f) if f.gangload_area));
+
+        for (e_os, os) in [(5, OS::Boss), (0x81, OS::PharLapOs2), (0x82, OS::PharLapWindows), (0x42, OS::Unknown(0x42))] {
+            header.e_os = e_os;
+            assert_eq!(header.target_os(), os);
+        }
+        assert!(matches!(header.extended_flags(), ExtendedFlags::Unknown(0x08)));
+        assert_eq!(OS::Unknown(0x42).to_string(), "unknown (0x42)");
+    }
+
+    #[test]
+    fn e286_validate() {
+        let mut layout = empty_layout();
+        layout.dos_header.e_lfanew = 0x20;
+        layout.new_header.e_cseg = 1;
+        layout.new_header.e_seg_tab = 0x40;
+        layout.new_header.e_nres_tab = 0x110;
+        layout.new_header.e_cbnres = 0x10;
+        layout.resn_tab.entries = vec![
+            ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("WEP"), ordinal: 5 },
+        ];
+        layout.seg_tab = vec![Segment {
+            header: SegmentHeader { sector_base: 0x10, sector_length: 0x20, flags: 0, min_alloc: 0 },
+            shift_count: 4,
+            data: None,
+            relocs: RelocationTable { rel_entries: vec![] },
+        }];
+
+        let anomalies = layout.validate(0x118);
+        let messages: Vec<(Severity, &str)> = anomalies.iter().map(|a| (a.severity, a.message.as_str())).collect();
+
+        assert!(messages.contains(&(Severity::Error, "e_lfanew 0x20 points inside the MZ header")));
+        assert!(messages.contains(&(Severity::Warning, "NE header overlaps MZ header")));
+        assert!(messages.contains(&(Severity::Warning, "Non-resident names table is truncated by end of file (0x118)")));
+        assert!(messages.contains(&(Severity::Warning, "Name WEP refers to missing entry @5")));
+        assert!(messages.contains(&(Severity::Warning, "Segment #1 data extends past non-resident names table")));
+    }
+
+    #[test]
+    fn mz_header_display() {
+        let mut header = MzHeader::zeroed();
+        header.e_magic = 0x5A4D;
+        header.e_cp = 3;
+        header.e_cblp = 0x80;
+        header.e_cparhdr = 4;
+        header.e_sp = 0xB8;
+        header.e_maxalloc = 0xFFFF;
+        header.e_crc = 1;
+        header.e_lfanew = 0x80;
+
+        assert_eq!(header.image_size(), 1152);
+        let expected = "MZ executable\n  \
+                        Image size:   1152 bytes\n  \
+                        Header size:  64 bytes\n  \
+                        Relocations:  0\n  \
+                        Stack:        0000:00B8\n  \
+                        Entry point:  0000:0000\n  \
+                        Allocation:   0x0000..0xFFFF paragraphs\n  \
+                        CRC:          invalid\n  \
+                        Extension:    at 0x00000080";
+        assert_eq!(header.to_string(), expected);
+    }
+
+    /// 2x2 4-bpp DIB with full 16 colors palette.
+    /// `height` is doubled for icons.
+    fn dib_4bpp(height: i32) -> Vec<u8> {
+        let mut dib = Vec::new();
+        for v in [40_u32, 2, height as u32] {
+            dib.extend_from_slice(&v.to_le_bytes());
+        }
+        dib.extend_from_slice(&1_u16.to_le_bytes());
+        dib.extend_from_slice(&4_u16.to_le_bytes());
+        dib.extend_from_slice(&[0; 24]);
+        dib.extend_from_slice(&[0x80; 16 * 4]);
+        dib.extend_from_slice(&[0x12; 8]);
+        dib
+    }
+
+    #[test]
+    fn e286_resources() {
+        // resource table: shift 4, RT_ICON #1 at 0x100, RT_GROUP_ICON named "APP" at 0x200
+        let mut file = vec![0_u8; 0x300];
+        let table: Vec<u16> = vec![
+            4,
+            0x8000 | RT_ICON, 1, 0, 0,
+            0x10, 0x08, 0x1030, 0x8001, 0, 0,
+            0x8000 | RT_GROUP_ICON, 1, 0, 0,
+            0x20, 0x02, 0x1030, 0x2C, 0, 0,
+            0,
+        ];
+        let table: Vec<u8> = table.iter().flat_map(|w| w.to_le_bytes()).collect();
+        file[..table.len()].copy_from_slice(&table);
+        file[0x2C..0x30].copy_from_slice(b"\x03APP");
+
+        let icon = dib_4bpp(4);
+        file[0x100..0x100 + icon.len()].copy_from_slice(&icon);
+        let group = [0, 0, 1, 0, 1, 0, 2, 2, 16, 0, 1, 0, 4, 0, icon.len() as u8, 0, 0, 0, 1, 0];
+        file[0x200..0x200 + group.len()].copy_from_slice(&group);
+
+        let rsrc_tab = ResourceTable::read(&mut Cursor::new(&file), 0).unwrap();
+        assert_eq!(rsrc_tab.types.len(), 2);
+        assert_eq!(rsrc_tab.types[1].resources[0].id, ResourceId::Name(pascal("APP")));
+        assert_eq!(rsrc_tab.find(RT_ICON, 1).unwrap().offset, 0x100);
+
+        let parsed = Icon::parse(&rsrc_tab.find(RT_ICON, 1).unwrap().data).unwrap();
+        assert_eq!((parsed.width(), parsed.height()), (2, 2));
+        assert_eq!(parsed.bitmap.palette_size, 64);
+
+        let group = GroupIcon::parse(&rsrc_tab.types[1].resources[0].data).unwrap();
+        let ico = group.to_ico_bytes(&rsrc_tab).unwrap();
+        assert_eq!(&ico[..6], &[0, 0, 1, 0, 1, 0]);
+        assert_eq!(&ico[6..10], &[2, 2, 16, 0]);
+        // image offset right after directory, image is whole RT_ICON data (0x80 bytes)
+        assert_eq!(&ico[18..22], &22_u32.to_le_bytes());
+        assert_eq!(ico.len(), 22 + 0x80);
+    }
+
+    #[test]
+    fn e286_bitmap_file() {
+        let bitmap = Bitmap::parse(&dib_4bpp(2)).unwrap();
+        let bmp = bitmap.to_bmp_bytes();
+
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(u32::from_le_bytes(bmp[2..6].try_into().unwrap()), 14 + 40 + 64 + 8);
+        assert_eq!(u32::from_le_bytes(bmp[10..14].try_into().unwrap()), 14 + 40 + 64);
+
+        let mut core = vec![12, 0, 0, 0, 2, 0, 2, 0, 1, 0, 1, 0];
+        core.extend_from_slice(&[0xFF; 6]);
+        core.extend_from_slice(&[0; 8]);
+        assert_eq!(Bitmap::parse(&core).unwrap().bits_offset(), 18);
+        assert!(Bitmap::parse(&core[..14]).is_err());
+    }
+
+    #[test]
+    fn e286_bitmap_untrusted_sizes() {
+        // biClrUsed of 4G colors: palette size doesn't fit in u32
+        let mut dib = dib_4bpp(2);
+        dib[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(Bitmap::parse(&dib).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(Icon::parse(&dib).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // OS/2 2.x header with cclrUsed of 3.2G colors and no palette at all
+        let mut os2 = b"BM".to_vec();
+        os2.extend_from_slice(&[0; 12]);
+        os2.extend_from_slice(&40_u32.to_le_bytes());
+        os2.extend_from_slice(&[2, 0, 0, 0, 2, 0, 0, 0, 1, 0, 8, 0]);
+        os2.resize(14 + 32, 0);
+        os2.extend_from_slice(&0xC000_0000_u32.to_le_bytes());
+        os2.resize(14 + 40, 0);
+        assert_eq!(Os2Bitmap::parse(&os2).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(Os2BitmapArray::parse(&os2).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn mz_relocations() {
+        let mut header = MzHeader::zeroed();
+        header.e_lfarlc = 2;
+        header.e_crlc = 2;
+        let file = [0, 0, 0x10, 0, 0x20, 0, 0x30, 0, 0x40, 0];
+        let relocs = MzRelocationTable::read(&mut Cursor::new(&file), &header).unwrap();
+
+        assert_eq!(relocs.count(), 2);
+        assert!(!relocs.is_empty());
+        let pointers: Vec<(u16, u16)> = (&relocs).into_iter().map(|p| (p.segment, p.offset)).collect();
+        // offset word first, segment word second
+        assert_eq!(pointers, [(0x20, 0x10), (0x40, 0x30)]);
+        assert_eq!(relocs.iter().count(), 2);
+        assert_eq!(relocs.len(), 2);
+        assert_eq!(relocs[1].offset, 0x30);
+        assert!(relocs.get(2).is_none());
+        let owned: Vec<FarPointer> = relocs.into_iter().collect();
+        assert_eq!(owned[0].segment, 0x20);
+    }
+
+    #[test]
+    fn mz_relocation_bytes() {
+        // 1234:5678 as a DOS loader reads it: offset 5678h, then segment 1234h
+        let mut builder = MzBuilder::new(vec![0xCB]);
+        builder.relocations = vec![FarPointer { segment: 0x1234, offset: 0x5678 }];
+        let program = builder.build().unwrap();
+        let header = MzHeader::read(&mut Cursor::new(&program)).unwrap();
+        let at = header.e_lfarlc as usize;
+        assert_eq!(&program[at..at + 4], &[0x78, 0x56, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn mz_builder_round_trip() {
+        let mut builder = MzBuilder::new(vec![0x90; 0x123]);
+        builder.relocations = vec![FarPointer { segment: 0, offset: 0x10 }, FarPointer { segment: 1, offset: 0x20 }, FarPointer { segment: 0, offset: 0x30 }];
+        (builder.cs, builder.ip, builder.ss, builder.sp) = (0, 0x10, 0x20, 0x100);
+        builder.min_alloc = 0x10;
+        let program = builder.build().unwrap();
+
+        let mut reader = Cursor::new(&program);
+        let header = MzHeader::read(&mut reader).unwrap();
+        assert_eq!(header, builder.header().unwrap());
+        let relocs = MzRelocationTable::read(&mut reader, &header).unwrap();
+        assert_eq!(relocs.relocations, builder.relocations);
+        assert_eq!((header.e_cs, header.e_ip, header.e_ss, header.e_sp, header.e_minalloc), (0, 0x10, 0x20, 0x100, 0x10));
+
+        let mut written = Vec::new();
+        header.write(&mut written).unwrap();
+        assert_eq!(written, program[..0x40].to_vec());
+        assert_eq!(MzHeader::read(&mut Cursor::new(&written)).unwrap(), header);
+    }
+
+    #[test]
+    fn mz_builder_geometry() {
+        for (load_module, relocations) in [(0_usize, 0_usize), (5, 0), (0x1C0, 0), (0x1C0, 1), (0x1000, 4), (0x10000, 300)] {
+            let mut builder = MzBuilder::new(vec![0xCC; load_module]);
+            builder.relocations = vec![FarPointer { segment: 0, offset: 0 }; relocations];
+            let program = builder.build().unwrap();
+            let header = builder.header().unwrap();
+
+            assert_eq!(header.image_size(), program.len() as u64);
+            assert_eq!(header.load_module_size(), load_module as u64);
+            assert_eq!((header.e_crlc as usize, header.e_lfarlc), (relocations, 0x40));
+            // relocations fit into the header, header is whole paragraphs
+            assert!(header.e_cparhdr as usize * 16 >= 0x40 + relocations * 4);
+            assert!(header.e_cparhdr as usize * 16 < 0x40 + relocations * 4 + 16);
+            assert!(header.e_cblp < 512);
+            assert_eq!(header.e_cp as usize, program.len().div_ceil(512));
+            assert_eq!(&program[header.e_cparhdr as usize * 16..], vec![0xCC; load_module].as_slice());
+        }
+        // 0x1C0 bytes of code after 0x40 bytes of header are exactly one page
+        let header = MzBuilder::new(vec![0; 0x1C0]).header().unwrap();
+        assert_eq!((header.e_cp, header.e_cblp), (1, 0));
+
+        let mut builder = MzBuilder::new(Vec::new());
+        builder.relocations = vec![FarPointer { segment: 0, offset: 0 }; 0x10000];
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn e286_read_from_cursor() {
+        let mut header = NewExecutableHeader::zeroed();
+        header.e_magic = *b"NE";
+        // every table is empty and starts right after the header
+        for table in [&mut header.e_ent_tab, &mut header.e_seg_tab, &mut header.e_rsrc_tab, &mut header.e_resn_tab, &mut header.e_mod_tab, &mut header.e_imp_tab] {
+            *table = 0x40;
+        }
+        header.e_nres_tab = 0x80;
+
+        let mut builder = MzBuilder::new(Vec::new());
+        builder.lfanew = 0x40;
+        let mut file = builder.build().unwrap();
+        file.extend_from_slice(bytemuck::bytes_of(&header));
+        file.extend_from_slice(&[0, 0]);
+
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(&layout.new_header.e_magic, b"NE");
+        assert!(layout.seg_tab.is_empty() && layout.resn_tab.entries.is_empty());
+        assert!(matches!(ExecutableFile::read_from(&mut Cursor::new(&file)).unwrap(), ExecutableFile::Ne(_)));
+
+        let program = MzBuilder::new(vec![0xCD, 0x20]).build().unwrap();
+        let layout = MzExecutableLayout::read_from(&mut Cursor::new(&program)).unwrap();
+        assert_eq!(layout.header.load_module_size(), 2);
+        assert!(matches!(ExecutableFile::read_from(&mut Cursor::new(&program)).unwrap(), ExecutableFile::Mz(_)));
+    }
+
+    #[test]
+    fn e286_executable_file_from_slice() {
+        let module = ne_builder_fixture().build().unwrap();
+        let file = ExecutableFile::read_from_slice(&module).unwrap();
+        let ExecutableFile::Ne(layout) = file else { panic!("NE module is read as other format") };
+        assert_eq!(layout.resn_tab.entries[0].name.to_string(), "HELLO");
+
+        let program = MzBuilder::new(vec![0xCD, 0x20]).build().unwrap();
+        assert!(matches!(ExecutableFile::read_from_slice(&program).unwrap(), ExecutableFile::Mz(_)));
+        // NE header doesn't fit: pointer is invalid, not just a short file
+        assert_eq!(ExecutableFile::read_from_slice(&module[..0x50]).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn mz_invalid_lfanew() {
+        use crate::exe::InvalidLfanew;
+        let module = ne_builder_fixture().build().unwrap();
+        let with_lfanew = |lfanew: u32| {
+            let mut file = module.clone();
+            file[0x3C..0x40].copy_from_slice(&lfanew.to_le_bytes());
+            file
+        };
+        let lfanew_error = |error: std::io::Error| {
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+            *error.get_ref().unwrap().downcast_ref::<InvalidLfanew>().unwrap()
+        };
+        let file_len = module.len() as u64;
+
+        let far = with_lfanew(0xFFFF_FFF0);
+        let error = lfanew_error(NewExecutableLayout::read_from(&mut Cursor::new(&far)).unwrap_err());
+        assert_eq!(error, InvalidLfanew::PastEnd { e_lfanew: 0xFFFF_FFF0, header_len: 0x40, file_len });
+        assert!(error.to_string().starts_with("Extended header pointer 0xFFFFFFF0 is invalid"));
+        let error = lfanew_error(crate::exe386::LinearExecutableLayout::read_from(&mut Cursor::new(&far)).unwrap_err());
+        assert!(matches!(error, InvalidLfanew::PastEnd { header_len: 0xB8, .. }));
+        let inside = with_lfanew(0x20);
+        assert_eq!(lfanew_error(NewExecutableLayout::read_from(&mut Cursor::new(&inside)).unwrap_err()), InvalidLfanew::InsideHeader(0x20));
+        assert_eq!(lfanew_error(NewExecutableLayout::read_from(&mut Cursor::new(with_lfanew(0))).unwrap_err()), InvalidLfanew::Zero);
+
+        // auto-detection falls back to DOS program and warns about the pointer
+        let file = ExecutableFile::read_from_slice(&far).unwrap();
+        assert!(matches!(file, ExecutableFile::Mz(_)));
+        let anomalies = file.validate(file_len);
+        assert!(anomalies.iter().any(|a| a.severity == Severity::Warning && a.offset == 0x3C && a.message.contains("0xFFFFFFF0")));
+        let file = ExecutableFile::read_from_slice(&inside).unwrap();
+        assert!(matches!(file, ExecutableFile::Mz(_)));
+        assert!(file.validate(file_len).iter().any(|a| a.severity == Severity::Error && a.offset == 0x3C));
+        // valid pointer is not an anomaly
+        let program = MzBuilder::new(vec![0xCD, 0x20]).build().unwrap();
+        let file = ExecutableFile::read_from_slice(&program).unwrap();
+        assert!(file.validate(program.len() as u64).iter().all(|a| a.offset != 0x3C));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn e286_executable_file_mmap() {
+        let module = ne_builder_fixture().build().unwrap();
+        let path = std::env::temp_dir().join(format!("os2omf-mmap-{}.dll", std::process::id()));
+        std::fs::write(&path, &module).unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let mapped = ExecutableFile::open_mmap(path_str).unwrap();
+        let buffered = ExecutableFile::open(path_str).unwrap();
+        assert_eq!(mapped.to_string(), buffered.to_string());
+        assert!(matches!(mapped, ExecutableFile::Ne(_)));
+
+        // empty file can't be mapped: it's read as usual and fails the same way
+        std::fs::write(&path, []).unwrap();
+        assert_eq!(ExecutableFile::open_mmap(path_str).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn e286_resident_names_read() {
+        let mut file = vec![0xEE_u8; 4];
+        file.extend_from_slice(b"\x06SAMPLE\x00\x00\x03WEP\x01\x00\x00");
+
+        let mut reader = Cursor::new(&file);
+        reader.set_position(file.len() as u64);
+        let table = ResidentNameTable::read(&mut reader, 4).unwrap();
+        assert_eq!(table.entries, vec![
+            ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("WEP"), ordinal: 1 },
+        ]);
+        assert!(ResidentNameTable::read(&mut reader, file.len() as u64).is_err());
+    }
+
+    fn ne_builder_fixture() -> NeBuilder {
+        let mut builder = NeBuilder::new("HELLO");
+        builder.description = "Hello DLL".to_string();
+        builder.cs = 1;
+        // call far KERNEL.70; call far USER.MessageBox; retf
+        builder.segments.push(SegmentSpec::code(vec![0x9A, 0, 0, 0, 0, 0x9A, 0, 0, 0, 0, 0xCB]));
+        builder.segments.push(SegmentSpec::data(b"Hello\0".to_vec()));
+        builder.exports.push(ExportSpec::new("Hello", 1, 1, 0));
+        builder.exports.push(ExportSpec { moveable: true, resident: false, ..ExportSpec::new("Hidden", 3, 2, 0) });
+        builder.imports.push(ImportSpec::new("KERNEL", ImportEntry::Ordinal(70), 1, 1));
+        builder.imports.push(ImportSpec::new("USER", ImportEntry::Name("MessageBox".to_string()), 1, 6));
+        builder
+    }
+
+    #[test]
+    fn e286_unreadable_resources() {
+        let mut file = ne_builder_fixture().build().unwrap();
+        let lfanew = u32::from_le_bytes(file[0x3C..0x40].try_into().unwrap()) as usize;
+        // e_rsrc_tab points to the NE magic: alignment shift 0x454E
+        file[lfanew + 0x24..lfanew + 0x26].copy_from_slice(&0_u16.to_le_bytes());
+
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        assert!(layout.rsrc_tab.types.is_empty());
+        assert_eq!(layout.seg_tab.len(), 2);
+        let anomaly = layout.validate(file.len() as u64).into_iter().find(|a| a.message.starts_with("Resource table is unreadable")).unwrap();
+        assert_eq!((anomaly.severity, anomaly.offset), (Severity::Error, lfanew as u64));
+        assert!(anomaly.message.contains("alignment shift"));
+
+        // OS/2 module which declares more resource segments than it has
+        let mut builder = ne_builder_fixture();
+        builder.os = 1;
+        let mut file = builder.build().unwrap();
+        let seg_tab = file[lfanew + 0x22..lfanew + 0x24].to_vec();
+        file[lfanew + 0x24..lfanew + 0x26].copy_from_slice(&seg_tab);
+        file[lfanew + 0x34..lfanew + 0x36].copy_from_slice(&5_u16.to_le_bytes());
+
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+        assert!(layout.rsrc_tab.types.is_empty());
+        assert_eq!(layout.rsrc_error.as_deref(), Some("5 resources but 2 segments"));
+    }
+
+    #[test]
+    fn e286_builder_round_trip() {
+        let file = ne_builder_fixture().build().unwrap();
+        let mut reader = Cursor::new(&file);
+        let layout = NewExecutableLayout::read_from(&mut reader).unwrap();
+        let header = &layout.new_header;
+
+        assert_eq!((header.e_cseg, header.e_cmod, header.e_cmov_ent, header.e_align), (2, 2, 1, 4));
+        assert_eq!(header.e_csip, 0x0001_0000);
+        assert_eq!(header.e_rsrc_tab, header.e_resn_tab);
+        assert!(layout.rsrc_tab.types.is_empty());
+        assert_eq!(layout.resn_tab.entries, vec![
+            ResidentNameEntry { name: pascal("HELLO"), ordinal: 0 },
+            ResidentNameEntry { name: pascal("Hello"), ordinal: 1 },
+        ]);
+        assert_eq!(layout.nres_tab.entries, vec![
+            NonResidentNameEntry { name: pascal("Hello DLL"), ordinal: 0 },
+            NonResidentNameEntry { name: pascal("Hidden"), ordinal: 3 },
+        ]);
+        assert_eq!(layout.ent_tab.entries, vec![
+            (1, Entry::Fixed(FixedEntry { segment: 1, flags: 1, offset: 0 })),
+            (2, Entry::Unused),
+            (3, Entry::Moveable(MoveableEntry { flags: 1, magic: [0xCD, 0x3F], segment: 2, offset: 0 })),
+        ]);
+
+        let imports: Vec<(String, String)> = layout.imp_tab[0].imp_list.iter().map(|i| (i.dll_name_str(), i.display_symbol())).collect();
+        assert_eq!(imports, vec![("KERNEL".to_string(), "#70".to_string()), ("USER".to_string(), "MessageBox".to_string())]);
+        assert!(layout.imp_tab[1].imp_list.is_empty());
+
+        let mut code = layout.seg_tab[0].clone();
+        assert_eq!(code.file_offset() % 16, 0);
+        assert_eq!(code.header.flags & SEG_RELOCS, SEG_RELOCS);
+        assert_eq!(code.import_relocation_count(), 2);
+        code.read_data(&mut reader).unwrap();
+        let data = code.data.unwrap();
+        assert_eq!(data, vec![0x9A, 0xFF, 0xFF, 0, 0, 0x9A, 0xFF, 0xFF, 0, 0, 0xCB]);
+        assert_eq!(code.relocs.chain_sites(&data), vec![vec![1], vec![6]]);
+
+        let data = &layout.seg_tab[1];
+        assert_eq!(data.header.flags, SEG_DATA);
+        assert!(!data.has_relocations());
+        assert!(layout.validate(file.len() as u64).iter().all(|a| a.severity != Severity::Error));
+    }
+
+    #[test]
+    fn e286_tables_write_read() {
+        let file = ne_builder_fixture().build().unwrap();
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+
+        let mut buf = Vec::new();
+        layout.new_header.write(&mut buf).unwrap();
+        assert_eq!(buf.len(), 0x40);
+        assert_eq!(NewExecutableHeader::read(&mut Cursor::new(&buf), 0).unwrap(), layout.new_header);
+
+        let mut buf = Vec::new();
+        layout.ent_tab.write(&mut buf).unwrap();
+        assert_eq!(buf.len() as u16 - 1, layout.new_header.e_cb_ent);
+        assert_eq!(EntryTable::read(&mut Cursor::new(&buf), 0, layout.new_header.e_cb_ent).unwrap(), layout.ent_tab);
+
+        let mut buf = Vec::new();
+        layout.resn_tab.write(&mut buf).unwrap();
+        assert_eq!(ResidentNameTable::read(&mut Cursor::new(&buf), 0).unwrap(), layout.resn_tab);
+
+        // zero pointer means there's no non-resident names
+        let mut buf = vec![0];
+        layout.nres_tab.write(&mut buf).unwrap();
+        assert_eq!(buf.len() as u16 - 1, layout.new_header.e_cbnres);
+        assert_eq!(NonResidentNameTable::read(&mut Cursor::new(&buf), 1).unwrap(), layout.nres_tab);
+
+        let mut buf = Vec::new();
+        layout.mod_tab.write(&mut buf).unwrap();
+        assert_eq!(ModuleReferencesTable::read(&mut Cursor::new(&buf), 0, layout.new_header.e_cmod).unwrap(), layout.mod_tab);
+
+        let segment = &layout.seg_tab[0];
+        let mut buf = Vec::new();
+        segment.header.write(&mut buf).unwrap();
+        assert_eq!(SegmentHeader::read(&mut Cursor::new(&buf)).unwrap(), segment.header);
+        let mut buf = Vec::new();
+        segment.relocs.write(&mut buf).unwrap();
+        assert_eq!(RelocationTable::read(&mut Cursor::new(&buf)).unwrap(), segment.relocs);
+
+        // long runs of unused entries are split into bundles of 255
+        let mut entries: Vec<(u16, Entry)> = (1..=300).map(|o| (o, Entry::Unused)).collect();
+        entries.push((301, Entry::Fixed(FixedEntry { segment: 1, flags: 0, offset: 0x10 })));
+        let table = EntryTable { entries };
+        let mut buf = Vec::new();
+        table.write(&mut buf).unwrap();
+        assert_eq!(buf, vec![255, 0, 45, 0, 1, 1, 0, 0x10, 0, 0]);
+        assert_eq!(EntryTable::read(&mut Cursor::new(&buf), 0, buf.len() as u16 - 1).unwrap(), table);
+    }
+
+    #[test]
+    fn e286_builder_errors() {
+        let mut builder = ne_builder_fixture();
+        builder.exports.push(ExportSpec::new("Again", 1, 1, 0));
+        assert_eq!(builder.build().unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let mut builder = ne_builder_fixture();
+        builder.imports.push(ImportSpec::new("KERNEL", ImportEntry::Ordinal(1), 3, 0));
+        assert_eq!(builder.build().unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let mut builder = ne_builder_fixture();
+        builder.imports[0].offset = 10;
+        assert!(builder.build().is_err());
+
+        // segment bytes of unused and moveable bundles
+        for segment in [0, 0xFF] {
+            let mut builder = ne_builder_fixture();
+            builder.exports.push(ExportSpec::new("Marker", 9, segment, 0));
+            let error = builder.build().unwrap_err();
+            assert_eq!((error.kind(), error.to_string()), (std::io::ErrorKind::InvalidInput, format!("Export Marker has segment {} which is reserved for bundles", segment)));
+        }
+    }
+
+    #[test]
+    fn e286_cpu_try_from() {
+        // bit numbers of e_flags, not e32_cpu values
+        assert_eq!(CPU::try_from(6), Ok(CPU::I386));
+        assert_eq!(CPU::try_from(2), Err(2));
+        assert_eq!(CPU::from(2), CPU::Undefined);
+        assert_eq!(CPU::I8087.to_string(), "i8087");
+        assert_eq!(CPU::try_from(CPU::I286 as u16).unwrap().to_string(), "i286");
+    }
+
+    #[test]
+    fn e286_read_segments_data() {
+        let file = ne_builder_fixture().build().unwrap();
+        let mut reader = Cursor::new(&file);
+        let mut layout = NewExecutableLayout::read_from(&mut reader).unwrap();
+        assert!(layout.seg_tab.iter().all(|s| s.data.is_none()));
+
+        layout.read_segments_data(&mut reader).unwrap();
+        assert_eq!(layout.seg_tab[0].data.as_ref().unwrap()[..6], [0x9A, 0xFF, 0xFF, 0, 0, 0x9A]);
+        assert_eq!(layout.seg_tab[1].data.as_deref(), Some(&b"Hello\0"[..]));
+    }
+
+    #[test]
+    fn e286_inspect() {
+        let file = ne_builder_fixture().build().unwrap();
+        let path = std::env::temp_dir().join(format!("os2omf-inspect-{}.dll", std::process::id()));
+        std::fs::write(&path, &file).unwrap();
+        let path_str = path.to_str().unwrap();
+
+        assert_eq!(inspect::format(path_str).unwrap(), "NE");
+        assert_eq!(inspect::module_name(path_str).unwrap(), Some("HELLO".to_string()));
+        // @2 is unused
+        assert_eq!(inspect::export_count(path_str).unwrap(), 2);
+        std::fs::remove_file(&path).unwrap();
+
+        let program = MzBuilder::new(vec![0xCD, 0x20]).build().unwrap();
+        let mut reader = Cursor::new(&program);
+        assert_eq!(inspect::read_format(&mut reader).unwrap(), "MZ");
+        assert_eq!(inspect::read_module_name(&mut reader).unwrap(), None);
+        assert_eq!(inspect::read_export_count(&mut reader).unwrap(), 0);
+    }
+
+    #[test]
+    fn e286_expected_os() {
+        for (os, expected, name) in [(1, OS::Os2, "OS/2"), (2, OS::Windows286, "Windows"), (0x81, OS::PharLapOs2, "PharLap 286 (OS/2)")] {
+            let mut builder = ne_builder_fixture();
+            builder.os = os;
+            let layout = NewExecutableLayout::read_from(&mut Cursor::new(&builder.build().unwrap())).unwrap();
+            assert_eq!(layout.expected_os(), expected);
+            assert_eq!(layout.expected_os().to_string(), name);
+        }
+    }
+
+    #[test]
+    fn e286_entry_point_and_stack() {
+        let mut builder = ne_builder_fixture();
+        builder.ip = 5;
+        builder.autodata = 2;
+        builder.sp = 0x100;
+        builder.segments.push(SegmentSpec { min_alloc: 0x20, ..SegmentSpec::data(Vec::new()) });
+        let file = builder.build().unwrap();
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+
+        let code = layout.segment_file_range(0).unwrap();
+        assert_eq!(code, (layout.seg_tab[0].file_offset(), 11));
+        let data = layout.segment_file_range(1).unwrap();
+        assert_eq!(&file[data.0 as usize..(data.0 + data.1) as usize], b"Hello\0");
+        assert_eq!(layout.segment_file_range(2), None);
+        assert_eq!(layout.segment_file_range(3), None);
+        // 11 bytes of code and 6 bytes of data take a paragraph each
+        assert_eq!((0..4).map(|i| layout.image_offset(i)).collect::<Vec<_>>(), vec![Some(0), Some(0x10), Some(0x20), Some(0x40)]);
+
+        let entry = layout.entry_point().unwrap();
+        assert_eq!(entry, SegmentedAddress { segment: 1, offset: 5, file_offset: Some(code.0 + 5), image_offset: 5 });
+        assert_eq!(file[entry.file_offset.unwrap() as usize], 0x9A);
+
+        // SS = 0 is DGROUP, stack is past data in file
+        let stack = layout.initial_stack().unwrap();
+        assert_eq!(stack, SegmentedAddress { segment: 2, offset: 0x100, file_offset: None, image_offset: 0x110 });
+        assert_eq!(layout.address(2, 1).unwrap().file_offset, Some(data.0 + 1));
+        assert_eq!(layout.address(3, 0).unwrap().file_offset, None);
+        assert_eq!(layout.address(5, 0), None);
+
+        // library without initialization routine and DGROUP
+        let mut library = ne_builder_fixture();
+        library.cs = 0;
+        let layout = NewExecutableLayout::read_from(&mut Cursor::new(&library.build().unwrap())).unwrap();
+        assert_eq!((layout.entry_point(), layout.initial_stack()), (None, None));
+    }
+
+    #[test]
+    fn e286_patch_header_fields() {
+        let mut builder = ne_builder_fixture();
+        builder.flags |= 0x8000;
+        let original = builder.build().unwrap();
+        let mut file = Cursor::new(original.clone());
+
+        patch::clear_ne_linkage_error_flag(&mut file).unwrap();
+        patch::set_mz_checksum(&mut file, 0xBEEF).unwrap();
+
+        // only high byte of e_flags and e_crc are changed
+        let patched = file.get_ref().clone();
+        let changed: Vec<usize> = (0..original.len()).filter(|&i| original[i] != patched[i]).collect();
+        assert_eq!(changed, vec![0x12, 0x13, 0x40 + 0x0D]);
+
+        let layout = NewExecutableLayout::read_from(&mut file).unwrap();
+        assert!(!layout.new_header.module_flags().linkage_errors);
+        assert_eq!(layout.new_header.e_flags, builder.flags & 0x7FFF);
+        assert_eq!(layout.dos_header.e_crc, 0xBEEF);
+
+        patch::set_ne_flags(&mut file, 0x0302).unwrap();
+        assert_eq!(NewExecutableLayout::read_from(&mut file).unwrap().new_header.e_flags, 0x0302);
+
+        // not a DOS program: nothing is written
+        let mut file = Cursor::new(vec![0_u8; 0x80]);
+        assert_eq!(patch::set_mz_checksum(&mut file, 1).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(patch::clear_ne_linkage_error_flag(&mut file).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(file.get_ref(), &vec![0_u8; 0x80]);
+    }
+
+    #[test]
+    fn mz_extension_type() {
+        let image = |lfanew: u32, magic: &[u8]| {
+            let mut file = vec![0u8; 0x40];
+            file[0..2].copy_from_slice(b"MZ");
+            file[0x3C..0x40].copy_from_slice(&lfanew.to_le_bytes());
+            file.resize(0x80, 0);
+            file.extend_from_slice(magic);
+            Cursor::new(file)
+        };
+
+        assert_eq!(extension_type_at(&mut image(0x80, b"NE")).unwrap(), Some(ExtensionType::Ne));
+        assert_eq!(extension_type_at(&mut image(0x80, b"LE")).unwrap(), Some(ExtensionType::Le));
+        assert_eq!(extension_type_at(&mut image(0x80, b"LX")).unwrap(), Some(ExtensionType::Lx));
+        assert_eq!(extension_type_at(&mut image(0x80, b"PE")).unwrap(), None);
+        assert_eq!(extension_type_at(&mut image(0, b"NE")).unwrap(), None);
+        assert_eq!(extension_type_at(&mut image(0x1000, b"NE")).unwrap(), None);
+        assert!(extension_type_at(&mut Cursor::new(vec![0u8; 0x40])).is_err());
+
+        let mut reader = image(0x80, b"NE");
+        let layout = MzExecutableLayout {
+            header: MzHeader::read(&mut reader).unwrap(),
+            relocs: MzRelocationTable { relocations: vec![] },
+        };
+        assert!(layout.has_protected_mode_extension());
+    }
+
+    #[test]
+    fn mz_extended_signatures() {
+        use crate::exe::pharlap::{PharLapHeader, P3_MAGIC};
+
+        // bound P3 program: flat image of 0x10 bytes right after 0x180 bytes of header
+        let mut p3 = b"P3".to_vec();
+        p3.extend_from_slice(&1_u16.to_le_bytes());
+        p3.extend_from_slice(&0x180_u16.to_le_bytes());
+        p3.extend_from_slice(&0x190_u32.to_le_bytes());
+        p3.resize(0x26, 0);
+        p3.extend_from_slice(&0x180_u32.to_le_bytes());
+        p3.extend_from_slice(&0x10_u32.to_le_bytes());
+        p3.resize(0x190, 0x90);
+        let mut file = MzBuilder::new(vec![0xCB]).build().unwrap();
+        file.resize(0x80, 0);
+        file[0x3C..0x40].copy_from_slice(&0x80_u32.to_le_bytes());
+        file.extend_from_slice(&p3);
+
+        let mut reader = Cursor::new(&file);
+        assert_eq!(extension_type_at(&mut reader).unwrap(), Some(ExtensionType::UnknownExtended { signature: *b"P3", offset: 0x80 }));
+        assert_eq!(inspect::read_format(&mut reader).unwrap(), "P3");
+        assert_eq!(inspect::read_module_name(&mut reader).unwrap(), None);
+        assert_eq!(inspect::read_export_count(&mut reader).unwrap(), 0);
+        assert!(matches!(ExecutableFile::read_from_slice(&file).unwrap(), ExecutableFile::Mz(_)));
+
+        let header = PharLapHeader::read(&mut reader, 0x80).unwrap();
+        assert_eq!((header.magic, header.level, header.header_size, header.file_size), (P3_MAGIC, 1, 0x180, 0x190));
+        assert!(header.is_386() && header.is_flat());
+        assert_eq!(header.load_image(0x80), (0x200, 0x210));
+        assert_eq!(PharLapHeader::read(&mut reader, 0).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // the other named signatures; anything else is a plain DOS program
+        for signature in [b"P2", b"FB", b"W3", b"W4"] {
+            file[0x80..0x82].copy_from_slice(signature);
+            assert_eq!(extension_type_at(&mut Cursor::new(&file)).unwrap(), Some(ExtensionType::UnknownExtended { signature: *signature, offset: 0x80 }));
+        }
+        file[0x80..0x82].copy_from_slice(b"ZZ");
+        assert_eq!(extension_type_at(&mut Cursor::new(&file)).unwrap(), None);
+        assert_eq!(inspect::read_format(&mut Cursor::new(&file)).unwrap(), "MZ");
+    }
+
+    /// 16-bit version info block with DWORD-aligned key, value and children
+    fn version_block(key: &str, value: &[u8], children: &[Vec<u8>]) -> Vec<u8> {
+        let mut block = vec![0; 4];
+        block.extend_from_slice(key.as_bytes());
+        block.push(0);
+        block.resize((block.len() + 3) & !3, 0);
+        block.extend_from_slice(value);
+        block.resize((block.len() + 3) & !3, 0);
+        for child in children {
+            block.extend_from_slice(child);
+        }
+        let length = block.len() as u16;
+        block[0..2].copy_from_slice(&length.to_le_bytes());
+        block[2..4].copy_from_slice(&(value.len() as u16).to_le_bytes());
+        block
+    }
+
+    #[test]
+    fn e286_version_info() {
+        let fixed: Vec<u8> = [0xFEEF04BD_u32, 0x10000, 0x0003_000A, 0x0000_0067, 0x0003_000A, 0, 0x3F, 0, 1, 2, 0, 0, 0]
+            .iter()
+            .flat_map(|d| d.to_le_bytes())
+            .collect();
+        let strings = version_block(
+            "StringFileInfo",
+            &[],
+            &[version_block(
+                "040904E4",
+                &[],
+                &[version_block("FileVersion", b"3.10\0", &[]), version_block("CompanyName", b"Microsoft\0", &[])],
+            )],
+        );
+        let vars = version_block("VarFileInfo", &[], &[version_block("Translation", &[0x09, 0x04, 0xE4, 0x04], &[])]);
+        let mut bytes = version_block("VS_VERSION_INFO", &fixed, &[strings, vars]);
+
+        let info = VersionInfo::parse(&bytes).unwrap();
+        let fixed = info.fixed.unwrap();
+        assert_eq!(fixed.file_version, (3, 10, 0, 0x67));
+        assert_eq!((fixed.file_os, fixed.file_type), (1, 2));
+        assert_eq!(info.strings["040904E4"]["FileVersion"], "3.10");
+        assert_eq!(info.strings["040904E4"]["CompanyName"], "Microsoft");
+        assert_eq!(info.translations, [(0x0409, 0x04E4)]);
+
+        // broken child is dropped, not an error
+        let translation = bytes.len() - 20;
+        bytes[translation..translation + 2].copy_from_slice(&0x100_u16.to_le_bytes());
+        let info = VersionInfo::parse(&bytes).unwrap();
+        assert!(info.translations.is_empty());
+        assert_eq!(info.strings["040904E4"].len(), 2);
+    }
+
+    #[test]
+    fn e286_module_references() {
+        let file = [0xFF, 0xFF, 0x01, 0x00, 0x04, 0x00];
+        let table = ModuleReferencesTable::read(&mut Cursor::new(&file), 2, 2).unwrap();
+
+        assert_eq!(table.len(), 2);
+        assert!(!table.is_empty());
+        assert_eq!(table.iter().collect::<Vec<_>>(), [1, 4]);
+        assert_eq!((table[1], table.get(0), table.get(2)), (4, Some(1), None));
+        assert_eq!((&table).into_iter().sum::<u16>(), 5);
+
+        // position of reader before the call doesn't matter
+        let mut reader = Cursor::new(&file);
+        reader.set_position(file.len() as u64);
+        assert_eq!(ModuleReferencesTable::read(&mut reader, 2, 2).unwrap(), table);
+    }
+
+    #[test]
+    fn e286_dialog() {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(0x80C0_0000_u32 | 0x40).to_le_bytes());
+        bytes.push(2);
+        bytes.extend([10_u16, 20, 180, 60].iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend_from_slice(&[0xFF, 0x05, 0x00]); // menu #5
+        bytes.push(0); // default class
+        bytes.extend_from_slice(b"About\0");
+        bytes.extend_from_slice(&8_u16.to_le_bytes());
+        bytes.extend_from_slice(b"Helv\0");
+        // OK button
+        bytes.extend([60_u16, 40, 40, 14, 1].iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend_from_slice(&0x5001_0001_u32.to_le_bytes());
+        bytes.push(0x80);
+        bytes.extend_from_slice(b"OK\0");
+        bytes.push(0);
+        // static icon by ordinal, custom class
+        bytes.extend([4_u16, 4, 16, 16, 0xFFFF].iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend_from_slice(&0x5000_0003_u32.to_le_bytes());
+        bytes.extend_from_slice(b"MyIcon\0");
+        bytes.extend_from_slice(&[0xFF, 0x01, 0x00]);
+        bytes.extend_from_slice(&[2, 0xAA, 0xBB]);
+
+        let dialog = DialogTemplate::parse(&bytes).unwrap();
+        assert_eq!((dialog.x, dialog.y, dialog.cx, dialog.cy), (10, 20, 180, 60));
+        assert_eq!(dialog.menu, NameOrOrdinal::Ordinal(5));
+        assert_eq!(dialog.class, NameOrOrdinal::None);
+        assert_eq!(dialog.caption, "About");
+        assert_eq!(dialog.font, Some((8, String::from("Helv"))));
+        assert_eq!(dialog.items[0].class, DialogClass::Button);
+        assert_eq!(dialog.items[0].text, NameOrOrdinal::Name(String::from("OK")));
+        assert_eq!(dialog.items[1].class, DialogClass::Name(String::from("MyIcon")));
+        assert_eq!(dialog.items[1].text, NameOrOrdinal::Ordinal(1));
+        assert_eq!(dialog.items[1].extra, [0xAA, 0xBB]);
+        assert!(DialogTemplate::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn e286_menu() {
+        let mut bytes = vec![0, 0, 0, 0];
+        bytes.extend_from_slice(&0x0010_u16.to_le_bytes());
+        bytes.extend_from_slice(b"&File\0");
+        bytes.extend_from_slice(&0x0000_u16.to_le_bytes());
+        bytes.extend_from_slice(&100_u16.to_le_bytes());
+        bytes.extend_from_slice(b"&Open\0");
+        bytes.extend_from_slice(&0x0080_u16.to_le_bytes());
+        bytes.extend_from_slice(&101_u16.to_le_bytes());
+        bytes.extend_from_slice(b"E&xit\0");
+        bytes.extend_from_slice(&0x0080_u16.to_le_bytes());
+        bytes.extend_from_slice(&200_u16.to_le_bytes());
+        bytes.extend_from_slice(b"&Help\0");
+
+        let menu = Menu::parse(&bytes).unwrap();
+        assert_eq!(menu.items.len(), 2);
+        let MenuItem::Popup { text, children, .. } = &menu.items[0] else {
+            panic!("popup expected");
+        };
+        assert_eq!(text, "&File");
+        assert_eq!(children[1], MenuItem::Item { flags: 0x80, id: 101, text: String::from("E&xit") });
+        assert_eq!(menu.items[1], MenuItem::Item { flags: 0x80, id: 200, text: String::from("&Help") });
+
+        // popups in popups: deepest allowed level, one more, and a hostile stream
+        let nested = |levels: usize| {
+            let mut bytes = vec![0, 0, 0, 0];
+            for _ in 0..levels {
+                bytes.extend_from_slice(&[0x90, 0, 0]);
+            }
+            bytes.extend_from_slice(&[0x80, 0, 1, 0, 0]);
+            bytes
+        };
+        assert!(Menu::parse(&nested(MAX_MENU_DEPTH - 1)).is_ok());
+        assert_eq!(Menu::parse(&nested(MAX_MENU_DEPTH)).unwrap_err().kind(), ErrorKind::InvalidData);
+        let mut hostile = vec![0, 0, 0, 0];
+        hostile.extend([0x10, 0, 0].repeat(200_000));
+        assert_eq!(Menu::parse(&hostile).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn e286_name_tables() {
+        let resident = ResidentNameTable {
+            entries: vec![
+                ResidentNameEntry { name: pascal("SAMPLE"), ordinal: 0 },
+                ResidentNameEntry { name: pascal("WEP"), ordinal: 1 },
+            ],
+        };
+        let non_resident = NonResidentNameTable {
+            entries: vec![
+                NonResidentNameEntry { name: pascal("Sample library"), ordinal: 0 },
+                NonResidentNameEntry { name: PascalString::new(3, vec![b'F', 0xFF, b'N']), ordinal: 7 },
+            ],
+        };
+
+        assert_eq!(resident.iter_exports().collect::<Vec<_>>(), [(1, String::from("WEP"))]);
+        assert_eq!(non_resident.iter_exports().collect::<Vec<_>>(), [(7, String::from("F\u{FFFD}N"))]);
+    }
+
+    /// Raster font with one 3x2 glyph 'A' and face name "Tms"
+    fn fnt(version: u16) -> Vec<u8> {
+        let table = if version == 0x0300 { 148 } else { 118 };
+        let offset_size = if version == 0x0300 { 4 } else { 2 };
+        let bits = table + 2 * (2 + offset_size);
+        let mut font = vec![0_u8; bits + 8];
+        font[0..2].copy_from_slice(&version.to_le_bytes());
+        font[6..10].copy_from_slice(b"(C)\0");
+        font[68..70].copy_from_slice(&10_u16.to_le_bytes());
+        font[88..90].copy_from_slice(&2_u16.to_le_bytes());
+        font[95] = b'A';
+        font[96] = b'A';
+        font[105..109].copy_from_slice(&(bits as u32 + 2).to_le_bytes());
+        font[113..117].copy_from_slice(&(bits as u32).to_le_bytes());
+        for (i, (width, offset)) in [(3_u16, bits), (0, bits + 2)].iter().enumerate() {
+            let entry = table + i * (2 + offset_size);
+            font[entry..entry + 2].copy_from_slice(&width.to_le_bytes());
+            font[entry + 2..entry + 2 + offset_size].copy_from_slice(&(*offset as u32).to_le_bytes()[..offset_size]);
+        }
+        font[bits] = 0b1010_0000;
+        font[bits + 1] = 0b0100_0000;
+        font[bits + 2..bits + 6].copy_from_slice(b"Tms\0");
+        font
+    }
+
+    #[test]
+    fn e286_fonts() {
+        for version in [0x0200, 0x0300] {
+            let font = Font::parse(&fnt(version)).unwrap();
+            assert_eq!((font.points, font.pix_height), (10, 2));
+            assert_eq!((font.copyright.as_str(), font.face.as_str()), ("(C)", "Tms"));
+            assert_eq!(font.chars.len(), 2);
+
+            let glyph = font.glyph(b'A').unwrap();
+            let pixels: Vec<bool> = (0..2).flat_map(|y| (0..3).map(move |x| (x, y))).map(|(x, y)| glyph.pixel(x, y)).collect();
+            assert_eq!(pixels, [true, false, true, false, true, false]);
+            assert!(font.glyph(b'B').is_none());
+        }
+
+        let mut vector = fnt(0x0200);
+        vector[66] = 1;
+        assert!(Font::parse(&vector).is_err());
+        assert!(Font::parse(&[0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn e286_font_dir() {
+        let mut dir = vec![1, 0, 7, 0];
+        let mut header = fnt(0x0200);
+        header.truncate(113);
+        dir.extend_from_slice(&header);
+        dir.extend_from_slice(b"\0Tms\0");
+
+        let dir = FontDir::parse(&dir).unwrap();
+        assert_eq!(dir.entries.len(), 1);
+        assert_eq!((dir.entries[0].ordinal, dir.entries[0].points), (7, 10));
+        assert_eq!((dir.entries[0].device.as_str(), dir.entries[0].face.as_str()), ("", "Tms"));
+    }
+
+    #[test]
+    fn e286_linker_version() {
+        let mut header = NewExecutableHeader::zeroed();
+        header.e_link_maj = 5;
+        header.e_link_min = 1;
+
+        assert_eq!(header.linker_version(), (5, 1));
+        let (major, minor) = NewExecutableHeader::LINK_VERSION_40;
+        assert!(header.is_at_least_version(major, minor));
+        let (major, minor) = NewExecutableHeader::LINK_VERSION_510;
+        assert!(!header.is_at_least_version(major, minor));
+        assert!(header.is_at_least_version(5, 1));
+    }
+
+    #[test]
+    fn e286_os2_resources() {
+        // two resource segments of three: pointer #1 at 0x40 and message #1 at 0x50
+        let mut file = vec![0_u8; 0x60];
+        let table: Vec<u8> = [OS2_RT_POINTER, 1, OS2_RT_MESSAGE, 1].iter().flat_map(|w| w.to_le_bytes()).collect();
+        file[..8].copy_from_slice(&table);
+        file[0x40..0x44].copy_from_slice(b"PT..");
+        let segments: Vec<Segment> = [(0, 0), (4, 4), (5, 0x10)]
+            .iter()
+            .map(|&(sector_base, sector_length)| Segment {
+                header: SegmentHeader { sector_base, sector_length, flags: 0x1000, min_alloc: 0 },
+                shift_count: 4,
+                data: None,
+                relocs: RelocationTable { rel_entries: vec![] },
+            })
+            .collect();
+
+        let rsrc_tab = ResourceTable::read_os2(&mut Cursor::new(&file), 0, 2, &segments).unwrap();
+        assert_eq!(rsrc_tab.types.len(), 2);
+        let pointer = rsrc_tab.find(OS2_RT_POINTER, 1).unwrap();
+        assert_eq!((pointer.offset, pointer.length), (0x40, 4));
+        assert_eq!(pointer.data, b"PT..");
+        assert_eq!(rsrc_tab.find(OS2_RT_MESSAGE, 1).unwrap().offset, 0x50);
+        assert!(ResourceTable::read_os2(&mut Cursor::new(&file), 0, 4, &segments).is_err());
+
+        assert_eq!(rsrctab::type_name(1, &OS::Os2), Some("POINTER"));
+        assert_eq!(rsrctab::type_name(1, &OS::Windows286), Some("CURSOR"));
+        assert_eq!(rsrctab::type_name(OS2_RT_MESSAGE, &OS::Os2), Some("MESSAGETABLE"));
+        assert_eq!(rsrctab::type_name(99, &OS::Os2), None);
+    }
+
+    /// OS/2 1.x BITMAPFILEHEADER with 1bpp 2x2 image
+    fn os2_bitmap(us_type: &[u8; 2], hotspot: (i16, i16), bits: u32) -> Vec<u8> {
+        let mut bitmap = us_type.to_vec();
+        bitmap.extend_from_slice(&32_u32.to_le_bytes());
+        bitmap.extend_from_slice(&hotspot.0.to_le_bytes());
+        bitmap.extend_from_slice(&hotspot.1.to_le_bytes());
+        bitmap.extend_from_slice(&bits.to_le_bytes());
+        for w in [12_u32, 2 | 2 << 16, 1 | 1 << 16] {
+            bitmap.extend_from_slice(&w.to_le_bytes());
+        }
+        bitmap.extend_from_slice(&[0, 0, 0, 0xFF, 0xFF, 0xFF]);
+        bitmap
+    }
+
+    #[test]
+    fn e286_os2_bitmap_array() {
+        let single = Os2BitmapArray::parse(&os2_bitmap(b"BM", (0, 0), 32)).unwrap();
+        assert_eq!(single.entries.len(), 1);
+        let header = &single.entries[0].bitmap.headers[0];
+        assert_eq!((header.kind, header.width, header.height, header.bit_count), (Os2BitmapKind::Bitmap, 2, 2, 1));
+        assert_eq!(header.palette, [[0, 0, 0], [0xFF, 0xFF, 0xFF]]);
+
+        // two array headers: color pointer for 640x480 and monochrome icon for any display
+        let mut array = Vec::new();
+        let second = 14 + 32 * 2;
+        for (next, display, images) in [
+            (second as u32, (640_u16, 480_u16), vec![os2_bitmap(b"CP", (1, 1), 0x100), os2_bitmap(b"CP", (1, 1), 0x110)]),
+            (0, (0, 0), vec![os2_bitmap(b"IC", (0, 0), 0x120)]),
+        ] {
+            array.extend_from_slice(b"BA");
+            array.extend_from_slice(&0_u32.to_le_bytes());
+            array.extend_from_slice(&next.to_le_bytes());
+            array.extend_from_slice(&display.0.to_le_bytes());
+            array.extend_from_slice(&display.1.to_le_bytes());
+            images.iter().for_each(|i| array.extend_from_slice(i));
+        }
+
+        let parsed = Os2BitmapArray::parse(&array).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!((parsed.entries[0].display_width, parsed.entries[0].display_height), (640, 480));
+        assert_eq!(parsed.entries[0].bitmap.kind(), Os2BitmapKind::ColorPointer);
+        assert_eq!(parsed.entries[0].bitmap.headers.len(), 2);
+        assert_eq!(parsed.entries[0].bitmap.headers[1].bits_offset, 0x110);
+        assert_eq!((parsed.entries[1].offset, parsed.entries[1].bitmap.kind()), (second as u32, Os2BitmapKind::Icon));
+
+        // second header pointing back to itself
+        let mut looped = array.clone();
+        looped[second + 6..second + 10].copy_from_slice(&(second as u32).to_le_bytes());
+        assert!(Os2BitmapArray::parse(&looped).is_err());
+        assert!(Os2BitmapArray::parse(b"XX\0\0").is_err());
+    }
+
+    #[test]
+    fn e286_os2_messages() {
+        // bound segment: "SYS", 2 messages from 100, 16-bit offsets
+        let mut segment = b"SYS".to_vec();
+        segment.extend_from_slice(&2_u16.to_le_bytes());
+        segment.extend_from_slice(&100_u16.to_le_bytes());
+        segment.push(1);
+        segment.extend_from_slice(&12_u16.to_le_bytes());
+        segment.extend_from_slice(&18_u16.to_le_bytes());
+        segment.extend_from_slice(b"EBad\r\nIOK %1\r\n");
+
+        let table = MessageTable::parse(&segment).unwrap();
+        assert_eq!(table.component, "SYS");
+        assert_eq!(table.messages.len(), 2);
+        assert_eq!((table.messages[0].number, table.messages[0].kind), (100, MessageKind::Error));
+        assert_eq!(table.messages[0].text, "Bad\r\n");
+        assert_eq!(table.get(101).unwrap().text, "OK %1\r\n");
+
+        // .MSG file: magic, same header, index pointer at 18, 32-bit offsets
+        let mut file = b"\xFFMKMSGF\0DOS".to_vec();
+        file.extend_from_slice(&1_u16.to_le_bytes());
+        file.extend_from_slice(&5_u16.to_le_bytes());
+        file.push(0);
+        file.extend_from_slice(&2_u16.to_le_bytes());
+        file.extend_from_slice(&0x20_u16.to_le_bytes());
+        file.resize(0x20, 0);
+        file.extend_from_slice(&0x24_u32.to_le_bytes());
+        file.extend_from_slice(b"WLow");
+
+        let table = MessageTable::parse(&file).unwrap();
+        assert_eq!(table.component, "DOS");
+        assert_eq!((table.messages[0].number, table.messages[0].kind), (5, MessageKind::Warning));
+        assert_eq!(table.messages[0].text, "Low");
+
+        segment[8..10].copy_from_slice(&0x100_u16.to_le_bytes());
+        assert!(MessageTable::parse(&segment).is_err());
+    }
+
+    #[test]
+    fn pascal_string_accessors() {
+        let name = pascal("KERNEL");
+        assert_eq!(name.as_bytes(), b"KERNEL");
+        assert_eq!((name.len(), name.pascal_len()), (6, 6));
+        assert!(!name.is_empty() && PascalString::empty().is_empty());
+
+        // length byte is kept as stored even if it lies
+        let broken = PascalString::new(9, b"GDI".to_vec());
+        assert_eq!((broken.len(), broken.pascal_len()), (3, 9));
+    }
+
+    #[test]
+    fn pascal_string_compare_ignore_case() {
+        let name = pascal("KERNEL");
+        assert!(name.compare_ignore_case(&pascal("kernel")) && name.compare_ignore_case(&pascal("KeRnEl")));
+        assert!(!name.compare_ignore_case(&pascal("KERNEL32")));
+        assert!(name.compare_str_ignore_case("Kernel") && !name.compare_str_ignore_case("USER"));
+        // OEM characters have no case here
+        assert!(!PascalString::new(1, vec![0x80]).compare_ignore_case(&PascalString::new(1, vec![0x87])));
+        assert!(name != pascal("kernel"));
+    }
+}
+
+#[cfg(test)]
+mod omf_tests {
+    use crate::omf::coment::{CommentClass, ImportEntry};
+    use crate::omf::fixupp::{FixupFrame, FixupLocation, FixupTarget};
+    use crate::omf::library::{Library, SymbolHash, DICTIONARY_BLOCK};
+    use crate::omf::record::Record;
+    use crate::omf::{OmfModule, SegmentAlign, SegmentCombine};
+    use std::io::Cursor;
+
+    fn record(kind: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![kind];
+        bytes.extend_from_slice(&(payload.len() as u16 + 1).to_le_bytes());
+        bytes.extend_from_slice(payload);
+        let sum = bytes.iter().fold(0_u8, |sum, b| sum.wrapping_add(*b));
+        bytes.push(0_u8.wrapping_sub(sum));
+        bytes
+    }
+
+    fn names(names: &[&str]) -> Vec<u8> {
+        names.iter().flat_map(|n| [&[n.len() as u8], n.as_bytes()].concat()).collect()
+    }
+
+    fn object_module() -> Vec<u8> {
+        let mut file = record(0x80, &names(&["hello.asm"]));
+        file.extend(record(0x88, &[&[0x00, 0x00][..], b"TC86 Borland"].concat()));
+        file.extend(record(0x88, &[&[0x80, 0x9F][..], b"SLIBCE"].concat()));
+        file.extend(record(0x96, &names(&["", "_TEXT", "CODE", "DGROUP", "_DATA", "DATA"])));
+        // byte aligned public 16-bit segment and paragraph aligned public USE32 one
+        file.extend(record(0x98, &[0x28, 0x10, 0x00, 2, 3, 1]));
+        file.extend(record(0x99, &[0x69, 0x0A, 0, 0, 0, 5, 6, 1]));
+        file.extend(record(0x9A, &[4, 0xFF, 2]));
+        file.extend(record(0x8C, &[&names(&["printf"])[..], &[0]].concat()));
+        file.extend(record(0xB0, &[&names(&["_buf"])[..], &[0, 0x62, 0x81, 0x00, 0x02], &names(&["_far"]), &[0, 0x61, 0x0A, 0x04]].concat()));
+        file.extend(record(0x90, &[&[0, 1][..], &names(&["_main"]), &[0x04, 0x00, 0]].concat()));
+        file.extend(record(0x91, &[&[1, 2][..], &names(&["_val"]), &[0x08, 0, 0, 0, 0]].concat()));
+        file.extend(record(0xA0, &[&[1, 0x00, 0x00][..], &[0x90; 16]].concat()));
+        // target thread #0 is external #1, then fixups by thread and by explicit datums
+        file.extend(record(0x9C, &[0x08, 1, 0xC4, 0x05, 0x5C, 0x8C, 0x0A, 0x10, 1, 2, 0x02, 0x00]));
+        // "AB" twice and "x" three times twice
+        file.extend(record(0xA3, &[2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 2, b'A', b'B', 2, 0, 0, 0, 1, 0, 3, 0, 0, 0, 0, 0, 1, b'x']));
+        file.extend(record(0x9D, &[0xE4, 0x00, 0x22, 2, 3, 0x10, 0, 0, 0]));
+        file.extend(record(0x8A, &[0xC1, 0x14, 1, 1]));
+        file
+    }
+
+    #[test]
+    fn omf_module_definitions() {
+        let module = OmfModule::read(&mut Cursor::new(object_module()), true).unwrap();
+        assert_eq!(module.name, "hello.asm");
+        assert_eq!(module.names.len(), 6);
+
+        assert_eq!(module.comments.len(), 2);
+        assert_eq!(module.comments[0].class, CommentClass::Translator);
+        assert_eq!(module.comments[0].text().as_deref(), Some("TC86 Borland"));
+        assert_eq!(module.comments[1].class, CommentClass::DefaultLibrary);
+        assert!(module.comments[1].no_purge);
+
+        let text = module.find_segment("_TEXT").unwrap();
+        assert_eq!((text.class.as_str(), text.overlay.as_str()), ("CODE", ""));
+        assert_eq!((text.align, text.combine, text.length, text.use32), (SegmentAlign::Byte, SegmentCombine::Public, 0x10, false));
+        let data = &module.segments[1];
+        assert_eq!((data.name.as_str(), data.align, data.length, data.use32), ("_DATA", SegmentAlign::Paragraph, 10, true));
+
+        assert_eq!(module.groups[0].name, "DGROUP");
+        assert_eq!(module.groups[0].segments, vec!["_DATA".to_string()]);
+
+        let externals: Vec<&str> = module.externals.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(externals, vec!["printf", "_buf", "_far"]);
+        assert_eq!(module.externals[0].communal, None);
+        let near = module.externals[1].communal.unwrap();
+        assert_eq!((near.is_far(), near.size()), (false, 512));
+        let far = module.externals[2].communal.unwrap();
+        assert_eq!((far.is_far(), far.elements, far.element_size, far.size()), (true, 10, 4, 40));
+
+        let main = module.find_public("_main").unwrap();
+        assert_eq!((main.segment.as_deref(), main.group.as_deref(), main.offset), (Some("_TEXT"), None, 4));
+        let val = module.find_public("_val").unwrap();
+        assert_eq!((val.segment.as_deref(), val.group.as_deref(), val.offset), (Some("_DATA"), Some("DGROUP"), 8));
+    }
+
+    #[test]
+    fn omf_module_data_and_fixups() {
+        let module = OmfModule::read(&mut Cursor::new(object_module()), false).unwrap();
+        assert_eq!(module.data.len(), 2);
+        assert_eq!((module.data[0].segment.as_str(), module.data[0].data.len(), module.data[0].iterated), ("_TEXT", 16, false));
+        assert_eq!(module.data[1].data, b"ABABxxxxxx".to_vec());
+        assert!(module.data[1].iterated);
+
+        assert_eq!(module.fixups.len(), 3);
+        let by_thread = &module.fixups[0];
+        assert_eq!((by_thread.segment.as_str(), by_thread.offset, by_thread.segment_relative), ("_TEXT", 5, true));
+        assert_eq!(by_thread.location, FixupLocation::Offset16);
+        assert_eq!(by_thread.frame, FixupFrame::Target);
+        assert_eq!(by_thread.target, FixupTarget::External("printf".to_string()));
+
+        let far = &module.fixups[1];
+        assert_eq!((far.offset, far.segment_relative, far.location), (0x0A, false, FixupLocation::Pointer32));
+        assert_eq!(far.frame, FixupFrame::Group("DGROUP".to_string()));
+        assert_eq!((far.target.clone(), far.displacement), (FixupTarget::Segment("_DATA".to_string()), 2));
+
+        let wide = &module.fixups[2];
+        assert_eq!((wide.segment.as_str(), wide.location, wide.displacement), ("_DATA", FixupLocation::Offset32, 0x10));
+        assert_eq!(wide.frame, FixupFrame::External("_buf".to_string()));
+        assert_eq!(wide.target, FixupTarget::External("_far".to_string()));
+
+        let end = module.end.unwrap();
+        assert!(end.is_main);
+        let start = end.start.unwrap();
+        assert_eq!((start.frame, start.target, start.displacement), (FixupFrame::Group("DGROUP".to_string()), FixupTarget::Segment("_TEXT".to_string()), 0));
+    }
+
+    #[test]
+    fn omf_checksums_and_errors() {
+        let mut file = object_module();
+        // checksum of THEADR is the last byte of first record
+        file[13] = file[13].wrapping_add(1);
+        assert!(OmfModule::read(&mut Cursor::new(file.clone()), true).is_err());
+        assert!(OmfModule::read(&mut Cursor::new(file.clone()), false).is_ok());
+        file[13] = 0;
+        assert!(OmfModule::read(&mut Cursor::new(file), true).is_ok());
+
+        let theadr = Record::read(&mut Cursor::new(object_module()), true).unwrap().unwrap();
+        assert_eq!((theadr.kind, theadr.base_kind(), theadr.is_32bit()), (0x80, 0x80, false));
+        assert!(Record::read(&mut Cursor::new(Vec::new()), true).unwrap().is_none());
+
+        // module must start with THEADR, indices must refer defined items
+        assert!(OmfModule::read(&mut Cursor::new(record(0x96, &names(&["A"]))), false).is_err());
+        let mut file = record(0x80, &names(&["bad"]));
+        file.extend(record(0x98, &[0x28, 0x10, 0x00, 7, 1, 1]));
+        assert!(OmfModule::read(&mut Cursor::new(file), false).is_err());
+    }
+
+    fn impdef_module(name: &str, dll: &str, ordinal: Option<u16>) -> Vec<u8> {
+        let mut comment = vec![0x00, 0xA0, 0x01, ordinal.is_some() as u8];
+        comment.extend(names(&[name, dll]));
+        match ordinal {
+            Some(ordinal) => comment.extend(ordinal.to_le_bytes()),
+            None => comment.push(0),
+        }
+        let mut module = record(0x80, &names(&[name]));
+        module.extend(record(0x88, &comment));
+        module.extend(record(0x8A, &[0x00]));
+        module
+    }
+
+    fn dictionary_insert(block: &mut [u8], name: &str, page: u16) {
+        let hash = SymbolHash::new(name.as_bytes(), 1);
+        let mut bucket = hash.bucket as usize;
+        while block[bucket] != 0 {
+            bucket = (bucket + hash.bucket_delta as usize) % 37;
+        }
+        let entry = block[37] as usize * 2;
+        block[bucket] = block[37];
+        block[entry] = name.len() as u8;
+        block[entry + 1..entry + 1 + name.len()].copy_from_slice(name.as_bytes());
+        block[entry + 1 + name.len()..entry + 3 + name.len()].copy_from_slice(&page.to_le_bytes());
+        block[37] = (entry + 3 + name.len()).div_ceil(2) as u8;
+    }
+
+    fn library() -> Vec<u8> {
+        let members = [
+            impdef_module("DosOpen", "DOSCALLS", Some(70)),
+            impdef_module("WinFoo", "PMWIN", None),
+            [record(0x80, &names(&["helper.c"])), record(0x90, &[&[0, 0, 0x00, 0x00][..], &names(&["_helper"]), &[0x10, 0x00, 0]].concat()), record(0x8A, &[0x00])].concat(),
+        ];
+        let mut file = vec![0_u8; 16];
+        let mut pages = Vec::new();
+        for member in members {
+            pages.push((file.len() / 16) as u16);
+            file.extend(member);
+            file.resize(file.len().next_multiple_of(16), 0);
+        }
+        file.extend([0xF1, 0x01, 0x00]);
+        file.resize(file.len().next_multiple_of(512), 0);
+
+        let dictionary_offset = file.len() as u32;
+        let header = record(0xF0, &[&dictionary_offset.to_le_bytes()[..], &[1, 0, 0], &[0; 5]].concat());
+        file[..16].copy_from_slice(&header);
+
+        let mut block = vec![0_u8; DICTIONARY_BLOCK];
+        block[37] = 19;
+        for (name, page) in [("DosOpen", pages[0]), ("WinFoo", pages[1]), ("_helper", pages[2])] {
+            dictionary_insert(&mut block, name, page);
+        }
+        file.extend(block);
+        file
+    }
+
+    #[test]
+    fn omf_library_members() {
+        let library = Library::read(&mut Cursor::new(library())).unwrap();
+        assert_eq!((library.page_size, library.dictionary_blocks, library.case_sensitive), (16, 1, false));
+        assert_eq!(library.members.len(), 3);
+        let pages: Vec<u16> = library.members.iter().map(|m| m.page).collect();
+        assert_eq!(pages, vec![1, 4, 7]);
+        assert_eq!(library.members[2].module.publics[0].frame, Some(0));
+
+        let imports = library.imports();
+        assert_eq!(imports.len(), 2);
+        assert_eq!((imports[0].internal_name.as_str(), imports[0].module.as_str()), ("DosOpen", "DOSCALLS"));
+        assert_eq!((imports[0].entry.clone(), imports[0].display_entry()), (ImportEntry::Ordinal(70), "#70".to_string()));
+        // empty entry name means the internal one
+        assert_eq!(imports[1].entry, ImportEntry::Name("WinFoo".to_string()));
+        assert!(library.members[2].module.imports().is_empty());
+    }
+
+    #[test]
+    fn omf_library_dictionary() {
+        let library = Library::read(&mut Cursor::new(library())).unwrap();
+        assert_eq!(library.find_symbol("_helper").unwrap().module.name, "helper.c");
+        assert_eq!(library.find_symbol("DOSOPEN").unwrap().module.name, "DosOpen");
+        assert_eq!(library.find_symbol_page("WinFoo"), Some(4));
+        assert!(library.find_symbol("DosClose").is_none());
+
+        let hash = SymbolHash::new(b"DosOpen", 7);
+        assert!(hash.block < 7 && hash.bucket < 37);
+        assert!(hash.block_delta != 0 && hash.bucket_delta != 0);
+        // names are hashed case-insensitive
+        assert_eq!(hash, SymbolHash::new(b"DOSOPEN", 7));
+    }
+
+    #[test]
+    fn omf_library_hash_known_answers() {
+        // (block, block delta, bucket, bucket delta) by the hashing routine
+        // of TIS OMF 1.1 appendix 2, computed apart from this crate
+        let known = [
+            ("DosOpen", 7, (0, 1, 1, 20)),
+            ("_printf", 2, (1, 1, 22, 6)),
+            ("_main", 3, (2, 1, 7, 10)),
+            ("DOSCALLS", 5, (0, 3, 3, 20)),
+            ("A", 1, (0, 1, 23, 33)),
+            ("WinInitialize", 13, (4, 10, 6, 15)),
+        ];
+        for (name, blocks, (block, block_delta, bucket, bucket_delta)) in known {
+            assert_eq!(SymbolHash::new(name.as_bytes(), blocks), SymbolHash { block, block_delta, bucket, bucket_delta }, "{}", name);
+        }
+
+        // dictionary of 3 blocks, every name is put where the routine says:
+        // DosOpen at block 1 bucket 1, WinFoo at 2:16, _helper at 0:20
+        let mut file = library();
+        file.truncate(file.len() - DICTIONARY_BLOCK);
+        let header = record(0xF0, &[&(file.len() as u32).to_le_bytes()[..], &[3, 0, 0], &[0; 5]].concat());
+        file[..16].copy_from_slice(&header);
+        let mut blocks = vec![0_u8; DICTIONARY_BLOCK * 3];
+        for (block, bucket, name, page) in [(1, 1, "DosOpen", 1_u16), (2, 16, "WinFoo", 4), (0, 20, "_helper", 7)] {
+            let block = &mut blocks[block * DICTIONARY_BLOCK..][..DICTIONARY_BLOCK];
+            block[bucket] = 19;
+            block[38] = name.len() as u8;
+            block[39..39 + name.len()].copy_from_slice(name.as_bytes());
+            block[39 + name.len()..41 + name.len()].copy_from_slice(&page.to_le_bytes());
+            block[37] = (41 + name.len()).div_ceil(2) as u8;
+        }
+        file.extend(blocks);
+
+        let library = Library::read(&mut Cursor::new(file)).unwrap();
+        assert_eq!(library.dictionary_blocks, 3);
+        assert_eq!(library.find_symbol_page("DosOpen"), Some(1));
+        assert_eq!(library.find_symbol_page("WinFoo"), Some(4));
+        assert_eq!(library.find_symbol("_helper").unwrap().module.name, "helper.c");
+        assert!(library.find_symbol("_main").is_none());
     }
 }
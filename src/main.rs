@@ -0,0 +1,514 @@
+//! `os2omf` command line inspector.
+//! Prints summary or chosen sections of MZ, NE and LE/LX executables
+//! using only the public API of the crate.
+//! ```text
+//! os2omf [--exports] [--imports] [--objects] [--fixups] [--resources] [--regions] [--json] FILE...
+//! os2omf --extract-resource TYPE:ID -o OUT FILE
+//! ```
+//! `--json` needs the `serde` feature and prints one JSON object per file.
+//! Exit codes: `0` success, `1` file can't be read, parsed or has no
+//! requested data, `2` wrong arguments, `3` file is not a recognized executable.
+use os2omf::dump;
+use os2omf::exe::{E_CIGAM, E_MAGIC};
+use os2omf::exe386::header::{LE_CIGAM, LE_MAGIC, LX_CIGAM, LX_MAGIC};
+use os2omf::file::ExecutableFile;
+use os2omf::regions::Region;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_UNRECOGNIZED: u8 = 3;
+
+const USAGE: &str = "\
+usage: os2omf [OPTIONS] FILE...
+       os2omf --extract-resource TYPE:ID -o OUT FILE
+
+options:
+  --exports     entry points and exported names
+  --imports     imported modules and procedures
+  --objects     segments or objects
+  --fixups      relocations and fixup records
+  --resources   resource table
+  --regions     map of file regions
+  --json        JSON object per file (build with serde feature)
+  -h, --help    print this message
+
+Without section options the summary of every section is printed.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Exports,
+    Imports,
+    Objects,
+    Fixups,
+    Resources,
+    Regions,
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    sections: Vec<Section>,
+    extract: Option<(u16, u16)>,
+    output: Option<String>,
+    files: Vec<String>,
+    json: bool,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("os2omf: {}\n\n{}", message, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let mut code = 0;
+    for (i, path) in options.files.iter().enumerate() {
+        if options.files.len() > 1 && !options.json {
+            if i > 0 {
+                println!();
+            }
+            println!("{}:", path);
+        }
+        if let Err(failure) = inspect(path, &options) {
+            eprintln!("os2omf: {}: {}", path, failure.message);
+            // first failure decides the exit code of batch
+            if code == 0 {
+                code = failure.code;
+            }
+        }
+    }
+    ExitCode::from(code)
+}
+
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--exports" => options.sections.push(Section::Exports),
+            "--imports" => options.sections.push(Section::Imports),
+            "--objects" => options.sections.push(Section::Objects),
+            "--fixups" => options.sections.push(Section::Fixups),
+            "--resources" => options.sections.push(Section::Resources),
+            "--regions" => options.sections.push(Section::Regions),
+            "--json" if cfg!(feature = "serde") => options.json = true,
+            "--json" => {
+                return Err("--json requires os2omf built with serde feature".to_string());
+            }
+            "--extract-resource" => {
+                let spec = args.next().ok_or("--extract-resource expects TYPE:ID")?;
+                options.extract = Some(parse_resource_spec(spec)?);
+            }
+            "-o" | "--output" => {
+                let path = args.next().ok_or("-o expects output path")?;
+                options.output = Some(path.clone());
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option {}", flag));
+            }
+            path => options.files.push(path.to_string()),
+        }
+    }
+
+    if options.files.is_empty() {
+        return Err("no input files".to_string());
+    }
+    match (&options.extract, &options.output) {
+        (Some(_), None) => return Err("--extract-resource expects -o OUT".to_string()),
+        (None, Some(_)) => return Err("-o is used only with --extract-resource".to_string()),
+        (Some(_), Some(_)) if options.files.len() > 1 => {
+            return Err("--extract-resource takes exactly one file".to_string());
+        }
+        _ => {}
+    }
+    Ok(Some(options))
+}
+
+fn parse_resource_spec(spec: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("invalid resource {}, expected TYPE:ID numbers", spec);
+    let (type_id, id) = spec.split_once(':').ok_or_else(invalid)?;
+    let type_id = type_id.parse::<u16>().map_err(|_| invalid())?;
+    let id = id.parse::<u16>().map_err(|_| invalid())?;
+    Ok((type_id, id))
+}
+
+struct Failure {
+    code: u8,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: u8, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Self::new(EXIT_FAILURE, e.to_string())
+    }
+}
+
+fn inspect(path: &str, options: &Options) -> Result<(), Failure> {
+    if !is_recognized(path)? {
+        return Err(Failure::new(
+            EXIT_UNRECOGNIZED,
+            "not a MZ, NE, LE or LX executable".to_string(),
+        ));
+    }
+    let file = ExecutableFile::open(path)?;
+
+    if let (Some((type_id, id)), Some(output)) = (options.extract, &options.output) {
+        return extract_resource(&file, type_id, id, output);
+    }
+
+    let mut out = io::stdout().lock();
+    #[cfg(feature = "serde")]
+    if options.json {
+        let file_len = std::fs::metadata(path)?.len();
+        let report = json::report(path, &file, &options.sections, file_len);
+        return writeln!(out, "{}", report).map_err(Failure::from);
+    }
+    if options.sections.is_empty() {
+        dump::write_summary(&file, &mut out)?;
+        writeln!(out)?;
+        return write_regions(&file, &mut out).map_err(Failure::from);
+    }
+    // sections which make no sense for the format write nothing
+    let mut written = false;
+    for section in &options.sections {
+        let mut buffer = Vec::new();
+        match section {
+            Section::Exports => dump::write_exports(&file, &mut buffer)?,
+            Section::Imports => dump::write_imports(&file, &mut buffer)?,
+            Section::Objects => dump::write_objects(&file, &mut buffer)?,
+            Section::Fixups => dump::write_fixups(&file, &mut buffer)?,
+            Section::Resources => dump::write_resources(&file, &mut buffer)?,
+            Section::Regions => write_regions(&file, &mut buffer)?,
+        }
+        if buffer.is_empty() {
+            continue;
+        }
+        if written {
+            writeln!(out)?;
+        }
+        out.write_all(&buffer)?;
+        written = true;
+    }
+    Ok(())
+}
+///
+/// Checks the signature at the start of file: DOS header
+/// or linear executable header without DOS stub.
+///
+fn is_recognized(path: &str) -> io::Result<bool> {
+    let mut magic = [0u8; 2];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+        Err(e) => return Err(e),
+    }
+    let magic = u16::from_le_bytes(magic);
+    Ok([E_MAGIC, E_CIGAM, LX_MAGIC, LX_CIGAM, LE_MAGIC, LE_CIGAM].contains(&magic))
+}
+
+fn extract_resource(
+    file: &ExecutableFile,
+    type_id: u16,
+    id: u16,
+    output: &str,
+) -> Result<(), Failure> {
+    let layout = match file {
+        ExecutableFile::Ne(layout) => layout,
+        _ => {
+            return Err(Failure::new(
+                EXIT_FAILURE,
+                "resources are extracted only from new executables".to_string(),
+            ));
+        }
+    };
+    let resource = layout.rsrc_tab.find(type_id, id).ok_or_else(|| {
+        Failure::new(
+            EXIT_FAILURE,
+            format!("resource {}:{} not found", type_id, id),
+        )
+    })?;
+    std::fs::write(output, &resource.data)?;
+    Ok(())
+}
+
+fn write_regions<W: Write>(file: &ExecutableFile, w: &mut W) -> io::Result<()> {
+    let regions: Vec<Region> = match file {
+        ExecutableFile::Mz(layout) => layout.regions(),
+        ExecutableFile::Ne(layout) => layout.regions(),
+        ExecutableFile::Lx(layout) => layout.regions(),
+    };
+    writeln!(w, "Regions")?;
+    writeln!(w, " start    length   kind         name")?;
+    for region in regions {
+        let kind = format!("{:?}", region.kind);
+        writeln!(
+            w,
+            " {:08X} {:08X} {:<12} {}",
+            region.start, region.len, kind, region.name
+        )?;
+    }
+    Ok(())
+}
+
+///
+/// JSON report of one file. Sections are the same as in text output,
+/// the summary has all of them and anomalies of layout.
+///
+#[cfg(feature = "serde")]
+mod json {
+    use super::Section;
+    use os2omf::exe286::enttab::Entry;
+    use os2omf::exe286::rsrctab::ResourceId;
+    use os2omf::exe386::enttab::Entry as LxEntry;
+    use os2omf::exe386::imptab::DllImport;
+    use os2omf::file::ExecutableFile;
+    use os2omf::types::PascalString;
+    use serde_json::{json, Map, Value};
+    use std::collections::BTreeMap;
+
+    pub fn report(path: &str, file: &ExecutableFile, sections: &[Section], file_len: u64) -> Value {
+        let (format, module) = match file {
+            ExecutableFile::Mz(_) => ("MZ", None),
+            ExecutableFile::Ne(layout) => ("NE", layout.resn_tab.iter().find(|e| e.ordinal == 0).map(|e| e.name.to_string_lossy())),
+            ExecutableFile::Lx(layout) => (
+                if layout.header.is_lx() { "LX" } else { "LE" },
+                layout.resident_names.iter().find(|e| e.ordinal == 0).map(|e| e.name.to_string_lossy()),
+            ),
+        };
+        let mut report = Map::new();
+        report.insert("path".to_string(), json!(path));
+        report.insert("format".to_string(), json!(format));
+        report.insert("module".to_string(), json!(module));
+
+        let all = [
+            Section::Objects,
+            Section::Exports,
+            Section::Imports,
+            Section::Fixups,
+            Section::Resources,
+            Section::Regions,
+        ];
+        let summary = sections.is_empty();
+        for section in if summary { &all[..] } else { sections } {
+            let (key, value) = match section {
+                Section::Exports => ("exports", exports(file)),
+                Section::Imports => ("imports", imports(file)),
+                Section::Objects => ("objects", objects(file)),
+                Section::Fixups => ("fixups", Some(fixups(file))),
+                Section::Resources => ("resources", resources(file)),
+                Section::Regions => ("regions", Some(json!(regions(file)))),
+            };
+            // sections which make no sense for the format are left out
+            if let Some(value) = value {
+                report.insert(key.to_string(), value);
+            }
+        }
+        if summary {
+            report.insert("anomalies".to_string(), json!(file.validate(file_len)));
+        }
+        Value::Object(report)
+    }
+
+    fn names<'a, I: Iterator<Item = (&'a PascalString, u16)>>(entries: I) -> BTreeMap<u16, String> {
+        let mut names = BTreeMap::new();
+        for (name, ordinal) in entries.filter(|(_, ordinal)| *ordinal != 0) {
+            names.entry(ordinal).or_insert_with(|| name.to_string_lossy());
+        }
+        names
+    }
+
+    fn exports(file: &ExecutableFile) -> Option<Value> {
+        let mut rows = Vec::new();
+        match file {
+            ExecutableFile::Mz(_) => return None,
+            ExecutableFile::Ne(layout) => {
+                let resident = layout.resn_tab.iter().map(|e| (&e.name, e.ordinal));
+                let names = names(resident.chain(layout.nres_tab.iter().map(|e| (&e.name, e.ordinal))));
+                for (ordinal, entry) in layout.ent_tab.iter() {
+                    let (segment, offset) = match entry {
+                        Entry::Unused => continue,
+                        Entry::Fixed(e) => (e.segment, e.offset),
+                        Entry::Moveable(e) => (e.segment, e.offset),
+                    };
+                    rows.push(json!({ "ordinal": ordinal, "segment": segment, "offset": offset, "name": names.get(ordinal) }));
+                }
+            }
+            ExecutableFile::Lx(layout) => {
+                let resident = layout.resident_names.iter().map(|e| (&e.name, e.ordinal));
+                let names = names(resident.chain(layout.non_resident_names.iter().map(|e| (&e.name, e.ordinal))));
+                let modules = layout.import_table.modules();
+                for bundle in &layout.entry_table {
+                    for (i, entry) in bundle.entries.iter().enumerate() {
+                        let ordinal = bundle.first_ordinal.wrapping_add(i as u16);
+                        let name = names.get(&ordinal);
+                        rows.push(match entry {
+                            LxEntry::Unused => continue,
+                            LxEntry::Entry16(e) => json!({ "ordinal": ordinal, "object": bundle.object, "offset": e.offset, "name": name }),
+                            LxEntry::Entry32(e) => json!({ "ordinal": ordinal, "object": bundle.object, "offset": e.offset, "name": name }),
+                            LxEntry::EntryCallGate(e) => json!({ "ordinal": ordinal, "object": bundle.object, "offset": e.offset, "name": name }),
+                            LxEntry::EntryForwarder(e) => {
+                                let module = modules.get((e.module_ordinal as usize).wrapping_sub(1)).map(|m| m.to_string_lossy());
+                                // bit 0 of forwarder flags: import by ordinal
+                                let target = match e.flags & 0x01 {
+                                    0 => json!({ "module": module, "procedure_name_offset": e.offset_or_ordinal }),
+                                    _ => json!({ "module": module, "ordinal": e.offset_or_ordinal }),
+                                };
+                                json!({ "ordinal": ordinal, "forwarder": target, "name": name })
+                            }
+                        });
+                    }
+                }
+            }
+        }
+        Some(Value::Array(rows))
+    }
+
+    fn imports(file: &ExecutableFile) -> Option<Value> {
+        let rows: Vec<Value> = match file {
+            ExecutableFile::Mz(_) => return None,
+            ExecutableFile::Ne(layout) => layout
+                .imports()
+                .iter()
+                .map(|i| {
+                    let import = &i.import;
+                    let (name, ordinal) = match import.name.as_bytes().is_empty() {
+                        true => (None, Some(import.ordinal)),
+                        false => (Some(import.name.to_string_lossy()), None),
+                    };
+                    json!({ "module": import.dll_name.to_string_lossy(), "name": name, "ordinal": ordinal, "references": i.sites.len() })
+                })
+                .collect(),
+            ExecutableFile::Lx(layout) => layout
+                .import_table
+                .unique_imports()
+                .iter()
+                .map(|usage| {
+                    let (name, ordinal) = match &usage.import {
+                        DllImport::ImportName(i) => (Some(i.import_name.to_string_lossy()), None),
+                        DllImport::ImportOrdinal(i) => (None, Some(i.import_ordinal)),
+                    };
+                    json!({ "module": usage.import.module_name().to_string_lossy(), "name": name, "ordinal": ordinal, "references": usage.fixup_count })
+                })
+                .collect(),
+        };
+        Some(Value::Array(rows))
+    }
+
+    fn objects(file: &ExecutableFile) -> Option<Value> {
+        let rows: Vec<Value> = match file {
+            ExecutableFile::Mz(_) => return None,
+            ExecutableFile::Ne(layout) => layout
+                .seg_tab
+                .iter()
+                .enumerate()
+                .map(|(i, segment)| {
+                    let header = &segment.header;
+                    let (offset, length) = match header.sector_base {
+                        0 => (0, 0),
+                        _ => (segment.file_offset(), header.physical_size()),
+                    };
+                    json!({
+                        "number": i + 1,
+                        "class": if header.is_data() { "DATA" } else { "CODE" },
+                        "offset": offset,
+                        "length": length,
+                        "min_alloc": header.min_alloc(),
+                        "flags": header.flags,
+                    })
+                })
+                .collect(),
+            ExecutableFile::Lx(layout) => layout
+                .object_table
+                .iter()
+                .enumerate()
+                .map(|(i, object)| {
+                    json!({
+                        "number": i + 1,
+                        "base": object.virtual_addr,
+                        "size": object.virtual_size,
+                        "pages": object.map_size,
+                        "flags": object.flags,
+                    })
+                })
+                .collect(),
+        };
+        Some(Value::Array(rows))
+    }
+
+    fn fixups(file: &ExecutableFile) -> Value {
+        let rows: Vec<Value> = match file {
+            ExecutableFile::Mz(layout) => layout
+                .relocs
+                .iter()
+                .map(|p| json!({ "segment": p.segment, "offset": p.offset }))
+                .collect(),
+            ExecutableFile::Ne(layout) => layout
+                .seg_tab
+                .iter()
+                .enumerate()
+                .flat_map(|(i, segment)| {
+                    segment.relocs.iter().map(move |reloc| {
+                        json!({ "segment": i + 1, "offset": reloc.rel_seg_ptr, "address_type": reloc.rel_atp, "additive": reloc.rel_add })
+                    })
+                })
+                .collect(),
+            ExecutableFile::Lx(layout) => layout
+                .fixup_records_table
+                .iter()
+                .map(|record| {
+                    json!({ "page": record.page, "offset": record.source_offset_or_count, "source": record.source, "target_flags": record.target_flags })
+                })
+                .collect(),
+        };
+        Value::Array(rows)
+    }
+
+    fn resources(file: &ExecutableFile) -> Option<Value> {
+        let layout = match file {
+            ExecutableFile::Ne(layout) => layout,
+            _ => return None,
+        };
+        let id = |id: &ResourceId| match id {
+            ResourceId::Integer(id) => json!(id),
+            ResourceId::Name(name) => json!(name.to_string_lossy()),
+        };
+        let mut rows = Vec::new();
+        for resource_type in &layout.rsrc_tab.types {
+            for resource in &resource_type.resources {
+                rows.push(json!({
+                    "type": id(&resource_type.type_id),
+                    "id": id(&resource.id),
+                    "offset": resource.offset,
+                    "length": resource.length,
+                    "flags": resource.flags,
+                }));
+            }
+        }
+        Some(Value::Array(rows))
+    }
+
+    fn regions(file: &ExecutableFile) -> Vec<os2omf::regions::Region> {
+        match file {
+            ExecutableFile::Mz(layout) => layout.regions(),
+            ExecutableFile::Ne(layout) => layout.regions(),
+            ExecutableFile::Lx(layout) => layout.regions(),
+        }
+    }
+}
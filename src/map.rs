@@ -0,0 +1,159 @@
+//! This module represents writers of linker-style `.MAP` reports.
+//! Format follows the classic Microsoft LINK map file:
+//! ```text
+//!  SAMPLE
+//!
+//!  Start     Length     Class
+//!  0001:0000 00120H     CODE
+//!  0002:0000 00040H     DATA
+//!
+//!   Address         Publics by Value
+//!
+//!  0001:0010       WEP
+//!
+//! Program entry point at 0001:0000
+//! ```
+//! Segments (objects) are unnamed in executables, so only classes are known.
+//! Publics are exported entries joined with resident and non-resident names.
+//! Entries without names are written as `@ordinal`.
+use crate::exe286::enttab::Entry;
+use crate::exe286::NewExecutableLayout;
+use crate::exe386::enttab::{BundleType, Entry as LxEntry};
+use crate::exe386::objtab::OBJ_EXECUTABLE;
+use crate::exe386::LinearExecutableLayout;
+use crate::types::PascalString;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+///
+/// Writes map of segmented New Executable.
+/// Moveable entries are resolved to their segments too.
+///
+pub fn write_ne_map<W: Write>(layout: &NewExecutableLayout, w: &mut W) -> io::Result<()> {
+    writeln!(w, " {}", module_name(layout.resn_tab.entries.first().map(|e| &e.name)))?;
+    writeln!(w)?;
+    writeln!(w, " Start     Length     Class")?;
+    for (i, segment) in layout.seg_tab.iter().enumerate() {
+        let class = if segment.header.is_data() { "DATA" } else { "CODE" };
+        writeln!(
+            w,
+            " {:04X}:0000 {:05X}H     {}",
+            i + 1,
+            segment.header.min_alloc(),
+            class
+        )?;
+    }
+
+    let resident = layout.resn_tab.entries.iter().map(|e| (&e.name, e.ordinal));
+    let non_resident = layout.nres_tab.entries.iter().map(|e| (&e.name, e.ordinal));
+    let names = names(resident.chain(non_resident));
+    let mut publics = Vec::new();
+    for (ordinal, entry) in layout.ent_tab.entries.iter() {
+        let (segment, offset) = match entry {
+            Entry::Unused => continue,
+            Entry::Fixed(e) => (e.segment, e.offset),
+            Entry::Moveable(e) => (e.segment, e.offset),
+        };
+        publics.push(((segment as u16, offset as u32), public_name(&names, *ordinal)));
+    }
+    write_publics(w, &mut publics, 4)?;
+
+    let cs = (layout.new_header.e_csip >> 16) as u16;
+    let ip = layout.new_header.e_csip as u16;
+    writeln!(w)?;
+    writeln!(w, "Program entry point at {:04X}:{:04X}", cs, ip)
+}
+
+///
+/// Writes map of Linear Executable. Offsets are 32-bit,
+/// forwarders have no address and are not written.
+///
+pub fn write_lx_map<W: Write>(layout: &LinearExecutableLayout, w: &mut W) -> io::Result<()> {
+    writeln!(w, " {}", module_name(layout.resident_names.entries.first().map(|e| &e.name)))?;
+    writeln!(w)?;
+    writeln!(w, " Start         Length     Class")?;
+    for (i, object) in layout.object_table.objects.iter().enumerate() {
+        let class = if object.flags & OBJ_EXECUTABLE as u32 != 0 { "CODE" } else { "DATA" };
+        writeln!(w, " {:04X}:00000000 {:08X}H  {}", i + 1, object.virtual_size, class)?;
+    }
+
+    let resident = layout.resident_names.entries.iter().map(|e| (&e.name, e.ordinal));
+    let non_resident = layout.non_resident_names.entries.iter().map(|e| (&e.name, e.ordinal));
+    let names = names(resident.chain(non_resident));
+    let mut publics = Vec::new();
+    for bundle in &layout.entry_table.bundles {
+        if bundle.bundle_type == BundleType::Unused {
+            continue;
+        }
+        for (i, entry) in bundle.entries.iter().enumerate() {
+            let ordinal = bundle.first_ordinal.wrapping_add(i as u16);
+            let offset = match entry {
+                LxEntry::Entry16(e) => Some(e.offset as u32),
+                LxEntry::Entry32(e) => Some(e.offset),
+                LxEntry::EntryCallGate(e) => Some(e.offset as u32),
+                LxEntry::EntryForwarder(_) | LxEntry::Unused => None,
+            };
+            if let Some(offset) = offset {
+                publics.push(((bundle.object, offset), public_name(&names, ordinal)));
+            }
+        }
+    }
+    write_publics(w, &mut publics, 8)?;
+
+    writeln!(w)?;
+    writeln!(
+        w,
+        "Program entry point at {:04X}:{:08X}",
+        layout.header.e32_cs, layout.header.e32_eip
+    )
+}
+
+fn module_name(name: Option<&PascalString>) -> String {
+    name.map(|n| String::from_utf8_lossy(n.as_bytes()).to_string())
+        .unwrap_or_default()
+}
+
+///
+/// Joins names to ordinals. `@0` entries are module name
+/// and description, they aren't publics.
+///
+pub(crate) fn names<'a, I: Iterator<Item = (&'a PascalString, u16)>>(entries: I) -> BTreeMap<u16, String> {
+    let mut names = BTreeMap::new();
+    for (name, ordinal) in entries {
+        if ordinal != 0 {
+            names
+                .entry(ordinal)
+                .or_insert_with(|| String::from_utf8_lossy(name.as_bytes()).to_string());
+        }
+    }
+    names
+}
+
+fn public_name(names: &BTreeMap<u16, String>, ordinal: u16) -> String {
+    names
+        .get(&ordinal)
+        .cloned()
+        .unwrap_or_else(|| format!("@{}", ordinal))
+}
+
+fn write_publics<W: Write>(
+    w: &mut W,
+    publics: &mut [((u16, u32), String)],
+    offset_digits: usize,
+) -> io::Result<()> {
+    publics.sort();
+    writeln!(w)?;
+    writeln!(w, "  Address         Publics by Value")?;
+    writeln!(w)?;
+    for ((segment, offset), name) in publics.iter() {
+        writeln!(
+            w,
+            " {:04X}:{:0width$X}       {}",
+            segment,
+            offset,
+            name,
+            width = offset_digits
+        )?;
+    }
+    Ok(())
+}
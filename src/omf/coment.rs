@@ -0,0 +1,240 @@
+//! This module represents COMENT records of object module.
+//! Comment is not a remark only: translators pass through it
+//! default libraries, memory model, OMF extensions and other
+//! directives for the linker.
+//! ```text
+//! +-------------+-------+-----------------+
+//! | comment type| class | comment bytes   |
+//! | u8          | u8    | rest of record  |
+//! +-------------+-------+-----------------+
+//! ```
+//! Comment type keeps "no purge" (`0x80`) and "no list" (`0x40`) bits.
+use crate::omf::record::Record;
+use std::io;
+
+///
+/// Class of comment. Classes `0x00`-`0x9F` and `0xA0`-`0xBF` are
+/// defined by Intel and Microsoft, others are used by translator vendors.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentClass {
+    /// Name of translator which made the module
+    Translator,
+    /// Copyright of Intel
+    IntelCopyright,
+    /// Name of library to search (obsolete)
+    LibrarySpecifier,
+    /// Watcom processor and memory model
+    WatcomModel,
+    /// Version of MS-DOS
+    DosVersion,
+    /// Memory model of the module
+    MemoryModel,
+    /// Segments are ordered by DOS convention
+    DosSeg,
+    /// Name of default library to search
+    DefaultLibrary,
+    /// IMPDEF, EXPDEF and other OMF extensions
+    OmfExtension,
+    /// Symbolic debug information version
+    NewOmfExtension,
+    /// Linker may stop the first pass here
+    LinkPassSeparator,
+    /// Name of library module
+    LibMod,
+    /// String copied into the executable
+    ExeStr,
+    /// Include file error
+    IncErr,
+    /// Segments are not padded
+    NoPad,
+    /// Weak externals
+    WeakExtern,
+    /// Lazy externals
+    LazyExtern,
+    /// Free text comment
+    Comment,
+    /// Name of compiler
+    Compiler,
+    /// Date of compilation
+    Date,
+    /// Time of compilation
+    Timestamp,
+    /// User comment
+    User,
+    /// Borland source or include file for dependency check
+    BorlandDependency,
+    /// Watcom linker directive
+    WatcomLinkerDirective,
+    /// Command line of translator
+    CommandLine,
+    Unknown(u8),
+}
+
+impl CommentClass {
+    pub fn from(class: u8) -> Self {
+        match class {
+            0x00 => CommentClass::Translator,
+            0x01 => CommentClass::IntelCopyright,
+            0x81 => CommentClass::LibrarySpecifier,
+            0x9B => CommentClass::WatcomModel,
+            0x9C => CommentClass::DosVersion,
+            0x9D => CommentClass::MemoryModel,
+            0x9E => CommentClass::DosSeg,
+            0x9F => CommentClass::DefaultLibrary,
+            0xA0 => CommentClass::OmfExtension,
+            0xA1 => CommentClass::NewOmfExtension,
+            0xA2 => CommentClass::LinkPassSeparator,
+            0xA3 => CommentClass::LibMod,
+            0xA4 => CommentClass::ExeStr,
+            0xA6 => CommentClass::IncErr,
+            0xA7 => CommentClass::NoPad,
+            0xA8 => CommentClass::WeakExtern,
+            0xA9 => CommentClass::LazyExtern,
+            0xDA => CommentClass::Comment,
+            0xDB => CommentClass::Compiler,
+            0xDC => CommentClass::Date,
+            0xDD => CommentClass::Timestamp,
+            0xDF => CommentClass::User,
+            0xE9 => CommentClass::BorlandDependency,
+            0xFE => CommentClass::WatcomLinkerDirective,
+            0xFF => CommentClass::CommandLine,
+            other => CommentClass::Unknown(other),
+        }
+    }
+    ///
+    /// Comment bytes of this class are text
+    ///
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            CommentClass::Translator
+                | CommentClass::IntelCopyright
+                | CommentClass::DefaultLibrary
+                | CommentClass::Comment
+                | CommentClass::Compiler
+                | CommentClass::Date
+                | CommentClass::Timestamp
+                | CommentClass::User
+                | CommentClass::CommandLine
+        )
+    }
+}
+
+///
+/// Decoded COMENT record
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// Linker must keep the comment in the output
+    pub no_purge: bool,
+    /// Comment isn't listed
+    pub no_list: bool,
+    pub class: CommentClass,
+    /// Comment bytes after the class
+    pub data: Vec<u8>,
+}
+
+impl Comment {
+    pub fn read(record: &Record) -> io::Result<Self> {
+        let mut fields = record.fields();
+        let flags = fields.u8()?;
+        let class = CommentClass::from(fields.u8()?);
+        Ok(Self {
+            no_purge: flags & 0x80 != 0,
+            no_list: flags & 0x40 != 0,
+            class,
+            data: fields.rest().to_vec(),
+        })
+    }
+    ///
+    /// Comment bytes as text. Library name of `LibMod` and Borland
+    /// dependency are prefixed by length, other texts take the rest of record.
+    ///
+    pub fn text(&self) -> Option<String> {
+        let bytes = match self.class {
+            CommentClass::LibMod => counted(&self.data)?,
+            // time and date of file go before its name
+            CommentClass::BorlandDependency => counted(self.data.get(4..)?)?,
+            class if class.is_text() => self.data.as_slice(),
+            _ => return None,
+        };
+        Some(String::from_utf8_lossy(bytes).to_string())
+    }
+    ///
+    /// Import definition if this is IMPDEF comment.
+    /// Empty entry name means the entry is named by internal name.
+    ///
+    pub fn import_definition(&self) -> Option<ImportDefinition> {
+        if self.class != CommentClass::OmfExtension || self.data.first() != Some(&IMPDEF) {
+            return None;
+        }
+        let by_ordinal = *self.data.get(1)? != 0;
+        let rest = self.data.get(2..)?;
+        let internal_name = counted(rest)?;
+        let rest = &rest[1 + internal_name.len()..];
+        let module = counted(rest)?;
+        let rest = &rest[1 + module.len()..];
+        let internal_name = String::from_utf8_lossy(internal_name).to_string();
+
+        let entry = if by_ordinal {
+            ImportEntry::Ordinal(u16::from_le_bytes([*rest.first()?, *rest.get(1)?]))
+        } else {
+            match counted(rest) {
+                Some(name) if !name.is_empty() => {
+                    ImportEntry::Name(String::from_utf8_lossy(name).to_string())
+                }
+                _ => ImportEntry::Name(internal_name.clone()),
+            }
+        };
+        Some(ImportDefinition {
+            internal_name,
+            module: String::from_utf8_lossy(module).to_string(),
+            entry,
+        })
+    }
+}
+
+/// Subtype of `OmfExtension` comment which defines an import
+pub const IMPDEF: u8 = 0x01;
+
+///
+/// Entry of DLL which import binds to
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportEntry {
+    Ordinal(u16),
+    Name(String),
+}
+
+///
+/// Import definition (IMPDEF) of import libraries made by IMPLIB:
+/// ```text
+/// A0 01 | by ordinal (u8) | internal name | module name | ordinal (u16) or entry name
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportDefinition {
+    /// Name which modules of program refer
+    pub internal_name: String,
+    /// Name of DLL
+    pub module: String,
+    pub entry: ImportEntry,
+}
+
+impl ImportDefinition {
+    ///
+    /// Entry as it's written in module-definition files: `#ordinal` or name
+    ///
+    pub fn display_entry(&self) -> String {
+        match &self.entry {
+            ImportEntry::Ordinal(ordinal) => format!("#{}", ordinal),
+            ImportEntry::Name(name) => name.clone(),
+        }
+    }
+}
+
+fn counted(bytes: &[u8]) -> Option<&[u8]> {
+    let length = *bytes.first()? as usize;
+    bytes.get(1..1 + length)
+}
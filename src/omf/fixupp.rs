@@ -0,0 +1,263 @@
+//! This module represents FIXUPP records of object module.
+//! FIXUPP follows LEDATA or LIDATA record and patches its data.
+//! Record is a sequence of subrecords of two kinds:
+//! ```text
+//! THREAD: 0 D 0 method(3) thread(2) [index]
+//!         remembers frame (D = 1) or target (D = 0) for later fixups
+//!
+//! FIXUP:  1 M location(4) offset(10)  fix data  [frame datum] [target datum] [displacement]
+//!         fix data: F frame(3) T P target(2)
+//! ```
+//! `F` and `T` bits take frame or target from the thread instead of
+//! the method and datum. `P = 0` means displacement is present:
+//! 2 bytes in FIXUPP (`0x9C`) and 4 bytes in FIXUPP32 (`0x9D`).
+//! Threads live until they are redefined, through all FIXUPP records of module.
+use crate::omf::record::{Fields, Record};
+use std::io;
+
+///
+/// Kind of patched location
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixupLocation {
+    /// Low byte of offset (8-bit)
+    LowByte,
+    /// 16-bit offset
+    Offset16,
+    /// 16-bit segment base (selector)
+    Base,
+    /// 16:16 far pointer
+    Pointer32,
+    /// High byte of offset (8-bit)
+    HighByte,
+    /// 16-bit offset resolved by the loader
+    LoaderOffset16,
+    /// 32-bit offset
+    Offset32,
+    /// 16:32 far pointer
+    Pointer48,
+    /// 32-bit offset resolved by the loader
+    LoaderOffset32,
+    Unknown(u8),
+}
+
+impl FixupLocation {
+    pub fn from(location: u8) -> Self {
+        match location {
+            0 => FixupLocation::LowByte,
+            1 => FixupLocation::Offset16,
+            2 => FixupLocation::Base,
+            3 => FixupLocation::Pointer32,
+            4 => FixupLocation::HighByte,
+            5 => FixupLocation::LoaderOffset16,
+            9 => FixupLocation::Offset32,
+            11 => FixupLocation::Pointer48,
+            13 => FixupLocation::LoaderOffset32,
+            other => FixupLocation::Unknown(other),
+        }
+    }
+}
+
+///
+/// Target of fixup as it's written in record: by 1-based index
+/// of segment, group or external name, or by frame number.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetDatum {
+    /// `T0`: segment index
+    Segment(u16),
+    /// `T1`: group index
+    Group(u16),
+    /// `T2`: external index
+    External(u16),
+    /// `T3`: frame number
+    Frame(u16),
+}
+
+///
+/// Frame of fixup as it's written in record
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDatum {
+    /// `F0`: segment index
+    Segment(u16),
+    /// `F1`: group index
+    Group(u16),
+    /// `F2`: external index
+    External(u16),
+    /// `F3`: frame number
+    Frame(u16),
+    /// `F4`: frame of the patched location
+    Location,
+    /// `F5`: frame of the target
+    Target,
+}
+
+///
+/// FIXUP subrecord with frame and target taken from threads
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawFixup {
+    /// Fixup is segment-relative, else self-relative
+    pub segment_relative: bool,
+    pub location: FixupLocation,
+    /// Offset of patched location in data of previous LEDATA or LIDATA record
+    pub data_offset: u16,
+    pub frame: FrameDatum,
+    pub target: TargetDatum,
+    pub displacement: u32,
+}
+
+///
+/// Frame and target threads defined by THREAD subrecords
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixupThreads {
+    pub frames: [Option<FrameDatum>; 4],
+    pub targets: [Option<TargetDatum>; 4],
+}
+
+impl FixupThreads {
+    ///
+    /// Reads FIXUP subrecords of FIXUPP record.
+    /// THREAD subrecords update the threads and aren't returned.
+    ///
+    pub fn read(&mut self, record: &Record) -> io::Result<Vec<RawFixup>> {
+        let mut fields = record.fields();
+        let mut fixups = Vec::new();
+
+        while !fields.is_empty() {
+            let first = fields.u8()?;
+            if first & 0x80 == 0 {
+                let method = (first >> 2) & 0x07;
+                let thread = (first & 0x03) as usize;
+                if first & 0x40 != 0 {
+                    self.frames[thread] = Some(frame_datum(method, &mut fields)?);
+                } else {
+                    self.targets[thread] = Some(target_datum(method & 0x03, &mut fields)?);
+                }
+                continue;
+            }
+
+            let data_offset = (((first & 0x03) as u16) << 8) | fields.u8()? as u16;
+            let fix_data = fields.u8()?;
+            let frame_method = (fix_data >> 4) & 0x07;
+            let frame = if fix_data & 0x80 != 0 {
+                self.frame_thread(frame_method & 0x03)?
+            } else {
+                frame_datum(frame_method, &mut fields)?
+            };
+            let target = if fix_data & 0x08 != 0 {
+                self.target_thread(fix_data & 0x03)?
+            } else {
+                target_datum(fix_data & 0x03, &mut fields)?
+            };
+            let displacement = match fix_data & 0x04 {
+                0 => fields.offset()?,
+                _ => 0,
+            };
+
+            fixups.push(RawFixup {
+                segment_relative: first & 0x40 != 0,
+                location: FixupLocation::from((first >> 2) & 0x0F),
+                data_offset,
+                frame,
+                target,
+                displacement,
+            });
+        }
+        Ok(fixups)
+    }
+
+    pub(crate) fn frame_thread(&self, thread: u8) -> io::Result<FrameDatum> {
+        self.frames[thread as usize].ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Frame thread #{} is used before definition", thread),
+            )
+        })
+    }
+
+    pub(crate) fn target_thread(&self, thread: u8) -> io::Result<TargetDatum> {
+        self.targets[thread as usize].ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Target thread #{} is used before definition", thread),
+            )
+        })
+    }
+}
+
+pub(crate) fn frame_datum(method: u8, fields: &mut Fields) -> io::Result<FrameDatum> {
+    Ok(match method {
+        0 => FrameDatum::Segment(fields.index()?),
+        1 => FrameDatum::Group(fields.index()?),
+        2 => FrameDatum::External(fields.index()?),
+        3 => FrameDatum::Frame(fields.u16()?),
+        4 => FrameDatum::Location,
+        5 => FrameDatum::Target,
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid fixup frame method F{}", other),
+            ));
+        }
+    })
+}
+
+///
+/// Target methods `T4`-`T6` are `T0`-`T2` without displacement,
+/// so only two low bits of the method choose the datum.
+///
+pub(crate) fn target_datum(method: u8, fields: &mut Fields) -> io::Result<TargetDatum> {
+    Ok(match method & 0x03 {
+        0 => TargetDatum::Segment(fields.index()?),
+        1 => TargetDatum::Group(fields.index()?),
+        2 => TargetDatum::External(fields.index()?),
+        _ => TargetDatum::Frame(fields.u16()?),
+    })
+}
+
+///
+/// Target of fixup with resolved names
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixupTarget {
+    Segment(String),
+    Group(String),
+    External(String),
+    Frame(u16),
+}
+
+///
+/// Frame of fixup with resolved names
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixupFrame {
+    Segment(String),
+    Group(String),
+    External(String),
+    Frame(u16),
+    /// Frame of the patched location
+    Location,
+    /// Frame of the target
+    Target,
+}
+
+///
+/// Fixup of object module with resolved names
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixup {
+    /// Name of segment which data is patched
+    pub segment: String,
+    /// Offset of patched location in the segment. For LIDATA records
+    /// it's counted from the start of record data before expansion.
+    pub offset: u32,
+    /// Fixup is segment-relative, else self-relative
+    pub segment_relative: bool,
+    pub location: FixupLocation,
+    pub frame: FixupFrame,
+    pub target: FixupTarget,
+    pub displacement: u32,
+}
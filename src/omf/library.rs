@@ -0,0 +1,259 @@
+//! This module represents OMF libraries (`.LIB`) of LIB, IMPLIB and TLIB.
+//! Library is a set of object modules aligned by pages and
+//! a hashed dictionary of their public names:
+//! ```text
+//! +--------------------+ <-- 0
+//! | LIBHDR             | page size, dictionary offset and size
+//! +--------------------+ <-- page 1
+//! | module #1          | THEADR ... MODEND
+//! +--------------------+ <-- next page
+//! | module #2          |
+//! | ...                |
+//! +--------------------+
+//! | LIBEND             | padding up to 512 bytes boundary
+//! +--------------------+ <-- dictionary offset
+//! | dictionary blocks  | 512 bytes each
+//! +--------------------+
+//! ```
+//! Dictionary block has 37 buckets. Bucket keeps offset of entry in words,
+//! entry is a name prefixed by its length and the page of module.
+//! Byte after the buckets is the free space of block or `0xFF` when it's full.
+//!
+//! Import libraries made by IMPLIB keep one small module per import
+//! with IMPDEF comment (see [crate::omf::coment::ImportDefinition]):
+//! ```rust,no_run
+//! use os2omf::omf::library::Library;
+//!
+//! let library = Library::get("put here your import library path.LIB")?;
+//! for import in library.imports() {
+//!     println!("{} -> {}.{}", import.internal_name, import.module, import.display_entry());
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::omf::OmfModule;
+use crate::omf::coment::ImportDefinition;
+use crate::omf::record::Record;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+/// Library header record
+pub const LIBHDR: u8 = 0xF0;
+/// Library end record
+pub const LIBEND: u8 = 0xF1;
+/// Size of dictionary block
+pub const DICTIONARY_BLOCK: usize = 512;
+/// Count of buckets in dictionary block
+pub const DICTIONARY_BUCKETS: u16 = 37;
+
+///
+/// Starting positions and steps of dictionary search for a name
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolHash {
+    pub block: u16,
+    pub block_delta: u16,
+    pub bucket: u16,
+    pub bucket_delta: u16,
+}
+
+impl SymbolHash {
+    ///
+    /// Two-level rotation hash of the name for dictionary of `blocks` blocks.
+    /// Name is hashed from both ends at once, case-insensitive:
+    /// the front pass starts with the length byte, the back pass
+    /// starts with the last character.
+    ///
+    pub fn new(name: &[u8], blocks: u16) -> Self {
+        let length = name.len().min(u8::MAX as usize);
+        let mut block = length as u16 | 0x20;
+        let mut bucket_delta = block;
+        let mut block_delta = 0_u16;
+        let mut bucket = 0_u16;
+
+        for i in 0..length {
+            let back = (name[length - 1 - i] | 0x20) as u16;
+            bucket = bucket.rotate_right(2) ^ back;
+            block_delta = block_delta.rotate_left(2) ^ back;
+            if i + 1 == length {
+                break;
+            }
+            let front = (name[i] | 0x20) as u16;
+            block = block.rotate_left(2) ^ front;
+            bucket_delta = bucket_delta.rotate_right(2) ^ front;
+        }
+
+        let blocks = blocks.max(1);
+        Self {
+            block: block % blocks,
+            block_delta: (block_delta % blocks).max(1),
+            bucket: bucket % DICTIONARY_BUCKETS,
+            bucket_delta: (bucket_delta % DICTIONARY_BUCKETS).max(1),
+        }
+    }
+}
+
+///
+/// Object module of library
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryMember {
+    /// Page of module which dictionary refers
+    pub page: u16,
+    /// File offset of module
+    pub offset: u64,
+    pub module: OmfModule,
+}
+
+///
+/// OMF library with its modules and dictionary
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    /// Modules are aligned by page size: power of two from 16 to 32768
+    pub page_size: u32,
+    pub dictionary_offset: u32,
+    pub dictionary_blocks: u16,
+    /// Names of dictionary are case-sensitive
+    pub case_sensitive: bool,
+    pub members: Vec<LibraryMember>,
+    dictionary: Vec<u8>,
+}
+
+impl Library {
+    pub fn get(path: &str) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read(&mut reader)
+    }
+    ///
+    /// Reads library header, every module up to LIBEND and the dictionary.
+    /// Checksums of modules are not verified.
+    ///
+    pub fn read<T: Read + Seek>(reader: &mut T) -> io::Result<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = Record::read(reader, false)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "Library is empty")
+        })?;
+        if header.kind != LIBHDR {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Library starts with record 0x{:02X}", header.kind),
+            ));
+        }
+        let page_size = header.payload.len() as u32 + 4;
+        if !page_size.is_power_of_two() || page_size < 16 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid library page size {}", page_size),
+            ));
+        }
+        let mut fields = header.fields();
+        let dictionary_offset = fields.u32()?;
+        let dictionary_blocks = fields.u16()?;
+        let case_sensitive = fields.u8()? & 0x01 != 0;
+
+        let mut members = Vec::new();
+        let mut offset = page_size as u64;
+        loop {
+            reader.seek(SeekFrom::Start(offset))?;
+            let mut kind = [0_u8; 1];
+            if reader.read(&mut kind)? == 0 || kind[0] == LIBEND {
+                break;
+            }
+            let page = u16::try_from(offset / page_size as u64).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Library module at 0x{:X} is out of pages", offset),
+                )
+            })?;
+
+            reader.seek(SeekFrom::Start(offset))?;
+            let module = OmfModule::read(reader, false)?;
+            members.push(LibraryMember {
+                page,
+                offset,
+                module,
+            });
+            offset = reader
+                .stream_position()?
+                .next_multiple_of(page_size as u64);
+        }
+
+        let mut dictionary = vec![0_u8; dictionary_blocks as usize * DICTIONARY_BLOCK];
+        if dictionary_blocks != 0 {
+            reader.seek(SeekFrom::Start(dictionary_offset as u64))?;
+            reader.read_exact(&mut dictionary)?;
+        }
+
+        Ok(Self {
+            page_size,
+            dictionary_offset,
+            dictionary_blocks,
+            case_sensitive,
+            members,
+            dictionary,
+        })
+    }
+    ///
+    /// Page of module which defines public `name`, by dictionary search
+    ///
+    pub fn find_symbol_page(&self, name: &str) -> Option<u16> {
+        let blocks = self.dictionary_blocks;
+        if blocks == 0 {
+            return None;
+        }
+        let hash = SymbolHash::new(name.as_bytes(), blocks);
+        let mut block = hash.block;
+
+        for _ in 0..blocks {
+            let data = &self.dictionary[block as usize * DICTIONARY_BLOCK..][..DICTIONARY_BLOCK];
+            let is_full = data[DICTIONARY_BUCKETS as usize] == 0xFF;
+            let mut bucket = hash.bucket;
+
+            for _ in 0..DICTIONARY_BUCKETS {
+                let entry = data[bucket as usize] as usize * 2;
+                if entry == 0 {
+                    // name would be here if the block had space for it
+                    if !is_full {
+                        return None;
+                    }
+                    break;
+                }
+                if let Some(page) = self.entry_page(data, entry, name) {
+                    return Some(page);
+                }
+                bucket = (bucket + hash.bucket_delta) % DICTIONARY_BUCKETS;
+            }
+            block = (block + hash.block_delta) % blocks;
+        }
+        None
+    }
+    ///
+    /// Module which defines public `name`
+    ///
+    pub fn find_symbol(&self, name: &str) -> Option<&LibraryMember> {
+        let page = self.find_symbol_page(name)?;
+        self.members.iter().find(|m| m.page == page)
+    }
+    ///
+    /// Import definitions of all modules (import library)
+    ///
+    pub fn imports(&self) -> Vec<ImportDefinition> {
+        self.members
+            .iter()
+            .flat_map(|m| m.module.imports())
+            .collect()
+    }
+
+    fn entry_page(&self, block: &[u8], entry: usize, name: &str) -> Option<u16> {
+        let length = *block.get(entry)? as usize;
+        let stored = block.get(entry + 1..entry + 1 + length)?;
+        let page = block.get(entry + 1 + length..entry + 3 + length)?;
+
+        let matches = if self.case_sensitive {
+            stored == name.as_bytes()
+        } else {
+            stored.eq_ignore_ascii_case(name.as_bytes())
+        };
+        matches.then(|| u16::from_le_bytes([page[0], page[1]]))
+    }
+}
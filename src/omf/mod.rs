@@ -0,0 +1,631 @@
+//! This module represents Object Module Format (OMF): `.OBJ` files
+//! of Intel, Microsoft, IBM, Borland and Watcom translators for DOS and OS/2.
+//! Object module is a sequence of records (see [record]):
+//! ```text
+//! +--------------------+
+//! | THEADR             | name of module
+//! +--------------------+
+//! | COMENT ...         | translator, default libraries, extensions
+//! +--------------------+
+//! | LNAMES             | names of segments, classes and groups
+//! | SEGDEF GRPDEF      | segments and groups refer names by index
+//! | EXTDEF COMDEF      | external and communal names
+//! | PUBDEF             | public names
+//! +--------------------+
+//! | LEDATA / LIDATA    | data of segment
+//! | FIXUPP             | fixups of previous data record
+//! | ...                |
+//! +--------------------+
+//! | MODEND             | end of module and start address
+//! +--------------------+
+//! ```
+//! Records refer names, segments, groups and externals by 1-based indices.
+//! [OmfModule] resolves them, so everything it keeps is named:
+//! ```rust,no_run
+//! use os2omf::omf::OmfModule;
+//!
+//! let module = OmfModule::get("put here your object module path.OBJ")?;
+//! for public in &module.publics {
+//!     println!("{} {:?}:{:08X}", public.name, public.segment, public.offset);
+//! }
+//! for fixup in &module.fixups {
+//!     println!("{}:{:08X} -> {:?}", fixup.segment, fixup.offset, fixup.target);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Odd record types (`SEGDEF32`, `PUBDEF32`, `LEDATA32`, `FIXUPP32`...)
+//! are 32-bit variants with 4-byte offsets. Libraries of object modules
+//! are read by [library::Library]. Records this module doesn't know
+//! (`LINNUM`, `TYPDEF`, debug records) are skipped.
+use crate::omf::coment::{Comment, ImportDefinition};
+use crate::omf::fixupp::{
+    Fixup, FixupFrame, FixupTarget, FixupThreads, FrameDatum, TargetDatum, frame_datum,
+    target_datum,
+};
+use crate::omf::record::{
+    COMDEF, COMENT, EXTDEF, FIXUPP, GRPDEF, LCOMDEF, LEDATA, LEXTDEF, LHEADR, LIDATA, LNAMES,
+    LPUBDEF, MODEND, PUBDEF, Record, SEGDEF, THEADR,
+};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+
+pub mod coment;
+pub mod fixupp;
+pub mod library;
+pub mod record;
+
+///
+/// Alignment of segment from `A` field of SEGDEF
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentAlign {
+    /// Segment is at fixed frame
+    Absolute { frame: u16, offset: u8 },
+    Byte,
+    Word,
+    Paragraph,
+    /// 256 bytes page
+    Page,
+    DWord,
+    Unknown(u8),
+}
+
+///
+/// Combination of segments with the same name from `C` field of SEGDEF
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentCombine {
+    /// Segment isn't combined
+    Private,
+    /// Segments are concatenated
+    Public,
+    /// Segments are concatenated and make the stack
+    Stack,
+    /// Segments overlap
+    Common,
+    Unknown(u8),
+}
+
+impl SegmentCombine {
+    pub fn from(combine: u8) -> Self {
+        match combine {
+            0 => SegmentCombine::Private,
+            2 | 4 | 7 => SegmentCombine::Public,
+            5 => SegmentCombine::Stack,
+            6 => SegmentCombine::Common,
+            other => SegmentCombine::Unknown(other),
+        }
+    }
+}
+
+///
+/// Segment definition (SEGDEF)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub name: String,
+    pub class: String,
+    pub overlay: String,
+    pub align: SegmentAlign,
+    pub combine: SegmentCombine,
+    /// Length of segment. `B` bit of empty length field
+    /// makes it 64K (or 4G for 32-bit records).
+    pub length: u64,
+    /// `P` bit: segment is 32-bit (`USE32`)
+    pub use32: bool,
+}
+
+///
+/// Group definition (GRPDEF)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub name: String,
+    /// Names of segments in the group
+    pub segments: Vec<String>,
+}
+
+///
+/// Size of communal variable (COMDEF)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Communal {
+    /// `0x61` far, `0x62` near, other values are Borland segment indices
+    pub data_type: u8,
+    pub elements: u32,
+    pub element_size: u32,
+}
+
+impl Communal {
+    pub fn is_far(&self) -> bool {
+        self.data_type == 0x61
+    }
+    pub fn size(&self) -> u64 {
+        self.elements as u64 * self.element_size as u64
+    }
+}
+
+///
+/// External name (EXTDEF, LEXTDEF, COMDEF or LCOMDEF).
+/// All of them share one index space used by fixups.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct External {
+    pub name: String,
+    pub type_index: u16,
+    /// Name is local to module (LEXTDEF, LCOMDEF)
+    pub local: bool,
+    /// Size of communal variable, `None` for plain externals
+    pub communal: Option<Communal>,
+}
+
+///
+/// Public name (PUBDEF or LPUBDEF)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Public {
+    pub name: String,
+    /// Segment of the name. `None` means absolute `frame`
+    pub segment: Option<String>,
+    pub group: Option<String>,
+    /// Frame of absolute name
+    pub frame: Option<u16>,
+    pub offset: u32,
+    pub type_index: u16,
+    /// Name is local to module (LPUBDEF)
+    pub local: bool,
+}
+
+///
+/// Data of segment (LEDATA or expanded LIDATA)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataRecord {
+    pub segment: String,
+    pub offset: u32,
+    pub data: Vec<u8>,
+    /// Data is expanded from LIDATA record
+    pub iterated: bool,
+}
+
+///
+/// Start address of main module (MODEND)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartAddress {
+    pub frame: FixupFrame,
+    pub target: FixupTarget,
+    pub displacement: u32,
+}
+
+///
+/// End of module (MODEND)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleEnd {
+    /// Module is main program module
+    pub is_main: bool,
+    pub start: Option<StartAddress>,
+}
+
+/// Limit of LIDATA expansion: iterated blocks can't make more data than this
+const LIDATA_LIMIT: usize = 16 << 20;
+/// Limit of nested LIDATA blocks
+const LIDATA_DEPTH: usize = 32;
+
+///
+/// Object module with resolved names
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OmfModule {
+    /// Name from THEADR or LHEADR
+    pub name: String,
+    /// All LNAMES of the module in order
+    pub names: Vec<String>,
+    pub segments: Vec<Segment>,
+    pub groups: Vec<Group>,
+    pub externals: Vec<External>,
+    pub publics: Vec<Public>,
+    pub data: Vec<DataRecord>,
+    pub fixups: Vec<Fixup>,
+    pub comments: Vec<Comment>,
+    /// `None` if file ends without MODEND
+    pub end: Option<ModuleEnd>,
+}
+
+impl OmfModule {
+    ///
+    /// Reads object module by path. Checksums are not verified.
+    ///
+    pub fn get(path: &str) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read(&mut reader, false)
+    }
+    ///
+    /// Reads records of one module up to MODEND.
+    /// When `verify_checksum` is set, nonzero checksums must match.
+    ///
+    pub fn read<R: Read>(reader: &mut R, verify_checksum: bool) -> io::Result<Self> {
+        let mut module = Self::default();
+        let mut threads = FixupThreads::default();
+        // segment index and offset of previous LEDATA or LIDATA
+        let mut last_data: Option<(u16, u32)> = None;
+
+        let first = Record::read(reader, verify_checksum)?;
+        match &first {
+            Some(record) if matches!(record.kind, THEADR | LHEADR) => {
+                module.name = record.fields().name()?;
+            }
+            Some(record) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Object module starts with record 0x{:02X}", record.kind),
+                ));
+            }
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Object module is empty",
+                ));
+            }
+        }
+
+        while let Some(record) = Record::read(reader, verify_checksum)? {
+            match record.base_kind() {
+                COMENT => module.comments.push(Comment::read(&record)?),
+                LNAMES => {
+                    let mut fields = record.fields();
+                    while !fields.is_empty() {
+                        module.names.push(fields.name()?);
+                    }
+                }
+                SEGDEF => module.read_segment(&record)?,
+                GRPDEF => module.read_group(&record)?,
+                EXTDEF | LEXTDEF => module.read_externals(&record)?,
+                COMDEF | LCOMDEF => module.read_communals(&record)?,
+                PUBDEF | LPUBDEF => module.read_publics(&record)?,
+                LEDATA | LIDATA => {
+                    let mut fields = record.fields();
+                    let segment = fields.index()?;
+                    let offset = fields.offset()?;
+                    let data = if record.base_kind() == LEDATA {
+                        fields.rest().to_vec()
+                    } else {
+                        let mut data = Vec::new();
+                        while !fields.is_empty() {
+                            expand_block(&mut fields, &mut data, 0)?;
+                        }
+                        data
+                    };
+                    module.data.push(DataRecord {
+                        segment: module.segment_name(segment)?,
+                        offset,
+                        data,
+                        iterated: record.base_kind() == LIDATA,
+                    });
+                    last_data = Some((segment, offset));
+                }
+                FIXUPP => {
+                    for raw in threads.read(&record)? {
+                        let (segment, offset) = last_data.ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "FIXUPP record without previous data record",
+                            )
+                        })?;
+                        module.fixups.push(Fixup {
+                            segment: module.segment_name(segment)?,
+                            offset: offset.wrapping_add(raw.data_offset as u32),
+                            segment_relative: raw.segment_relative,
+                            location: raw.location,
+                            frame: module.resolve_frame(raw.frame)?,
+                            target: module.resolve_target(raw.target)?,
+                            displacement: raw.displacement,
+                        });
+                    }
+                }
+                MODEND => {
+                    module.end = Some(module.read_end(&record, &threads)?);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        Ok(module)
+    }
+    ///
+    /// Segment by its name
+    ///
+    pub fn find_segment(&self, name: &str) -> Option<&Segment> {
+        self.segments.iter().find(|s| s.name == name)
+    }
+    ///
+    /// Public name by its name
+    ///
+    pub fn find_public(&self, name: &str) -> Option<&Public> {
+        self.publics.iter().find(|p| p.name == name)
+    }
+
+    ///
+    /// Import definitions (IMPDEF comments) of the module
+    ///
+    pub fn imports(&self) -> Vec<ImportDefinition> {
+        self.comments
+            .iter()
+            .filter_map(|c| c.import_definition())
+            .collect()
+    }
+
+    fn read_segment(&mut self, record: &Record) -> io::Result<()> {
+        let mut fields = record.fields();
+        let acbp = fields.u8()?;
+        let align = match acbp >> 5 {
+            0 => SegmentAlign::Absolute {
+                frame: fields.u16()?,
+                offset: fields.u8()?,
+            },
+            1 => SegmentAlign::Byte,
+            2 => SegmentAlign::Word,
+            3 => SegmentAlign::Paragraph,
+            4 => SegmentAlign::Page,
+            5 => SegmentAlign::DWord,
+            other => SegmentAlign::Unknown(other),
+        };
+        let mut length = fields.offset()? as u64;
+        if acbp & 0x02 != 0 && length == 0 {
+            length = if record.is_32bit() { 1 << 32 } else { 1 << 16 };
+        }
+        let name = fields.index()?;
+        let class = fields.index()?;
+        let overlay = fields.index()?;
+
+        self.segments.push(Segment {
+            name: self.name(name)?,
+            class: self.name(class)?,
+            overlay: self.name(overlay)?,
+            align,
+            combine: SegmentCombine::from((acbp >> 2) & 0x07),
+            length,
+            use32: acbp & 0x01 != 0,
+        });
+        Ok(())
+    }
+
+    fn read_group(&mut self, record: &Record) -> io::Result<()> {
+        let mut fields = record.fields();
+        let name = self.name(fields.index()?)?;
+        let mut segments = Vec::new();
+        while !fields.is_empty() {
+            let kind = fields.u8()?;
+            if kind != 0xFF {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Group {} has unsupported component 0x{:02X}", name, kind),
+                ));
+            }
+            segments.push(self.segment_name(fields.index()?)?);
+        }
+        self.groups.push(Group { name, segments });
+        Ok(())
+    }
+
+    fn read_externals(&mut self, record: &Record) -> io::Result<()> {
+        let mut fields = record.fields();
+        while !fields.is_empty() {
+            self.externals.push(External {
+                name: fields.name()?,
+                type_index: fields.index()?,
+                local: record.base_kind() == LEXTDEF,
+                communal: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_communals(&mut self, record: &Record) -> io::Result<()> {
+        let mut fields = record.fields();
+        while !fields.is_empty() {
+            let name = fields.name()?;
+            let type_index = fields.index()?;
+            let data_type = fields.u8()?;
+            let (elements, element_size) = match data_type {
+                0x61 => (communal_length(&mut fields)?, communal_length(&mut fields)?),
+                _ => (1, communal_length(&mut fields)?),
+            };
+            self.externals.push(External {
+                name,
+                type_index,
+                local: record.base_kind() == LCOMDEF,
+                communal: Some(Communal {
+                    data_type,
+                    elements,
+                    element_size,
+                }),
+            });
+        }
+        Ok(())
+    }
+
+    fn read_publics(&mut self, record: &Record) -> io::Result<()> {
+        let mut fields = record.fields();
+        let group = fields.index()?;
+        let segment = fields.index()?;
+        let frame = match segment {
+            0 => Some(fields.u16()?),
+            _ => None,
+        };
+        let group = self.optional(group, |m, i| m.group_name(i))?;
+        let segment = self.optional(segment, |m, i| m.segment_name(i))?;
+
+        while !fields.is_empty() {
+            self.publics.push(Public {
+                name: fields.name()?,
+                segment: segment.clone(),
+                group: group.clone(),
+                frame,
+                offset: fields.offset()?,
+                type_index: fields.index()?,
+                local: record.base_kind() == LPUBDEF,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_end(&self, record: &Record, threads: &FixupThreads) -> io::Result<ModuleEnd> {
+        let mut fields = record.fields();
+        let module_type = fields.u8()?;
+        let mut start = None;
+        if module_type & 0x40 != 0 {
+            // end data has the same layout as fix data of FIXUP
+            let end_data = fields.u8()?;
+            let frame_method = (end_data >> 4) & 0x07;
+            let frame = if end_data & 0x80 != 0 {
+                threads.frame_thread(frame_method & 0x03)?
+            } else {
+                frame_datum(frame_method, &mut fields)?
+            };
+            let target = if end_data & 0x08 != 0 {
+                threads.target_thread(end_data & 0x03)?
+            } else {
+                target_datum(end_data & 0x03, &mut fields)?
+            };
+            let displacement = match end_data & 0x04 {
+                0 => fields.offset()?,
+                _ => 0,
+            };
+            start = Some(StartAddress {
+                frame: self.resolve_frame(frame)?,
+                target: self.resolve_target(target)?,
+                displacement,
+            });
+        }
+        Ok(ModuleEnd {
+            is_main: module_type & 0x80 != 0,
+            start,
+        })
+    }
+
+    fn resolve_target(&self, target: TargetDatum) -> io::Result<FixupTarget> {
+        Ok(match target {
+            TargetDatum::Segment(i) => FixupTarget::Segment(self.segment_name(i)?),
+            TargetDatum::Group(i) => FixupTarget::Group(self.group_name(i)?),
+            TargetDatum::External(i) => FixupTarget::External(self.external_name(i)?),
+            TargetDatum::Frame(frame) => FixupTarget::Frame(frame),
+        })
+    }
+
+    fn resolve_frame(&self, frame: FrameDatum) -> io::Result<FixupFrame> {
+        Ok(match frame {
+            FrameDatum::Segment(i) => FixupFrame::Segment(self.segment_name(i)?),
+            FrameDatum::Group(i) => FixupFrame::Group(self.group_name(i)?),
+            FrameDatum::External(i) => FixupFrame::External(self.external_name(i)?),
+            FrameDatum::Frame(frame) => FixupFrame::Frame(frame),
+            FrameDatum::Location => FixupFrame::Location,
+            FrameDatum::Target => FixupFrame::Target,
+        })
+    }
+
+    fn optional(
+        &self,
+        index: u16,
+        resolve: impl Fn(&Self, u16) -> io::Result<String>,
+    ) -> io::Result<Option<String>> {
+        match index {
+            0 => Ok(None),
+            i => resolve(self, i).map(Some),
+        }
+    }
+
+    fn name(&self, index: u16) -> io::Result<String> {
+        lookup(&self.names, index, "name").cloned()
+    }
+
+    fn segment_name(&self, index: u16) -> io::Result<String> {
+        lookup(&self.segments, index, "segment").map(|s| s.name.clone())
+    }
+
+    fn group_name(&self, index: u16) -> io::Result<String> {
+        lookup(&self.groups, index, "group").map(|g| g.name.clone())
+    }
+
+    fn external_name(&self, index: u16) -> io::Result<String> {
+        lookup(&self.externals, index, "external").map(|e| e.name.clone())
+    }
+}
+
+///
+/// Item by 1-based index of record
+///
+fn lookup<'a, T>(items: &'a [T], index: u16, what: &str) -> io::Result<&'a T> {
+    (index as usize)
+        .checked_sub(1)
+        .and_then(|i| items.get(i))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid {} index {} (of {})", what, index, items.len()),
+            )
+        })
+}
+
+///
+/// Length of communal: one byte up to `0x80`, else `0x81`, `0x84`
+/// or `0x88` followed by 2, 3 or 4 bytes of length.
+///
+fn communal_length(fields: &mut record::Fields) -> io::Result<u32> {
+    let first = fields.u8()?;
+    let count = match first {
+        0..=0x80 => return Ok(first as u32),
+        0x81 => 2,
+        0x84 => 3,
+        0x88 => 4,
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid communal length prefix 0x{:02X}", other),
+            ));
+        }
+    };
+    let bytes = fields.bytes(count)?;
+    Ok(bytes
+        .iter()
+        .rev()
+        .fold(0_u32, |value, b| (value << 8) | *b as u32))
+}
+
+///
+/// Expands one LIDATA block: repeat count, count of nested blocks
+/// and either nested blocks or the content prefixed by its length.
+///
+fn expand_block(fields: &mut record::Fields, out: &mut Vec<u8>, depth: usize) -> io::Result<()> {
+    if depth > LIDATA_DEPTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "LIDATA blocks are nested too deep",
+        ));
+    }
+    let repeat = fields.offset()? as usize;
+    let blocks = fields.u16()?;
+
+    let mut content = Vec::new();
+    if blocks == 0 {
+        let length = fields.u8()? as usize;
+        content.extend_from_slice(fields.bytes(length)?);
+    } else {
+        for _ in 0..blocks {
+            expand_block(fields, &mut content, depth + 1)?;
+        }
+    }
+
+    let total = content.len().saturating_mul(repeat);
+    if out.len().saturating_add(total) > LIDATA_LIMIT {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "LIDATA expands beyond the limit",
+        ));
+    }
+    for _ in 0..repeat {
+        out.extend_from_slice(&content);
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! This module represents records of Object Module Format.
+//! Every record has the same frame:
+//! ```text
+//! +------+--------+-----------------+----------+
+//! | type | length | payload         | checksum |
+//! | u8   | u16    | length - 1      | u8       |
+//! +------+--------+-----------------+----------+
+//! ```
+//! Record length counts the payload and the checksum byte.
+//! Checksum makes the sum of all bytes of the record zero,
+//! but many translators write `0` instead of it: checksum is
+//! verified only by request.
+use std::io::{self, Read};
+
+/// Translator header: name of the module
+pub const THEADR: u8 = 0x80;
+/// Library module header
+pub const LHEADR: u8 = 0x82;
+/// Comment record
+pub const COMENT: u8 = 0x88;
+/// Module end record
+pub const MODEND: u8 = 0x8A;
+/// External names definition
+pub const EXTDEF: u8 = 0x8C;
+/// Public names definition
+pub const PUBDEF: u8 = 0x90;
+/// List of names
+pub const LNAMES: u8 = 0x96;
+/// Segment definition
+pub const SEGDEF: u8 = 0x98;
+/// Group definition
+pub const GRPDEF: u8 = 0x9A;
+/// Fixup record
+pub const FIXUPP: u8 = 0x9C;
+/// Logical enumerated data
+pub const LEDATA: u8 = 0xA0;
+/// Logical iterated data
+pub const LIDATA: u8 = 0xA2;
+/// Communal names definition
+pub const COMDEF: u8 = 0xB0;
+/// Local external names definition
+pub const LEXTDEF: u8 = 0xB4;
+/// Local public names definition
+pub const LPUBDEF: u8 = 0xB6;
+/// Local communal names definition
+pub const LCOMDEF: u8 = 0xB8;
+
+///
+/// One record of object module. Odd record types are 32-bit variants
+/// of even ones: their offsets and lengths take 4 bytes instead of 2.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Record type with the 32-bit bit
+    pub kind: u8,
+    /// Record contents between length and checksum
+    pub payload: Vec<u8>,
+    pub checksum: u8,
+}
+
+impl Record {
+    ///
+    /// Reads the next record. Returns `None` at the end of file
+    /// before record type. When `verify_checksum` is set, nonzero
+    /// checksum which doesn't match the record is an error.
+    ///
+    pub fn read<R: Read>(reader: &mut R, verify_checksum: bool) -> io::Result<Option<Self>> {
+        let mut kind = [0_u8; 1];
+        if reader.read(&mut kind)? == 0 {
+            return Ok(None);
+        }
+        let kind = kind[0];
+
+        let mut length = [0_u8; 2];
+        reader.read_exact(&mut length)?;
+        let length = u16::from_le_bytes(length);
+        if length == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Record 0x{:02X} has zero length", kind),
+            ));
+        }
+
+        let mut payload = vec![0_u8; length as usize - 1];
+        reader.read_exact(&mut payload)?;
+        let mut checksum = [0_u8; 1];
+        reader.read_exact(&mut checksum)?;
+
+        let record = Self {
+            kind,
+            payload,
+            checksum: checksum[0],
+        };
+        if verify_checksum && !record.has_valid_checksum() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Record 0x{:02X} has invalid checksum 0x{:02X}",
+                    kind, record.checksum
+                ),
+            ));
+        }
+        Ok(Some(record))
+    }
+    ///
+    /// Record type without the 32-bit bit
+    ///
+    pub fn base_kind(&self) -> u8 {
+        self.kind & !1
+    }
+    ///
+    /// Offsets and lengths of the record are 32-bit
+    ///
+    pub fn is_32bit(&self) -> bool {
+        self.kind & 1 != 0
+    }
+    ///
+    /// Checksum `0` means it isn't computed and is valid too
+    ///
+    pub fn has_valid_checksum(&self) -> bool {
+        if self.checksum == 0 {
+            return true;
+        }
+        let length = (self.payload.len() + 1) as u16;
+        let sum = self
+            .payload
+            .iter()
+            .chain(&length.to_le_bytes())
+            .fold(self.kind.wrapping_add(self.checksum), |sum, b| {
+                sum.wrapping_add(*b)
+            });
+        sum == 0
+    }
+    pub(crate) fn fields(&self) -> Fields<'_> {
+        Fields {
+            bytes: &self.payload,
+            pos: 0,
+            kind: self.kind,
+        }
+    }
+}
+
+///
+/// Sequential decoder of record payload. Unlike fixed tables,
+/// records are variable-length, so running out of payload is an error.
+///
+pub(crate) struct Fields<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    kind: u8,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+    pub(crate) fn bytes(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + count)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Record 0x{:02X} ends unexpectedly at 0x{:X}",
+                        self.kind, self.pos
+                    ),
+                )
+            })?;
+        self.pos += count;
+        Ok(bytes)
+    }
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let bytes = self.bytes.get(self.pos..).unwrap_or_default();
+        self.pos = self.bytes.len();
+        bytes
+    }
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+    pub(crate) fn u16(&mut self) -> io::Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+    pub(crate) fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    ///
+    /// Offset or length: 4 bytes in 32-bit records, else 2 bytes
+    ///
+    pub(crate) fn offset(&mut self) -> io::Result<u32> {
+        match self.kind & 1 {
+            0 => self.u16().map(|v| v as u32),
+            _ => self.u32(),
+        }
+    }
+    ///
+    /// Index of name, segment, group, type or external.
+    /// Index above `0x7F` takes 2 bytes: high bit of the first
+    /// one is set and it keeps the high part of the index.
+    ///
+    pub(crate) fn index(&mut self) -> io::Result<u16> {
+        let first = self.u8()?;
+        if first & 0x80 == 0 {
+            return Ok(first as u16);
+        }
+        Ok((((first & 0x7F) as u16) << 8) | self.u8()? as u16)
+    }
+    ///
+    /// Name prefixed by its length
+    ///
+    pub(crate) fn name(&mut self) -> io::Result<String> {
+        let length = self.u8()? as usize;
+        let bytes = self.bytes(length)?;
+        Ok(String::from_utf8_lossy(bytes).to_string())
+    }
+}
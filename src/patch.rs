@@ -0,0 +1,284 @@
+//! This module patches single header fields of existing files.
+//! Every patcher reads the headers first (so the magic is checked
+//! before anything is written), finds the field by its offset in
+//! the header and writes only bytes of this field:
+//! ```text
+//! MZ: 0                       + 0x12 e_crc
+//! NE: e_lfanew                + 0x0C e_flags
+//! LX: e_lfanew (or 0 w/o MZ)  + 0x18 e32_cs, 0x1C e32_eip,
+//!                               0xA8 e32_heapsize, 0xAC e32_stacksize
+//! ```
+//! LX fields are written in byte order of module. Fields which are
+//! patched here for LX modules are refused for LE modules.
+//!
+//! Debug information ([strip_debug], [extract_debug]) is the only
+//! region which is cut out of the file. Pointers to it, to page data
+//! and to non-resident names are absolute, so the ones which point
+//! past the cut are moved back by its length.
+//! ```rust
+//! use os2omf::exe286::builder::{NeBuilder, SegmentSpec};
+//! use os2omf::exe286::NewExecutableLayout;
+//! use os2omf::patch;
+//! use std::io::Cursor;
+//!
+//! let mut builder = NeBuilder::new("BROKEN");
+//! builder.flags |= 0x8000;
+//! builder.segments.push(SegmentSpec::code(vec![0xCB]));
+//! let mut file = Cursor::new(builder.build()?);
+//!
+//! patch::clear_ne_linkage_error_flag(&mut file)?;
+//! let layout = NewExecutableLayout::read_from(&mut file)?;
+//! assert!(!layout.new_header.module_flags().linkage_errors);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::exe::{MzHeader, NE_HEADER_LEN};
+use crate::exe286::header::NewExecutableHeader;
+use crate::exe386::header::LinearExecutableHeader;
+use crate::exe386::LinearExecutableLayout;
+use crate::regions::RegionKind;
+use crate::types::ByteOrder;
+use std::fs;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+
+/// `e_crc` of DOS header
+const MZ_CRC: u64 = 0x12;
+/// `e_flags` of NE header
+const NE_FLAGS: u64 = 0x0C;
+/// Errors detected at link time, module will not load
+const NE_LINKAGE_ERRORS: u16 = 0x8000;
+/// `e32_cs` of LX header, `e32_eip` follows it
+const LX_CS: u64 = 0x18;
+const LX_EIP: u64 = 0x1C;
+/// `e32_heapsize` of LX header, `e32_stacksize` follows it
+const LX_HEAPSIZE: u64 = 0xA8;
+const LX_STACKSIZE: u64 = 0xAC;
+/// Absolute pointers of LX header
+const LX_DATAPAGE: u64 = 0x80;
+const LX_NRESTAB: u64 = 0x88;
+/// `e32_debuginfo` of LX header, `e32_debuglen` follows it
+const LX_DEBUGINFO: u64 = 0x98;
+const LX_DEBUGLEN: u64 = 0x9C;
+
+///
+/// Sets checksum `e_crc` of DOS header
+///
+pub fn set_mz_checksum<T: Read + Write + Seek>(file: &mut T, checksum: u16) -> io::Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    MzHeader::read(file)?;
+
+    file.seek(SeekFrom::Start(MZ_CRC))?;
+    ByteOrder::Little.write_u16(file, checksum)
+}
+///
+/// Replaces all bits of NE `e_flags` by `flags`
+///
+pub fn set_ne_flags<T: Read + Write + Seek>(file: &mut T, flags: u16) -> io::Result<()> {
+    let (offset, _) = ne_header(file)?;
+
+    file.seek(SeekFrom::Start(offset + NE_FLAGS))?;
+    ByteOrder::Little.write_u16(file, flags)
+}
+///
+/// Clears "linkage errors" bit of NE `e_flags` which makes
+/// loader refuse the module. Other flags stay as they are.
+///
+pub fn clear_ne_linkage_error_flag<T: Read + Write + Seek>(file: &mut T) -> io::Result<()> {
+    let (_, header) = ne_header(file)?;
+    set_ne_flags(file, header.e_flags & !NE_LINKAGE_ERRORS)
+}
+///
+/// Sets `e32_stacksize` of LX module
+///
+pub fn set_lx_stack_size<T: Read + Write + Seek>(file: &mut T, size: u32) -> io::Result<()> {
+    let (offset, header) = lx_header(file, "e32_stacksize")?;
+
+    file.seek(SeekFrom::Start(offset + LX_STACKSIZE))?;
+    header.byte_order().write_u32(file, size)
+}
+///
+/// Sets `e32_heapsize` of LX module
+///
+pub fn set_lx_heap_size<T: Read + Write + Seek>(file: &mut T, size: u32) -> io::Result<()> {
+    let (offset, header) = lx_header(file, "e32_heapsize")?;
+
+    file.seek(SeekFrom::Start(offset + LX_HEAPSIZE))?;
+    header.byte_order().write_u32(file, size)
+}
+///
+/// Moves entry point of LX module to `offset` of `object` (1-based).
+/// `object` must be one of the objects of module.
+///
+pub fn set_lx_entry<T: Read + Write + Seek>(
+    file: &mut T,
+    object: u32,
+    offset: u32,
+) -> io::Result<()> {
+    let (header_offset, header) = lx_header(file, "e32_cs:e32_eip")?;
+    if object == 0 || object > header.e32_objcnt {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Module has no object #{} (objects: {})", object, header.e32_objcnt),
+        ));
+    }
+
+    let order = header.byte_order();
+    file.seek(SeekFrom::Start(header_offset + LX_CS))?;
+    order.write_u32(file, object)?;
+    file.seek(SeekFrom::Start(header_offset + LX_EIP))?;
+    order.write_u32(file, offset)
+}
+
+///
+/// Cuts debug information out of LE/LX module in the file by `path`.
+/// Returns `false` when module has no debug information (file is not changed).
+///
+pub fn strip_debug(path: &str) -> io::Result<bool> {
+    let mut image = fs::read(path)?;
+    match strip_debug_image(&mut image)? {
+        Some(_) => fs::write(path, image).map(|_| true),
+        None => Ok(false),
+    }
+}
+///
+/// Saves debug information of LE/LX module by `path` to `debug_path`
+/// and cuts it out of module. Returns `false` when module has no
+/// debug information (no files are written).
+///
+pub fn extract_debug(path: &str, debug_path: &str) -> io::Result<bool> {
+    let mut image = fs::read(path)?;
+    let Some(debug) = strip_debug_image(&mut image)? else {
+        return Ok(false);
+    };
+    // symbols are saved first: nothing is lost if module can't be written
+    fs::write(debug_path, debug)?;
+    fs::write(path, image)?;
+    Ok(true)
+}
+///
+/// Cuts debug information out of LE/LX module `image` and returns it.
+///
+/// Debug information is found by `e32_debuginfo` and `e32_debuglen`,
+/// or, when header has no pointer to it, by CodeView/HLL signature
+/// (`NBxx` and distance to the start) in the last 8 bytes of file.
+/// Both header fields are zeroed, `e32_datapage` and `e32_nrestab`
+/// are moved back when they point past debug information.
+///
+/// Refuses to cut (`InvalidData`) when any known structure overlaps
+/// debug information or follows it and isn't addressed by those pointers:
+/// such structures can't be moved without rewriting the tables.
+///
+pub fn strip_debug_image(image: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+    let layout = LinearExecutableLayout::read_from(&mut Cursor::new(&image[..]))?;
+    let header = &layout.header;
+    let base = layout.dos_header.map(|h| h.e_lfanew as u64).unwrap_or(0);
+
+    let (start, len) = match header.e32_debuginfo {
+        0 => match debug_trailer(image) {
+            Some(debug) => debug,
+            None => return Ok(None),
+        },
+        ptr => (ptr as u64, header.e32_debuglen as u64),
+    };
+    let end = start + len;
+    if end > image.len() as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Debug information 0x{:X}..0x{:X} is out of file", start, end),
+        ));
+    }
+
+    let datapage = header.e32_datapage as u64;
+    let nrestab = header.e32_nrestab as u64;
+    for region in layout.regions() {
+        if matches!(region.kind, RegionKind::Debug | RegionKind::Unclaimed) {
+            continue;
+        }
+        let overlaps = region.start < end && region.end() > start;
+        let moved = match region.kind {
+            RegionKind::Data => datapage >= end,
+            _ => region.start == nrestab,
+        };
+        if overlaps || (len != 0 && region.start >= end && !moved) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} at 0x{:X} can't be kept with debug information 0x{:X}..0x{:X} cut",
+                    region.name, region.start, start, end
+                ),
+            ));
+        }
+    }
+
+    let moved = |ptr: u64| -> u32 {
+        match ptr {
+            ptr if ptr >= end => (ptr - len) as u32,
+            ptr if ptr > start => start as u32,
+            ptr => ptr as u32,
+        }
+    };
+    let order = header.byte_order();
+    let fields = [
+        (LX_DATAPAGE, moved(datapage)),
+        (LX_NRESTAB, moved(nrestab)),
+        (LX_DEBUGINFO, 0),
+        (LX_DEBUGLEN, 0),
+    ];
+    let debug = image.drain(start as usize..end as usize).collect();
+    let mut writer = Cursor::new(&mut image[..]);
+    for (field, value) in fields {
+        writer.seek(SeekFrom::Start(base + field))?;
+        order.write_u32(&mut writer, value)?;
+    }
+    Ok(Some(debug))
+}
+///
+/// CodeView/HLL debug information appended to the file ends with
+/// its signature and distance from the end of file to the start,
+/// where the same signature is. Returns `(start, len)`.
+///
+fn debug_trailer(image: &[u8]) -> Option<(u64, u64)> {
+    let trailer = image.len().checked_sub(8)?;
+    let signature = &image[trailer..trailer + 4];
+    if &signature[..2] != b"NB" || !signature[2..].iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let lfo = u32::from_le_bytes(image[trailer + 4..].try_into().ok()?) as usize;
+    let start = image.len().checked_sub(lfo)?;
+    if lfo < 8 || image[start..start + 4] != *signature {
+        return None;
+    }
+    Some((start as u64, lfo as u64))
+}
+
+fn ne_header<T: Read + Seek>(file: &mut T) -> io::Result<(u64, NewExecutableHeader)> {
+    file.seek(SeekFrom::Start(0))?;
+    let dos_header = MzHeader::read(file)?;
+    let file_len = file.seek(SeekFrom::End(0))?;
+    dos_header.validate_lfanew(file_len, NE_HEADER_LEN)?;
+    let header = NewExecutableHeader::read(file, dos_header.e_lfanew)?;
+    if !header.is_valid_magic() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("No NE header at 0x{:X}", dos_header.e_lfanew),
+        ));
+    }
+    Ok((dos_header.e_lfanew as u64, header))
+}
+
+fn lx_header<T: Read + Seek>(
+    file: &mut T,
+    field: &str,
+) -> io::Result<(u64, LinearExecutableHeader)> {
+    let (_, offset) = LinearExecutableLayout::define_base_offset(file)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No LE/LX header"))?;
+
+    file.seek(SeekFrom::Start(offset))?;
+    let header = LinearExecutableHeader::read(file)?;
+    if !header.is_lx() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is patched only in LX modules, module at 0x{:X} is LE", field, offset),
+        ));
+    }
+    Ok((offset, header))
+}
@@ -0,0 +1,130 @@
+//! This module represents map of file regions for parsed layouts.
+//! Every known structure of the file (headers, tables, segments data)
+//! becomes a byte range with a name. Useful for hex viewers and for
+//! looking at what the linker put between tables.
+//! ```rust,no_run
+//! use os2omf::exe286::NewExecutableLayout;
+//! use os2omf::regions;
+//!
+//! let layout = NewExecutableLayout::get("put here Windows 3.1 app/dll path")?;
+//! let regions = layout.regions();
+//!
+//! for region in regions::regions_at(&regions, 0x400) {
+//!     println!("{:08X} {:08X} {}", region.start, region.len, region.name);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//! Regions are computed from header pointers and sizes of parsed tables,
+//! nothing is read again. Overlapping regions (packed files do this)
+//! are reported as is. Layouts don't know the file length, so
+//! unclaimed gaps are found only between claimed regions.
+use crate::exe::MzHeader;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RegionKind {
+    /// MZ, NE or LE/LX header
+    Header,
+    /// DOS relocations or per-segment relocations
+    Relocations,
+    /// Real-mode program following by the MZ header
+    DosStub,
+    /// Any table pointed by the header
+    Table,
+    /// Segment data or object page
+    Data,
+    /// Debug information
+    Debug,
+    /// Bytes which are not claimed by any known structure
+    Unclaimed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Region {
+    /// Raw file pointer to the first byte
+    pub start: u64,
+    pub len: u64,
+    pub kind: RegionKind,
+    pub name: String,
+}
+
+impl Region {
+    pub fn new(start: u64, len: u64, kind: RegionKind, name: &str) -> Self {
+        Self {
+            start,
+            len,
+            kind,
+            name: name.to_string(),
+        }
+    }
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.len)
+    }
+    pub fn contains(&self, offset: u64) -> bool {
+        offset >= self.start && offset < self.end()
+    }
+}
+
+///
+/// Returns all regions what holds byte at `offset`.
+/// More than one region means overlapping structures.
+///
+pub fn regions_at(regions: &[Region], offset: u64) -> Vec<&Region> {
+    regions.iter().filter(|r| r.contains(offset)).collect()
+}
+
+///
+/// Regions of DOS part of the file: header, relocations
+/// and real-mode program image (stub).
+///
+pub(crate) fn dos_regions(header: &MzHeader) -> Vec<Region> {
+    let mut regions = vec![Region::new(0, 0x40, RegionKind::Header, "MZ header")];
+
+    if header.e_crlc != 0 {
+        regions.push(Region::new(
+            header.e_lfarlc as u64,
+            header.e_crlc as u64 * 4,
+            RegionKind::Relocations,
+            "DOS relocations",
+        ));
+    }
+    let image_end = header.image_size();
+    let stub_start = header.e_cparhdr as u64 * 16;
+    if image_end > stub_start {
+        regions.push(Region::new(
+            stub_start,
+            image_end - stub_start,
+            RegionKind::DosStub,
+            "DOS stub",
+        ));
+    }
+    regions
+}
+
+///
+/// Sorts regions by position, drops empty ones
+/// and fills spaces between them by [RegionKind::Unclaimed] regions.
+///
+pub(crate) fn finish(mut regions: Vec<Region>) -> Vec<Region> {
+    regions.retain(|r| r.len != 0);
+    regions.sort_by_key(|r| (r.start, r.len));
+
+    let mut gaps = Vec::new();
+    let mut claimed_end = 0_u64;
+    for region in &regions {
+        if region.start > claimed_end {
+            gaps.push(Region::new(
+                claimed_end,
+                region.start - claimed_end,
+                RegionKind::Unclaimed,
+                "Unclaimed",
+            ));
+        }
+        claimed_end = claimed_end.max(region.end());
+    }
+
+    regions.extend(gaps);
+    regions.sort_by_key(|r| (r.start, r.len));
+    regions
+}
@@ -1,5 +1,9 @@
 //! Specific types what used in formats are contained here.
+pub mod readable;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
 ///
 /// ### Pascal String
 /// Type of ASCII string mostly used in Pascal.
@@ -14,32 +18,266 @@ use std::fmt::Debug;
 /// //                    ...
 /// //                    bytes[13] - 'g'
 /// ```
+/// Characters are shared by clones: imports of one module keep
+/// one copy of its name however many fixups reference it.
 ///
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct PascalString {
     length: u8,
-    string: Vec<u8>,
+    string: Arc<[u8]>,
 }
 impl PascalString {
     pub fn empty() -> Self {
         PascalString {
             length: 0,
-            string: Vec::new(),
+            string: Arc::from([]),
         }
     }
     pub fn new(len: u8, bytes: Vec<u8>) -> Self {
         PascalString {
             length: len,
-            string: bytes,
+            string: Arc::from(bytes),
         }
     }
+    ///
+    /// Same as [PascalString::to_string_lossy]: names of broken
+    /// files are not UTF-8 and must not panic the caller.
+    ///
     #[allow(dead_code)]
     pub fn to_string(&self) -> String {
-        std::str::from_utf8(&self.string.as_slice()).expect("").to_string()
+        self.to_string_lossy()
     }
-    #[allow(dead_code)]
+    #[deprecated(since = "0.1.2", note = "use `as_bytes` instead")]
     pub fn to_bytes(&self) -> &[u8] {
-        self.string.as_slice()
+        self.as_bytes()
+    }
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.string
+    }
+    ///
+    /// Count of characters. Equals to `as_bytes().len()`
+    ///
+    pub fn len(&self) -> usize {
+        self.string.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.string.is_empty()
+    }
+    ///
+    /// Raw length byte as it was stored in file.
+    /// Broken files may have it different from [PascalString::len].
+    ///
+    pub fn pascal_len(&self) -> u8 {
+        self.length
+    }
+    ///
+    /// Converts string replacing invalid UTF-8 sequences.
+    /// Names in old modules may hold any OEM characters.
+    ///
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.string).to_string()
+    }
+    ///
+    /// Compares characters ignoring case of ASCII letters: module
+    /// names are uppercase by convention, but "kernel" is "KERNEL" too.
+    /// OEM characters (`0x80..`) are compared as they are.
+    ///
+    pub fn compare_ignore_case(&self, other: &PascalString) -> bool {
+        self.string.eq_ignore_ascii_case(&other.string)
+    }
+    ///
+    /// Same as [PascalString::compare_ignore_case] for Rust string
+    ///
+    pub fn compare_str_ignore_case(&self, other: &str) -> bool {
+        self.string.eq_ignore_ascii_case(other.as_bytes())
+    }
+}
+///
+/// Table of exported names joined with ordinals
+/// (resident and non-resident names of NE and LE/LX modules)
+///
+pub trait NameTable {
+    ///
+    /// All entries of table as `(ordinal, name)` pairs, with `@0` too
+    ///
+    fn name_entries(&self) -> impl Iterator<Item = (u16, &PascalString)>;
+    ///
+    /// Exported names without module name or description (`@0` entries)
+    ///
+    fn iter_exports(&self) -> impl Iterator<Item = (u16, String)> {
+        self.name_entries()
+            .filter(|(ordinal, _)| *ordinal != 0)
+            .map(|(ordinal, name)| (ordinal, name.to_string_lossy()))
+    }
+}
+///
+/// Strings are equal when their characters are equal.
+/// Length byte is not compared: it's a part of storage, not of a value.
+///
+impl PartialEq for PascalString {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
+impl Eq for PascalString {}
+impl Hash for PascalString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.string.hash(state);
+    }
+}
+///
+/// Order of bytes in multibyte fields of file.
+/// LX header keeps it in `e32_border` and `e32_worder`,
+/// all other formats are little-endian only.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+impl ByteOrder {
+    ///
+    /// Order by flag of LX header: `0` is little-endian, `1` is big-endian
+    ///
+    pub fn from(order: u8) -> Option<Self> {
+        match order {
+            0 => Some(ByteOrder::Little),
+            1 => Some(ByteOrder::Big),
+            _ => None,
+        }
+    }
+    pub fn u16(&self, bytes: [u8; 2]) -> u16 {
+        match self {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        }
+    }
+    pub fn u32(&self, bytes: [u8; 4]) -> u32 {
+        match self {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        }
     }
+    pub fn read_u16<R: Read>(&self, r: &mut R) -> io::Result<u16> {
+        let mut buf = [0; 2];
+        r.read_exact(&mut buf)?;
+        Ok(self.u16(buf))
+    }
+    pub fn read_u32<R: Read>(&self, r: &mut R) -> io::Result<u32> {
+        let mut buf = [0; 4];
+        r.read_exact(&mut buf)?;
+        Ok(self.u32(buf))
+    }
+    pub fn write_u16<W: Write>(&self, w: &mut W, value: u16) -> io::Result<()> {
+        match self {
+            ByteOrder::Little => w.write_all(&value.to_le_bytes()),
+            ByteOrder::Big => w.write_all(&value.to_be_bytes()),
+        }
+    }
+    pub fn write_u32<W: Write>(&self, w: &mut W, value: u32) -> io::Result<()> {
+        match self {
+            ByteOrder::Little => w.write_all(&value.to_le_bytes()),
+            ByteOrder::Big => w.write_all(&value.to_be_bytes()),
+        }
+    }
+}
+///
+/// Capacity to reserve for `count` records which count is read from
+/// file. Broken files declare billions of records: the vector grows
+/// by itself if they are really there, else reading fails on EOF
+/// before anything huge is allocated.
+///
+pub(crate) fn bounded_capacity(count: usize) -> usize {
+    count.min(0x1000)
+}
+///
+/// Sequential decoder of fields from the buffer of known length.
+/// Replaces casts of buffers into structures: those depend on host byte order.
+///
+pub(crate) struct FieldCursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    order: ByteOrder,
+}
+impl<'a> FieldCursor<'a> {
+    pub(crate) fn new(bytes: &'a [u8], order: ByteOrder) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            order,
+        }
+    }
+    ///
+    /// Next `N` bytes. Buffer is sized by caller, so running out of it is a bug.
+    ///
+    #[allow(clippy::expect_used)]
+    pub(crate) fn array<const N: usize>(&mut self) -> [u8; N] {
+        let bytes = self.bytes[self.pos..self.pos + N]
+            .try_into()
+            .expect("field is out of buffer");
+        self.pos += N;
+        bytes
+    }
+    pub(crate) fn u8(&mut self) -> u8 {
+        self.array::<1>()[0]
+    }
+    pub(crate) fn u16(&mut self) -> u16 {
+        let bytes = self.array();
+        self.order.u16(bytes)
+    }
+    pub(crate) fn u32(&mut self) -> u32 {
+        let bytes = self.array();
+        self.order.u32(bytes)
+    }
+}
+///
+/// Implements `len`, `is_empty`, `iter`, `get`, `Index<usize>` and
+/// `IntoIterator` (owned and borrowed) for a table which keeps
+/// its records in one `Vec` field.
+/// ```ignore
+/// table_accessors!(ObjectsTable, objects, Object);
+/// ```
+///
+macro_rules! table_accessors {
+    ($table:ty, $field:ident, $item:ty) => {
+        impl $table {
+            pub fn len(&self) -> usize {
+                self.$field.len()
+            }
+            pub fn is_empty(&self) -> bool {
+                self.$field.is_empty()
+            }
+            pub fn iter(&self) -> std::slice::Iter<'_, $item> {
+                self.$field.iter()
+            }
+            pub fn get(&self, index: usize) -> Option<&$item> {
+                self.$field.get(index)
+            }
+        }
+        impl std::ops::Index<usize> for $table {
+            type Output = $item;
+
+            fn index(&self, index: usize) -> &Self::Output {
+                &self.$field[index]
+            }
+        }
+        impl IntoIterator for $table {
+            type Item = $item;
+            type IntoIter = std::vec::IntoIter<$item>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.$field.into_iter()
+            }
+        }
+        impl<'a> IntoIterator for &'a $table {
+            type Item = &'a $item;
+            type IntoIter = std::slice::Iter<'a, $item>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.$field.iter()
+            }
+        }
+    };
 }
+pub(crate) use table_accessors;
@@ -0,0 +1,46 @@
+//! Uniform reader of fixed-size structures.
+//!
+//! Every core structure keeps its own `read` which takes what the
+//! format needs (byte order, pointer of the table). [Readable] reads
+//! the same structure from the current position of any reader, so
+//! generic code doesn't have to know those parameters:
+//! ```text
+//! MzHeader                 0x40 bytes
+//! NewExecutableHeader      0x40 bytes, from current position (not e_lfanew)
+//! LinearExecutableHeader   0xB8 bytes, in byte order of e32_border/e32_worder
+//! SegmentHeader            8 bytes
+//! Object                   24 bytes, little-endian
+//! LXObjectPageHeader       8 bytes, little-endian
+//! LEObjectPageHeader       4 bytes
+//! Entry16                  3 bytes, little-endian
+//! Entry32                  5 bytes, little-endian
+//! EntryCallGate            5 bytes, little-endian
+//! EntryForwarder           7 bytes, little-endian
+//! ```
+//! Structures of big-endian LE/LX modules must be read by their own
+//! `read` with [crate::exe386::header::LinearExecutableHeader::byte_order].
+//! ```rust
+//! use os2omf::exe::MzHeader;
+//! use os2omf::exe::builder::MzBuilder;
+//! use os2omf::types::readable::Readable;
+//! use std::io::Cursor;
+//!
+//! fn read_twice<T: Readable<T>>(bytes: &[u8]) -> std::io::Result<(T, T)> {
+//!     let mut reader = Cursor::new(bytes);
+//!     Ok((T::read(&mut reader)?, T::read(&mut Cursor::new(bytes))?))
+//! }
+//!
+//! let file = MzBuilder::new(vec![0xCB]).build()?;
+//! let (first, second) = read_twice::<MzHeader>(&file)?;
+//! assert_eq!(first.e_magic, second.e_magic);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use std::io::{self, Read};
+
+///
+/// Structure which is read from the current position of reader
+/// without any other parameters.
+///
+pub trait Readable<T> {
+    fn read<R: Read>(r: &mut R) -> io::Result<T>;
+}
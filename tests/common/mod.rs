@@ -0,0 +1,148 @@
+//! Fixtures of integration tests. Every binary in `tests/fixtures`
+//! is made by one of the functions here and embedded into tests by
+//! `include_bytes!`, so tests don't need any files outside of the
+//! repository. After a change of builders run
+//! ```text
+//! OS2OMF_BLESS=1 cargo test --tests
+//! ```
+//! to write fixtures again.
+#![allow(dead_code)]
+
+use bytemuck::Zeroable;
+use os2omf::exe::builder::MzBuilder;
+use os2omf::exe286::builder::{ExportSpec as NeExport, ImportSpec as NeImport, NeBuilder, SegmentSpec};
+use os2omf::exe386::builder::{ExportSpec as LxExport, ImportSpec as LxImport, LxBuilder, ObjectSpec};
+use os2omf::exe386::header::{LinearExecutableHeader, LE_MAGIC};
+use os2omf::omf::coment::ImportEntry;
+use os2omf::types::PascalString;
+use std::path::Path;
+
+///
+/// Compares `embedded` fixture with the one made now, or writes
+/// `tests/fixtures/{name}` when `OS2OMF_BLESS` is set.
+///
+pub fn check_fixture(name: &str, embedded: &[u8], made: Vec<u8>) {
+    if std::env::var_os("OS2OMF_BLESS").is_some() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+        std::fs::write(path, made).unwrap();
+        return;
+    }
+    assert!(embedded == made, "fixture {} is stale, run tests with OS2OMF_BLESS=1", name);
+}
+
+///
+/// Module name and description are the first entries of name tables:
+/// garbage in them is the quickest sign of a wrong table pointer.
+///
+pub fn assert_printable(what: &str, name: &PascalString) {
+    let bytes = name.as_bytes();
+    assert!(
+        !bytes.is_empty() && bytes.iter().all(|b| (0x20..0x7F).contains(b)),
+        "{} {:?} is not printable ASCII",
+        what,
+        bytes
+    );
+}
+
+///
+/// `HELLO.DLL`: 16-bit OS/2 library with one code and one data segment,
+/// two exports and two imports (`DOSCALLS.5` and `VIOCALLS.VIOWRTTTY`)
+///
+pub fn ne_dll() -> Vec<u8> {
+    let mut builder = NeBuilder::new("HELLO");
+    builder.description = "Hello library".to_string();
+    builder.os = 1;
+    builder.cs = 1;
+    // call far DOSCALLS.5; call far VIOCALLS.VIOWRTTTY; retf
+    builder.segments.push(SegmentSpec::code(vec![0x9A, 0, 0, 0, 0, 0x9A, 0, 0, 0, 0, 0xCB]));
+    builder.segments.push(SegmentSpec::data(b"Hello, world\0".to_vec()));
+    builder.exports.push(NeExport::new("HELLO", 1, 1, 0));
+    builder.exports.push(NeExport { resident: false, ..NeExport::new("GREETING", 2, 2, 0) });
+    builder.imports.push(NeImport::new("DOSCALLS", ImportEntry::Ordinal(5), 1, 1));
+    builder.imports.push(NeImport::new("VIOCALLS", ImportEntry::Name("VIOWRTTTY".to_string()), 1, 6));
+    builder.build().unwrap()
+}
+
+///
+/// `HELLO32.DLL`: 32-bit OS/2 library with a code object of two pages,
+/// a data object and imports from `DOSCALLS` by ordinal and by name
+///
+pub fn lx_dll() -> Vec<u8> {
+    let mut builder = LxBuilder::new("HELLO32");
+    builder.description = "Hello 32-bit library".to_string();
+    builder.objects.push(ObjectSpec::code(vec![0x90; 0x1800]));
+    builder.objects.push(ObjectSpec { virtual_size: 0x100, ..ObjectSpec::data(b"Hello, world".to_vec()) });
+    builder.cs = 1;
+    builder.eip = 0x10;
+    builder.exports.push(LxExport::new("Hello", 1, 1, 0x10));
+    builder.exports.push(LxExport { resident: false, ..LxExport::new("Greeting", 2, 2, 0) });
+    builder.imports.push(LxImport::new("DOSCALLS", ImportEntry::Ordinal(282), 1, 2));
+    builder.imports.push(LxImport::new("DOSCALLS", ImportEntry::Name("DosWrite".to_string()), 1, 0x1004));
+    builder.build().unwrap()
+}
+
+/// `e_lfanew` of [le_stub]
+pub const LE_OFFSET: usize = 0x80;
+
+///
+/// `STUB`: LE module with one code object of one short page
+/// (`ret` at the entry point) and one exported entry `START`.
+/// Builders make only LX modules, so tables are written here by hand.
+///
+pub fn le_stub() -> Vec<u8> {
+    let code = [0xC3_u8];
+    let mut loader = Vec::new();
+
+    // object table: 1 object of 1 page
+    let objtab = 0xC4;
+    for value in [0x10_u32, 0x10000, 0x2005, 1, 1, 0] {
+        loader.extend_from_slice(&value.to_le_bytes());
+    }
+    // object page map: 1st page of file, no flags
+    let objmap = objtab + loader.len();
+    loader.extend_from_slice(&[0, 0, 1, 0]);
+    // resident names: module name and START
+    let restab = objtab + loader.len();
+    loader.extend_from_slice(b"\x04STUB\0\0\x05START\x01\0\0");
+    // entry table: 1 exported 32-bit entry in object 1 at 0
+    let enttab = objtab + loader.len();
+    loader.extend_from_slice(&[1, 3, 1, 0, 0x01, 0, 0, 0, 0, 0]);
+    // fixup page table: page without fixups and end marker
+    let fpagetab = objtab + loader.len();
+    loader.extend_from_slice(&[0; 8]);
+    let frectab = objtab + loader.len();
+
+    let mut header = LinearExecutableHeader::zeroed();
+    header.e32_magic = LE_MAGIC;
+    header.e32_cpu = 0x02;
+    header.e32_os = 0x01;
+    header.e32_mflags = 0x00008000;
+    header.e32_mpages = 1;
+    header.e32_cs = 1;
+    header.e32_ss = 1;
+    header.e32_esp = 0x10;
+    header.e32_pagesize = 0x1000;
+    header.e32_pageshift_or_lastpage = code.len() as u32;
+    header.e32_ldrsize = (fpagetab - objtab) as u32;
+    header.e32_objtab = objtab as u32;
+    header.e32_objcnt = 1;
+    header.e32_objmap = objmap as u32;
+    header.e32_restab = restab as u32;
+    header.e32_enttab = enttab as u32;
+    header.e32_fpagetab = fpagetab as u32;
+    header.e32_frectab = frectab as u32;
+    header.e32_impmod = frectab as u32;
+    header.e32_impproc = frectab as u32;
+    header.e32_fixupsize = (frectab - fpagetab) as u32;
+    header.e32_datapage = (LE_OFFSET + frectab) as u32;
+
+    // mov ax, 4C01h; int 21h
+    let mut stub = MzBuilder::new(vec![0xB8, 0x01, 0x4C, 0xCD, 0x21]);
+    stub.lfanew = LE_OFFSET as u32;
+    let mut file = stub.build().unwrap();
+    file.resize(LE_OFFSET, 0);
+    header.write(&mut file).unwrap();
+    file.extend_from_slice(&loader);
+    file.extend_from_slice(&code);
+    file
+}
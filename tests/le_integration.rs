@@ -0,0 +1,70 @@
+//! Reads the LE module of `tests/fixtures` made by [common::le_stub]
+mod common;
+
+use os2omf::exe386::enttab::Entry;
+use os2omf::exe386::objpagetab::ObjectPage;
+use os2omf::exe386::LinearExecutableLayout;
+use os2omf::file::ExecutableFile;
+use std::io::Cursor;
+
+const STUB_LE: &[u8] = include_bytes!("fixtures/stub_le.exe");
+
+fn layout() -> LinearExecutableLayout {
+    LinearExecutableLayout::read_from(&mut Cursor::new(STUB_LE)).unwrap()
+}
+
+#[test]
+fn le_fixture_is_current() {
+    common::check_fixture("stub_le.exe", STUB_LE, common::le_stub());
+}
+
+#[test]
+fn le_header() {
+    let layout = layout();
+    let header = &layout.header;
+
+    assert!(header.is_le() && !header.is_lx());
+    assert_eq!(layout.dos_header.map(|h| h.e_lfanew), Some(common::LE_OFFSET as u32));
+    assert_eq!((header.e32_objcnt, header.e32_mpages), (1, 1));
+    assert_eq!(header.bytes_on_last_page(), 1);
+    assert_eq!(header.data_pages_offset(0), STUB_LE.len() as u64 - 1);
+
+    let entry = layout.entry_point().unwrap();
+    assert_eq!((entry.object, entry.offset, entry.virtual_address), (1, 0, 0x10000));
+    let stack = layout.initial_stack().unwrap();
+    assert_eq!((stack.object, stack.offset), (1, 0x10));
+}
+
+#[test]
+fn le_pages_and_entries() {
+    let layout = layout();
+
+    let ObjectPage::LEPageFormat(page) = &layout.object_pages.pages[0] else {
+        panic!("LE module has LX page map");
+    };
+    assert_eq!((page.page_number, page.flags), ([0, 0, 1], 0));
+    // last page has 1 byte in file, the rest of object is not allocated
+    assert_eq!(layout.read_object_data(&mut Cursor::new(STUB_LE), 1).unwrap(), vec![0xC3]);
+
+    let resident: Vec<(String, u16)> = layout.resident_names.entries.iter().map(|e| (e.name.to_string(), e.ordinal)).collect();
+    assert_eq!(resident, vec![("STUB".to_string(), 0), ("START".to_string(), 1)]);
+    assert_eq!(layout.entry_table.len(), 1);
+    assert!(matches!(layout.entry_table[0].entries[..], [Entry::Entry32(ref e)] if e.offset == 0 && e.flags == 0x01));
+    assert!(layout.fixup_records_table.records.is_empty());
+    assert!(layout.import_table.imports().is_empty());
+}
+
+#[test]
+fn le_executable_file() {
+    let file = ExecutableFile::read_from(&mut Cursor::new(STUB_LE)).unwrap();
+    assert!(matches!(file, ExecutableFile::Lx(ref layout) if layout.header.is_le()));
+}
+
+#[test]
+fn le_names_are_printable() {
+    let layout = layout();
+    // stub has no non-resident names, so no description
+    assert!(layout.non_resident_names.entries.is_empty());
+    assert_eq!(layout.resident_names.entries[0].ordinal, 0);
+    common::assert_printable("Module name", &layout.resident_names.entries[0].name);
+}
@@ -0,0 +1,83 @@
+//! Reads the LX library of `tests/fixtures` made by [common::lx_dll]
+mod common;
+
+use os2omf::exe386::header::CPU;
+use os2omf::exe386::objpagetab::ObjectPage;
+use os2omf::exe386::LinearExecutableLayout;
+use os2omf::inspect;
+use std::io::Cursor;
+
+const HELLO_LX: &[u8] = include_bytes!("fixtures/hello_lx.dll");
+
+fn layout() -> LinearExecutableLayout {
+    LinearExecutableLayout::read_from(&mut Cursor::new(HELLO_LX)).unwrap()
+}
+
+#[test]
+fn lx_fixture_is_current() {
+    common::check_fixture("hello_lx.dll", HELLO_LX, common::lx_dll());
+}
+
+#[test]
+fn lx_header() {
+    let layout = layout();
+    let header = &layout.header;
+
+    assert!(header.is_lx() && header.le_byte_ordering());
+    assert_eq!(layout.dos_header.map(|h| h.e_lfanew), Some(0x40));
+    assert_eq!((header.e32_objcnt, header.e32_mpages, header.e32_impmodcnt), (2, 3, 1));
+    assert_eq!(header.e32_pagesize, 0x1000);
+    assert_eq!(CPU::try_from(header.e32_cpu), Ok(CPU::I386));
+    assert_eq!(layout.expected_os().to_string(), "OS/2 2.x");
+
+    let entry = layout.entry_point().unwrap();
+    assert_eq!((entry.object, entry.offset), (1, 0x10));
+    assert_eq!(entry.virtual_address, layout.object_table.objects[0].virtual_addr + 0x10);
+}
+
+#[test]
+fn lx_objects() {
+    let layout = layout();
+    let mut reader = Cursor::new(HELLO_LX);
+
+    let objects = &layout.object_table.objects;
+    assert_eq!((objects[0].map_index, objects[0].map_size), (1, 2));
+    assert_eq!((objects[1].map_index, objects[1].map_size, objects[1].virtual_size), (3, 1, 0x100));
+    assert_eq!(layout.object_pages.pages.len(), 3);
+    assert!(matches!(layout.object_pages.pages[0], ObjectPage::LXPageFormat(_)));
+
+    assert_eq!(layout.read_object_data(&mut reader, 1).unwrap(), vec![0x90; 0x1800]);
+    let data = layout.read_object_data(&mut reader, 2).unwrap();
+    assert_eq!(&data[..], &b"Hello, world"[..]);
+}
+
+#[test]
+fn lx_names_and_imports() {
+    let layout = layout();
+
+    let resident: Vec<(String, u16)> = layout.resident_names.entries.iter().map(|e| (e.name.to_string(), e.ordinal)).collect();
+    assert_eq!(resident, vec![("HELLO32".to_string(), 0), ("Hello".to_string(), 1)]);
+    let non_resident: Vec<(String, u16)> = layout.non_resident_names.entries.iter().map(|e| (e.name.to_string(), e.ordinal)).collect();
+    assert_eq!(non_resident, vec![("Hello 32-bit library".to_string(), 0), ("Greeting".to_string(), 2)]);
+
+    assert_eq!(layout.import_table.modules()[0].to_string(), "DOSCALLS");
+    let imports: Vec<String> = layout.import_table.imports().iter().map(|i| i.display_symbol()).collect();
+    assert_eq!(imports, vec!["#282".to_string(), "DosWrite".to_string()]);
+    assert_eq!(layout.fixup_records_table.records.iter().map(|r| r.page).collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn lx_inspect() {
+    let mut reader = Cursor::new(HELLO_LX);
+    assert_eq!(inspect::read_format(&mut reader).unwrap(), "LX");
+    assert_eq!(inspect::read_module_name(&mut reader).unwrap(), Some("HELLO32".to_string()));
+    assert_eq!(inspect::read_export_count(&mut reader).unwrap(), 2);
+}
+
+#[test]
+fn lx_names_are_printable() {
+    let layout = layout();
+    assert_eq!((layout.resident_names.entries[0].ordinal, layout.non_resident_names.entries[0].ordinal), (0, 0));
+    common::assert_printable("Module name", &layout.resident_names.entries[0].name);
+    common::assert_printable("Description", &layout.non_resident_names.entries[0].name);
+}
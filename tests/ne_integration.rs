@@ -0,0 +1,72 @@
+//! Reads the NE library of `tests/fixtures` made by [common::ne_dll]
+mod common;
+
+use os2omf::exe286::NewExecutableLayout;
+use os2omf::file::ExecutableFile;
+use std::io::Cursor;
+
+const HELLO_NE: &[u8] = include_bytes!("fixtures/hello_ne.dll");
+
+fn layout() -> NewExecutableLayout {
+    NewExecutableLayout::read_from(&mut Cursor::new(HELLO_NE)).unwrap()
+}
+
+#[test]
+fn ne_fixture_is_current() {
+    common::check_fixture("hello_ne.dll", HELLO_NE, common::ne_dll());
+}
+
+#[test]
+fn ne_header() {
+    let layout = layout();
+    let header = &layout.new_header;
+
+    assert_eq!(&HELLO_NE[..2], b"MZ");
+    assert_eq!(header.e_cseg, 2);
+    assert_eq!(header.e_cmod, 2);
+    assert_eq!(header.e_csip, 0x0001_0000);
+    assert_eq!(header.e_align, 4);
+    assert!(!header.module_flags().linkage_errors);
+    assert_eq!(layout.expected_os().to_string(), "OS/2");
+}
+
+#[test]
+fn ne_names() {
+    let layout = layout();
+
+    let resident: Vec<(String, u16)> = layout.resn_tab.entries.iter().map(|e| (e.name.to_string(), e.ordinal)).collect();
+    assert_eq!(resident, vec![("HELLO".to_string(), 0), ("HELLO".to_string(), 1)]);
+    let non_resident: Vec<(String, u16)> = layout.nres_tab.entries.iter().map(|e| (e.name.to_string(), e.ordinal)).collect();
+    assert_eq!(non_resident, vec![("Hello library".to_string(), 0), ("GREETING".to_string(), 2)]);
+}
+
+#[test]
+fn ne_segments_and_imports() {
+    let mut layout = layout();
+    layout.read_segments_data(&mut Cursor::new(HELLO_NE)).unwrap();
+
+    assert_eq!(layout.seg_tab.len(), 2);
+    assert_eq!(layout.seg_tab[0].data.as_deref().unwrap().last(), Some(&0xCB));
+    assert_eq!(layout.seg_tab[1].data.as_deref(), Some(&b"Hello, world\0"[..]));
+    assert_eq!(layout.entry_point().unwrap().file_offset, layout.segment_file_range(0).map(|(start, _)| start));
+
+    let imports: Vec<(String, String)> = layout.imports().iter().map(|i| (i.import.dll_name_str(), i.import.display_symbol())).collect();
+    assert_eq!(imports, vec![
+        ("DOSCALLS".to_string(), "#5".to_string()),
+        ("VIOCALLS".to_string(), "VIOWRTTTY".to_string()),
+    ]);
+}
+
+#[test]
+fn ne_executable_file() {
+    let file = ExecutableFile::read_from(&mut Cursor::new(HELLO_NE)).unwrap();
+    assert!(matches!(file, ExecutableFile::Ne(_)));
+}
+
+#[test]
+fn ne_names_are_printable() {
+    let layout = layout();
+    assert_eq!((layout.resn_tab.entries[0].ordinal, layout.nres_tab.entries[0].ordinal), (0, 0));
+    common::assert_printable("Module name", &layout.resn_tab.entries[0].name);
+    common::assert_printable("Description", &layout.nres_tab.entries[0].name);
+}
@@ -0,0 +1,168 @@
+//! Modules assembled here byte by byte from the format specifications,
+//! without builders and writers of the crate. Fixtures of `tests/fixtures`
+//! are made by builders and read back by readers of the same crate,
+//! so a field order mistaken by both sides passes them. These don't.
+use os2omf::exe::reltab::MzRelocationTable;
+use os2omf::exe::MzHeader;
+use os2omf::exe386::enttab::{BundleType, Entry};
+use os2omf::exe386::frectab::FixupTarget;
+use os2omf::exe386::LinearExecutableLayout;
+use std::io::Cursor;
+
+fn put_u16(buf: &mut [u8], at: usize, value: u16) {
+    buf[at..at + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut [u8], at: usize, value: u32) {
+    buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+///
+/// DOS program of one paragraph of code with two relocations:
+/// `0000:0001` and `0001:0003`, every one is `offset, segment`.
+///
+fn mz_program() -> Vec<u8> {
+    let mut file = vec![0_u8; 0x47];
+    file[0..2].copy_from_slice(b"MZ");
+    put_u16(&mut file, 0x02, 0x47); // e_cblp: bytes on last page
+    put_u16(&mut file, 0x04, 1); // e_cp
+    put_u16(&mut file, 0x06, 2); // e_crlc
+    put_u16(&mut file, 0x08, 4); // e_cparhdr: 0x40 bytes
+    put_u16(&mut file, 0x18, 0x1C); // e_lfarlc
+    file[0x1C..0x24].copy_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x00]);
+    // mov ax, seg; mov bx, seg; retf
+    file[0x40..0x47].copy_from_slice(&[0xB8, 0, 0, 0xBB, 0, 0, 0xCB]);
+    file
+}
+
+///
+/// LX library without DOS header: one object of one page, an exported
+/// 32-bit entry `@1`, forwarders `@2` (DOSCALLS.282) and `@3`
+/// (DOSCALLS.DosWrite), and import of DOSCALLS.282 at offset 4.
+///
+fn lx_library() -> Vec<u8> {
+    let mut file = vec![0_u8; 0x150];
+    file[0..2].copy_from_slice(b"LX");
+    put_u16(&mut file, 0x08, 2); // e32_cpu: 386
+    put_u16(&mut file, 0x0A, 1); // e32_os: OS/2
+    put_u32(&mut file, 0x10, 0x8000); // e32_mflags: library
+    put_u32(&mut file, 0x14, 1); // e32_mpages
+    put_u32(&mut file, 0x28, 0x1000); // e32_pagesize
+    put_u32(&mut file, 0x30, 0x22); // e32_fixupsize
+    put_u32(&mut file, 0x38, 0x110 - 0xC4); // e32_ldrsize
+    put_u32(&mut file, 0x40, 0xC4); // e32_objtab
+    put_u32(&mut file, 0x44, 1); // e32_objcnt
+    put_u32(&mut file, 0x48, 0xDC); // e32_objmap
+    put_u32(&mut file, 0x58, 0xE4); // e32_restab
+    put_u32(&mut file, 0x5C, 0xF4); // e32_enttab
+    put_u32(&mut file, 0x68, 0x110); // e32_fpagetab
+    put_u32(&mut file, 0x6C, 0x118); // e32_frectab
+    put_u32(&mut file, 0x70, 0x11F); // e32_impmod
+    put_u32(&mut file, 0x74, 1); // e32_impmodcnt
+    put_u32(&mut file, 0x78, 0x128); // e32_impproc
+    put_u32(&mut file, 0x80, 0x140); // e32_datapage (absolute)
+    put_u32(&mut file, 0x88, 0x132); // e32_nrestab (absolute)
+    put_u32(&mut file, 0x8C, 8); // e32_cbnrestab
+
+    // object: size, base, readable|executable|32-bit, page map 1..=1
+    for (i, value) in [0x10_u32, 0x10000, 0x2005, 1, 1, 0].into_iter().enumerate() {
+        put_u32(&mut file, 0xC4 + i * 4, value);
+    }
+    // page map: offset 0 from e32_datapage, 0x10 bytes, legal page
+    file[0xDC..0xE4].copy_from_slice(&[0, 0, 0, 0, 0x10, 0, 0, 0]);
+    file[0xE4..0xF4].copy_from_slice(b"\x05HELLO\0\0\x04Proc\x01\0\0");
+    // 32-bit bundle: count, type, object, exported entry at 0
+    file[0xF4..0xFD].copy_from_slice(&[1, 3, 1, 0, 0x01, 0, 0, 0, 0]);
+    // forwarder bundle: count, type, reserved word, then 7-byte entries:
+    // flags (1 is by ordinal), module ordinal, ordinal or name offset
+    file[0xFD..0x101].copy_from_slice(&[2, 4, 0, 0]);
+    file[0x101..0x108].copy_from_slice(&[0x01, 1, 0, 0x1A, 0x01, 0, 0]);
+    file[0x108..0x10F].copy_from_slice(&[0x00, 1, 0, 1, 0, 0, 0]);
+    // 0x10F: end of entry table
+
+    // fixup page table: records of page 1 are 0..7
+    put_u32(&mut file, 0x114, 7);
+    // 32-bit offset at 4 of page 1 to import by ordinal DOSCALLS.282
+    file[0x118..0x11F].copy_from_slice(&[0x07, 0x01, 4, 0, 1, 0x1A, 0x01]);
+    file[0x11F..0x128].copy_from_slice(b"\x08DOSCALLS");
+    file[0x128..0x132].copy_from_slice(b"\0\x08DosWrite");
+    file[0x132..0x13A].copy_from_slice(b"\x04HAND\0\0\0");
+
+    // nop x4; dd 0 (import); ret
+    file[0x140..0x149].copy_from_slice(&[0x90, 0x90, 0x90, 0x90, 0, 0, 0, 0, 0xC3]);
+    file
+}
+
+#[test]
+fn spec_mz_relocations() {
+    let file = mz_program();
+    let mut reader = Cursor::new(&file);
+    let header = MzHeader::read(&mut reader).unwrap();
+    let relocations = MzRelocationTable::read(&mut reader, &header).unwrap();
+
+    let pointers: Vec<(u16, u16)> = relocations.iter().map(|p| (p.segment, p.offset)).collect();
+    assert_eq!(pointers, vec![(0, 1), (1, 3)]);
+}
+
+#[test]
+fn spec_lx_forwarders() {
+    let file = lx_library();
+    let layout = LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+
+    let bundles = &layout.entry_table.bundles;
+    assert_eq!(bundles.iter().map(|b| (b.bundle_type, b.entries.len())).collect::<Vec<_>>(), vec![(BundleType::Entry32, 1), (BundleType::Forwarder, 2)]);
+    let forwarders: Vec<(u8, u16, u32)> = bundles[1]
+        .entries
+        .iter()
+        .map(|e| match e {
+            Entry::EntryForwarder(f) => (f.flags, f.module_ordinal, f.offset_or_ordinal),
+            e => panic!("{:?} is not a forwarder", e),
+        })
+        .collect();
+    assert_eq!(forwarders, vec![(0x01, 1, 282), (0x00, 1, 1)]);
+    assert_eq!(layout.entry_table.export_count(), 3);
+}
+
+#[test]
+fn spec_lx_imports_and_data() {
+    let file = lx_library();
+    let layout = LinearExecutableLayout::read_from(&mut Cursor::new(&file)).unwrap();
+
+    assert_eq!(layout.resident_names.entries[0].name.to_string(), "HELLO");
+    assert_eq!(layout.non_resident_names.entries[0].name.to_string(), "HAND");
+    assert_eq!(layout.import_table.modules()[0].to_string(), "DOSCALLS");
+
+    let record = &layout.fixup_records_table.records[0];
+    assert_eq!((record.page, record.source_offset_or_count), (1, 4));
+    assert_eq!((record.target_data.module_ordinal(), record.target_data.imported_ordinal()), (Some(1), Some(282)));
+    assert!(matches!(record.target_data, FixupTarget::ImportedOrdinal(_)));
+
+    let data = layout.read_object_data(&mut Cursor::new(&file), 1).unwrap();
+    assert_eq!(&data[..9], &[0x90, 0x90, 0x90, 0x90, 0, 0, 0, 0, 0xC3]);
+}
+
+#[test]
+fn spec_lx_def_forwarders() {
+    let def = |file: &[u8]| {
+        let layout = LinearExecutableLayout::read_from(&mut Cursor::new(file)).unwrap();
+        let mut def = Vec::new();
+        os2omf::def::write_lx_def(&layout, &mut def).unwrap();
+        String::from_utf8(def).unwrap()
+    };
+
+    // name of DosWrite is taken from procedure names table, no fixup imports it
+    let mut file = lx_library();
+    assert_eq!(
+        def(&file),
+        "LIBRARY HELLO\n\
+         DESCRIPTION 'HAND'\n\
+         EXPORTS\n    \
+         Proc @1 RESIDENTNAME\n    \
+         ORDINAL_2 = DOSCALLS.282 @2 NONAME\n    \
+         ORDINAL_3 = DOSCALLS.DosWrite @3 NONAME\n"
+    );
+
+    // name offset past the end of table (fixup section) is left as comment
+    put_u32(&mut file, 0x10B, 0x20);
+    assert!(def(&file).ends_with("    ; ORDINAL_3 @3: forwarder to module #1 procedure name at 0x20\n"));
+}